@@ -0,0 +1,1437 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use num_bigint::BigUint;
+use regex::Regex;
+use serde_json::{Map, Value};
+use starknet::{
+    core::{
+        types::{
+            contract::{
+                AbiEntry, AbiEvent, AbiFunction, AbiNamedMember, EventFieldKind, StateMutability,
+                TypedAbiEvent,
+            },
+            BlockId, BlockTag, ContractClass, FieldElement, FunctionCall, FunctionStateMutability,
+            LegacyContractAbiEntry, LegacyFunctionAbiEntry, LegacyFunctionAbiType, StarknetError,
+        },
+        utils::{get_selector_from_name, starknet_keccak},
+    },
+    providers::{MaybeUnknownErrorCode, Provider, ProviderError, StarknetErrorWithMessage},
+};
+
+use crate::{
+    path::ExpandedPathbufParser,
+    verbosity::{progress, warning},
+};
+
+/// Maximum number of function names suggested for a mistyped one.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Names of the functions common proxy contracts expose their implementation with.
+const PROXY_IMPLEMENTATION_GETTERS: &[&str] = &[
+    "get_implementation",
+    "getImplementation",
+    "get_implementation_hash",
+    "getImplementationHash",
+    "get_implementation_class_hash",
+    "implementation",
+];
+
+#[derive(Debug, Clone, Parser)]
+pub struct AbiArgs {
+    #[clap(
+        long,
+        value_parser = ExpandedPathbufParser,
+        help = "Path to a local ABI or contract class file to use instead of fetching the ABI \
+        from the network"
+    )]
+    abi_file: Option<PathBuf>,
+    #[clap(
+        long,
+        conflicts_with = "abi_file",
+        help = "When the contract is a proxy, use the ABI of its implementation instead"
+    )]
+    follow_proxy: bool,
+}
+
+/// Where ABIs come from: a local file supplied by the user, or the class deployed on-chain.
+pub struct AbiSource {
+    local: Option<ContractAbi>,
+    follow_proxy: bool,
+}
+
+/// What a proxy contract forwards calls to, as returned by its implementation getter.
+#[derive(Clone, Copy)]
+pub enum ProxyImplementation {
+    /// Cairo 0 proxies that `library_call` into a class.
+    ClassHash(FieldElement),
+    /// Proxies that forward calls to another contract.
+    Address(FieldElement),
+}
+
+/// Contract ABI from either a legacy (Cairo 0) or a Sierra (Cairo 1) class.
+#[derive(Clone)]
+pub enum ContractAbi {
+    Legacy(Vec<LegacyContractAbiEntry>),
+    Sierra(Vec<AbiEntry>),
+}
+
+/// An event definition with its members split by whether they're emitted as keys or data.
+pub struct EventDefinition {
+    pub name: String,
+    pub keys: Vec<TypedMember>,
+    pub data: Vec<TypedMember>,
+}
+
+pub struct TypedMember {
+    pub name: String,
+    pub r#type: String,
+}
+
+/// A function, event, or type definition flattened out of an ABI, for comparing interfaces.
+pub struct AbiItem {
+    pub kind: &'static str,
+    pub name: String,
+    /// Rendered with type paths shortened to their last segment.
+    pub signature: String,
+}
+
+/// A function that can be called on a class, with its kind being one of `external`, `view`,
+/// `l1_handler`, or `constructor`.
+pub struct Entrypoint {
+    pub kind: &'static str,
+    pub name: String,
+    /// Rendered the same way as [`ContractAbi::find_function_signature`].
+    pub signature: String,
+}
+
+/// A successfully decoded event with named fields.
+pub struct DecodedEvent {
+    pub name: String,
+    pub fields: Map<String, Value>,
+}
+
+impl AbiArgs {
+    /// Loads the local ABI file, if any, so that malformed files are reported upfront.
+    pub fn into_source(self) -> Result<AbiSource> {
+        Ok(AbiSource {
+            local: match self.abi_file {
+                Some(path) => Some(ContractAbi::from_file(&path)?),
+                None => None,
+            },
+            follow_proxy: self.follow_proxy,
+        })
+    }
+}
+
+impl AbiSource {
+    pub fn is_local(&self) -> bool {
+        self.local.is_some()
+    }
+
+    /// Gets the ABI of the contract at `address`. A local ABI always takes precedence over the
+    /// on-chain one.
+    pub async fn load<P>(&self, provider: P, address: FieldElement) -> Result<Option<ContractAbi>>
+    where
+        P: Provider + Sync,
+        P::Error: 'static,
+    {
+        let abi = match &self.local {
+            Some(abi) => return Ok(Some(abi.clone())),
+            None => ContractAbi::fetch(&provider, address).await?,
+        };
+
+        let getter = match abi
+            .as_ref()
+            .and_then(|abi| abi.find_proxy_implementation_getter())
+        {
+            Some(getter) => getter,
+            None => return Ok(abi),
+        };
+        let implementation = match ProxyImplementation::fetch(&provider, address, &getter).await {
+            Ok(implementation) => implementation,
+            Err(err) => {
+                // Contracts can have functions named like this without being proxies
+                if self.follow_proxy {
+                    warning!(
+                        "WARNING: unable to resolve the implementation of {:#064x} through \
+                        \"{}\", using its own ABI: {}",
+                        address,
+                        getter,
+                        err
+                    );
+                }
+                return Ok(abi);
+            }
+        };
+
+        if !self.follow_proxy {
+            progress!(
+                "Contract {:#064x} looks like a proxy for {}. Use --follow-proxy to use the ABI \
+                of the implementation",
+                address,
+                implementation
+            );
+            return Ok(abi);
+        }
+
+        progress!(
+            "Contract {:#064x} is a proxy, using the ABI of {}",
+            address,
+            implementation.to_string().bright_yellow()
+        );
+        // Only one level of proxies is followed
+        match implementation {
+            ProxyImplementation::ClassHash(class_hash) => {
+                ContractAbi::fetch_by_class_hash(&provider, class_hash).await
+            }
+            ProxyImplementation::Address(address) => ContractAbi::fetch(&provider, address).await,
+        }
+    }
+}
+
+impl ProxyImplementation {
+    /// Calls the implementation getter, and tells class hashes from addresses by looking them up.
+    async fn fetch<P>(provider: P, address: FieldElement, getter: &str) -> Result<Self>
+    where
+        P: Provider,
+        P::Error: 'static,
+    {
+        let result = provider
+            .call(
+                FunctionCall {
+                    contract_address: address,
+                    entry_point_selector: get_selector_from_name(getter)?,
+                    calldata: vec![],
+                },
+                BlockId::Tag(BlockTag::Pending),
+            )
+            .await?;
+        let implementation = match result.as_slice() {
+            [implementation] if *implementation != FieldElement::ZERO => *implementation,
+            _ => anyhow::bail!("unexpected result from \"{}\"", getter),
+        };
+
+        match provider
+            .get_class(BlockId::Tag(BlockTag::Pending), implementation)
+            .await
+        {
+            Ok(_) => return Ok(Self::ClassHash(implementation)),
+            Err(ProviderError::StarknetError(StarknetErrorWithMessage {
+                code: MaybeUnknownErrorCode::Known(StarknetError::ClassHashNotFound),
+                ..
+            })) => {}
+            Err(err) => return Err(err.into()),
+        }
+        provider
+            .get_class_hash_at(BlockId::Tag(BlockTag::Pending), implementation)
+            .await
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "{:#064x} is neither a declared class nor a deployed contract",
+                    implementation
+                )
+            })?;
+        Ok(Self::Address(implementation))
+    }
+}
+
+impl Display for ProxyImplementation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ClassHash(class_hash) => write!(f, "class {:#064x}", class_hash),
+            Self::Address(address) => write!(f, "contract {:#064x}", address),
+        }
+    }
+}
+
+impl ContractAbi {
+    /// Reads an ABI from a file containing either a bare ABI array, or a contract class artifact
+    /// with an `abi` field.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let file_content: Value = match serde_json::from_reader(std::fs::File::open(path)?) {
+            Ok(value) => value,
+            Err(err) => anyhow::bail!("invalid ABI file: {}", err),
+        };
+
+        let abi = match file_content.get("abi") {
+            // Sierra classes store the ABI as a JSON string
+            Some(Value::String(abi)) => match serde_json::from_str(abi) {
+                Ok(abi) => abi,
+                Err(err) => anyhow::bail!("invalid ABI file: {}", err),
+            },
+            Some(abi) => abi.clone(),
+            None => file_content,
+        };
+
+        // Sierra entries are tried first as legacy entries are more permissive: Sierra functions
+        // always come with `state_mutability`, which legacy ABIs never use.
+        if let Ok(entries) = serde_json::from_value::<Vec<AbiEntry>>(abi.clone()) {
+            Ok(Self::Sierra(entries))
+        } else if let Ok(entries) = serde_json::from_value::<Vec<LegacyContractAbiEntry>>(abi) {
+            Ok(Self::Legacy(entries))
+        } else {
+            anyhow::bail!("invalid ABI file: not a valid Sierra or legacy contract ABI")
+        }
+    }
+
+    /// Extracts the ABI from a class. Returns `None` for legacy classes deployed without ABI.
+    pub fn from_class(class: &ContractClass) -> Result<Option<Self>> {
+        Ok(match class {
+            ContractClass::Sierra(class) => Some(Self::Sierra(serde_json::from_str(&class.abi)?)),
+            ContractClass::Legacy(class) => class.abi.clone().map(Self::Legacy),
+        })
+    }
+
+    /// Fetches the ABI of the class deployed at `address`.
+    pub async fn fetch<P>(provider: P, address: FieldElement) -> Result<Option<Self>>
+    where
+        P: Provider,
+        P::Error: 'static,
+    {
+        let class = provider
+            .get_class_at(BlockId::Tag(BlockTag::Pending), address)
+            .await?;
+        Self::from_class(&class)
+    }
+
+    /// Fetches the ABI of the class with `class_hash`.
+    pub async fn fetch_by_class_hash<P>(
+        provider: P,
+        class_hash: FieldElement,
+    ) -> Result<Option<Self>>
+    where
+        P: Provider,
+        P::Error: 'static,
+    {
+        let class = provider
+            .get_class(BlockId::Tag(BlockTag::Pending), class_hash)
+            .await?;
+        Self::from_class(&class)
+    }
+
+    /// An empty Sierra ABI, which can still decode types from the core library.
+    pub fn core_types() -> Self {
+        Self::Sierra(vec![])
+    }
+
+    /// Returns the output types of the function named `name`.
+    pub fn find_function_outputs(&self, name: &str) -> Option<Vec<String>> {
+        match self {
+            Self::Legacy(entries) => entries.iter().find_map(|entry| match entry {
+                LegacyContractAbiEntry::Function(function) if function.name == name => Some(
+                    function
+                        .outputs
+                        .iter()
+                        .map(|output| output.r#type.clone())
+                        .collect(),
+                ),
+                _ => None,
+            }),
+            Self::Sierra(entries) => find_sierra_function(entries, name).map(|function| {
+                function
+                    .outputs
+                    .iter()
+                    .map(|output| output.r#type.clone())
+                    .collect()
+            }),
+        }
+    }
+
+    /// Returns the input types of the constructor, which are empty for classes without one.
+    pub fn constructor_inputs(&self) -> Vec<String> {
+        self.constructor_parameters()
+            .into_iter()
+            .map(|(_, r#type)| r#type)
+            .collect()
+    }
+
+    /// Returns the names and types of the constructor inputs.
+    pub fn constructor_parameters(&self) -> Vec<(String, String)> {
+        match self {
+            Self::Legacy(entries) => entries
+                .iter()
+                .find_map(|entry| match entry {
+                    LegacyContractAbiEntry::Function(function)
+                        if function.name == "constructor" =>
+                    {
+                        Some(
+                            function
+                                .inputs
+                                .iter()
+                                .map(|input| (input.name.clone(), input.r#type.clone()))
+                                .collect(),
+                        )
+                    }
+                    _ => None,
+                })
+                .unwrap_or_default(),
+            Self::Sierra(entries) => entries
+                .iter()
+                .find_map(|entry| match entry {
+                    AbiEntry::Constructor(constructor) => Some(
+                        constructor
+                            .inputs
+                            .iter()
+                            .map(|input| (input.name.clone(), input.r#type.clone()))
+                            .collect(),
+                    ),
+                    _ => None,
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the names and types of the inputs of the function named `name`.
+    pub fn find_function_parameters(&self, name: &str) -> Option<Vec<(String, String)>> {
+        match self {
+            Self::Legacy(entries) => entries.iter().find_map(|entry| match entry {
+                LegacyContractAbiEntry::Function(function) if function.name == name => Some(
+                    function
+                        .inputs
+                        .iter()
+                        .map(|input| (input.name.clone(), input.r#type.clone()))
+                        .collect(),
+                ),
+                _ => None,
+            }),
+            Self::Sierra(entries) => find_sierra_function(entries, name).map(|function| {
+                function
+                    .inputs
+                    .iter()
+                    .map(|input| (input.name.clone(), input.r#type.clone()))
+                    .collect()
+            }),
+        }
+    }
+
+    /// Returns a human-readable signature of the function named `name`, like
+    /// `balanceOf(account: ContractAddress) -> u256`. Type paths are shortened to their last
+    /// segment.
+    pub fn find_function_signature(&self, name: &str) -> Option<String> {
+        let (inputs, outputs) = match self {
+            Self::Legacy(entries) => entries.iter().find_map(|entry| match entry {
+                LegacyContractAbiEntry::Function(function) if function.name == name => Some((
+                    function
+                        .inputs
+                        .iter()
+                        .map(|input| format!("{}: {}", input.name, input.r#type))
+                        .collect::<Vec<_>>(),
+                    // Legacy outputs are named too
+                    function
+                        .outputs
+                        .iter()
+                        .map(|output| format!("{}: {}", output.name, output.r#type))
+                        .collect::<Vec<_>>(),
+                )),
+                _ => None,
+            })?,
+            Self::Sierra(entries) => {
+                let function = find_sierra_function(entries, name)?;
+                (
+                    function
+                        .inputs
+                        .iter()
+                        .map(|input| format!("{}: {}", input.name, short_type_name(&input.r#type)))
+                        .collect(),
+                    function
+                        .outputs
+                        .iter()
+                        .map(|output| short_type_name(&output.r#type))
+                        .collect(),
+                )
+            }
+        };
+
+        let mut signature = format!("{}({})", name, inputs.join(", "));
+        match (self, outputs.len()) {
+            (_, 0) => {}
+            (Self::Sierra(_), 1) => {
+                signature.push_str(" -> ");
+                signature.push_str(&outputs[0]);
+            }
+            _ => {
+                signature.push_str(&format!(" -> ({})", outputs.join(", ")));
+            }
+        }
+
+        Some(signature)
+    }
+
+    /// Whether the contract exposes a function with `selector`, or a legacy `__default__`
+    /// entrypoint that catches all selectors.
+    pub fn has_function(&self, selector: FieldElement) -> bool {
+        match self {
+            Self::Legacy(entries) => entries.iter().any(|entry| match entry {
+                LegacyContractAbiEntry::Function(function) => {
+                    function.name == "__default__" || matches_selector(&function.name, selector)
+                }
+                _ => false,
+            }),
+            Self::Sierra(entries) => sierra_has_function(entries, selector),
+        }
+    }
+
+    /// Reverse lookup of the name of the function with `selector`.
+    pub fn find_function_name(&self, selector: FieldElement) -> Option<String> {
+        match self {
+            Self::Legacy(entries) => entries.iter().find_map(|entry| match entry {
+                LegacyContractAbiEntry::Function(function)
+                    if matches_selector(&function.name, selector) =>
+                {
+                    Some(function.name.clone())
+                }
+                _ => None,
+            }),
+            Self::Sierra(entries) => find_sierra_function_name(entries, selector),
+        }
+    }
+
+    /// Number of top-level ABI entries. Sierra interfaces count as a single entry.
+    pub fn item_count(&self) -> usize {
+        match self {
+            Self::Legacy(entries) => entries.len(),
+            Self::Sierra(entries) => entries.len(),
+        }
+    }
+
+    /// Counts the functions marked as view. Entry points don't carry this distinction, so it's only
+    /// known from the ABI.
+    pub fn view_function_count(&self) -> usize {
+        match self {
+            Self::Legacy(entries) => entries
+                .iter()
+                .filter(|entry| {
+                    matches!(
+                        entry,
+                        LegacyContractAbiEntry::Function(LegacyFunctionAbiEntry {
+                            state_mutability: Some(FunctionStateMutability::View),
+                            ..
+                        })
+                    )
+                })
+                .count(),
+            Self::Sierra(entries) => count_sierra_view_functions(entries),
+        }
+    }
+
+    /// Finds a view function without inputs returning a single value, named like the getters
+    /// proxies usually have for their implementation.
+    pub fn find_proxy_implementation_getter(&self) -> Option<String> {
+        PROXY_IMPLEMENTATION_GETTERS
+            .iter()
+            .find(|name| match self {
+                Self::Legacy(entries) => entries.iter().any(|entry| {
+                    matches!(
+                        entry,
+                        LegacyContractAbiEntry::Function(function)
+                            if function.name == **name
+                                && function.inputs.is_empty()
+                                && function.outputs.len() == 1
+                    )
+                }),
+                Self::Sierra(entries) => find_sierra_function(entries, name)
+                    .map_or(false, |function| {
+                        function.inputs.is_empty() && function.outputs.len() == 1
+                    }),
+            })
+            .map(|name| name.to_string())
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        matches!(self, Self::Legacy(_))
+    }
+
+    /// Flattens the ABI into functions, L1 handlers, events, structs, and enums. Sierra functions
+    /// inside interfaces are listed as plain functions, as where they're declared doesn't change
+    /// how they're called.
+    pub fn interface_items(&self) -> Vec<AbiItem> {
+        let mut items = vec![];
+        match self {
+            Self::Legacy(entries) => {
+                for entry in entries.iter() {
+                    items.push(match entry {
+                        LegacyContractAbiEntry::Function(function) => AbiItem {
+                            kind: match function.r#type {
+                                LegacyFunctionAbiType::L1Handler => "l1_handler",
+                                LegacyFunctionAbiType::Function
+                                | LegacyFunctionAbiType::Constructor => "function",
+                            },
+                            name: function.name.clone(),
+                            signature: format!(
+                                "{}{}",
+                                self.find_function_signature(&function.name)
+                                    .unwrap_or_else(|| function.name.clone()),
+                                match function.state_mutability {
+                                    Some(FunctionStateMutability::View) => " [view]",
+                                    None => "",
+                                }
+                            ),
+                        },
+                        LegacyContractAbiEntry::Event(event) => AbiItem {
+                            kind: "event",
+                            name: event.name.clone(),
+                            signature: format!(
+                                "{} {{ {} }}",
+                                event.name,
+                                event
+                                    .keys
+                                    .iter()
+                                    .map(|key| format!("{}: {} (key)", key.name, key.r#type))
+                                    .chain(
+                                        event
+                                            .data
+                                            .iter()
+                                            .map(|data| format!("{}: {}", data.name, data.r#type))
+                                    )
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            ),
+                        },
+                        LegacyContractAbiEntry::Struct(entry) => {
+                            let mut members = entry.members.iter().collect::<Vec<_>>();
+                            members.sort_by_key(|member| member.offset);
+
+                            AbiItem {
+                                kind: "struct",
+                                name: entry.name.clone(),
+                                signature: format!(
+                                    "{} {{ {} }}",
+                                    entry.name,
+                                    members
+                                        .into_iter()
+                                        .map(|member| format!("{}: {}", member.name, member.r#type))
+                                        .collect::<Vec<_>>()
+                                        .join(", ")
+                                ),
+                            }
+                        }
+                    });
+                }
+            }
+            Self::Sierra(entries) => collect_sierra_items(entries, &mut items),
+        }
+        items
+    }
+
+    /// Lists the functions, L1 handlers, and constructor in the order the ABI declares them.
+    pub fn entrypoints(&self) -> Vec<Entrypoint> {
+        let mut entrypoints = vec![];
+        match self {
+            Self::Legacy(entries) => {
+                for entry in entries.iter() {
+                    if let LegacyContractAbiEntry::Function(function) = entry {
+                        entrypoints.push(Entrypoint {
+                            kind: match (function.r#type, function.state_mutability) {
+                                (LegacyFunctionAbiType::Constructor, _) => "constructor",
+                                (LegacyFunctionAbiType::L1Handler, _) => "l1_handler",
+                                (_, Some(FunctionStateMutability::View)) => "view",
+                                (_, None) => "external",
+                            },
+                            name: function.name.clone(),
+                            signature: self
+                                .find_function_signature(&function.name)
+                                .unwrap_or_else(|| function.name.clone()),
+                        });
+                    }
+                }
+            }
+            Self::Sierra(entries) => collect_sierra_entrypoints(self, entries, &mut entrypoints),
+        }
+        entrypoints
+    }
+
+    /// Suggests the function names closest to a name that's not in the ABI, for "did you mean"
+    /// hints on typos.
+    pub fn suggest_function_names(&self, name: &str) -> Vec<String> {
+        let mut names = vec![];
+        match self {
+            Self::Legacy(entries) => {
+                for entry in entries.iter() {
+                    if let LegacyContractAbiEntry::Function(function) = entry {
+                        names.push(function.name.as_str());
+                    }
+                }
+            }
+            Self::Sierra(entries) => collect_sierra_function_names(entries, &mut names),
+        }
+
+        // Anything further away than a third of the name is unlikely to be a typo
+        let max_distance = (name.chars().count() / 3).max(1);
+        let mut candidates = names
+            .into_iter()
+            .map(|candidate| (edit_distance(name, candidate), candidate))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .collect::<Vec<_>>();
+        candidates.sort();
+        candidates.dedup();
+
+        candidates
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, candidate)| candidate.to_owned())
+            .collect()
+    }
+
+    pub fn find_event(&self, selector: FieldElement) -> Option<EventDefinition> {
+        match self {
+            Self::Legacy(entries) => entries.iter().find_map(|entry| match entry {
+                LegacyContractAbiEntry::Event(event) if matches_selector(&event.name, selector) => {
+                    Some(EventDefinition {
+                        name: event.name.clone(),
+                        keys: event
+                            .keys
+                            .iter()
+                            .map(|key| TypedMember {
+                                name: key.name.clone(),
+                                r#type: key.r#type.clone(),
+                            })
+                            .collect(),
+                        data: event
+                            .data
+                            .iter()
+                            .map(|data| TypedMember {
+                                name: data.name.clone(),
+                                r#type: data.r#type.clone(),
+                            })
+                            .collect(),
+                    })
+                }
+                _ => None,
+            }),
+            Self::Sierra(entries) => {
+                // With Cairo 2 events, the first key is the selector of the variant name in the
+                // contract event enum, which is almost always the same as the name of the struct.
+                let struct_name = entries.iter().find_map(|entry| match entry {
+                    AbiEntry::Event(AbiEvent::Typed(TypedAbiEvent::Enum(event))) => {
+                        event.variants.iter().find_map(|variant| {
+                            if matches_selector(&variant.name, selector) {
+                                Some(variant.r#type.clone())
+                            } else {
+                                None
+                            }
+                        })
+                    }
+                    _ => None,
+                });
+
+                entries.iter().find_map(|entry| match entry {
+                    AbiEntry::Event(AbiEvent::Typed(TypedAbiEvent::Struct(event)))
+                        if struct_name.as_ref() == Some(&event.name)
+                            || matches_selector(last_path_segment(&event.name), selector) =>
+                    {
+                        let mut keys = vec![];
+                        let mut data = vec![];
+                        for member in event.members.iter() {
+                            let typed_member = TypedMember {
+                                name: member.name.clone(),
+                                r#type: member.r#type.clone(),
+                            };
+                            match member.kind {
+                                EventFieldKind::Key => keys.push(typed_member),
+                                EventFieldKind::Data => data.push(typed_member),
+                                EventFieldKind::Nested => return None,
+                            }
+                        }
+
+                        Some(EventDefinition {
+                            name: last_path_segment(&event.name).to_owned(),
+                            keys,
+                            data,
+                        })
+                    }
+                    // Cairo 1 events put everything in data
+                    AbiEntry::Event(AbiEvent::Untyped(event))
+                        if matches_selector(&event.name, selector) =>
+                    {
+                        Some(EventDefinition {
+                            name: event.name.clone(),
+                            keys: vec![],
+                            data: event
+                                .inputs
+                                .iter()
+                                .map(|input| TypedMember {
+                                    name: input.name.clone(),
+                                    r#type: input.r#type.clone(),
+                                })
+                                .collect(),
+                        })
+                    }
+                    _ => None,
+                })
+            }
+        }
+    }
+
+    /// Decodes event keys and data against the ABI. Returns `None` if the event is not found or the
+    /// emitted values don't match the definition.
+    pub fn decode_event(
+        &self,
+        keys: &[FieldElement],
+        data: &[FieldElement],
+    ) -> Option<DecodedEvent> {
+        let (selector, keys) = keys.split_first()?;
+        let definition = self.find_event(*selector)?;
+
+        let mut fields = Map::new();
+        self.decode_members(&definition.keys, keys, &mut fields)?;
+        self.decode_members(&definition.data, data, &mut fields)?;
+
+        Some(DecodedEvent {
+            name: definition.name,
+            fields,
+        })
+    }
+
+    /// Decodes function inputs or outputs against the ABI, requiring all values to be consumed.
+    pub fn decode_members(
+        &self,
+        members: &[TypedMember],
+        values: &[FieldElement],
+        fields: &mut Map<String, Value>,
+    ) -> Option<()> {
+        let types = members
+            .iter()
+            .map(|member| member.r#type.clone())
+            .collect::<Vec<_>>();
+
+        for (member, value) in members.iter().zip(self.decode_values(&types, values)?) {
+            fields.insert(member.name.clone(), value);
+        }
+
+        Some(())
+    }
+
+    /// Decodes values of the given types in order, requiring all values to be consumed.
+    pub fn decode_values(&self, types: &[String], values: &[FieldElement]) -> Option<Vec<Value>> {
+        let mut values = values.iter();
+        let mut decoded = vec![];
+
+        // Cairo 0 arrays (`felt*`) are always preceded by their length
+        let mut last_felt = None;
+
+        for r#type in types.iter() {
+            let value = match (self, r#type.strip_suffix('*')) {
+                (Self::Legacy(_), Some(element_type)) => {
+                    let len = felt_to_usize(last_felt?)?;
+                    let mut elements = vec![];
+                    for _ in 0..len {
+                        elements.push(self.decode_value(element_type, &mut values)?);
+                    }
+                    Value::Array(elements)
+                }
+                _ => {
+                    last_felt = values.as_slice().first().copied();
+                    self.decode_value(r#type, &mut values)?
+                }
+            };
+
+            decoded.push(value);
+        }
+
+        if values.next().is_some() {
+            None
+        } else {
+            Some(decoded)
+        }
+    }
+
+    fn decode_value(
+        &self,
+        r#type: &str,
+        values: &mut std::slice::Iter<FieldElement>,
+    ) -> Option<Value> {
+        match self {
+            Self::Legacy(entries) => match r#type {
+                "felt" => Some(felt_to_value(*values.next()?)),
+                "Uint256" => decode_u256(values),
+                _ => {
+                    let struct_entry = entries.iter().find_map(|entry| match entry {
+                        LegacyContractAbiEntry::Struct(entry) if entry.name == r#type => {
+                            Some(entry)
+                        }
+                        _ => None,
+                    })?;
+
+                    let mut members = struct_entry.members.iter().collect::<Vec<_>>();
+                    members.sort_by_key(|member| member.offset);
+
+                    let mut fields = Map::new();
+                    for member in members.into_iter() {
+                        fields.insert(
+                            member.name.clone(),
+                            self.decode_value(&member.r#type, values)?,
+                        );
+                    }
+                    Some(Value::Object(fields))
+                }
+            },
+            Self::Sierra(entries) => {
+                if r#type == "()" {
+                    return Some(Value::Null);
+                }
+                if r#type == "core::integer::u256" {
+                    return decode_u256(values);
+                }
+                if r#type == "core::bool" {
+                    return Some(Value::Bool(*values.next()? != FieldElement::ZERO));
+                }
+                if let Some(inner) = r#type
+                    .strip_prefix('(')
+                    .and_then(|inner| inner.strip_suffix(')'))
+                {
+                    let mut elements = vec![];
+                    for element_type in split_top_level(inner)?.into_iter() {
+                        elements.push(self.decode_value(element_type, values)?);
+                    }
+                    return Some(Value::Array(elements));
+                }
+                if let Some(element_type) = r#type
+                    .strip_prefix("core::array::Array::<")
+                    .or_else(|| r#type.strip_prefix("core::array::Span::<"))
+                    .and_then(|inner| inner.strip_suffix('>'))
+                {
+                    let len = felt_to_usize(*values.next()?)?;
+                    let mut elements = vec![];
+                    for _ in 0..len {
+                        elements.push(self.decode_value(element_type, values)?);
+                    }
+                    return Some(Value::Array(elements));
+                }
+
+                for entry in entries.iter() {
+                    match entry {
+                        AbiEntry::Struct(entry) if entry.name == r#type => {
+                            let mut fields = Map::new();
+                            for member in entry.members.iter() {
+                                fields.insert(
+                                    member.name.clone(),
+                                    self.decode_value(&member.r#type, values)?,
+                                );
+                            }
+                            return Some(Value::Object(fields));
+                        }
+                        AbiEntry::Enum(entry) if entry.name == r#type => {
+                            let variant = entry.variants.get(felt_to_usize(*values.next()?)?)?;
+                            return Some(if variant.r#type == "()" {
+                                Value::String(variant.name.clone())
+                            } else {
+                                let mut fields = Map::new();
+                                fields.insert(
+                                    variant.name.clone(),
+                                    self.decode_value(&variant.r#type, values)?,
+                                );
+                                Value::Object(fields)
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+
+                // Anything else from the core library is assumed to be a single felt (integers,
+                // addresses, class hashes, etc.). Tuples and unknown types can't be decoded.
+                if r#type.starts_with("core::") {
+                    Some(felt_to_value(*values.next()?))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Parses a comma-separated list of types. Besides full Cairo type paths, short forms like `felt`,
+/// `u256`, `Array<u8>` and `(u32, bool)` are accepted.
+pub fn parse_type_list(raw: &str) -> Result<Vec<String>> {
+    match split_top_level(raw) {
+        Some(types) => types.into_iter().map(normalize_type).collect(),
+        None => anyhow::bail!("invalid type list: {}", raw),
+    }
+}
+
+fn normalize_type(raw: &str) -> Result<String> {
+    let raw = raw.trim();
+
+    if raw.is_empty() {
+        anyhow::bail!("empty type");
+    }
+    if raw == "()" {
+        return Ok(raw.to_owned());
+    }
+
+    if let Some(inner) = raw
+        .strip_prefix('(')
+        .and_then(|inner| inner.strip_suffix(')'))
+    {
+        let elements = match split_top_level(inner) {
+            Some(elements) => elements
+                .into_iter()
+                .map(normalize_type)
+                .collect::<Result<Vec<_>>>()?,
+            None => anyhow::bail!("invalid type: {}", raw),
+        };
+        return Ok(format!("({})", elements.join(", ")));
+    }
+
+    if let Some(inner) = [
+        "Array<",
+        "Span<",
+        "core::array::Array::<",
+        "core::array::Span::<",
+    ]
+    .iter()
+    .find_map(|prefix| raw.strip_prefix(prefix))
+    .and_then(|inner| inner.strip_suffix('>'))
+    {
+        return Ok(format!("core::array::Array::<{}>", normalize_type(inner)?));
+    }
+
+    if raw.contains(['<', '>', '(', ')', ',']) {
+        anyhow::bail!("invalid type: {}", raw);
+    }
+
+    Ok(match raw {
+        "felt" | "felt252" => "core::felt252".into(),
+        "u256" => "core::integer::u256".into(),
+        "bool" => "core::bool".into(),
+        _ if raw.contains("::") => raw.into(),
+        // Anything else is assumed to be a single-felt type from the core library
+        _ => format!("core::{}", raw),
+    })
+}
+
+/// Splits on commas that are not nested in `<>` or `()`. Returns `None` if brackets don't match.
+fn split_top_level(raw: &str) -> Option<Vec<&str>> {
+    let mut parts = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (ind, char) in raw.char_indices() {
+        match char {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                parts.push(raw[start..ind].trim());
+                start = ind + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if depth != 0 {
+        return None;
+    }
+
+    // Allows a trailing comma, as in single element tuples
+    let last = raw[start..].trim();
+    if !last.is_empty() {
+        parts.push(last);
+    }
+
+    Some(parts)
+}
+
+fn find_sierra_function<'a>(entries: &'a [AbiEntry], name: &str) -> Option<&'a AbiFunction> {
+    entries.iter().find_map(|entry| match entry {
+        AbiEntry::Function(function) if function.name == name => Some(function),
+        // Cairo 2 puts external functions inside interfaces
+        AbiEntry::Interface(interface) => find_sierra_function(&interface.items, name),
+        _ => None,
+    })
+}
+
+fn collect_sierra_function_names<'a>(entries: &'a [AbiEntry], names: &mut Vec<&'a str>) {
+    for entry in entries.iter() {
+        match entry {
+            AbiEntry::Function(function) => names.push(&function.name),
+            AbiEntry::Interface(interface) => {
+                collect_sierra_function_names(&interface.items, names)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn count_sierra_view_functions(entries: &[AbiEntry]) -> usize {
+    entries
+        .iter()
+        .map(|entry| match entry {
+            AbiEntry::Function(function) => {
+                usize::from(matches!(function.state_mutability, StateMutability::View))
+            }
+            AbiEntry::Interface(interface) => count_sierra_view_functions(&interface.items),
+            _ => 0,
+        })
+        .sum()
+}
+
+fn sierra_has_function(entries: &[AbiEntry], selector: FieldElement) -> bool {
+    find_sierra_function_name(entries, selector).is_some()
+}
+
+fn find_sierra_function_name(entries: &[AbiEntry], selector: FieldElement) -> Option<String> {
+    entries.iter().find_map(|entry| match entry {
+        AbiEntry::Function(function) if matches_selector(&function.name, selector) => {
+            Some(function.name.clone())
+        }
+        AbiEntry::Interface(interface) => find_sierra_function_name(&interface.items, selector),
+        _ => None,
+    })
+}
+
+fn collect_sierra_entrypoints(
+    abi: &ContractAbi,
+    entries: &[AbiEntry],
+    entrypoints: &mut Vec<Entrypoint>,
+) {
+    let format_inputs = |name: &str, inputs: &[AbiNamedMember]| {
+        format!(
+            "{}({})",
+            name,
+            inputs
+                .iter()
+                .map(|input| format!("{}: {}", input.name, short_type_name(&input.r#type)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+
+    for entry in entries.iter() {
+        entrypoints.push(match entry {
+            AbiEntry::Function(function) => Entrypoint {
+                kind: match function.state_mutability {
+                    StateMutability::View => "view",
+                    StateMutability::External => "external",
+                },
+                name: function.name.clone(),
+                signature: abi
+                    .find_function_signature(&function.name)
+                    .unwrap_or_else(|| function.name.clone()),
+            },
+            AbiEntry::L1Handler(function) => Entrypoint {
+                kind: "l1_handler",
+                name: function.name.clone(),
+                signature: format_inputs(&function.name, &function.inputs),
+            },
+            AbiEntry::Constructor(constructor) => Entrypoint {
+                kind: "constructor",
+                name: constructor.name.clone(),
+                signature: format_inputs(&constructor.name, &constructor.inputs),
+            },
+            AbiEntry::Interface(interface) => {
+                collect_sierra_entrypoints(abi, &interface.items, entrypoints);
+                continue;
+            }
+            _ => continue,
+        });
+    }
+}
+
+fn collect_sierra_items(entries: &[AbiEntry], items: &mut Vec<AbiItem>) {
+    let format_members = |members: &[AbiNamedMember]| {
+        members
+            .iter()
+            .map(|member| format!("{}: {}", member.name, short_type_name(&member.r#type)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    for entry in entries.iter() {
+        let (kind, function) = match entry {
+            AbiEntry::Function(function) => ("function", function),
+            AbiEntry::L1Handler(function) => ("l1_handler", function),
+            AbiEntry::Constructor(constructor) => {
+                items.push(AbiItem {
+                    kind: "function",
+                    name: constructor.name.clone(),
+                    signature: format!(
+                        "{}({})",
+                        constructor.name,
+                        format_members(&constructor.inputs)
+                    ),
+                });
+                continue;
+            }
+            AbiEntry::Interface(interface) => {
+                collect_sierra_items(&interface.items, items);
+                continue;
+            }
+            AbiEntry::Event(AbiEvent::Typed(TypedAbiEvent::Struct(event))) => {
+                items.push(AbiItem {
+                    kind: "event",
+                    name: event.name.clone(),
+                    signature: format!(
+                        "{} {{ {} }}",
+                        last_path_segment(&event.name),
+                        event
+                            .members
+                            .iter()
+                            .map(|member| format!(
+                                "{}: {}{}",
+                                member.name,
+                                short_type_name(&member.r#type),
+                                match member.kind {
+                                    EventFieldKind::Key => " (key)",
+                                    EventFieldKind::Data => "",
+                                    EventFieldKind::Nested => " (nested)",
+                                }
+                            ))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                });
+                continue;
+            }
+            AbiEntry::Event(AbiEvent::Typed(TypedAbiEvent::Enum(event))) => {
+                items.push(AbiItem {
+                    kind: "event",
+                    name: event.name.clone(),
+                    signature: format!(
+                        "{} {{ {} }}",
+                        last_path_segment(&event.name),
+                        event
+                            .variants
+                            .iter()
+                            .map(|variant| format!(
+                                "{}: {}",
+                                variant.name,
+                                short_type_name(&variant.r#type)
+                            ))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                });
+                continue;
+            }
+            AbiEntry::Event(AbiEvent::Untyped(event)) => {
+                items.push(AbiItem {
+                    kind: "event",
+                    name: event.name.clone(),
+                    signature: format!("{}({})", event.name, format_members(&event.inputs)),
+                });
+                continue;
+            }
+            AbiEntry::Struct(entry) => {
+                items.push(AbiItem {
+                    kind: "struct",
+                    name: entry.name.clone(),
+                    signature: format!(
+                        "{} {{ {} }}",
+                        last_path_segment(&entry.name),
+                        format_members(&entry.members)
+                    ),
+                });
+                continue;
+            }
+            AbiEntry::Enum(entry) => {
+                items.push(AbiItem {
+                    kind: "enum",
+                    name: entry.name.clone(),
+                    signature: format!(
+                        "{} {{ {} }}",
+                        last_path_segment(&entry.name),
+                        format_members(&entry.variants)
+                    ),
+                });
+                continue;
+            }
+            // Impls only name the interface they implement, whose items are listed already
+            AbiEntry::Impl(_) => continue,
+        };
+
+        let mut signature = format!("{}({})", function.name, format_members(&function.inputs));
+        match function.outputs.as_slice() {
+            [] => {}
+            [output] => signature.push_str(&format!(" -> {}", short_type_name(&output.r#type))),
+            outputs => signature.push_str(&format!(
+                " -> ({})",
+                outputs
+                    .iter()
+                    .map(|output| short_type_name(&output.r#type))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+        if matches!(function.state_mutability, StateMutability::View) {
+            signature.push_str(" [view]");
+        }
+
+        items.push(AbiItem {
+            kind,
+            name: function.name.clone(),
+            signature,
+        });
+    }
+}
+
+fn matches_selector(name: &str, selector: FieldElement) -> bool {
+    starknet_keccak(name.as_bytes()) == selector
+}
+
+/// Formats a "did you mean" hint to append to an error message, if there's anything to suggest.
+pub fn format_suggestions(suggestions: &[String]) -> String {
+    match suggestions {
+        [] => String::new(),
+        [suggestion] => format!(" Did you mean \"{}\"?", suggestion),
+        _ => format!(
+            " Did you mean one of {}?",
+            suggestions
+                .iter()
+                .map(|suggestion| format!("\"{}\"", suggestion))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+fn last_path_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+/// Shortens all paths in a type name, e.g. `core::array::Array::<core::felt252>` becomes
+/// `Array::<felt252>`.
+pub fn short_type_name(r#type: &str) -> String {
+    let regex_path = Regex::new("(?:[A-Za-z_][A-Za-z0-9_]*::)+([A-Za-z_][A-Za-z0-9_]*)").unwrap();
+    regex_path.replace_all(r#type, "$1").into_owned()
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+
+    let mut previous_row = (0..=b.len()).collect::<Vec<_>>();
+    for (ind_a, char_a) in a.chars().enumerate() {
+        let mut current_row = vec![ind_a + 1];
+        for (ind_b, char_b) in b.iter().enumerate() {
+            let substitution = previous_row[ind_b] + usize::from(char_a != *char_b);
+            let insertion = current_row[ind_b] + 1;
+            let deletion = previous_row[ind_b + 1] + 1;
+            current_row.push(substitution.min(insertion).min(deletion));
+        }
+        previous_row = current_row;
+    }
+
+    previous_row[b.len()]
+}
+
+fn felt_to_value(felt: FieldElement) -> Value {
+    Value::String(format!("{:#x}", felt))
+}
+
+fn felt_to_usize(felt: FieldElement) -> Option<usize> {
+    u64::try_from(felt).ok()?.try_into().ok()
+}
+
+fn decode_u256(values: &mut std::slice::Iter<FieldElement>) -> Option<Value> {
+    let low = BigUint::from_bytes_be(&values.next()?.to_bytes_be());
+    let high = BigUint::from_bytes_be(&values.next()?.to_bytes_be());
+
+    Some(Value::String(format!("{:#x}", (high << 128) + low)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn sierra_function(name: &str) -> Value {
+        json!({
+            "type": "function",
+            "name": name,
+            "inputs": [],
+            "outputs": [],
+            "state_mutability": "external"
+        })
+    }
+
+    fn sierra_abi() -> ContractAbi {
+        ContractAbi::Sierra(
+            serde_json::from_value(json!([
+                sierra_function("constructor_helper"),
+                {
+                    "type": "interface",
+                    "name": "IERC20",
+                    "items": [
+                        sierra_function("transfer"),
+                        sierra_function("transfer_from"),
+                        sierra_function("total_supply"),
+                        sierra_function("balance_of"),
+                    ]
+                },
+            ]))
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_suggest_function_names() {
+        let abi = sierra_abi();
+
+        // Functions nested in interfaces are suggested too
+        assert_eq!(abi.suggest_function_names("tranfser"), ["transfer"]);
+        assert_eq!(abi.suggest_function_names("balanceof"), ["balance_of"]);
+        // Closest first
+        assert_eq!(
+            abi.suggest_function_names("transfer_frm"),
+            ["transfer_from", "transfer"]
+        );
+        assert_eq!(
+            format_suggestions(&abi.suggest_function_names("transfe")),
+            " Did you mean \"transfer\"?"
+        );
+
+        // Nothing close enough
+        assert!(abi.suggest_function_names("mint").is_empty());
+        assert_eq!(format_suggestions(&abi.suggest_function_names("mint")), "");
+    }
+
+    #[test]
+    fn test_suggest_function_names_legacy() {
+        let abi = ContractAbi::Legacy(
+            serde_json::from_value(json!([
+                {
+                    "type": "function",
+                    "name": "balanceOf",
+                    "inputs": [{ "name": "account", "type": "felt" }],
+                    "outputs": [{ "name": "balance", "type": "felt" }],
+                    "stateMutability": "view"
+                },
+                {
+                    "type": "function",
+                    "name": "balanceAt",
+                    "inputs": [],
+                    "outputs": []
+                },
+            ]))
+            .unwrap(),
+        );
+
+        assert_eq!(
+            abi.suggest_function_names("balanceOff"),
+            ["balanceOf", "balanceAt"]
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("transfer", "transfer"), 0);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("abc", ""), 3);
+        // Substitution, insertion, and deletion
+        assert_eq!(edit_distance("transfer", "transfar"), 1);
+        assert_eq!(edit_distance("transfer", "transfers"), 1);
+        assert_eq!(edit_distance("transfer", "tranfer"), 1);
+        // A swap is 2 edits
+        assert_eq!(edit_distance("tranfser", "transfer"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        // Characters are compared, not bytes
+        assert_eq!(edit_distance("café", "cafe"), 1);
+    }
+}
@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::{fmt::Display, path::Path};
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -10,17 +10,44 @@ use starknet::{
     macros::felt,
 };
 
+use crate::{
+    address_book::format_chain_id,
+    chain_id::ChainIdSource,
+    config::{parse_chain_id, Config},
+    signer::SignerArgs,
+};
+
+/// OpenZeppelin account contract v0.6.1 compiled with cairo-lang v0.11.0.2
+pub const OZ_ACCOUNT_CLASS_HASH: FieldElement =
+    felt!("0x048dd59fabc729a5db3afdf649ecaf388e931647ab2f53ca3c6183fa480aa292");
+
 pub const KNOWN_ACCOUNT_CLASSES: [KnownAccountClass; 1] = [KnownAccountClass {
-    class_hash: felt!("0x048dd59fabc729a5db3afdf649ecaf388e931647ab2f53ca3c6183fa480aa292"),
+    class_hash: OZ_ACCOUNT_CLASS_HASH,
     variant: AccountVariantType::OpenZeppelin,
     description: "OpenZeppelin account contract v0.6.1 compiled with cairo-lang v0.11.0.2",
 }];
 
+#[serde_as]
 #[derive(Serialize, Deserialize)]
 pub struct AccountConfig {
     pub version: u64,
     pub variant: AccountVariant,
     pub deployment: DeploymentStatus,
+    /// Chain the account is deployed on. Only recorded once the account is known to be deployed,
+    /// and missing from older config files.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<FieldElement>,
+}
+
+/// A portable bundle of an account config, optionally along with its encrypted keystore, for
+/// moving account setups between machines.
+#[derive(Serialize, Deserialize)]
+pub struct AccountBundle {
+    pub version: u64,
+    pub account: AccountConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keystore: Option<serde_json::Value>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -73,6 +100,125 @@ pub struct DeployedStatus {
 }
 
 impl AccountConfig {
+    /// Loads the account config from `source`, which is either a file path, `-` for stdin, or the
+    /// config JSON itself when it starts with `{`. Inline JSON makes it possible to pass the
+    /// config through `STARKNET_ACCOUNT` where mounting files is inconvenient.
+    pub fn load(source: &Path) -> Result<Self> {
+        if source.as_os_str() == "-" {
+            return serde_json::from_reader(std::io::stdin())
+                .map_err(|err| anyhow::anyhow!("invalid account config JSON from stdin: {}", err));
+        }
+
+        if let Some(json) = source
+            .to_str()
+            .filter(|source| source.trim_start().starts_with('{'))
+        {
+            return serde_json::from_str(json)
+                .map_err(|err| anyhow::anyhow!("invalid inline account config JSON: {}", err));
+        }
+
+        if !source.exists() {
+            anyhow::bail!("account config file not found");
+        }
+
+        Ok(serde_json::from_reader(&mut std::fs::File::open(source)?)?)
+    }
+
+    /// Same as [`Self::load`], except that a plain name with no file behind it is looked up in
+    /// the accounts of the address book for the chain `chain_id_source` is on.
+    pub async fn load_or_resolve<S>(source: &Path, chain_id_source: S) -> Result<Self>
+    where
+        S: ChainIdSource,
+    {
+        let name = match source.to_str() {
+            Some(name) if !source.exists() && is_account_name(name) => name,
+            _ => return Self::load(source),
+        };
+
+        let chain_id = chain_id_source.get_chain_id().await?;
+
+        let mut account = None;
+        let mut other_chain_id = None;
+        for (book_chain_id, mut book) in Config::load()?.address_book.into_iter() {
+            let book_chain_id = parse_chain_id(&book_chain_id).ok_or_else(|| {
+                anyhow::anyhow!("invalid address book chain id: {}", book_chain_id)
+            })?;
+            match book.accounts.remove(name) {
+                Some(named_account) if book_chain_id == chain_id => account = Some(named_account),
+                Some(_) => other_chain_id = Some(book_chain_id),
+                None => {}
+            }
+        }
+
+        let account = match (account, other_chain_id) {
+            (Some(account), _) => account,
+            (None, Some(other_chain_id)) => anyhow::bail!(
+                "address book account \"{}\" only exists on chain {}, but the provider is on \
+                chain {}",
+                name,
+                format_chain_id(other_chain_id),
+                format_chain_id(chain_id)
+            ),
+            (None, None) => anyhow::bail!(
+                "account config file not found, and there's no account named \"{}\" in the \
+                address book",
+                name
+            ),
+        };
+        let address = account.address.ok_or_else(|| {
+            anyhow::anyhow!(
+                "address book account \"{}\" has no deployed address on chain {}",
+                name,
+                format_chain_id(chain_id)
+            )
+        })?;
+
+        Ok(Self {
+            version: 1,
+            variant: account.variant,
+            deployment: DeploymentStatus::Deployed(DeployedStatus {
+                class_hash: account.class_hash,
+                address,
+            }),
+            chain_id: Some(chain_id),
+        })
+    }
+
+    /// Loads the account config from the `--wallet` file if one is given, and from `account`
+    /// with [`Self::load_or_resolve`] otherwise.
+    pub async fn load_from_args<S>(
+        account: Option<&Path>,
+        signer: &SignerArgs,
+        chain_id_source: S,
+    ) -> Result<Self>
+    where
+        S: ChainIdSource,
+    {
+        if let Some(account_config) = signer.wallet_account()? {
+            return Ok(account_config);
+        }
+
+        match account {
+            Some(account) => Self::load_or_resolve(account, chain_id_source).await,
+            None => anyhow::bail!("no account provided. Use --account or --wallet"),
+        }
+    }
+
+    /// Makes sure the provider is on the chain recorded in the config, as using an account with
+    /// the wrong network only fails later with confusing errors. Configs without a recorded chain
+    /// pass.
+    pub fn check_chain_id(&self, chain_id: FieldElement) -> Result<()> {
+        match self.chain_id {
+            Some(account_chain_id) if account_chain_id != chain_id => anyhow::bail!(
+                "the account config is for chain {}, but the provider is on chain {}. Use \
+                --skip-chain-check to bypass this check.",
+                format_chain_id(account_chain_id),
+                format_chain_id(chain_id)
+            ),
+            _ => Ok(()),
+        }
+    }
+
     pub fn deploy_account_address(&self) -> Result<FieldElement> {
         let undeployed_status = match &self.deployment {
             DeploymentStatus::Undeployed(value) => value,
@@ -81,17 +227,34 @@ impl AccountConfig {
             }
         };
 
-        match &self.variant {
-            AccountVariant::OpenZeppelin(oz) => Ok(get_contract_address(
-                undeployed_status.salt,
-                undeployed_status.class_hash,
-                &[oz.public_key],
-                FieldElement::ZERO,
-            )),
+        Ok(self
+            .variant
+            .deployment_address(undeployed_status.class_hash, undeployed_status.salt))
+    }
+}
+
+impl AccountVariant {
+    /// Computes the address at which an account of this variant is deployed with a
+    /// `DEPLOY_ACCOUNT` transaction. This only depends on the public key, so the address can be
+    /// known before any signer exists.
+    pub fn deployment_address(&self, class_hash: FieldElement, salt: FieldElement) -> FieldElement {
+        match self {
+            AccountVariant::OpenZeppelin(oz) => {
+                get_contract_address(salt, class_hash, &[oz.public_key], FieldElement::ZERO)
+            }
         }
     }
 }
 
+/// Names are told apart from paths by having no separators or extensions, so that a missing file
+/// is never mistaken for an account name.
+fn is_account_name(source: &str) -> bool {
+    !source.is_empty()
+        && source
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
 impl Display for AccountVariantType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -99,3 +262,66 @@ impl Display for AccountVariantType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use starknet::core::chain_id;
+
+    fn deployed_account(chain_id: Option<&str>) -> AccountConfig {
+        let mut config = serde_json::json!({
+            "version": 1,
+            "variant": {
+                "type": "open_zeppelin",
+                "version": 1,
+                "public_key": "0x1"
+            },
+            "deployment": {
+                "status": "deployed",
+                "class_hash": format!("{:#x}", OZ_ACCOUNT_CLASS_HASH),
+                "address": "0x1234"
+            }
+        });
+        if let Some(chain_id) = chain_id {
+            config["chain_id"] = chain_id.into();
+        }
+
+        AccountConfig::load(Path::new(&config.to_string())).unwrap()
+    }
+
+    #[test]
+    fn test_check_chain_id() {
+        let account = deployed_account(Some("0x534e5f474f45524c49"));
+        assert_eq!(account.chain_id, Some(chain_id::TESTNET));
+        assert!(account.check_chain_id(chain_id::TESTNET).is_ok());
+        assert_eq!(
+            account
+                .check_chain_id(chain_id::MAINNET)
+                .unwrap_err()
+                .to_string(),
+            "the account config is for chain SN_GOERLI, but the provider is on chain SN_MAIN. Use \
+            --skip-chain-check to bypass this check."
+        );
+
+        // Chains that can't be decoded as a short string are shown in hex
+        assert!(account
+            .check_chain_id(FieldElement::MAX)
+            .unwrap_err()
+            .to_string()
+            .contains(&format!(
+                "the provider is on chain {:#x}.",
+                FieldElement::MAX
+            )));
+
+        // Older configs don't record the chain, so any chain passes
+        let account = deployed_account(None);
+        assert_eq!(account.chain_id, None);
+        for chain_id in [chain_id::MAINNET, chain_id::TESTNET, chain_id::TESTNET2] {
+            assert!(account.check_chain_id(chain_id).is_ok());
+        }
+        assert!(!serde_json::to_string(&account)
+            .unwrap()
+            .contains("chain_id"));
+    }
+}
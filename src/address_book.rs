@@ -1,14 +1,32 @@
 // Very temporary implementation of a hard-coded addres book
 
-use std::cell::OnceCell;
+use std::{cell::OnceCell, collections::HashMap, path::PathBuf};
 
 use anyhow::Result;
+use clap::Parser;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use starknet::{
-    core::{chain_id, types::FieldElement},
+    core::{chain_id, types::FieldElement, utils::parse_cairo_short_string},
     macros::felt,
 };
+use url::Url;
 
-use crate::chain_id::ChainIdSource;
+use crate::{
+    chain_id::ChainIdSource,
+    config::{parse_chain_id, AddressBookConfig, Config},
+    offline,
+    output::persist,
+    utils::unix_timestamp,
+    verbosity::warning,
+};
+
+/// Directory fetched address books are cached in.
+const REGISTRY_CACHE_DIR: &str = "~/.starkli/cache/address-book";
+
+/// Fetched address books are used for an hour unless configured otherwise.
+const DEFAULT_REGISTRY_TTL: u64 = 3600;
 
 pub const HARDCODED_ADDRESS_BOOK: [AddressBookEntry; 4] = [
     AddressBookEntry {
@@ -39,10 +57,94 @@ pub struct AddressBookEntry {
     pub address: FieldElement,
 }
 
+#[derive(Debug, Clone, Parser)]
+pub struct AliasArgs {
+    #[clap(
+        long = "alias",
+        help = "Address alias for this invocation only, in the form of name=address. Aliases take \
+        precedence over the address book"
+    )]
+    aliases: Vec<String>,
+    #[clap(
+        long,
+        value_name = "URL",
+        help = "URL of a shared address book registry to resolve names from, overriding the one \
+        in the config file. Entries in the config file take precedence over the registry"
+    )]
+    address_book_url: Option<Url>,
+    #[clap(
+        long,
+        help = "Fetch the address book registry again even if the cached copy is still fresh"
+    )]
+    refresh_address_book: bool,
+}
+
+/// Chain id to name to address.
+type AddressBookEntries = HashMap<FieldElement, HashMap<String, FieldElement>>;
+
 /// A resolver that lazily fetches chain id to avoid unnecessary network calls.
 pub struct AddressBookResolver<S> {
     chain_id_source: S,
     chain_id: OnceCell<FieldElement>,
+    aliases: HashMap<String, FieldElement>,
+    /// Entries from the config file, keyed by chain id and then by name.
+    user_entries: AddressBookEntries,
+    registry: Option<AddressBookRegistry>,
+    /// Entries from the registry, only fetched once a name needs to be looked up.
+    registry_entries: OnceCell<AddressBookEntries>,
+}
+
+/// Shared address book fetched from a URL and cached on disk.
+struct AddressBookRegistry {
+    url: Url,
+    ttl: u64,
+    refresh: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedAddressBook {
+    url: Url,
+    /// Unix timestamp of the fetch.
+    fetched_at: u64,
+    book: serde_json::Value,
+}
+
+impl AliasArgs {
+    pub fn into_resolver<S>(self, chain_id_source: S) -> Result<AddressBookResolver<S>> {
+        let mut aliases = HashMap::new();
+        for alias in self.aliases.into_iter() {
+            let (name, address) = alias.split_once('=').ok_or_else(|| {
+                anyhow::anyhow!("invalid alias \"{}\": expected name=address", alias)
+            })?;
+            let address = address
+                .parse::<FieldElement>()
+                .map_err(|_| anyhow::anyhow!("invalid address for alias \"{}\"", name))?;
+
+            if aliases.insert(name.to_owned(), address).is_some() {
+                anyhow::bail!("duplicate alias: {}", name);
+            }
+        }
+
+        let mut resolver = AddressBookResolver::with_config(chain_id_source)?;
+        if let Some(url) = self.address_book_url {
+            resolver.registry = Some(AddressBookRegistry {
+                url,
+                ttl: resolver
+                    .registry
+                    .as_ref()
+                    .map_or(DEFAULT_REGISTRY_TTL, |registry| registry.ttl),
+                refresh: false,
+            });
+        }
+        if let Some(registry) = &mut resolver.registry {
+            registry.refresh = self.refresh_address_book;
+        }
+
+        Ok(AddressBookResolver {
+            aliases,
+            ..resolver
+        })
+    }
 }
 
 impl<S> AddressBookResolver<S> {
@@ -50,8 +152,167 @@ impl<S> AddressBookResolver<S> {
         Self {
             chain_id_source,
             chain_id: OnceCell::new(),
+            aliases: HashMap::new(),
+            user_entries: HashMap::new(),
+            registry: None,
+            registry_entries: OnceCell::new(),
         }
     }
+
+    /// Creates a resolver with the address book from the config file, but no aliases.
+    pub fn with_config(chain_id_source: S) -> Result<Self> {
+        let config = Config::load()?;
+
+        Ok(Self {
+            user_entries: merge_address_book(config.address_book)?,
+            registry: config
+                .address_book_registry
+                .map(|registry| AddressBookRegistry {
+                    url: registry.url,
+                    ttl: registry.ttl_seconds.unwrap_or(DEFAULT_REGISTRY_TTL),
+                    refresh: false,
+                }),
+            ..Self::new(chain_id_source)
+        })
+    }
+
+    /// Falls back to no registry entries when the registry can't be loaded, so that local and
+    /// built-in entries keep working. This is only attempted once.
+    async fn registry_entries(&self) -> Option<&AddressBookEntries> {
+        let registry = match &self.registry {
+            Some(registry) => registry,
+            None => return None,
+        };
+
+        if self.registry_entries.get().is_none() {
+            let entries = match registry.load().await {
+                Ok(entries) => entries,
+                Err(err) => {
+                    warning!(
+                        "WARNING: {}. Only local and built-in address book entries are used.",
+                        err
+                    );
+                    HashMap::new()
+                }
+            };
+
+            // It's OK if another thread set it first
+            let _ = self.registry_entries.set(entries);
+        }
+
+        self.registry_entries.get()
+    }
+}
+
+impl AddressBookRegistry {
+    /// Uses the cached copy while it's fresh. A stale copy is still better than failing when the
+    /// registry can't be reached.
+    async fn load(&self) -> Result<AddressBookEntries> {
+        let cache_path = self.cache_path();
+        let cached = std::fs::read(&cache_path)
+            .ok()
+            .and_then(|content| serde_json::from_slice::<CachedAddressBook>(&content).ok())
+            .filter(|cached| cached.url == self.url);
+
+        let book = match cached {
+            Some(cached)
+                if !self.refresh
+                    && unix_timestamp() < cached.fetched_at.saturating_add(self.ttl) =>
+            {
+                cached.book
+            }
+            cached => match self.fetch().await {
+                Ok(book) => {
+                    let cached = CachedAddressBook {
+                        url: self.url.clone(),
+                        fetched_at: unix_timestamp(),
+                        book,
+                    };
+                    if let Err(err) = persist(
+                        &cache_path,
+                        &format!("{}\n", serde_json::to_string_pretty(&cached)?),
+                    ) {
+                        warning!(
+                            "WARNING: unable to cache the address book from {}: {}",
+                            self.url,
+                            err
+                        );
+                    }
+                    cached.book
+                }
+                Err(err) => match cached {
+                    Some(cached) => {
+                        warning!(
+                            "WARNING: {}. Using the copy cached {} seconds ago.",
+                            err,
+                            unix_timestamp().saturating_sub(cached.fetched_at)
+                        );
+                        cached.book
+                    }
+                    None => return Err(err),
+                },
+            },
+        };
+
+        merge_address_book(serde_json::from_value(book)?)
+    }
+
+    async fn fetch(&self) -> Result<serde_json::Value> {
+        offline::ensure_online(&format!("fetching the address book from {}", self.url))?;
+        let response = Client::new()
+            .get(self.url.clone())
+            .send()
+            .await
+            .map_err(|err| {
+                anyhow::anyhow!(
+                    "unable to fetch the address book from {}: {}",
+                    self.url,
+                    err
+                )
+            })?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "unable to fetch the address book from {}: HTTP status {}",
+                self.url,
+                response.status()
+            );
+        }
+        let book: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|err| anyhow::anyhow!("invalid address book from {}: {}", self.url, err))?;
+
+        // Validated before caching so that a broken registry never replaces a good copy
+        serde_json::from_value::<HashMap<String, AddressBookConfig>>(book.clone())
+            .map_err(|err| anyhow::anyhow!("invalid address book from {}: {}", self.url, err))
+            .and_then(merge_address_book)?;
+
+        Ok(book)
+    }
+
+    /// One file per URL, named after its hash.
+    fn cache_path(&self) -> PathBuf {
+        let digest = Sha256::digest(self.url.as_str().as_bytes());
+        PathBuf::from(shellexpand::tilde(REGISTRY_CACHE_DIR).into_owned())
+            .join(format!("{}.json", hex::encode(&digest[..8])))
+    }
+}
+
+/// Merges address book sections keyed by chain id. Different spellings of the same chain id are
+/// merged.
+fn merge_address_book(books: HashMap<String, AddressBookConfig>) -> Result<AddressBookEntries> {
+    let mut entries = HashMap::new();
+    for (chain_id, book) in books.into_iter() {
+        let chain_id = parse_chain_id(&chain_id)
+            .ok_or_else(|| anyhow::anyhow!("invalid address book chain id: {}", chain_id))?;
+
+        entries
+            .entry(chain_id)
+            .or_insert_with(HashMap::new)
+            .extend(book.entries);
+    }
+
+    Ok(entries)
 }
 
 impl<S> AddressBookResolver<S>
@@ -59,26 +320,136 @@ where
     S: ChainIdSource,
 {
     pub async fn resolve_name(&self, name: &str) -> Result<Option<FieldElement>> {
+        // Aliases are chain-agnostic so there's no need to fetch chain id for them
+        if let Some(address) = self.aliases.get(name) {
+            return Ok(Some(*address));
+        }
+
+        let chain_id = self.chain_id().await?;
+
+        // The registry is only fetched when the name isn't defined locally
+        if let Some(address) = self
+            .user_entries
+            .get(&chain_id)
+            .and_then(|entries| entries.get(name))
+        {
+            return Ok(Some(*address));
+        }
+
+        let registry_entries = self.registry_entries().await;
+        if let Some(address) = Self::find_entry(registry_entries, chain_id, name) {
+            return Ok(Some(address));
+        }
+
+        // Falling back to an entry from another chain would be dangerous, but pointing it out
+        // helps when the wrong network is selected by accident.
+        let other_chain_id = self
+            .user_entries
+            .iter()
+            .chain(registry_entries.into_iter().flatten())
+            .find(|(_, entries)| entries.contains_key(name))
+            .map(|(chain_id, _)| *chain_id)
+            .or_else(|| {
+                HARDCODED_ADDRESS_BOOK
+                    .iter()
+                    .find(|entry| entry.name == name)
+                    .map(|entry| entry.chain_id)
+            });
+        match other_chain_id {
+            Some(other_chain_id) => anyhow::bail!(
+                "address book entry \"{}\" only exists on chain {}, but the provider is on chain {}",
+                name,
+                format_chain_id(other_chain_id),
+                format_chain_id(chain_id)
+            ),
+            None => Ok(None),
+        }
+    }
+
+    /// Reverse lookup of [`Self::resolve_name`], returning the name of a known address on the
+    /// current chain.
+    pub async fn resolve_address(&self, address: FieldElement) -> Result<Option<&str>> {
+        if let Some((name, _)) = self.aliases.iter().find(|(_, value)| **value == address) {
+            return Ok(Some(name));
+        }
+
+        let chain_id = self.chain_id().await?;
+
+        if let Some(name) = find_name(&self.user_entries, chain_id, address) {
+            return Ok(Some(name));
+        }
+        if let Some(name) = self
+            .registry_entries()
+            .await
+            .and_then(|entries| find_name(entries, chain_id, address))
+        {
+            return Ok(Some(name));
+        }
+
+        Ok(HARDCODED_ADDRESS_BOOK.iter().find_map(|entry| {
+            if entry.chain_id == chain_id && entry.address == address {
+                Some(entry.name)
+            } else {
+                None
+            }
+        }))
+    }
+
+    /// Registry entries take precedence over the built-in entries. User entries are looked up
+    /// before, as they take precedence over both.
+    fn find_entry(
+        registry_entries: Option<&AddressBookEntries>,
+        chain_id: FieldElement,
+        name: &str,
+    ) -> Option<FieldElement> {
+        registry_entries
+            .and_then(|entries| entries.get(&chain_id))
+            .and_then(|entries| entries.get(name).copied())
+            .or_else(|| {
+                HARDCODED_ADDRESS_BOOK.iter().find_map(|entry| {
+                    if entry.chain_id == chain_id && entry.name == name {
+                        Some(entry.address)
+                    } else {
+                        None
+                    }
+                })
+            })
+    }
+
+    async fn chain_id(&self) -> Result<FieldElement> {
         let chain_id_cell = &self.chain_id;
 
-        let chain_id = match chain_id_cell.get() {
-            Some(chain_id) => *chain_id,
+        match chain_id_cell.get() {
+            Some(chain_id) => Ok(*chain_id),
             None => {
                 let chain_id = self.chain_id_source.get_chain_id().await?;
 
                 // It's OK if another thread set it first
                 let _ = chain_id_cell.set(chain_id);
 
-                chain_id
+                Ok(chain_id)
             }
-        };
+        }
+    }
+}
 
-        Ok(HARDCODED_ADDRESS_BOOK.iter().find_map(|entry| {
-            if entry.chain_id == chain_id && entry.name == name {
-                Some(entry.address)
-            } else {
-                None
-            }
-        }))
+fn find_name(
+    entries: &AddressBookEntries,
+    chain_id: FieldElement,
+    address: FieldElement,
+) -> Option<&str> {
+    entries.get(&chain_id).and_then(|entries| {
+        entries
+            .iter()
+            .find(|(_, value)| **value == address)
+            .map(|(name, _)| name.as_str())
+    })
+}
+
+/// Shows chain ids as short strings like `SN_MAIN` when possible.
+pub fn format_chain_id(chain_id: FieldElement) -> String {
+    match parse_cairo_short_string(&chain_id) {
+        Ok(name) if !name.is_empty() => name,
+        _ => format!("{:#x}", chain_id),
     }
 }
@@ -0,0 +1,127 @@
+use std::path::Path;
+
+use anyhow::Result;
+use clap::Parser;
+use reqwest::{header::CONTENT_TYPE, Client, Url};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    offline,
+    verbosity::{progress, warning},
+};
+
+/// Downloads larger than this are aborted. Real-world class artifacts are well below it.
+const MAX_DOWNLOAD_SIZE: usize = 32 * 1024 * 1024;
+
+#[derive(Debug, Clone, Parser)]
+pub struct ArtifactArgs {
+    #[clap(
+        long,
+        value_name = "HEX",
+        value_parser = parse_sha256,
+        help = "Abort unless the SHA-256 digest of the contract artifact file matches this hex value"
+    )]
+    artifact_sha256: Option<[u8; 32]>,
+}
+
+impl ArtifactArgs {
+    /// Reads the artifact at `file`, downloading it instead if it's an `http://` or `https://`
+    /// URL, and checks it against `--artifact-sha256` before it's parsed.
+    pub async fn read(&self, file: &Path) -> Result<Vec<u8>> {
+        let url = file
+            .to_str()
+            .filter(|file| file.starts_with("https://") || file.starts_with("http://"));
+        let content = match url {
+            Some(url) => download(url, self.artifact_sha256.is_some()).await?,
+            None => std::fs::read(file)?,
+        };
+
+        if let Some(expected_digest) = &self.artifact_sha256 {
+            let digest: [u8; 32] = Sha256::digest(&content).into();
+            if &digest != expected_digest {
+                anyhow::bail!(
+                    "artifact SHA-256 mismatch: the artifact hashes to {}, but {} is expected",
+                    hex::encode(digest),
+                    hex::encode(expected_digest)
+                );
+            }
+        }
+
+        Ok(content)
+    }
+}
+
+async fn download(url: &str, verified: bool) -> Result<Vec<u8>> {
+    let url = Url::parse(url).map_err(|err| anyhow::anyhow!("invalid artifact URL: {}", err))?;
+    if url.scheme() == "http" && !verified {
+        warning!(
+            "WARNING: downloading the contract artifact over plain HTTP. Consider using HTTPS, or \
+            verifying the artifact with --artifact-sha256."
+        );
+    }
+
+    offline::ensure_online(&format!("downloading the contract artifact from {}", url))?;
+    progress!("Downloading contract artifact from {}", url);
+
+    let mut response = Client::new().get(url.clone()).send().await?;
+    if !response.status().is_success() {
+        anyhow::bail!(
+            "unable to download artifact from {}: HTTP status {}",
+            url,
+            response.status()
+        );
+    }
+
+    // Artifact stores commonly serve files without a specific type. Anything that can't be a JSON
+    // file, like an HTML login page, is rejected.
+    if let Some(content_type) = response.headers().get(CONTENT_TYPE) {
+        let content_type = content_type.to_str().unwrap_or_default().to_lowercase();
+        let mime_type = content_type.split(';').next().unwrap_or_default().trim();
+        if !matches!(
+            mime_type,
+            "application/json" | "application/octet-stream" | "binary/octet-stream" | "text/plain"
+        ) && !mime_type.ends_with("+json")
+        {
+            anyhow::bail!(
+                "unexpected content type \"{}\" for artifact downloaded from {}",
+                mime_type,
+                url
+            );
+        }
+    }
+
+    if response
+        .content_length()
+        .map_or(false, |length| length > MAX_DOWNLOAD_SIZE as u64)
+    {
+        anyhow::bail!(
+            "artifact at {} is larger than the {} MiB limit",
+            url,
+            MAX_DOWNLOAD_SIZE / 1024 / 1024
+        );
+    }
+
+    // The length header is optional, so the limit is enforced while reading too
+    let mut content = vec![];
+    while let Some(chunk) = response.chunk().await? {
+        if content.len() + chunk.len() > MAX_DOWNLOAD_SIZE {
+            anyhow::bail!(
+                "artifact at {} is larger than the {} MiB limit",
+                url,
+                MAX_DOWNLOAD_SIZE / 1024 / 1024
+            );
+        }
+        content.extend_from_slice(&chunk);
+    }
+
+    Ok(content)
+}
+
+fn parse_sha256(raw: &str) -> Result<[u8; 32]> {
+    let digits = raw.strip_prefix("0x").unwrap_or(raw);
+    let mut digest = [0u8; 32];
+    hex::decode_to_slice(digits, &mut digest)
+        .map_err(|_| anyhow::anyhow!("expected a SHA-256 digest as 64 hex digits"))?;
+
+    Ok(digest)
+}
@@ -6,6 +6,7 @@ use starknet::core::types::{contract::SierraClass, FieldElement};
 use crate::{
     compiler::{BuiltInCompiler, CompilerVersion},
     network::{Network, NetworkSource},
+    verbosity::progress,
 };
 
 #[derive(Debug, Clone, Parser)]
@@ -34,7 +35,7 @@ impl CasmArgs {
             (None, Some(casm_hash)) => Ok(CasmHashSource::Hash(casm_hash.parse()?)),
             // Tries to detect compiler version if nothing provided
             (None, None) => {
-                eprintln!(
+                progress!(
                     "Sierra compiler version not specified. \
                     Attempting to automatically decide version to use..."
                 );
@@ -49,7 +50,7 @@ impl CasmArgs {
                             | Network::Integration => CompilerVersion::V2_0_1,
                         };
 
-                        eprintln!(
+                        progress!(
                             "Network detected: {}. \
                             Using the default compiler version for this network: {}. \
                             Use the --compiler-version flag to choose a different version.",
@@ -62,7 +63,7 @@ impl CasmArgs {
                     None => {
                         let default_version: CompilerVersion = Default::default();
 
-                        eprintln!(
+                        progress!(
                             "Unknown network. Falling back to the default compiler version {}. \
                             Use the --compiler-version flag to choose a different version.",
                             format!("{}", default_version).bright_yellow()
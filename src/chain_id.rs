@@ -20,3 +20,17 @@ where
             .map_err(|err| anyhow::anyhow!("unable to get chain id: {err}"))
     }
 }
+
+/// Chain id source for local utilities that don't take a provider. Address book names depend on
+/// the chain and thus can't be resolved with it.
+pub struct NoChainIdSource;
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl ChainIdSource for NoChainIdSource {
+    async fn get_chain_id(&self) -> Result<FieldElement> {
+        Err(anyhow::anyhow!(
+            "address book names can't be resolved without a provider. Use the address instead"
+        ))
+    }
+}
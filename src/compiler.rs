@@ -28,6 +28,18 @@ impl BuiltInCompiler {
     }
 
     pub fn compile(&self, class: &SierraClass) -> Result<FieldElement> {
+        if let Some(sierra_version) = SierraVersion::detect(class) {
+            let max_version = self.version.max_sierra_version();
+            if sierra_version > max_version {
+                anyhow::bail!(
+                    "unsupported Sierra version {}: compiler version {} only supports Sierra up to {}",
+                    sierra_version,
+                    self.version,
+                    max_version
+                );
+            }
+        }
+
         // We do this because the Sierra doesn't need ABI anyways. Feeding it with the ABI could
         // actually cause unnecessary deserialization errors due to ABI structure changes between
         // compiler versions.
@@ -58,6 +70,62 @@ impl BuiltInCompiler {
     }
 }
 
+/// Sierra version as encoded in the first felts of the Sierra program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SierraVersion {
+    major: u64,
+    minor: u64,
+    patch: u64,
+}
+
+impl SierraVersion {
+    /// Returns `None` for artifacts from early compilers that didn't embed the version.
+    pub fn detect(class: &SierraClass) -> Option<Self> {
+        Self::from_program(&class.sierra_program)
+    }
+
+    /// Reads the version embedded at the start of a Sierra program, as found in both artifacts
+    /// and classes fetched from the network.
+    pub fn from_program(program: &[FieldElement]) -> Option<Self> {
+        let mut felts = program.iter();
+
+        let mut next_component = || -> Option<u64> {
+            let component = u64::try_from(*felts.next()?).ok()?;
+
+            // Anything this large can't be a version number
+            if component > u16::MAX as u64 {
+                None
+            } else {
+                Some(component)
+            }
+        };
+
+        Some(Self {
+            major: next_component()?,
+            minor: next_component()?,
+            patch: next_component()?,
+        })
+    }
+}
+
+impl Display for SierraVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl CompilerVersion {
+    fn max_sierra_version(&self) -> SierraVersion {
+        match self {
+            Self::V2_0_1 => SierraVersion {
+                major: 1,
+                minor: 2,
+                patch: 0,
+            },
+        }
+    }
+}
+
 impl Default for CompilerVersion {
     fn default() -> Self {
         Self::V2_0_1
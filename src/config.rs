@@ -0,0 +1,123 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_with::serde_as;
+use starknet::core::{
+    serde::unsigned_field_element::UfeHex, types::FieldElement, utils::cairo_short_string_to_felt,
+};
+use url::Url;
+
+use crate::account::AccountVariant;
+
+const DEFAULT_CONFIG_PATH: &str = "~/.starkli/config.json";
+
+/// User configuration file, loaded from `~/.starkli/config.json` by default. The path can be
+/// changed with the `STARKLI_CONFIG` environment variable.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub networks: HashMap<String, NetworkConfig>,
+    /// Named addresses keyed by chain id, which can be either hex or a short string like
+    /// `SN_MAIN`, so that the same name resolves to the right address on each network.
+    #[serde(default)]
+    pub address_book: HashMap<String, AddressBookConfig>,
+    /// Shared address book fetched from a URL, with the same format as `address_book`. Entries
+    /// from `address_book` take precedence.
+    #[serde(default)]
+    pub address_book_registry: Option<AddressBookRegistryConfig>,
+    /// Transaction URL templates of block explorers keyed by chain id, with `{hash}` standing in
+    /// for the transaction hash. Used with `--explorer custom`.
+    #[serde(default)]
+    pub explorers: HashMap<String, String>,
+    /// Fee settings keyed by chain id, so that e.g. mainnet can use a more conservative buffer
+    /// than a local devnet. Command line options take precedence.
+    #[serde(default)]
+    pub fees: HashMap<String, FeeConfig>,
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+pub struct AddressBookConfig {
+    /// Accounts tracked by name, which can be used with `--account` in place of an account config
+    /// file.
+    #[serde(default)]
+    pub accounts: HashMap<String, NamedAccountConfig>,
+    #[serde_as(as = "HashMap<_, UfeHex>")]
+    #[serde(flatten)]
+    pub entries: HashMap<String, FieldElement>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AddressBookRegistryConfig {
+    pub url: Url,
+    /// How long a fetched copy is used before fetching it again. Defaults to an hour.
+    #[serde(default)]
+    pub ttl_seconds: Option<u64>,
+}
+
+/// Same as the account config file, but without a local file to keep in sync. The address is
+/// left out for accounts that aren't deployed on the chain yet.
+#[serde_as]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NamedAccountConfig {
+    pub variant: AccountVariant,
+    #[serde_as(as = "UfeHex")]
+    pub class_hash: FieldElement,
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default)]
+    pub address: Option<FieldElement>,
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkConfig {
+    pub rpc: Url,
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default)]
+    pub chain_id: Option<FieldElement>,
+}
+
+#[serde_as]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeeConfig {
+    /// Percentage added on top of fee estimates, same as `--fee-buffer`.
+    #[serde(default)]
+    pub buffer_percentage: Option<u64>,
+    /// Address of the fee token contract, for chains without a built-in one.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default)]
+    pub token_address: Option<FieldElement>,
+}
+
+/// Chain ids used as keys in the config file can be written either in hex or as short strings
+/// like `SN_MAIN`.
+pub fn parse_chain_id(raw: &str) -> Option<FieldElement> {
+    if raw.starts_with("0x") {
+        FieldElement::from_hex_be(raw).ok()
+    } else {
+        cairo_short_string_to_felt(raw).ok()
+    }
+}
+
+impl Config {
+    /// Loads the config file, falling back to the default config if the file doesn't exist.
+    pub fn load() -> Result<Self> {
+        let path = match std::env::var("STARKLI_CONFIG") {
+            Ok(path) => PathBuf::from(shellexpand::tilde(&path).into_owned()),
+            Err(_) => PathBuf::from(shellexpand::tilde(DEFAULT_CONFIG_PATH).into_owned()),
+        };
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        serde_json::from_reader(std::fs::File::open(&path)?)
+            .map_err(|err| anyhow::anyhow!("invalid config file {}: {}", path.display(), err))
+    }
+}
@@ -1,19 +1,51 @@
 use anyhow::Result;
 use num_bigint::BigUint;
-use starknet::core::{types::FieldElement, utils::cairo_short_string_to_felt};
+use starknet::{
+    core::{
+        types::FieldElement,
+        utils::{cairo_short_string_to_felt, parse_cairo_short_string},
+    },
+    macros::felt,
+};
 
-use crate::{address_book::AddressBookResolver, chain_id::ChainIdSource};
+use crate::{
+    address_book::AddressBookResolver,
+    chain_id::ChainIdSource,
+    timing::{self, Phase},
+};
+
+/// Contract addresses must be less than `2 ** 251 - 256`.
+const ADDR_BOUND: FieldElement =
+    felt!("0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff00");
+
+/// Marker placed before a `ByteArray` in panic data, so that it isn't mistaken for other felts.
+const BYTE_ARRAY_MAGIC: FieldElement =
+    felt!("0x46a6158a16a947e5916b2a2ca68501a45e93d7110e81aa2d6438b1c57c879a3");
 
 pub struct FeltDecoder<S> {
     address_book_resolver: AddressBookResolver<S>,
+    account_address: Option<FieldElement>,
 }
 
 impl<S> FeltDecoder<S> {
     pub fn new(address_book_resolver: AddressBookResolver<S>) -> Self {
         Self {
             address_book_resolver,
+            account_address: None,
+        }
+    }
+
+    /// Makes the `self` argument resolve to the address of the account in use.
+    pub fn with_account_address(self, account_address: FieldElement) -> Self {
+        Self {
+            account_address: Some(account_address),
+            ..self
         }
     }
+
+    pub fn address_book_resolver(&self) -> &AddressBookResolver<S> {
+        &self.address_book_resolver
+    }
 }
 
 impl<S> FeltDecoder<S>
@@ -21,7 +53,7 @@ where
     S: ChainIdSource,
 {
     pub async fn decode_single_with_addr_fallback(&self, raw: &str) -> Result<FieldElement> {
-        let decoded = self.decode_inner(raw, true).await?;
+        let decoded = timing::measure(Phase::Decode, self.decode_inner(raw, true)).await?;
 
         if decoded.len() == 1 {
             Ok(decoded[0])
@@ -34,48 +66,73 @@ where
     }
 
     pub async fn decode(&self, raw: &str) -> Result<Vec<FieldElement>> {
-        self.decode_inner(raw, false).await
+        timing::measure(Phase::Decode, self.decode_inner(raw, false)).await
     }
 
     async fn decode_inner(&self, raw: &str, addr_fallback: bool) -> Result<Vec<FieldElement>> {
-        if let Some(addr_name) = raw.strip_prefix("addr:") {
-            Ok(vec![self.resolve_addr(addr_name).await?])
-        } else if let Some(u256_str) = raw.strip_prefix("u256:") {
-            let bigint = if let Some(hex_str) = u256_str.strip_prefix("0x") {
-                let unsigned_bytes = if hex_str.len() % 2 == 0 {
-                    hex::decode(hex_str)?
-                } else {
-                    let mut padded = String::from("0");
-                    padded.push_str(hex_str);
-                    hex::decode(&padded)?
-                };
-
-                BigUint::from_bytes_be(&unsigned_bytes)
-            } else {
-                // If it's not prefixed with "0x" we assume decimal repr
-
-                let digits = u256_str
-                    .chars()
-                    .map(|c| c.to_string().parse::<u8>())
-                    .collect::<std::result::Result<Vec<_>, _>>()?;
+        if !is_array_literal(raw) {
+            return self.decode_scalar(raw, addr_fallback).await;
+        }
 
-                // All elements in `digits` must be less than 10 so this is safe
-                BigUint::from_radix_be(&digits, 10).unwrap()
-            };
+        let mut decoded = vec![];
+        for piece in parse_array_literal(raw)?.into_iter() {
+            match piece {
+                ArrayPiece::Length(len) => decoded.push(FieldElement::from(len)),
+                ArrayPiece::Element(element) => decoded.append(
+                    &mut self
+                        .decode_scalar(element, addr_fallback)
+                        .await
+                        .map_err(|err| {
+                            anyhow::anyhow!("invalid array element \"{}\": {}", element, err)
+                        })?,
+                ),
+            }
+        }
 
-            let u128_max_plus_1 =
-                BigUint::from_bytes_be(&hex_literal::hex!("0100000000000000000000000000000000"));
+        Ok(decoded)
+    }
 
-            let high = &bigint / &u128_max_plus_1;
-            if high >= u128_max_plus_1 {
-                anyhow::bail!("u256 value out of range");
+    async fn decode_scalar(&self, raw: &str, addr_fallback: bool) -> Result<Vec<FieldElement>> {
+        // Environment variables are substituted first, so that their values can use any other
+        // scheme
+        let env_value;
+        let raw = match raw.strip_prefix("env:") {
+            Some(var_name) => {
+                env_value = match std::env::var(var_name) {
+                    Ok(value) => value,
+                    Err(std::env::VarError::NotPresent) => {
+                        anyhow::bail!("environment variable {} is not set", var_name)
+                    }
+                    Err(std::env::VarError::NotUnicode(_)) => {
+                        anyhow::bail!("environment variable {} is not valid UTF-8", var_name)
+                    }
+                };
+                if env_value.starts_with("env:") {
+                    anyhow::bail!(
+                        "environment variable {} refers to another environment variable",
+                        var_name
+                    );
+                }
+                env_value.as_str()
             }
+            None => raw,
+        };
 
-            let low = &bigint % &u128_max_plus_1;
-
-            // Unwrapping is safe as these are never out of range
-            let high = FieldElement::from_byte_slice_be(&high.to_bytes_be()).unwrap();
-            let low = FieldElement::from_byte_slice_be(&low.to_bytes_be()).unwrap();
+        if raw == "self" {
+            match self.account_address {
+                Some(account_address) => Ok(vec![account_address]),
+                None => Err(anyhow::anyhow!(
+                    "\"self\" can only be used in commands that send transactions from an account"
+                )),
+            }
+        } else if let Some(addr_name) = raw.strip_prefix("addr:") {
+            // Literal addresses are validated instead of being looked up in the address book
+            match addr_name.strip_prefix("0x") {
+                Some(hex_str) => Ok(vec![parse_contract_address(hex_str)?]),
+                None => Ok(vec![self.resolve_addr(addr_name).await?]),
+            }
+        } else if let Some(u256_str) = raw.strip_prefix("u256:") {
+            let (low, high) = split_u256(&parse_u256(u256_str)?)?;
 
             Ok(vec![low, high])
         } else if let Some(const_name) = raw.strip_prefix("const:") {
@@ -99,14 +156,28 @@ where
             }
         } else if let Some(short_string) = raw.strip_prefix("str:") {
             Ok(vec![cairo_short_string_to_felt(short_string)?])
+        } else if let Some(dec_str) = raw.strip_prefix("dec:") {
+            if dec_str.is_empty() || !dec_str.chars().all(|c| c.is_ascii_digit()) {
+                anyhow::bail!("invalid decimal value: {}", dec_str);
+            }
+            Ok(vec![FieldElement::from_dec_str(dec_str)?])
+        } else if let Some(hex_str) = raw.strip_prefix("hex:") {
+            // The `0x` prefix is optional as the radix is already explicit
+            let digits = hex_str.strip_prefix("0x").unwrap_or(hex_str);
+            if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+                anyhow::bail!("invalid hex value: {}", hex_str);
+            }
+            Ok(vec![FieldElement::from_hex_be(digits)?])
         } else {
             match raw.parse::<FieldElement>() {
                 Ok(value) => Ok(vec![value]),
                 Err(err) => {
                     if addr_fallback {
-                        match self.resolve_addr(raw).await {
-                            Ok(value) => Ok(vec![value]),
-                            Err(_) => Err(err.into()),
+                        // Only a missing entry falls back to the parse error. Other failures like
+                        // an entry only existing on another chain are more helpful as they are.
+                        match self.address_book_resolver.resolve_name(raw).await? {
+                            Some(value) => Ok(vec![value]),
+                            None => Err(err.into()),
                         }
                     } else {
                         Err(err.into())
@@ -123,3 +194,584 @@ where
             .ok_or_else(|| anyhow::anyhow!("address book entry not found for \"{}\"", name))
     }
 }
+
+fn parse_contract_address(hex_str: &str) -> Result<FieldElement> {
+    if hex_str.is_empty() || hex_str.len() > 64 || !hex_str.chars().all(|c| c.is_ascii_hexdigit()) {
+        anyhow::bail!("invalid contract address: 0x{}", hex_str);
+    }
+
+    match FieldElement::from_hex_be(hex_str) {
+        Ok(address) if address < ADDR_BOUND => Ok(address),
+        _ => Err(anyhow::anyhow!(
+            "contract address out of range: 0x{}. Addresses must be less than 2^251 - 256",
+            hex_str
+        )),
+    }
+}
+
+/// Felts produced by an array literal, in order. Elements are decoded separately, as they can use
+/// any other scheme.
+#[derive(Debug, PartialEq, Eq)]
+enum ArrayPiece<'a> {
+    Length(usize),
+    Element(&'a str),
+}
+
+fn is_array_literal(raw: &str) -> bool {
+    raw.starts_with("arr:") || raw.starts_with("span:")
+}
+
+/// Flattens `arr:[a,b,c]` (fixed-size arrays, serialized without a length) and `span:[a,b,c]`
+/// (`Array` and `Span`, prefixed with the number of elements), including nested literals.
+fn parse_array_literal(raw: &str) -> Result<Vec<ArrayPiece<'_>>> {
+    let (with_length, content) = match (raw.strip_prefix("arr:"), raw.strip_prefix("span:")) {
+        (Some(content), _) => (false, content),
+        (_, Some(content)) => (true, content),
+        _ => anyhow::bail!("not an array literal: {}", raw),
+    };
+    let content = match content.strip_prefix('[') {
+        Some(content) => match content.strip_suffix(']') {
+            Some(content) => content,
+            None => anyhow::bail!("unclosed \"[\" in array literal: {}", raw),
+        },
+        None => anyhow::bail!("array literal must be enclosed in brackets: {}", raw),
+    };
+
+    // Splits on top-level commas only, so that nested literals stay whole
+    let mut elements = vec![];
+    let mut depth: usize = 0;
+    let mut start = 0;
+    for (ind, c) in content.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| anyhow::anyhow!("unmatched \"]\" in array literal: {}", raw))?;
+            }
+            ',' if depth == 0 => {
+                elements.push(&content[start..ind]);
+                start = ind + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth > 0 {
+        anyhow::bail!("unclosed \"[\" in array literal: {}", raw);
+    }
+    if !content.trim().is_empty() {
+        elements.push(&content[start..]);
+    }
+
+    let mut pieces = vec![];
+    if with_length {
+        pieces.push(ArrayPiece::Length(elements.len()));
+    }
+    for element in elements.into_iter().map(|element| element.trim()) {
+        if element.is_empty() {
+            anyhow::bail!("empty element in array literal: {}", raw);
+        } else if is_array_literal(element) {
+            pieces.append(&mut parse_array_literal(element)?);
+        } else if element.contains('[') || element.contains(']') {
+            anyhow::bail!(
+                "nested arrays must be written as arr:[...] or span:[...]: {}",
+                element
+            );
+        } else {
+            pieces.push(ArrayPiece::Element(element));
+        }
+    }
+
+    Ok(pieces)
+}
+
+/// Parses a u256 value from its hex (`0x`-prefixed) or decimal representation.
+pub fn parse_u256(raw: &str) -> Result<BigUint> {
+    if let Some(hex_str) = raw.strip_prefix("0x") {
+        let unsigned_bytes = if hex_str.len() % 2 == 0 {
+            hex::decode(hex_str)?
+        } else {
+            let mut padded = String::from("0");
+            padded.push_str(hex_str);
+            hex::decode(&padded)?
+        };
+
+        Ok(BigUint::from_bytes_be(&unsigned_bytes))
+    } else {
+        // If it's not prefixed with "0x" we assume decimal repr
+
+        let digits = raw
+            .chars()
+            .map(|c| c.to_string().parse::<u8>())
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        // All elements in `digits` must be less than 10 so this is safe
+        Ok(BigUint::from_radix_be(&digits, 10).unwrap())
+    }
+}
+
+/// Splits a u256 value into its `(low, high)` 128-bit halves, in the order Cairo serializes them.
+pub fn split_u256(value: &BigUint) -> Result<(FieldElement, FieldElement)> {
+    let u128_max_plus_1 = u128_max_plus_1();
+
+    let high = value / &u128_max_plus_1;
+    if high >= u128_max_plus_1 {
+        anyhow::bail!("u256 value out of range");
+    }
+
+    let low = value % &u128_max_plus_1;
+
+    // Unwrapping is safe as these are never out of range
+    let high = FieldElement::from_byte_slice_be(&high.to_bytes_be()).unwrap();
+    let low = FieldElement::from_byte_slice_be(&low.to_bytes_be()).unwrap();
+
+    Ok((low, high))
+}
+
+/// Reverse of [`split_u256`], rejecting halves that don't fit in 128 bits.
+pub fn join_u256(low: FieldElement, high: FieldElement) -> Result<BigUint> {
+    let u128_max_plus_1 = u128_max_plus_1();
+
+    let low = BigUint::from_bytes_be(&low.to_bytes_be());
+    let high = BigUint::from_bytes_be(&high.to_bytes_be());
+    if low >= u128_max_plus_1 {
+        anyhow::bail!("low half out of the 128-bit range");
+    }
+    if high >= u128_max_plus_1 {
+        anyhow::bail!("high half out of the 128-bit range");
+    }
+
+    Ok((high << 128) + low)
+}
+
+/// Decodes an error message from felts, either a serialized `ByteArray` (with or without the
+/// panic data marker) or a list of short strings, which are joined with `, `. Returns `None` when
+/// the felts don't look like text.
+pub fn decode_error_message(felts: &[FieldElement]) -> Option<String> {
+    let byte_array = match felts.split_first() {
+        Some((first, rest)) if *first == BYTE_ARRAY_MAGIC => rest,
+        _ => felts,
+    };
+    if let Some(message) = decode_byte_array(byte_array) {
+        return Some(message);
+    }
+
+    if felts.is_empty() {
+        return None;
+    }
+    let strings = felts
+        .iter()
+        .map(|felt| {
+            let string = parse_cairo_short_string(felt).ok()?;
+            if !string.is_empty() && string.chars().all(|c| c.is_ascii_graphic() || c == ' ') {
+                Some(string)
+            } else {
+                None
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(strings.join(", "))
+}
+
+/// Decodes `[word_count, words..., pending_word, pending_word_len]`, where each full word holds 31
+/// bytes.
+fn decode_byte_array(felts: &[FieldElement]) -> Option<String> {
+    let (word_count, rest) = felts.split_first()?;
+    let word_count: usize = u64::try_from(*word_count).ok()?.try_into().ok()?;
+    if rest.len() != word_count.checked_add(2)? {
+        return None;
+    }
+    let pending_word_len: usize = u64::try_from(rest[word_count + 1]).ok()?.try_into().ok()?;
+    if pending_word_len >= 31 {
+        return None;
+    }
+
+    let mut bytes = vec![];
+    for word in rest[..word_count].iter() {
+        let word = word.to_bytes_be();
+        if word[0] != 0 {
+            return None;
+        }
+        bytes.extend_from_slice(&word[1..]);
+    }
+    let pending_word = rest[word_count].to_bytes_be();
+    let (padding, pending_bytes) = pending_word.split_at(32 - pending_word_len);
+    if padding.iter().any(|byte| *byte != 0) {
+        return None;
+    }
+    bytes.extend_from_slice(pending_bytes);
+
+    let message = String::from_utf8(bytes).ok()?;
+    if message.is_empty() || message.chars().any(|c| c.is_control() && c != '\n') {
+        return None;
+    }
+
+    Some(message)
+}
+
+fn u128_max_plus_1() -> BigUint {
+    BigUint::from_bytes_be(&hex_literal::hex!("0100000000000000000000000000000000"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::chain_id::NoChainIdSource;
+
+    #[test]
+    fn test_parse_array_literal() {
+        use ArrayPiece::*;
+
+        assert_eq!(
+            parse_array_literal("arr:[1,2]").unwrap(),
+            [Element("1"), Element("2")]
+        );
+        assert_eq!(
+            parse_array_literal("span:[1, 2]").unwrap(),
+            [Length(2), Element("1"), Element("2")]
+        );
+        assert_eq!(
+            parse_array_literal("span:[arr:[1,2],3]").unwrap(),
+            [Length(2), Element("1"), Element("2"), Element("3")]
+        );
+        assert_eq!(
+            parse_array_literal("span:[span:[1],0x2]").unwrap(),
+            [Length(2), Length(1), Element("1"), Element("0x2")]
+        );
+        assert_eq!(parse_array_literal("span:[]").unwrap(), [Length(0)]);
+        assert_eq!(parse_array_literal("arr:[]").unwrap(), []);
+    }
+
+    #[test]
+    fn test_parse_array_literal_errors() {
+        for raw in [
+            "arr:[1,2",
+            "arr:1,2]",
+            "span:[arr:[1,2]",
+            "span:[1]]",
+            "arr:[1],2]",
+            "arr:[[1],2]",
+            "arr:[1,,2]",
+            "arr:[1,2,]",
+            "span:[,]",
+            "[1,2]",
+        ] {
+            assert!(parse_array_literal(raw).is_err(), "{raw}");
+        }
+    }
+
+    fn decoder() -> FeltDecoder<NoChainIdSource> {
+        FeltDecoder::new(AddressBookResolver::new(NoChainIdSource))
+    }
+
+    #[test]
+    fn test_parse_contract_address() {
+        assert_eq!(parse_contract_address("1234").unwrap(), felt!("0x1234"));
+        assert_eq!(
+            parse_contract_address(
+                "00000000000000000000000000000000000000000000000000000000000000ff"
+            )
+            .unwrap(),
+            felt!("0xff")
+        );
+        assert_eq!(
+            parse_contract_address(
+                "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeff"
+            )
+            .unwrap(),
+            ADDR_BOUND - FieldElement::ONE
+        );
+
+        // Out of range
+        for hex_str in [
+            "7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff00",
+            "800000000000000000000000000000000000000000000000000000000000000",
+        ] {
+            assert!(parse_contract_address(hex_str)
+                .unwrap_err()
+                .to_string()
+                .starts_with("contract address out of range"));
+        }
+
+        // Malformed
+        for hex_str in [
+            "",
+            "12g4",
+            "0x1234",
+            "10000000000000000000000000000000000000000000000000000000000000000",
+        ] {
+            assert!(parse_contract_address(hex_str)
+                .unwrap_err()
+                .to_string()
+                .starts_with("invalid contract address"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decode_addr_literal() {
+        let decoder = decoder();
+
+        assert_eq!(
+            decoder.decode("addr:0x1234").await.unwrap(),
+            [felt!("0x1234")]
+        );
+        assert!(decoder
+            .decode("addr:0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff00")
+            .await
+            .is_err());
+        assert!(decoder.decode("addr:0x").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_decode_explicit_radix() {
+        let decoder = decoder();
+
+        assert_eq!(decoder.decode("dec:1234").await.unwrap(), [felt!("1234")]);
+        assert_eq!(decoder.decode("dec:0010").await.unwrap(), [felt!("10")]);
+        assert_eq!(decoder.decode("hex:1234").await.unwrap(), [felt!("0x1234")]);
+        assert_eq!(
+            decoder.decode("hex:0x1234").await.unwrap(),
+            [felt!("0x1234")]
+        );
+        assert_eq!(decoder.decode("hex:10").await.unwrap(), [felt!("16")]);
+
+        for raw in [
+            "dec:",
+            "dec:0x10",
+            "dec:-1",
+            "dec:1e3",
+            "dec: 1",
+            "hex:",
+            "hex:0x",
+            "hex:0x0x1",
+            "hex:12g4",
+        ] {
+            assert!(decoder.decode(raw).await.is_err(), "{raw}");
+        }
+
+        // Values must still fit in a felt
+        assert!(decoder
+            .decode(
+                "dec:3618502788666131213697322783095070105623107215331596699973092056135872020481"
+            )
+            .await
+            .is_err());
+    }
+
+    /// Environment variables are process-wide, so each test sets its own.
+    fn set_env_var(value: &str) -> String {
+        let name = format!("STARKLI_TEST_{}", uuid::Uuid::new_v4().to_simple());
+        std::env::set_var(&name, value);
+        name
+    }
+
+    #[tokio::test]
+    async fn test_decode_env() {
+        let decoder = decoder();
+
+        let name = set_env_var("0x1234");
+        assert_eq!(
+            decoder.decode(&format!("env:{name}")).await.unwrap(),
+            [felt!("0x1234")]
+        );
+        std::env::remove_var(&name);
+
+        let err = decoder
+            .decode(&format!("env:{name}"))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, format!("environment variable {name} is not set"));
+    }
+
+    #[tokio::test]
+    async fn test_decode_env_nested() {
+        let decoder = decoder();
+
+        // Values can use any other scheme
+        let name = set_env_var("hex:10");
+        assert_eq!(
+            decoder.decode(&format!("env:{name}")).await.unwrap(),
+            [felt!("16")]
+        );
+        let name = set_env_var("u256:1");
+        assert_eq!(
+            decoder.decode(&format!("env:{name}")).await.unwrap(),
+            [felt!("1"), felt!("0")]
+        );
+
+        // But not refer to another variable
+        let inner_name = set_env_var("0x1");
+        let name = set_env_var(&format!("env:{inner_name}"));
+        let err = decoder
+            .decode(&format!("env:{name}"))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            format!("environment variable {name} refers to another environment variable")
+        );
+    }
+
+    #[test]
+    fn test_split_u256() {
+        let u128_max = felt!("0xffffffffffffffffffffffffffffffff");
+
+        assert_eq!(
+            split_u256(&parse_u256("340282366920938463463374607431768211455").unwrap()).unwrap(),
+            (u128_max, FieldElement::ZERO)
+        );
+        assert_eq!(
+            split_u256(&parse_u256("0x100000000000000000000000000000000").unwrap()).unwrap(),
+            (FieldElement::ZERO, FieldElement::ONE)
+        );
+        assert_eq!(
+            split_u256(&parse_u256("0x123").unwrap()).unwrap(),
+            (felt!("0x123"), FieldElement::ZERO)
+        );
+        assert_eq!(
+            split_u256(
+                &parse_u256("0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff")
+                    .unwrap()
+            )
+            .unwrap(),
+            (u128_max, u128_max)
+        );
+
+        assert!(split_u256(
+            &parse_u256("0x10000000000000000000000000000000000000000000000000000000000000000")
+                .unwrap()
+        )
+        .is_err());
+        assert!(parse_u256("12a").is_err());
+        assert!(parse_u256("0xzz").is_err());
+    }
+
+    #[test]
+    fn test_join_u256() {
+        let u128_max = felt!("0xffffffffffffffffffffffffffffffff");
+        let u128_max_plus_1 = felt!("0x100000000000000000000000000000000");
+
+        assert_eq!(
+            join_u256(u128_max, FieldElement::ZERO).unwrap(),
+            parse_u256("340282366920938463463374607431768211455").unwrap()
+        );
+        assert_eq!(
+            join_u256(FieldElement::ZERO, FieldElement::ONE).unwrap(),
+            parse_u256("0x100000000000000000000000000000000").unwrap()
+        );
+
+        // Round trip of the maximum value
+        let max = parse_u256(
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935",
+        )
+        .unwrap();
+        let (low, high) = split_u256(&max).unwrap();
+        assert_eq!(join_u256(low, high).unwrap(), max);
+
+        assert_eq!(
+            join_u256(u128_max_plus_1, FieldElement::ZERO)
+                .unwrap_err()
+                .to_string(),
+            "low half out of the 128-bit range"
+        );
+        assert_eq!(
+            join_u256(FieldElement::ZERO, u128_max_plus_1)
+                .unwrap_err()
+                .to_string(),
+            "high half out of the 128-bit range"
+        );
+    }
+
+    fn short_string(string: &str) -> FieldElement {
+        cairo_short_string_to_felt(string).unwrap()
+    }
+
+    #[test]
+    fn test_decode_error_message() {
+        assert_eq!(
+            decode_error_message(&[short_string("Invalid caller")]).unwrap(),
+            "Invalid caller"
+        );
+        assert_eq!(
+            decode_error_message(&[
+                short_string("u256_sub Overflow"),
+                short_string("ENTRYPOINT_FAILED")
+            ])
+            .unwrap(),
+            "u256_sub Overflow, ENTRYPOINT_FAILED"
+        );
+
+        // Felts that aren't printable short strings
+        assert_eq!(decode_error_message(&[FieldElement::ONE]), None);
+        assert_eq!(
+            decode_error_message(&[short_string("ok"), FieldElement::MAX]),
+            None
+        );
+        assert_eq!(decode_error_message(&[]), None);
+    }
+
+    #[test]
+    fn test_decode_error_message_byte_array() {
+        // A 35-byte message: one full word, and 4 pending bytes
+        let message = "Caller is not the owner of the nft!";
+        let felts = [
+            BYTE_ARRAY_MAGIC,
+            FieldElement::ONE,
+            short_string(&message[..31]),
+            short_string(&message[31..]),
+            FieldElement::from(4u64),
+        ];
+        assert_eq!(decode_error_message(&felts).unwrap(), message);
+        assert_eq!(decode_byte_array(&felts[1..]).unwrap(), message);
+
+        assert_eq!(
+            decode_byte_array(&[
+                FieldElement::ZERO,
+                short_string("hello"),
+                FieldElement::from(5u64)
+            ])
+            .unwrap(),
+            "hello"
+        );
+    }
+
+    #[test]
+    fn test_decode_byte_array_invalid() {
+        // Word count doesn't match the number of felts
+        assert_eq!(
+            decode_byte_array(&[
+                FieldElement::from(2u64),
+                short_string("hello"),
+                FieldElement::from(5u64)
+            ]),
+            None
+        );
+        // Pending word longer than its declared length
+        assert_eq!(
+            decode_byte_array(&[
+                FieldElement::ZERO,
+                short_string("hello"),
+                FieldElement::from(3u64)
+            ]),
+            None
+        );
+        // Pending word length must be below 31
+        assert_eq!(
+            decode_byte_array(&[
+                FieldElement::ZERO,
+                FieldElement::ZERO,
+                FieldElement::from(31u64)
+            ]),
+            None
+        );
+        assert_eq!(
+            decode_byte_array(&[FieldElement::ZERO, FieldElement::ZERO, FieldElement::ZERO]),
+            None
+        );
+        assert_eq!(decode_byte_array(&[]), None);
+    }
+}
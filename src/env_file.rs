@@ -0,0 +1,188 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use regex::Regex;
+use starknet::core::types::FieldElement;
+
+use crate::{output::persist, path::ExpandedPathbufParser};
+
+#[derive(Debug, Clone, Parser)]
+pub struct EnvFileArgs {
+    #[clap(
+        long,
+        value_name = "PATH",
+        value_parser = ExpandedPathbufParser,
+        help = "Write the results as KEY=0x... lines to a dotenv file. Keys already in the file are \
+        replaced and other lines are kept"
+    )]
+    env_out: Option<PathBuf>,
+    #[clap(
+        long,
+        value_name = "PREFIX",
+        requires = "env_out",
+        help = "Prefix the keys written with --env-out, as PREFIX_KEY"
+    )]
+    env_prefix: Option<String>,
+    #[clap(
+        long,
+        requires = "env_out",
+        help = "Replace the whole file written with --env-out instead of updating it"
+    )]
+    env_overwrite: bool,
+}
+
+/// A dotenv file that results are written to once known.
+pub struct EnvFile {
+    path: PathBuf,
+    prefix: Option<String>,
+    overwrite: bool,
+}
+
+impl EnvFileArgs {
+    /// Validates the options before anything is sent, so that results are never lost to a typo.
+    pub fn into_env_file(self) -> Result<Option<EnvFile>> {
+        let path = match self.env_out {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+
+        if let Some(prefix) = &self.env_prefix {
+            let regex_prefix = Regex::new("^[A-Za-z_][A-Za-z0-9_]*$").unwrap();
+            if !regex_prefix.is_match(prefix) {
+                anyhow::bail!(
+                    "invalid --env-prefix \"{}\": only letters, digits, and underscores are \
+                    allowed, and it must not start with a digit",
+                    prefix
+                );
+            }
+        }
+
+        Ok(Some(EnvFile {
+            path,
+            prefix: self.env_prefix,
+            overwrite: self.env_overwrite,
+        }))
+    }
+}
+
+impl EnvFile {
+    /// Writes `values` atomically, so that the file is never left half-written for the next
+    /// pipeline stage.
+    pub fn write(&self, values: &[(&str, FieldElement)]) -> Result<()> {
+        let lines = values
+            .iter()
+            .map(|(name, value)| {
+                let key = match &self.prefix {
+                    Some(prefix) => format!("{}_{}", prefix, name),
+                    None => name.to_string(),
+                };
+                (key, format!("{:#064x}", value))
+            })
+            .collect::<Vec<_>>();
+
+        let mut content = String::new();
+        if !self.overwrite && self.path.exists() {
+            // Lines for the keys being written are dropped, so that each key is only set once
+            for line in std::fs::read_to_string(&self.path)?.lines() {
+                let key = line.split_once('=').map(|(key, _)| key.trim());
+                if !lines
+                    .iter()
+                    .any(|(new_key, _)| Some(new_key.as_str()) == key)
+                {
+                    content.push_str(line);
+                    content.push('\n');
+                }
+            }
+        }
+        for (key, value) in lines.iter() {
+            content.push_str(&format!("{}={}\n", key, value));
+        }
+
+        persist(&self.path, &content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use starknet::macros::felt;
+
+    fn env_file(prefix: Option<&str>, overwrite: bool) -> EnvFile {
+        EnvFile {
+            path: std::env::temp_dir().join(format!("starkli-test-{}.env", uuid::Uuid::new_v4())),
+            prefix: prefix.map(|prefix| prefix.to_owned()),
+            overwrite,
+        }
+    }
+
+    #[test]
+    fn test_write_updates_keys() {
+        let env_file = env_file(None, false);
+        std::fs::write(
+            &env_file.path,
+            "# deployment\nRPC=http://localhost:5050\nCLASS_HASH=0x1\nADDRESS = 0x2\n",
+        )
+        .unwrap();
+
+        env_file
+            .write(&[
+                ("CLASS_HASH", felt!("0x1234")),
+                ("ADDRESS", felt!("0x5678")),
+            ])
+            .unwrap();
+        let content = std::fs::read_to_string(&env_file.path).unwrap();
+        std::fs::remove_file(&env_file.path).unwrap();
+
+        // Keys are only set once, and other lines are kept in place
+        assert_eq!(
+            content,
+            "# deployment\n\
+            RPC=http://localhost:5050\n\
+            CLASS_HASH=0x0000000000000000000000000000000000000000000000000000000000001234\n\
+            ADDRESS=0x0000000000000000000000000000000000000000000000000000000000005678\n"
+        );
+    }
+
+    #[test]
+    fn test_write_prefix_and_overwrite() {
+        let env_file = env_file(Some("TOKEN"), true);
+        std::fs::write(&env_file.path, "RPC=http://localhost:5050\n").unwrap();
+
+        env_file.write(&[("ADDRESS", felt!("0x1"))]).unwrap();
+        let content = std::fs::read_to_string(&env_file.path).unwrap();
+        std::fs::remove_file(&env_file.path).unwrap();
+
+        assert_eq!(
+            content,
+            "TOKEN_ADDRESS=0x0000000000000000000000000000000000000000000000000000000000000001\n"
+        );
+    }
+
+    #[test]
+    fn test_write_new_file() {
+        let env_file = env_file(None, false);
+
+        env_file.write(&[("ADDRESS", felt!("0x1"))]).unwrap();
+        let content = std::fs::read_to_string(&env_file.path).unwrap();
+        std::fs::remove_file(&env_file.path).unwrap();
+
+        assert_eq!(
+            content,
+            "ADDRESS=0x0000000000000000000000000000000000000000000000000000000000000001\n"
+        );
+    }
+
+    #[test]
+    fn test_invalid_prefix() {
+        for prefix in ["1ABC", "MY-TOKEN", ""] {
+            let args = EnvFileArgs {
+                env_out: Some(PathBuf::from("out.env")),
+                env_prefix: Some(prefix.to_owned()),
+                env_overwrite: false,
+            };
+            assert!(args.into_env_file().is_err(), "{prefix}");
+        }
+    }
+}
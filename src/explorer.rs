@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use colored::Colorize;
+use starknet::core::{chain_id, types::FieldElement};
+
+use crate::{
+    config::{parse_chain_id, Config},
+    verbosity::{progress, warning},
+};
+
+/// Placeholder for the transaction hash in custom explorer URL templates.
+const HASH_PLACEHOLDER: &str = "{hash}";
+
+#[derive(Debug, Clone, Parser)]
+pub struct ExplorerArgs {
+    #[clap(
+        long,
+        value_enum,
+        default_value_t,
+        help = "Block explorer to print a transaction link for after sending. \"custom\" uses the \
+        URL template from the config file for the chain"
+    )]
+    explorer: Explorer,
+}
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+enum Explorer {
+    #[default]
+    None,
+    Voyager,
+    Starkscan,
+    Custom,
+}
+
+/// Builds explorer links for sent transactions. Custom templates are loaded before anything is
+/// sent, so that config errors never surface after a transaction is already out.
+pub struct TransactionExplorer {
+    explorer: Explorer,
+    custom_templates: HashMap<FieldElement, String>,
+}
+
+impl Explorer {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Voyager => "voyager",
+            Self::Starkscan => "starkscan",
+            Self::Custom => "custom",
+        }
+    }
+}
+
+impl ExplorerArgs {
+    pub fn into_explorer(self) -> Result<TransactionExplorer> {
+        let custom_templates = match self.explorer {
+            Explorer::Custom => parse_custom_templates(Config::load()?.explorers)?,
+            _ => HashMap::new(),
+        };
+
+        Ok(TransactionExplorer {
+            explorer: self.explorer,
+            custom_templates,
+        })
+    }
+}
+
+/// Keys the URL templates of the config file by chain id, making sure each has a placeholder.
+fn parse_custom_templates(
+    templates: HashMap<String, String>,
+) -> Result<HashMap<FieldElement, String>> {
+    let mut custom_templates = HashMap::new();
+    for (chain_id, template) in templates.into_iter() {
+        let chain_id = parse_chain_id(&chain_id)
+            .ok_or_else(|| anyhow::anyhow!("invalid explorer chain id: {}", chain_id))?;
+        if !template.contains(HASH_PLACEHOLDER) {
+            anyhow::bail!(
+                "explorer URL template for chain {:#x} is missing the {} placeholder",
+                chain_id,
+                HASH_PLACEHOLDER
+            );
+        }
+
+        custom_templates.insert(chain_id, template);
+    }
+
+    Ok(custom_templates)
+}
+
+impl TransactionExplorer {
+    /// Prints the explorer link of a sent transaction to stderr, if an explorer is selected.
+    pub fn print_link(&self, chain_id: FieldElement, transaction_hash: FieldElement) {
+        if let Explorer::None = self.explorer {
+            return;
+        }
+
+        match self.transaction_url(chain_id, transaction_hash) {
+            Some(url) => progress!("Explorer: {}", url.bright_yellow()),
+            None => warning!(
+                "WARNING: no {} explorer URL known for chain id {:#x}. Not printing a \
+                transaction link.",
+                self.explorer.name(),
+                chain_id
+            ),
+        }
+    }
+
+    fn transaction_url(
+        &self,
+        chain_id: FieldElement,
+        transaction_hash: FieldElement,
+    ) -> Option<String> {
+        let transaction_hash = format!("{:#064x}", transaction_hash);
+
+        let base_urls: &[(FieldElement, &str)] = match self.explorer {
+            Explorer::None => return None,
+            Explorer::Custom => {
+                return self
+                    .custom_templates
+                    .get(&chain_id)
+                    .map(|template| template.replace(HASH_PLACEHOLDER, &transaction_hash))
+            }
+            Explorer::Voyager => &[
+                (chain_id::MAINNET, "https://voyager.online/tx/"),
+                (chain_id::TESTNET, "https://goerli.voyager.online/tx/"),
+                (chain_id::TESTNET2, "https://goerli-2.voyager.online/tx/"),
+            ],
+            Explorer::Starkscan => &[
+                (chain_id::MAINNET, "https://starkscan.co/tx/"),
+                (chain_id::TESTNET, "https://testnet.starkscan.co/tx/"),
+                (chain_id::TESTNET2, "https://testnet-2.starkscan.co/tx/"),
+            ],
+        };
+        let (_, base_url) = base_urls.iter().find(|(id, _)| *id == chain_id)?;
+
+        Some(format!("{}{}", base_url, transaction_hash))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use starknet::macros::felt;
+
+    const HASH: FieldElement = felt!("0x1234");
+    const PADDED_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000001234";
+
+    fn explorer(explorer: Explorer, templates: &[(&str, &str)]) -> TransactionExplorer {
+        TransactionExplorer {
+            explorer,
+            custom_templates: parse_custom_templates(
+                templates
+                    .iter()
+                    .map(|(chain_id, template)| (chain_id.to_string(), template.to_string()))
+                    .collect(),
+            )
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_transaction_url() {
+        assert_eq!(
+            explorer(Explorer::Voyager, &[])
+                .transaction_url(chain_id::MAINNET, HASH)
+                .unwrap(),
+            format!("https://voyager.online/tx/{PADDED_HASH}")
+        );
+        assert_eq!(
+            explorer(Explorer::Starkscan, &[])
+                .transaction_url(chain_id::TESTNET2, HASH)
+                .unwrap(),
+            format!("https://testnet-2.starkscan.co/tx/{PADDED_HASH}")
+        );
+
+        // Unknown chains and no explorer
+        assert!(explorer(Explorer::Voyager, &[])
+            .transaction_url(felt!("0x1"), HASH)
+            .is_none());
+        assert!(explorer(Explorer::None, &[])
+            .transaction_url(chain_id::MAINNET, HASH)
+            .is_none());
+    }
+
+    #[test]
+    fn test_transaction_url_custom() {
+        let explorer = explorer(
+            Explorer::Custom,
+            &[
+                ("SN_GOERLI", "https://explorer.example/tx/{hash}?tab=events"),
+                ("0x4b4154414e41", "http://localhost:4000/{hash}"),
+            ],
+        );
+
+        assert_eq!(
+            explorer.transaction_url(chain_id::TESTNET, HASH).unwrap(),
+            format!("https://explorer.example/tx/{PADDED_HASH}?tab=events")
+        );
+        // Chain ids can also be given in hex, here for `KATANA`
+        assert_eq!(
+            explorer
+                .transaction_url(felt!("0x4b4154414e41"), HASH)
+                .unwrap(),
+            format!("http://localhost:4000/{PADDED_HASH}")
+        );
+        assert!(explorer.transaction_url(chain_id::MAINNET, HASH).is_none());
+    }
+
+    #[test]
+    fn test_parse_custom_templates_invalid() {
+        let parse = |chain_id: &str, template: &str| {
+            parse_custom_templates([(chain_id.to_owned(), template.to_owned())].into())
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            parse("SN_MAIN", "https://explorer.example/tx/"),
+            "explorer URL template for chain 0x534e5f4d41494e is missing the {hash} placeholder"
+        );
+        assert_eq!(
+            parse("0xzz", "https://explorer.example/tx/{hash}"),
+            "invalid explorer chain id: 0xzz"
+        );
+    }
+}
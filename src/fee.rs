@@ -1,21 +1,143 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
 use bigdecimal::BigDecimal;
 use clap::Parser;
-use starknet::{core::types::FieldElement, macros::felt};
+use colored::Colorize;
+use num_bigint::{BigInt, BigUint, Sign};
+use starknet::{
+    core::{
+        chain_id,
+        types::{
+            FieldElement, MaybePendingTransactionReceipt, PendingTransactionReceipt, StarknetError,
+            TransactionReceipt,
+        },
+    },
+    macros::felt,
+    providers::{MaybeUnknownErrorCode, Provider, ProviderError, StarknetErrorWithMessage},
+};
+
+use crate::{
+    config::{parse_chain_id, Config, FeeConfig},
+    utils::{bigdecimal_to_felt, fetch_fee_balance},
+    verbosity::{progress, warning},
+};
+
+/// Addresses of the ETH token contract used for paying transaction fees, keyed by chain id. Other
+/// chains need an entry in the `fees` section of the config file.
+const KNOWN_FEE_TOKENS: [(FieldElement, FieldElement); 4] = [
+    (
+        chain_id::MAINNET,
+        felt!("0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7"),
+    ),
+    (
+        chain_id::TESTNET,
+        felt!("0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7"),
+    ),
+    (
+        chain_id::TESTNET2,
+        felt!("0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7"),
+    ),
+    // `KATANA`, the chain id of the Katana devnet
+    (
+        felt!("0x4b4154414e41"),
+        felt!("0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7"),
+    ),
+];
 
-use crate::utils::bigdecimal_to_felt;
+/// Percentage added on top of fee estimates when neither `--fee-buffer` nor the config file sets
+/// one.
+pub const DEFAULT_FEE_BUFFER: u64 = 50;
 
 #[derive(Debug, Clone, Parser)]
 pub struct FeeArgs {
-    #[clap(long, help = "Maximum transaction fee in Ether (18 decimals)")]
-    max_fee: Option<BigDecimal>,
+    #[clap(
+        long,
+        value_parser = parse_fee_amount,
+        help = "Maximum transaction fee in Ether (18 decimals). Can also be suffixed with a unit, \
+        as in 5gwei or 1000wei"
+    )]
+    max_fee: Option<FieldElement>,
     #[clap(long, help = "Maximum transaction fee in Wei")]
     max_fee_raw: Option<FieldElement>,
+    #[clap(
+        long,
+        value_name = "TX_HASH",
+        conflicts_with_all = ["max_fee", "max_fee_raw", "max_fee_ceiling"],
+        help = "Use the fee actually paid by an existing transaction as the maximum fee, e.g. \
+        for reproducing it"
+    )]
+    max_fee_from: Option<FieldElement>,
     #[clap(
         long,
         help = "Only estimate transaction fee without sending transaction"
     )]
     estimate_only: bool,
+    #[clap(
+        long,
+        requires = "eth_price",
+        help = "Also display fees in USD. Requires --eth-price as there's no built-in price source"
+    )]
+    fee_in_usd: bool,
+    #[clap(long, help = "ETH price in USD for displaying approximate fee values")]
+    eth_price: Option<BigDecimal>,
+    #[clap(
+        long,
+        help = "Skip checking that the account has enough ETH to pay for the max fee, e.g. when \
+        fees are sponsored"
+    )]
+    skip_balance_check: bool,
+    #[clap(
+        long,
+        default_value = "1000000000",
+        help = "Warn when the estimated fee in Wei is below this amount, as it usually means the \
+        transaction is failing validation"
+    )]
+    low_fee_threshold: u64,
+    #[clap(long, help = "Do not warn on implausibly low fee estimates")]
+    no_low_fee_warning: bool,
+    #[clap(
+        long,
+        value_parser = parse_fee_amount,
+        conflicts_with_all = ["max_fee", "max_fee_raw"],
+        help = "Abort instead of sending if the estimated fee in Ether (18 decimals) exceeds this \
+        amount. Takes the same unit suffixes as --max-fee"
+    )]
+    max_fee_ceiling: Option<FieldElement>,
+    #[clap(
+        long,
+        value_name = "PERCENT",
+        conflicts_with_all = ["max_fee", "max_fee_raw", "max_fee_from"],
+        help = "Percentage added on top of the fee estimate to get the max fee. Overrides the \
+        buffer configured for the network in the config file"
+    )]
+    fee_buffer: Option<u64>,
+    #[clap(
+        long,
+        value_name = "PERCENT",
+        value_parser = clap::value_parser!(u64).range(1..=100),
+        conflicts_with_all = ["max_fee", "max_fee_raw", "max_fee_from"],
+        help = "Limit the max fee to this percentage of the account's ETH balance, read before \
+        sending. The buffered fee estimate is still used when it's lower"
+    )]
+    max_fee_from_balance: Option<u64>,
+}
+
+/// Formats fee amounts for display, optionally with an approximate USD value.
+#[derive(Debug, Clone, Default)]
+pub struct FeeFormatter {
+    usd_price: Option<BigDecimal>,
+}
+
+/// Resolves the fee buffer for the chain a command ends up on. The config file is loaded before
+/// anything is sent, so that config errors never surface after a transaction is already out.
+#[derive(Debug, Clone)]
+pub struct FeeBuffer {
+    override_percentage: Option<u64>,
+    network_percentages: HashMap<FieldElement, u64>,
+    balance_percentage: Option<u64>,
+    /// Upper bound of buffered fees, once read with [`FeeBuffer::read_balance_budget`].
+    balance_budget: Option<FieldElement>,
 }
 
 #[derive(Debug)]
@@ -26,22 +148,89 @@ pub enum FeeSetting {
 }
 
 impl FeeArgs {
-    pub fn into_setting(self) -> Result<FeeSetting> {
+    pub fn formatter(&self) -> FeeFormatter {
+        FeeFormatter {
+            usd_price: if self.fee_in_usd {
+                self.eth_price.clone()
+            } else {
+                None
+            },
+        }
+    }
+
+    pub fn skip_balance_check(&self) -> bool {
+        self.skip_balance_check
+    }
+
+    /// Returns `None` if low fee estimate warnings are suppressed.
+    pub fn low_fee_threshold(&self) -> Option<u64> {
+        if self.no_low_fee_warning {
+            None
+        } else {
+            Some(self.low_fee_threshold)
+        }
+    }
+
+    pub fn max_fee_ceiling(&self) -> Result<Option<FieldElement>> {
+        Ok(self.max_fee_ceiling)
+    }
+
+    pub fn fee_buffer(&self) -> Result<FeeBuffer> {
+        let mut network_percentages = HashMap::new();
+        if self.fee_buffer.is_none() {
+            for (chain_id, fee_config) in Config::load()?.fees.into_iter() {
+                let chain_id = parse_chain_id(&chain_id)
+                    .ok_or_else(|| anyhow::anyhow!("invalid fee config chain id: {}", chain_id))?;
+                if let Some(percentage) = fee_config.buffer_percentage {
+                    network_percentages.insert(chain_id, percentage);
+                }
+            }
+        }
+
+        Ok(FeeBuffer {
+            override_percentage: self.fee_buffer,
+            network_percentages,
+            balance_percentage: self.max_fee_from_balance,
+            balance_budget: None,
+        })
+    }
+
+    pub async fn into_setting<P>(self, provider: P) -> Result<FeeSetting>
+    where
+        P: Provider,
+        P::Error: 'static,
+    {
+        if let Some(transaction_hash) = self.max_fee_from {
+            if self.max_fee.is_some() || self.max_fee_raw.is_some() || self.estimate_only {
+                anyhow::bail!(
+                    "invalid fee option. \
+                    --max-fee-from can't be used with --max-fee, --max-fee-raw, or --estimate-only."
+                );
+            }
+
+            let max_fee = fetch_actual_fee(provider, transaction_hash).await?;
+            progress!(
+                "Using the fee paid by transaction {:#064x} as max fee: {}",
+                transaction_hash,
+                self.formatter().format(max_fee).bright_yellow()
+            );
+
+            return Ok(FeeSetting::Manual(max_fee));
+        }
+
         match (self.max_fee, self.max_fee_raw, self.estimate_only) {
             (Some(max_fee), None, false) => {
-                let max_fee_felt = bigdecimal_to_felt(&max_fee, 18)?;
-
                 // The user is most likely making a mistake for using a max fee higher than 1 ETH
                 // TODO: allow skipping this safety check
-                if max_fee_felt > felt!("1000000000000000000") {
+                if max_fee > felt!("1000000000000000000") {
                     anyhow::bail!(
                         "the --max-fee value is too large. \
-                        --max-fee expects a value in Ether (18 decimals). \
+                        --max-fee expects a value in Ether (18 decimals) unless a unit is given. \
                         Use --max-fee-raw instead to use a raw max_fee amount in Wei."
                     )
                 }
 
-                Ok(FeeSetting::Manual(max_fee_felt))
+                Ok(FeeSetting::Manual(max_fee))
             }
             (None, Some(max_fee_raw), false) => Ok(FeeSetting::Manual(max_fee_raw)),
             (None, None, true) => Ok(FeeSetting::EstimateOnly),
@@ -54,8 +243,473 @@ impl FeeArgs {
     }
 }
 
+/// Parses a fee amount in Ether, or in the unit it's suffixed with, into Wei.
+fn parse_fee_amount(raw: &str) -> Result<FieldElement> {
+    let lowercase = raw.trim().to_lowercase();
+    let split_at = lowercase
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(lowercase.len());
+    let (amount, unit) = lowercase.split_at(split_at);
+
+    let decimals = match unit.trim() {
+        "" | "eth" | "ether" => 18,
+        "gwei" => 9,
+        "wei" => 0,
+        "fri" | "strk" => anyhow::bail!(
+            "fees can only be paid in ETH with this version of Starkli. Use eth, gwei, or wei \
+            instead of {}",
+            unit
+        ),
+        _ => anyhow::bail!(
+            "unknown fee unit \"{}\". Use eth, gwei, or wei, or no unit for Ether",
+            unit
+        ),
+    };
+    let amount = amount
+        .trim()
+        .parse::<BigDecimal>()
+        .map_err(|_| anyhow::anyhow!("invalid fee amount: {}", raw))?;
+
+    bigdecimal_to_felt(&amount, decimals)
+}
+
+/// Resolves the address of the fee token contract on `chain_id`. An address set in the config file
+/// takes precedence over the built-in one, so that appchains and devnets with their own token
+/// contract are supported.
+pub fn resolve_fee_token(chain_id: FieldElement) -> Result<FieldElement> {
+    find_fee_token(&Config::load()?.fees, chain_id)
+}
+
+/// Looks up the fee token of `chain_id` in the `fees` section of the config file, then among the
+/// built-in ones.
+fn find_fee_token(
+    fees: &HashMap<String, FeeConfig>,
+    chain_id: FieldElement,
+) -> Result<FieldElement> {
+    for (config_chain_id, fee_config) in fees.iter() {
+        let parsed_chain_id = parse_chain_id(config_chain_id)
+            .ok_or_else(|| anyhow::anyhow!("invalid fee config chain id: {}", config_chain_id))?;
+        if parsed_chain_id == chain_id {
+            if let Some(token_address) = fee_config.token_address {
+                return Ok(token_address);
+            }
+        }
+    }
+
+    KNOWN_FEE_TOKENS
+        .iter()
+        .find(|(known_chain_id, _)| *known_chain_id == chain_id)
+        .map(|(_, token_address)| *token_address)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "no fee token address known for chain id {:#x}. Set it as \"token_address\" in the \
+                \"fees\" section of the config file for this chain",
+                chain_id
+            )
+        })
+}
+
+impl FeeBuffer {
+    /// Returns `None` if neither `--fee-buffer` nor the config file sets a buffer for the chain.
+    pub fn percentage(&self, chain_id: FieldElement) -> Option<u64> {
+        self.override_percentage
+            .or_else(|| self.network_percentages.get(&chain_id).copied())
+    }
+
+    pub fn has_balance_percentage(&self) -> bool {
+        self.balance_percentage.is_some()
+    }
+
+    /// Reads the balance of the sending account for `--max-fee-from-balance`. Does nothing
+    /// without the option.
+    pub async fn read_balance_budget<P>(
+        &mut self,
+        provider: P,
+        chain_id: FieldElement,
+        account_address: FieldElement,
+    ) -> Result<()>
+    where
+        P: Provider,
+        P::Error: 'static,
+    {
+        let percentage = match self.balance_percentage {
+            Some(percentage) => percentage,
+            None => return Ok(()),
+        };
+
+        let (low, high) = fetch_fee_balance(provider, chain_id, account_address).await?;
+        let balance: BigUint = (BigUint::from_bytes_be(&high.to_bytes_be()) << 128usize)
+            + BigUint::from_bytes_be(&low.to_bytes_be());
+        let budget = FieldElement::from_byte_slice_be(
+            &(balance * BigUint::from(percentage) / BigUint::from(100u64)).to_bytes_be(),
+        )?;
+
+        progress!(
+            "Max fee limited to {}% of the account balance: {}",
+            percentage,
+            FeeFormatter::default().format(budget).bright_yellow()
+        );
+        self.balance_budget = Some(budget);
+
+        Ok(())
+    }
+
+    /// Applies the buffer for the chain to a fee estimate, falling back to `default_percentage`.
+    /// The result never exceeds the balance budget of `--max-fee-from-balance`.
+    pub fn apply(
+        &self,
+        estimated_fee: u64,
+        chain_id: FieldElement,
+        default_percentage: u64,
+    ) -> FieldElement {
+        let percentage = self.percentage(chain_id).unwrap_or(default_percentage) as u128;
+
+        // Can't overflow with a reasonable percentage, and any fee this high can't be paid anyway
+        let buffered_fee: FieldElement =
+            ((estimated_fee as u128).saturating_mul(100u128.saturating_add(percentage)) / 100)
+                .into();
+
+        match self.balance_budget {
+            Some(budget) if budget < buffered_fee => {
+                if budget < FieldElement::from(estimated_fee) {
+                    warning!(
+                        "WARNING: the fee budget from --max-fee-from-balance is below the fee \
+                        estimate. The transaction is likely to be rejected."
+                    );
+                }
+                budget
+            }
+            _ => buffered_fee,
+        }
+    }
+}
+
 impl FeeSetting {
     pub fn is_estimate_only(&self) -> bool {
         matches!(self, FeeSetting::EstimateOnly)
     }
 }
+
+/// Looks up the fee actually paid by a transaction. Transaction hashes commit to the chain id, so
+/// a transaction from another network is never found on the current provider.
+async fn fetch_actual_fee<P>(provider: P, transaction_hash: FieldElement) -> Result<FieldElement>
+where
+    P: Provider,
+    P::Error: 'static,
+{
+    let receipt = match provider.get_transaction_receipt(transaction_hash).await {
+        Ok(receipt) => receipt,
+        Err(ProviderError::StarknetError(StarknetErrorWithMessage {
+            code: MaybeUnknownErrorCode::Known(StarknetError::TransactionHashNotFound),
+            ..
+        })) => anyhow::bail!(
+            "transaction {:#064x} given to --max-fee-from not found on this network",
+            transaction_hash
+        ),
+        Err(err) => return Err(err.into()),
+    };
+
+    Ok(receipt_actual_fee(&receipt))
+}
+
+/// Fee actually charged for the transaction of `receipt`.
+pub fn receipt_actual_fee(receipt: &MaybePendingTransactionReceipt) -> FieldElement {
+    match receipt {
+        MaybePendingTransactionReceipt::Receipt(receipt) => match receipt {
+            TransactionReceipt::Invoke(receipt) => receipt.actual_fee,
+            TransactionReceipt::L1Handler(receipt) => receipt.actual_fee,
+            TransactionReceipt::Declare(receipt) => receipt.actual_fee,
+            TransactionReceipt::Deploy(receipt) => receipt.actual_fee,
+            TransactionReceipt::DeployAccount(receipt) => receipt.actual_fee,
+        },
+        MaybePendingTransactionReceipt::PendingReceipt(receipt) => match receipt {
+            PendingTransactionReceipt::Invoke(receipt) => receipt.actual_fee,
+            PendingTransactionReceipt::L1Handler(receipt) => receipt.actual_fee,
+            PendingTransactionReceipt::Declare(receipt) => receipt.actual_fee,
+            PendingTransactionReceipt::Deploy(receipt) => receipt.actual_fee,
+            PendingTransactionReceipt::DeployAccount(receipt) => receipt.actual_fee,
+        },
+    }
+}
+
+/// Warns about an estimated fee that's too low to be plausible. This is only a heuristic: it
+/// doesn't stop the transaction from being sent.
+pub fn warn_on_low_fee(estimated_fee: u64, threshold: Option<u64>) {
+    if let Some(threshold) = threshold {
+        if estimated_fee < threshold {
+            warning!(
+                "WARNING: the estimated fee ({} Wei) is implausibly low. The transaction might \
+                be failing validation. Double check the account and calldata before sending. \
+                Use --no-low-fee-warning to suppress this warning.",
+                estimated_fee
+            );
+        }
+    }
+}
+
+/// Refuses to proceed when the network is more expensive than the user is willing to pay. The
+/// estimate is compared before any buffer is applied.
+pub fn check_fee_ceiling(
+    estimated_fee: u64,
+    ceiling: Option<FieldElement>,
+    fee_formatter: &FeeFormatter,
+) -> Result<()> {
+    if let Some(ceiling) = ceiling {
+        let estimated_fee: FieldElement = estimated_fee.into();
+        if estimated_fee > ceiling {
+            anyhow::bail!(
+                "estimated fee {} exceeds the --max-fee-ceiling of {}. \
+                The transaction was not sent.",
+                fee_formatter.format(estimated_fee),
+                fee_formatter.format(ceiling)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+impl FeeFormatter {
+    pub fn format(&self, fee: FieldElement) -> String {
+        let fee_in_eth = fee.to_big_decimal(18);
+
+        match &self.usd_price {
+            // This is only informational as the price is supplied by the user
+            Some(price) => {
+                // `FieldElement::to_big_decimal` uses a different `bigdecimal` version
+                let fee_in_usd =
+                    BigDecimal::new(BigInt::from_bytes_be(Sign::Plus, &fee.to_bytes_be()), 18)
+                        * price;
+
+                format!(
+                    "{} ETH (~{} USD, approximate)",
+                    fee_in_eth,
+                    fee_in_usd.round(2)
+                )
+            }
+            None => format!("{} ETH", fee_in_eth),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+    use starknet::{
+        accounts::{Account, Call, SingleOwnerAccount},
+        macros::selector,
+        signers::{LocalWallet, SigningKey},
+    };
+
+    use crate::{mock_rpc::MockRpc, utils::send_execution};
+
+    #[test]
+    fn test_parse_fee_amount() {
+        assert_eq!(
+            parse_fee_amount("0.01eth").unwrap(),
+            FieldElement::from(10_000_000_000_000_000u64)
+        );
+        assert_eq!(
+            parse_fee_amount("5gwei").unwrap(),
+            FieldElement::from(5_000_000_000u64)
+        );
+        assert_eq!(
+            parse_fee_amount("1000wei").unwrap(),
+            FieldElement::from(1000u64)
+        );
+        assert_eq!(
+            parse_fee_amount(" 1.5 GWEI ").unwrap(),
+            FieldElement::from(1_500_000_000u64)
+        );
+        // Ether without a suffix
+        assert_eq!(
+            parse_fee_amount("0.001").unwrap(),
+            FieldElement::from(1_000_000_000_000_000u64)
+        );
+
+        assert!(parse_fee_amount("1.5wei").is_err());
+        assert!(parse_fee_amount("eth").is_err());
+    }
+
+    #[test]
+    fn test_parse_fee_amount_unit_errors() {
+        let err = parse_fee_amount("100fri").unwrap_err().to_string();
+        assert!(err.starts_with("fees can only be paid in ETH"), "{err}");
+
+        let err = parse_fee_amount("1btc").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "unknown fee unit \"btc\". Use eth, gwei, or wei, or no unit for Ether"
+        );
+    }
+
+    fn new_fee_buffer(
+        override_percentage: Option<u64>,
+        balance_budget: Option<FieldElement>,
+    ) -> FeeBuffer {
+        FeeBuffer {
+            override_percentage,
+            network_percentages: HashMap::from([(chain_id::TESTNET, 20)]),
+            balance_percentage: balance_budget.map(|_| 50),
+            balance_budget,
+        }
+    }
+
+    #[test]
+    fn test_fee_buffer_percentages() {
+        let fee_buffer = new_fee_buffer(None, None);
+        assert_eq!(
+            fee_buffer.apply(1000, chain_id::TESTNET, 50),
+            FieldElement::from(1200u64)
+        );
+        assert_eq!(
+            fee_buffer.apply(1000, chain_id::MAINNET, 50),
+            FieldElement::from(1500u64)
+        );
+
+        // --fee-buffer takes precedence over the config file
+        let fee_buffer = new_fee_buffer(Some(0), None);
+        assert_eq!(
+            fee_buffer.apply(1000, chain_id::TESTNET, 50),
+            FieldElement::from(1000u64)
+        );
+    }
+
+    #[test]
+    fn test_fee_buffer_balance_budget() {
+        // Budget above the buffered fee
+        assert_eq!(
+            new_fee_buffer(None, Some(FieldElement::from(2000u64))).apply(
+                1000,
+                chain_id::MAINNET,
+                50
+            ),
+            FieldElement::from(1500u64)
+        );
+        // Budget between the estimate and the buffered fee
+        assert_eq!(
+            new_fee_buffer(None, Some(FieldElement::from(1200u64))).apply(
+                1000,
+                chain_id::MAINNET,
+                50
+            ),
+            FieldElement::from(1200u64)
+        );
+        // Budget below the estimate, which is still used as is
+        assert_eq!(
+            new_fee_buffer(None, Some(FieldElement::from(800u64))).apply(
+                1000,
+                chain_id::MAINNET,
+                50
+            ),
+            FieldElement::from(800u64)
+        );
+    }
+
+    fn fee_config(token_address: Option<FieldElement>) -> FeeConfig {
+        FeeConfig {
+            buffer_percentage: None,
+            token_address,
+        }
+    }
+
+    #[test]
+    fn test_find_fee_token() {
+        let eth = KNOWN_FEE_TOKENS[0].1;
+        let custom_token = FieldElement::from(0x1234u64);
+        let fees = HashMap::from([
+            // Overrides a built-in token
+            ("SN_GOERLI".to_owned(), fee_config(Some(custom_token))),
+            // Only sets the buffer, so the built-in token is kept
+            ("SN_MAIN".to_owned(), fee_config(None)),
+            ("0x4d59415050".to_owned(), fee_config(Some(custom_token))),
+        ]);
+
+        assert_eq!(
+            find_fee_token(&fees, chain_id::TESTNET).unwrap(),
+            custom_token
+        );
+        assert_eq!(find_fee_token(&fees, chain_id::MAINNET).unwrap(), eth);
+        assert_eq!(find_fee_token(&fees, chain_id::TESTNET2).unwrap(), eth);
+        assert_eq!(
+            find_fee_token(&fees, FieldElement::from(0x4d59415050u64)).unwrap(),
+            custom_token
+        );
+        assert_eq!(
+            find_fee_token(&HashMap::new(), felt!("0x4b4154414e41")).unwrap(),
+            eth
+        );
+
+        assert!(find_fee_token(&fees, FieldElement::from(0x5678u64)).is_err());
+        let invalid = HashMap::from([("0xnot_hex".to_owned(), fee_config(None))]);
+        assert!(find_fee_token(&invalid, chain_id::MAINNET).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_manual_fee_skips_estimation() {
+        // A node that can't estimate fees at all
+        let rpc = MockRpc::start();
+        rpc.respond_error("starknet_estimateFee", 40, "Contract error")
+            .respond(
+                "starknet_addInvokeTransaction",
+                json!({ "transaction_hash": "0xabc" }),
+            );
+        let account = SingleOwnerAccount::new(
+            rpc.provider(),
+            LocalWallet::from_signing_key(SigningKey::from_secret_scalar(felt!("0x1"))),
+            felt!("0x1234"),
+            felt!("0x534e5f474f45524c49"),
+        );
+        let calls = vec![Call {
+            to: KNOWN_FEE_TOKENS[0].1,
+            selector: selector!("transfer"),
+            calldata: vec![felt!("0x1234"), FieldElement::ZERO, FieldElement::ZERO],
+        }];
+
+        for args in [
+            ["--max-fee-raw", "1000000000000000"],
+            ["--max-fee", "0.001"],
+        ] {
+            let fee_setting = FeeArgs::try_parse_from(std::iter::once("fee").chain(args))
+                .unwrap()
+                .into_setting(rpc.provider())
+                .await
+                .unwrap();
+            let max_fee = match fee_setting {
+                FeeSetting::Manual(max_fee) => max_fee,
+                _ => panic!("{:?} is not a manual fee", args),
+            };
+
+            // Prepared the same way as by the commands, estimation multiplier included
+            let prepare = |nonce| {
+                Ok(account
+                    .execute(calls.clone())
+                    .fee_estimate_multiplier(1.5f64)
+                    .nonce(nonce)
+                    .max_fee(max_fee)
+                    .prepared()?)
+            };
+            let nonce = felt!("0x5");
+            let (transaction_hash, _) = send_execution(
+                rpc.provider(),
+                &account,
+                prepare(nonce).unwrap(),
+                nonce,
+                0,
+                prepare,
+            )
+            .await
+            .unwrap();
+            assert_eq!(transaction_hash, felt!("0xabc"));
+        }
+
+        assert!(rpc.requests("starknet_estimateFee").is_empty());
+        let submissions = rpc.requests("starknet_addInvokeTransaction");
+        assert_eq!(submissions.len(), 2);
+        for submission in submissions.iter() {
+            assert_eq!(submission[0]["max_fee"], "0x38d7ea4c68000");
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::io::{BufRead, IsTerminal, Write};
+
+use anyhow::Result;
+use colored::Colorize;
+use starknet::core::types::FieldElement;
+
+use crate::{abi::short_type_name, chain_id::ChainIdSource, decode::FeltDecoder};
+
+/// Types encoded as a single felt, which the decoder can take as is.
+const SINGLE_FELT_TYPES: &[&str] = &[
+    "felt",
+    "felt252",
+    "ContractAddress",
+    "ClassHash",
+    "EthAddress",
+    "StorageAddress",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+];
+
+/// How a value the user types in is turned into calldata.
+enum ParameterKind {
+    Felt,
+    U256,
+    Bool,
+    /// Sierra arrays and spans, which are prefixed with their length.
+    Array(Box<ParameterKind>),
+    /// Legacy `felt*` arrays. Their length is a separate `_len` parameter, filled in automatically.
+    LegacyArray,
+    /// Structs, enums, and tuples, entered as their raw serialization.
+    Raw,
+}
+
+/// Makes sure there's someone to answer prompts, instead of waiting on input that never comes.
+pub fn ensure_terminal() -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("--interactive requires an interactive terminal");
+    }
+    Ok(())
+}
+
+/// Prompts for each parameter on stderr and returns the encoded calldata. Inputs go through the
+/// same decoder as command line arguments, so prefixes like `str:` and address book names work,
+/// and are prompted again until they're valid.
+pub async fn prompt_calldata<S>(
+    felt_decoder: &FeltDecoder<S>,
+    parameters: &[(String, String)],
+) -> Result<Vec<FieldElement>>
+where
+    S: ChainIdSource,
+{
+    let mut calldata = vec![];
+
+    for (ind_parameter, (name, r#type)) in parameters.iter().enumerate() {
+        // Legacy array lengths are derived from the array that follows
+        if let Some((next_name, next_type)) = parameters.get(ind_parameter + 1) {
+            if next_type.ends_with('*') && name == &format!("{}_len", next_name) {
+                continue;
+            }
+        }
+
+        let kind = ParameterKind::parse(r#type);
+        loop {
+            let input = read_line(&format!(
+                "{} ({}{}): ",
+                name.bright_yellow(),
+                short_type_name(r#type),
+                kind.hint()
+            ))?;
+
+            match kind.encode(felt_decoder, input.trim()).await {
+                Ok(mut encoded) => {
+                    calldata.append(&mut encoded);
+                    break;
+                }
+                Err(err) => eprintln!("{}", format!("Invalid value: {err}").red()),
+            }
+        }
+    }
+
+    Ok(calldata)
+}
+
+/// Asks a yes/no question on stderr, defaulting to no.
+pub fn confirm(question: &str) -> Result<bool> {
+    let answer = read_line(&format!("{} [y/N] ", question))?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn read_line(prompt: &str) -> Result<String> {
+    eprint!("{}", prompt);
+    std::io::stderr().flush()?;
+
+    let mut line = String::new();
+    if std::io::stdin().lock().read_line(&mut line)? == 0 {
+        anyhow::bail!("unexpected end of input");
+    }
+    Ok(line)
+}
+
+impl ParameterKind {
+    fn parse(r#type: &str) -> Self {
+        if r#type.ends_with('*') {
+            return Self::LegacyArray;
+        }
+
+        let base = r#type.split("::<").next().unwrap_or(r#type);
+        match base.rsplit("::").next().unwrap_or(base) {
+            "u256" | "Uint256" => Self::U256,
+            "bool" => Self::Bool,
+            "Array" | "Span" => {
+                let element_type = r#type
+                    .split_once('<')
+                    .and_then(|(_, rest)| rest.strip_suffix('>'))
+                    .unwrap_or_default();
+                match Self::parse(element_type) {
+                    // Nested arrays don't fit in a comma-separated list
+                    Self::Array(_) | Self::LegacyArray => Self::Raw,
+                    element_kind => Self::Array(Box::new(element_kind)),
+                }
+            }
+            name if SINGLE_FELT_TYPES.contains(&name) => Self::Felt,
+            _ => Self::Raw,
+        }
+    }
+
+    fn hint(&self) -> &'static str {
+        match self {
+            Self::Felt | Self::U256 => "",
+            Self::Bool => ", true or false",
+            Self::Array(_) | Self::LegacyArray => ", comma-separated",
+            Self::Raw => ", raw felts separated by spaces",
+        }
+    }
+
+    async fn encode<S>(
+        &self,
+        felt_decoder: &FeltDecoder<S>,
+        input: &str,
+    ) -> Result<Vec<FieldElement>>
+    where
+        S: ChainIdSource,
+    {
+        match self {
+            Self::Array(element_kind) => {
+                let elements = split_list(input);
+
+                let mut encoded = vec![elements.len().into()];
+                for element in elements.iter() {
+                    encoded.append(&mut element_kind.encode_element(felt_decoder, element).await?);
+                }
+                Ok(encoded)
+            }
+            Self::LegacyArray => {
+                let elements = split_list(input);
+
+                let mut encoded = vec![elements.len().into()];
+                for element in elements.iter() {
+                    encoded.append(&mut Self::Felt.encode_element(felt_decoder, element).await?);
+                }
+                Ok(encoded)
+            }
+            _ => self.encode_element(felt_decoder, input).await,
+        }
+    }
+
+    /// Encodes anything but arrays, which never contain other arrays here.
+    async fn encode_element<S>(
+        &self,
+        felt_decoder: &FeltDecoder<S>,
+        input: &str,
+    ) -> Result<Vec<FieldElement>>
+    where
+        S: ChainIdSource,
+    {
+        match self {
+            Self::Felt => Ok(vec![
+                felt_decoder.decode_single_with_addr_fallback(input).await?,
+            ]),
+            // Prefixed values are taken as they are
+            Self::U256 if input.contains(':') => felt_decoder.decode(input).await,
+            Self::U256 => felt_decoder.decode(&format!("u256:{}", input)).await,
+            Self::Bool => match input {
+                "true" | "1" => Ok(vec![FieldElement::ONE]),
+                "false" | "0" => Ok(vec![FieldElement::ZERO]),
+                _ => anyhow::bail!("expected true or false"),
+            },
+            Self::Array(_) | Self::LegacyArray | Self::Raw => {
+                let mut encoded = vec![];
+                for element in input.split_whitespace() {
+                    encoded.append(&mut felt_decoder.decode(element).await?);
+                }
+                Ok(encoded)
+            }
+        }
+    }
+}
+
+fn split_list(input: &str) -> Vec<&str> {
+    if input.is_empty() {
+        vec![]
+    } else {
+        input.split(',').map(|element| element.trim()).collect()
+    }
+}
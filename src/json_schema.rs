@@ -0,0 +1,183 @@
+use anyhow::Result;
+use serde_json::Value;
+
+/// Version of the `--json-envelope` format, bumped whenever a change could break consumers.
+pub const ENVELOPE_SCHEMA_VERSION: u64 = 1;
+
+/// JSON schema of the `--json-envelope` format. It's embedded so that `--strict-json` works
+/// without any file next to the binary.
+const ENVELOPE_SCHEMA: &str = include_str!("json_envelope.schema.json");
+
+/// Checks the envelope against the embedded schema, so that format drift is caught before tools
+/// relying on it see the output.
+pub fn validate_envelope(envelope: &Value) -> Result<()> {
+    let schema: Value = serde_json::from_str(ENVELOPE_SCHEMA)?;
+
+    validate(envelope, &schema, "").map_err(|err| {
+        anyhow::anyhow!(
+            "JSON output doesn't match envelope schema version {}: {}",
+            ENVELOPE_SCHEMA_VERSION,
+            err
+        )
+    })
+}
+
+/// Validates `value` against the subset of JSON schema used by the embedded schemas: `type`,
+/// `const`, `properties`, `required`, `additionalProperties`, and `items`. Other keywords are
+/// ignored.
+fn validate(value: &Value, schema: &Value, path: &str) -> Result<()> {
+    let location = if path.is_empty() { "/" } else { path };
+
+    if let Some(expected) = schema.get("const") {
+        if value != expected {
+            anyhow::bail!("{}: expected {}, found {}", location, expected, value);
+        }
+    }
+
+    if let Some(types) = schema.get("type") {
+        let types = match types {
+            Value::Array(types) => types.iter().filter_map(|t| t.as_str()).collect::<Vec<_>>(),
+            Value::String(single_type) => vec![single_type.as_str()],
+            _ => anyhow::bail!("{}: invalid schema type {}", location, types),
+        };
+        if !types.iter().any(|t| type_matches(value, t)) {
+            anyhow::bail!(
+                "{}: expected type {}, found {}",
+                location,
+                types.join(" or "),
+                type_name(value)
+            );
+        }
+    }
+
+    if let Value::Object(object) = value {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for field in required.iter().filter_map(|field| field.as_str()) {
+                if !object.contains_key(field) {
+                    anyhow::bail!("{}: missing field \"{}\"", location, field);
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(|p| p.as_object());
+        for (field, field_value) in object.iter() {
+            let field_path = format!("{}/{}", path, field);
+            match properties.and_then(|properties| properties.get(field)) {
+                Some(field_schema) => validate(field_value, field_schema, &field_path)?,
+                None => {
+                    if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+                        anyhow::bail!("{}: unexpected field \"{}\"", location, field);
+                    }
+                }
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (ind, item) in items.iter().enumerate() {
+            validate(item, item_schema, &format!("{}/{}", path, ind))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn type_matches(value: &Value, expected: &str) -> bool {
+    match expected {
+        "integer" => value.is_i64() || value.is_u64(),
+        expected => type_name(value) == expected,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use clap::CommandFactory;
+    use serde_json::json;
+
+    use crate::Cli;
+
+    fn block_number_envelope() -> Value {
+        let matches = Cli::command()
+            .try_get_matches_from(["starkli", "block-number"])
+            .unwrap();
+        crate::json_envelope_of(&matches, "{\"block_number\":10}\n".into(), &Ok(()))
+    }
+
+    #[test]
+    fn test_validate_envelope() {
+        let envelope = block_number_envelope();
+        assert_eq!(envelope["command"], "block-number");
+        validate_envelope(&envelope).unwrap();
+
+        let failed = crate::json_envelope_of(
+            &Cli::command()
+                .try_get_matches_from(["starkli", "block-number"])
+                .unwrap(),
+            String::new(),
+            &Err(anyhow::anyhow!("request failed")),
+        );
+        validate_envelope(&failed).unwrap();
+    }
+
+    #[test]
+    fn test_validate_envelope_missing_field() {
+        let mut envelope = block_number_envelope();
+        envelope.as_object_mut().unwrap().remove("warnings");
+
+        let err = validate_envelope(&envelope).unwrap_err().to_string();
+        assert!(err.contains("/: missing field \"warnings\""), "{err}");
+    }
+
+    #[test]
+    fn test_validate_envelope_wrong_type() {
+        let mut envelope = block_number_envelope();
+        envelope["command"] = json!(1);
+        let err = validate_envelope(&envelope).unwrap_err().to_string();
+        assert!(
+            err.contains("/command: expected type string, found number"),
+            "{err}"
+        );
+
+        let mut envelope = block_number_envelope();
+        envelope["warnings"] = json!(["ok", false]);
+        let err = validate_envelope(&envelope).unwrap_err().to_string();
+        assert!(err.contains("/warnings/1: expected type string"), "{err}");
+
+        let mut envelope = block_number_envelope();
+        envelope["schema_version"] = json!(2);
+        let err = validate_envelope(&envelope).unwrap_err().to_string();
+        assert!(
+            err.contains("/schema_version: expected 1, found 2"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_validate_envelope_additional_property() {
+        let mut envelope = block_number_envelope();
+        envelope["extra"] = json!(true);
+
+        let err = validate_envelope(&envelope).unwrap_err().to_string();
+        assert!(err.contains("/: unexpected field \"extra\""), "{err}");
+    }
+
+    #[test]
+    fn test_validate_integer() {
+        let schema = json!({ "type": "integer" });
+        validate(&json!(3), &schema, "").unwrap();
+        assert!(validate(&json!(3.5), &schema, "").is_err());
+    }
+}
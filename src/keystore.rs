@@ -0,0 +1,240 @@
+use std::path::Path;
+
+use aes::Aes128;
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use eth_keystore::{
+    CipherparamsJson, CryptoJson, EthKeystore, KdfType, KdfparamsType, KeystoreError,
+};
+use rand::{rngs::OsRng, RngCore};
+use sha3::{Digest, Keccak256};
+use starknet::{core::types::FieldElement, signers::SigningKey};
+use uuid::Uuid;
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+
+const SCRYPT_DKLEN: u8 = 32;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+#[derive(Debug, Clone, Parser)]
+pub struct KdfArgs {
+    #[clap(
+        long,
+        value_enum,
+        default_value_t,
+        help = "Scrypt parameters for encrypting the keystore. Heavier parameters make the \
+        password harder to brute force, but also make decryption slower"
+    )]
+    kdf_params: KdfPreset,
+}
+
+#[derive(Debug, Default, Clone, Copy, ValueEnum)]
+enum KdfPreset {
+    /// N = 2^10 (1 MiB of memory). Near instant to decrypt, for CI and other low-value keys.
+    Light,
+    /// N = 2^13 (8 MiB of memory), as used by keystores from earlier versions.
+    #[default]
+    Standard,
+    /// N = 2^18 (256 MiB of memory), as used by geth. Noticeably slower to decrypt.
+    Heavy,
+}
+
+impl KdfArgs {
+    /// Encrypts `key` into a Web3 Secret Storage keystore file. `eth-keystore` always uses its
+    /// own scrypt parameters, so the file is assembled here with its types instead. Decryption is
+    /// unaffected as the parameters are read from the file.
+    pub fn save_keystore(&self, key: &SigningKey, path: &Path, password: &str) -> Result<()> {
+        let mut salt = [0u8; 32];
+        OsRng.fill_bytes(&mut salt);
+        let mut iv = [0u8; 16];
+        OsRng.fill_bytes(&mut iv);
+
+        let log_n = self.kdf_params.log_n();
+        let scrypt_params = scrypt::Params::new(log_n, SCRYPT_R, SCRYPT_P)
+            .map_err(|_| anyhow::anyhow!("invalid scrypt parameters"))?;
+        let mut derived_key = [0u8; SCRYPT_DKLEN as usize];
+        scrypt::scrypt(password.as_bytes(), &salt, &scrypt_params, &mut derived_key)
+            .map_err(|_| anyhow::anyhow!("invalid scrypt output length"))?;
+
+        let mut ciphertext = key.secret_scalar().to_bytes_be().to_vec();
+        Aes128Ctr::new_from_slices(&derived_key[..16], &iv)
+            .map_err(|_| anyhow::anyhow!("invalid cipher key or iv length"))?
+            .apply_keystream(&mut ciphertext);
+
+        let mac = Keccak256::new()
+            .chain_update(&derived_key[16..32])
+            .chain_update(&ciphertext)
+            .finalize();
+
+        let keystore = EthKeystore {
+            crypto: CryptoJson {
+                cipher: String::from("aes-128-ctr"),
+                cipherparams: CipherparamsJson { iv: iv.to_vec() },
+                ciphertext,
+                kdf: KdfType::Scrypt,
+                kdfparams: KdfparamsType::Scrypt {
+                    dklen: SCRYPT_DKLEN,
+                    n: 1 << log_n,
+                    p: SCRYPT_P,
+                    r: SCRYPT_R,
+                    salt: salt.to_vec(),
+                },
+                mac: mac.to_vec(),
+            },
+            id: Uuid::new_v4(),
+            version: 3,
+        };
+        std::fs::write(path, serde_json::to_string(&keystore)?)?;
+
+        Ok(())
+    }
+}
+
+/// Decrypts a keystore that's already in memory, e.g. one embedded in a wallet file. `SigningKey`
+/// can only decrypt keystore files, and writing the keystore out just to read it back would leave
+/// it exposed on disk.
+pub fn decrypt_keystore(keystore: &serde_json::Value, password: &str) -> Result<SigningKey> {
+    let keystore: EthKeystore = serde_json::from_value(keystore.clone())
+        .map_err(|err| anyhow::anyhow!("invalid keystore: {}", err))?;
+
+    let derived_key = match &keystore.crypto.kdfparams {
+        KdfparamsType::Scrypt {
+            dklen,
+            n,
+            p,
+            r,
+            salt,
+        } => {
+            if !n.is_power_of_two() || *dklen < SCRYPT_DKLEN {
+                anyhow::bail!("invalid scrypt parameters");
+            }
+            let scrypt_params = scrypt::Params::new(n.trailing_zeros() as u8, *r, *p)
+                .map_err(|_| anyhow::anyhow!("invalid scrypt parameters"))?;
+            let mut derived_key = vec![0u8; *dklen as usize];
+            scrypt::scrypt(password.as_bytes(), salt, &scrypt_params, &mut derived_key)
+                .map_err(|_| anyhow::anyhow!("invalid scrypt output length"))?;
+            derived_key
+        }
+        KdfparamsType::Pbkdf2 { .. } => {
+            anyhow::bail!(
+                "only scrypt keystores can be embedded. Reference the keystore file instead"
+            )
+        }
+    };
+
+    let mac = Keccak256::new()
+        .chain_update(&derived_key[16..32])
+        .chain_update(&keystore.crypto.ciphertext)
+        .finalize();
+    if mac.as_slice() != keystore.crypto.mac.as_slice() {
+        return Err(KeystoreError::MacMismatch.into());
+    }
+
+    let mut plaintext = keystore.crypto.ciphertext;
+    Aes128Ctr::new_from_slices(&derived_key[..16], &keystore.crypto.cipherparams.iv)
+        .map_err(|_| anyhow::anyhow!("invalid cipher key or iv length"))?
+        .apply_keystream(&mut plaintext);
+
+    Ok(SigningKey::from_secret_scalar(
+        FieldElement::from_byte_slice_be(&plaintext)
+            .map_err(|_| anyhow::anyhow!("invalid keystore private key"))?,
+    ))
+}
+
+impl KdfPreset {
+    fn log_n(&self) -> u8 {
+        match self {
+            Self::Light => 10,
+            Self::Standard => 13,
+            Self::Heavy => 18,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use clap::Parser;
+    use starknet::signers::Signer;
+
+    use crate::signer::{AnySigner, SignerArgs};
+
+    /// Writes a keystore with `preset` and reads it back with the regular `--keystore` loader.
+    async fn assert_round_trip(preset: KdfPreset, expected_n: u32) {
+        let key = SigningKey::from_secret_scalar(FieldElement::from_hex_be("0x1234").unwrap());
+        let path = std::env::temp_dir().join(format!("starkli-test-{}.json", Uuid::new_v4()));
+        KdfArgs { kdf_params: preset }
+            .save_keystore(&key, &path, "password")
+            .unwrap();
+
+        let keystore: EthKeystore =
+            serde_json::from_reader(std::fs::File::open(&path).unwrap()).unwrap();
+        match keystore.crypto.kdfparams {
+            KdfparamsType::Scrypt { n, r, p, dklen, .. } => {
+                assert_eq!(
+                    (n, r, p, dklen),
+                    (expected_n, SCRYPT_R, SCRYPT_P, SCRYPT_DKLEN)
+                )
+            }
+            KdfparamsType::Pbkdf2 { .. } => panic!("keystore not encrypted with scrypt"),
+        }
+
+        let load = |password: &str| {
+            SignerArgs::try_parse_from([
+                "signer",
+                "--keystore",
+                path.to_str().unwrap(),
+                "--keystore-password",
+                password,
+            ])
+            .unwrap()
+            .into_signer()
+        };
+        let AnySigner::LocalWallet(signer) = load("password").unwrap();
+        let wrong_password = load("wrong");
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            signer.get_public_key().await.unwrap().scalar(),
+            key.verifying_key().scalar()
+        );
+        assert!(wrong_password.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_save_keystore_light() {
+        assert_round_trip(KdfPreset::Light, 1 << 10).await;
+    }
+
+    #[tokio::test]
+    async fn test_save_keystore_standard() {
+        assert_round_trip(KdfPreset::Standard, 1 << 13).await;
+    }
+
+    #[tokio::test]
+    async fn test_save_keystore_heavy() {
+        assert_round_trip(KdfPreset::Heavy, 1 << 18).await;
+    }
+
+    #[test]
+    fn test_decrypt_keystore() {
+        let key = SigningKey::from_secret_scalar(FieldElement::from_hex_be("0x1234").unwrap());
+        let path = std::env::temp_dir().join(format!("starkli-test-{}.json", Uuid::new_v4()));
+        KdfArgs {
+            kdf_params: KdfPreset::Light,
+        }
+        .save_keystore(&key, &path, "password")
+        .unwrap();
+        let keystore: serde_json::Value =
+            serde_json::from_reader(std::fs::File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let decrypted = decrypt_keystore(&keystore, "password").unwrap();
+        assert_eq!(decrypted.secret_scalar(), key.secret_scalar());
+
+        assert!(decrypt_keystore(&keystore, "wrong").is_err());
+    }
+}
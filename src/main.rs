@@ -1,29 +1,99 @@
+use std::{path::PathBuf, time::Instant};
+
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
 use colored::Colorize;
+use colored_json::{ColorMode, Output};
+use serde_json::json;
+use starknet::{core::utils::parse_cairo_short_string, providers::ProviderError};
 
-use crate::{provider::ProviderArgs, subcommands::*};
+use crate::{
+    path::ExpandedPathbufParser,
+    provider::{ExtendedProviderError, ProviderArgs},
+    subcommands::*,
+};
 
+mod abi;
 mod account;
 mod address_book;
+mod artifact;
 mod casm;
 mod chain_id;
 mod compiler;
+mod config;
 mod decode;
+mod env_file;
+mod explorer;
 mod fee;
+mod interactive;
+mod json_schema;
+mod keystore;
+mod manifest;
+mod metrics;
+#[cfg(test)]
+mod mock_rpc;
 mod network;
+mod offline;
+mod output;
 mod path;
 mod provider;
+mod replay_log;
 mod signer;
+mod spinner;
 mod subcommands;
+mod timing;
+mod typed_data;
 mod utils;
 mod verbosity;
+mod wallet;
 
 const VERSION_STRING: &str = concat!(env!("CARGO_PKG_VERSION"), " (", env!("VERGEN_GIT_SHA"), ")");
 
 #[derive(Debug, Parser)]
 #[clap(author, version, about, version = VERSION_STRING)]
 struct Cli {
+    #[clap(
+        long,
+        global = true,
+        value_parser = ExpandedPathbufParser,
+        help = "Write the command result to a file instead of stdout"
+    )]
+    output_file: Option<PathBuf>,
+    #[clap(
+        long,
+        global = true,
+        help = "Print a single JSON object with the command name, network, result, and warnings \
+        instead of the usual output"
+    )]
+    json_envelope: bool,
+    #[clap(
+        long,
+        global = true,
+        requires = "json_envelope",
+        help = "Check the --json-envelope output against its schema before printing it, and fail \
+        instead of printing an envelope that doesn't match"
+    )]
+    strict_json: bool,
+    #[clap(
+        long,
+        global = true,
+        help = "Print the JSON-RPC methods the command may use instead of running it"
+    )]
+    list_rpc_methods: bool,
+    #[clap(
+        long,
+        global = true,
+        help = "Print the time spent fetching the chain id, decoding arguments, estimating fees, \
+        sending, and waiting for confirmation to stderr once the command ends"
+    )]
+    timing: bool,
+    #[clap(
+        long,
+        global = true,
+        help = "Fail as soon as anything would contact the network, including the provider. \
+        Commands that always need the network are rejected upfront"
+    )]
+    offline: bool,
     #[clap(subcommand)]
     command: Subcommands,
 }
@@ -35,19 +105,41 @@ enum Subcommands {
     //
     #[clap(about = "Calculate selector from name")]
     Selector(Selector),
+    #[clap(about = "Calculate the storage address of a storage variable")]
+    StorageAddress(StorageAddress),
     #[clap(about = "Calculate class hash from any contract artifacts (Sierra, casm, legacy)")]
     ClassHash(ClassHash),
+    #[clap(about = "Compile a Sierra artifact to CASM and calculate the compiled class hash")]
+    CompiledClassHash(CompiledClassHash),
+    #[clap(about = "List the entrypoints of a local class artifact with their selectors")]
+    Entrypoints(Entrypoints),
     #[clap(about = "Encode string into felt with the Cairo short string representation")]
     ToCairoString(ToCairoString),
     #[clap(about = "Decode string from felt with the Cairo short string representation")]
     ParseCairoString(ParseCairoString),
+    #[clap(about = "Decode a revert reason from felts as a short string or ByteArray message")]
+    DecodeRevert(DecodeRevert),
     #[clap(about = "Print the montgomery representation of a field element")]
     Mont(Mont),
+    #[clap(about = "Split a u256 value into its low and high felts")]
+    SplitU256(SplitU256),
+    #[clap(about = "Join the low and high felts of a u256 value")]
+    JoinU256(JoinU256),
+    #[clap(about = "Convert token amounts between decimal and raw integer representations")]
+    Units(Units),
+    #[clap(about = "Compute Pedersen and Poseidon hashes of field elements")]
+    Hash(Hash),
+    #[clap(
+        about = "Compute the hash of a transaction offline, as the account would before signing"
+    )]
+    TxHash(TxHash),
     //
     // JSON-RPC query client
     //
     #[clap(about = "Call contract functions without sending transactions")]
     Call(Call),
+    #[clap(about = "Estimate the fee of a signed transaction given as JSON")]
+    Estimate(Estimate),
     #[clap(alias = "tx", about = "Get Starknet transaction by hash")]
     Transaction(Transaction),
     #[clap(about = "Get latest block number")]
@@ -62,6 +154,14 @@ enum Subcommands {
     StateUpdate(StateUpdate),
     #[clap(alias = "receipt", about = "Get transaction receipt by hash")]
     TransactionReceipt(TransactionReceipt),
+    #[clap(about = "Decode the multicall in an invoke transaction or raw account calldata")]
+    DecodeCalldata(DecodeCalldata),
+    #[clap(about = "Find where a contract was deployed via the UDC with a certain class and salt")]
+    FindDeployment(FindDeployment),
+    #[clap(about = "Scan a block range for events, optionally decoding them against the ABI")]
+    Events(Events),
+    #[clap(about = "Resolve names to addresses through the address book")]
+    Resolve(Resolve),
     #[clap(about = "Get Starknet network ID")]
     ChainId(ChainId),
     #[clap(about = "Get nonce for a certain contract")]
@@ -74,8 +174,14 @@ enum Subcommands {
     ClassByHash(ClassByHash),
     #[clap(about = "Get contract class deployed at a certain address")]
     ClassAt(ClassAt),
+    #[clap(about = "Summarize a contract class by hash without printing the whole class")]
+    ClassInfo(ClassInfo),
+    #[clap(about = "Compare the ABIs of two contract classes by hash")]
+    ClassDiff(ClassDiff),
     #[clap(about = "Get node syncing status")]
     Syncing(Syncing),
+    #[clap(about = "Check that the provider is set up correctly and report what it supports")]
+    NetworkProbe(NetworkProbe),
     //
     // Signer management
     //
@@ -94,7 +200,9 @@ enum Subcommands {
     #[clap(about = "Declare a contract class")]
     Declare(Declare),
     #[clap(about = "Deploy contract via the Universal Deployer Contract")]
-    Deploy(Deploy),
+    Deploy(Box<Deploy>),
+    #[clap(about = "Send a transaction with the nonce of a stuck one and a higher max fee")]
+    ReplaceTx(ReplaceTx),
     //
     // Misc
     //
@@ -113,20 +221,220 @@ enum Subcommands {
 
 #[tokio::main]
 async fn main() {
-    if let Err(err) = run_command(Cli::parse()).await {
+    let matches = Cli::command().get_matches();
+    let cli = match Cli::from_arg_matches(&matches) {
+        Ok(cli) => cli,
+        Err(err) => err.exit(),
+    };
+
+    let output_file = cli.output_file.clone();
+    let json_envelope = cli.json_envelope;
+    let strict_json = cli.strict_json;
+    if output_file.is_some() || json_envelope {
+        output::capture();
+    }
+    if json_envelope {
+        verbosity::collect_warnings();
+    }
+
+    let started_at = Instant::now();
+    if cli.timing {
+        timing::enable();
+    }
+
+    let result = run_command(cli).await;
+
+    timing::print_report(started_at.elapsed());
+
+    // Only failures caused by the provider, as the failed exchange is unrelated to anything else
+    if let Err(err) = &result {
+        if err
+            .chain()
+            .any(|cause| cause.is::<ProviderError<ExtendedProviderError>>())
+        {
+            verbosity::print_failed_rpc_request();
+        }
+    }
+
+    let content = if json_envelope {
+        let envelope = json_envelope_of(&matches, output::take_captured(), &result);
+        if strict_json {
+            if let Err(err) = json_schema::validate_envelope(&envelope) {
+                eprintln!("{}", format!("Error: {err}").red());
+                std::process::exit(1);
+            }
+        }
+        if output_file.is_none() {
+            // Failures are part of the envelope so that consumers only ever parse stdout
+            match colored_json::to_colored_json(&envelope, ColorMode::Auto(Output::StdOut)) {
+                Ok(envelope) => println!("{envelope}"),
+                Err(err) => eprintln!("{}", format!("Error: {err}").red()),
+            }
+            if result.is_err() {
+                std::process::exit(1);
+            }
+            return;
+        }
+        format!(
+            "{}\n",
+            serde_json::to_string_pretty(&envelope).unwrap_or_default()
+        )
+    } else {
+        output::take_captured()
+    };
+
+    // Partial results are still persisted on failure, e.g. from batch deployments
+    if let Some(output_file) = output_file {
+        if result.is_ok() || !content.is_empty() {
+            if let Err(err) = output::persist(&output_file, &content) {
+                eprintln!("{}", format!("Error: {err}").red());
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Err(err) = result {
         eprintln!("{}", format!("Error: {err}").red());
         std::process::exit(1);
     }
 }
 
+/// Wraps the captured output of a command. Output that's already JSON, e.g. from `--json`, is
+/// embedded as is, and anything else as a string.
+fn json_envelope_of(
+    matches: &ArgMatches,
+    captured: String,
+    result: &Result<()>,
+) -> serde_json::Value {
+    let mut command = vec![];
+    let mut current = matches;
+    while let Some((name, sub_matches)) = current.subcommand() {
+        command.push(name);
+        current = sub_matches;
+    }
+
+    // Only known when the command asked the provider for it
+    let network = output::recorded_chain_id().map(|chain_id| {
+        parse_cairo_short_string(&chain_id).unwrap_or_else(|_| format!("{:#x}", chain_id))
+    });
+
+    let captured = captured.trim_end();
+    let output = match serde_json::from_str::<serde_json::Value>(captured) {
+        Ok(value) => value,
+        Err(_) if captured.is_empty() => serde_json::Value::Null,
+        Err(_) => serde_json::Value::String(captured.to_owned()),
+    };
+
+    json!({
+        "schema_version": json_schema::ENVELOPE_SCHEMA_VERSION,
+        "command": command.join(" "),
+        "network": network,
+        "result": output,
+        "warnings": verbosity::take_warnings(),
+        "error": result.as_ref().err().map(|err| err.to_string()),
+    })
+}
+
+impl Subcommands {
+    /// JSON-RPC methods the command may use depending on its options. This excludes
+    /// `starknet_chainId` for checking the provider against `--network` and `starknet_specVersion`
+    /// for diagnosing unexpected responses, which any command using a provider may send.
+    fn rpc_methods(&self) -> &'static [&'static str] {
+        match self {
+            Self::Call(_) => Call::RPC_METHODS,
+            Self::Estimate(_) => Estimate::RPC_METHODS,
+            Self::Transaction(_) => Transaction::RPC_METHODS,
+            Self::BlockNumber(_) => BlockNumber::RPC_METHODS,
+            Self::BlockHash(_) => BlockHash::RPC_METHODS,
+            Self::Block(_) => Block::RPC_METHODS,
+            Self::BlockTime(_) => BlockTime::RPC_METHODS,
+            Self::StateUpdate(_) => StateUpdate::RPC_METHODS,
+            Self::TransactionReceipt(_) => TransactionReceipt::RPC_METHODS,
+            Self::DecodeCalldata(_) => DecodeCalldata::RPC_METHODS,
+            Self::FindDeployment(_) => FindDeployment::RPC_METHODS,
+            Self::Events(_) => Events::RPC_METHODS,
+            Self::Resolve(_) => Resolve::RPC_METHODS,
+            Self::ChainId(_) => ChainId::RPC_METHODS,
+            Self::Nonce(_) => Nonce::RPC_METHODS,
+            Self::Storage(_) => Storage::RPC_METHODS,
+            Self::ClassHashAt(_) => ClassHashAt::RPC_METHODS,
+            Self::ClassByHash(_) => ClassByHash::RPC_METHODS,
+            Self::ClassAt(_) => ClassAt::RPC_METHODS,
+            Self::ClassInfo(_) => ClassInfo::RPC_METHODS,
+            Self::ClassDiff(_) => ClassDiff::RPC_METHODS,
+            Self::Syncing(_) => Syncing::RPC_METHODS,
+            Self::NetworkProbe(_) => NetworkProbe::RPC_METHODS,
+            Self::Account(cmd) => cmd.rpc_methods(),
+            Self::Invoke(_) => Invoke::RPC_METHODS,
+            Self::Declare(_) => Declare::RPC_METHODS,
+            Self::Deploy(_) => Deploy::RPC_METHODS,
+            Self::ReplaceTx(_) => ReplaceTx::RPC_METHODS,
+            Self::Selector(_)
+            | Self::StorageAddress(_)
+            | Self::ClassHash(_)
+            | Self::CompiledClassHash(_)
+            | Self::Entrypoints(_)
+            | Self::ToCairoString(_)
+            | Self::ParseCairoString(_)
+            | Self::DecodeRevert(_)
+            | Self::Mont(_)
+            | Self::SplitU256(_)
+            | Self::JoinU256(_)
+            | Self::Units(_)
+            | Self::Hash(_)
+            | Self::TxHash(_)
+            | Self::Signer(_)
+            | Self::Completions(_)
+            | Self::Lab(_) => &[],
+        }
+    }
+
+    /// Whether the command can't do anything without a provider. Commands that only reach the
+    /// network with some of their options are left to the `--offline` guard instead.
+    fn requires_network(&self) -> bool {
+        match self {
+            Self::Account(_) | Self::DecodeCalldata(_) | Self::Resolve(_) => false,
+            _ => !self.rpc_methods().is_empty(),
+        }
+    }
+}
+
 async fn run_command(cli: Cli) -> Result<()> {
+    if cli.list_rpc_methods {
+        let methods = cli.command.rpc_methods();
+        if methods.is_empty() {
+            eprintln!("This command does not use any JSON-RPC method.");
+        }
+        for method in methods.iter() {
+            output::output!("{}", method);
+        }
+        return Ok(());
+    }
+
+    if cli.offline {
+        if cli.command.requires_network() {
+            anyhow::bail!("this command always needs the network and can't be used with --offline");
+        }
+        offline::enable();
+    }
+
     match cli.command {
         Subcommands::Selector(cmd) => cmd.run(),
-        Subcommands::ClassHash(cmd) => cmd.run(),
+        Subcommands::StorageAddress(cmd) => cmd.run().await,
+        Subcommands::ClassHash(cmd) => cmd.run().await,
+        Subcommands::CompiledClassHash(cmd) => cmd.run(),
+        Subcommands::Entrypoints(cmd) => cmd.run(),
         Subcommands::ToCairoString(cmd) => cmd.run(),
         Subcommands::ParseCairoString(cmd) => cmd.run(),
+        Subcommands::DecodeRevert(cmd) => cmd.run(),
         Subcommands::Mont(cmd) => cmd.run(),
+        Subcommands::SplitU256(cmd) => cmd.run(),
+        Subcommands::JoinU256(cmd) => cmd.run(),
+        Subcommands::Units(cmd) => cmd.run(),
+        Subcommands::Hash(cmd) => cmd.run().await,
+        Subcommands::TxHash(cmd) => cmd.run().await,
         Subcommands::Call(cmd) => cmd.run().await,
+        Subcommands::Estimate(cmd) => cmd.run().await,
         Subcommands::Transaction(cmd) => cmd.run().await,
         Subcommands::BlockNumber(cmd) => cmd.run().await,
         Subcommands::BlockHash(cmd) => cmd.run().await,
@@ -134,19 +442,121 @@ async fn run_command(cli: Cli) -> Result<()> {
         Subcommands::BlockTime(cmd) => cmd.run().await,
         Subcommands::StateUpdate(cmd) => cmd.run().await,
         Subcommands::TransactionReceipt(cmd) => cmd.run().await,
+        Subcommands::DecodeCalldata(cmd) => cmd.run().await,
+        Subcommands::FindDeployment(cmd) => cmd.run().await,
+        Subcommands::Events(cmd) => cmd.run().await,
+        Subcommands::Resolve(cmd) => cmd.run().await,
         Subcommands::ChainId(cmd) => cmd.run().await,
         Subcommands::Nonce(cmd) => cmd.run().await,
         Subcommands::Storage(cmd) => cmd.run().await,
         Subcommands::ClassHashAt(cmd) => cmd.run().await,
         Subcommands::ClassByHash(cmd) => cmd.run().await,
         Subcommands::ClassAt(cmd) => cmd.run().await,
+        Subcommands::ClassInfo(cmd) => cmd.run().await,
+        Subcommands::ClassDiff(cmd) => cmd.run().await,
         Subcommands::Syncing(cmd) => cmd.run().await,
-        Subcommands::Signer(cmd) => cmd.run(),
+        Subcommands::NetworkProbe(cmd) => cmd.run().await,
+        Subcommands::Signer(cmd) => cmd.run().await,
         Subcommands::Account(cmd) => cmd.run().await,
         Subcommands::Invoke(cmd) => cmd.run().await,
         Subcommands::Declare(cmd) => cmd.run().await,
         Subcommands::Deploy(cmd) => cmd.run().await,
+        Subcommands::ReplaceTx(cmd) => cmd.run().await,
         Subcommands::Completions(cmd) => cmd.run(),
         Subcommands::Lab(cmd) => cmd.run(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::try_parse_from(std::iter::once("starkli").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn test_requires_network() {
+        for args in [
+            &["block-number"][..],
+            &["nonce", "0x1"],
+            &["call", "0x1", "transfer"],
+            &["deploy", "--account", "account.json", "0x1"],
+        ] {
+            assert!(parse(args).command.requires_network(), "{args:?}");
+        }
+
+        // Local computations, and commands only reaching the network with some options
+        for args in [
+            &["selector", "transfer"][..],
+            &["hash", "pedersen", "0x1", "0x2"],
+            &["storage-address", "balance"],
+            &["resolve", "eth"],
+            &["decode-calldata", "0x1"],
+        ] {
+            assert!(!parse(args).command.requires_network(), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn test_rpc_methods() {
+        let deploy = parse(&["deploy", "--account", "account.json", "0x1"]).command;
+        let mut methods = deploy.rpc_methods().to_vec();
+        methods.sort_unstable();
+        assert_eq!(
+            methods,
+            [
+                "starknet_addInvokeTransaction",
+                "starknet_blockNumber",
+                "starknet_call",
+                "starknet_chainId",
+                "starknet_estimateFee",
+                "starknet_getClass",
+                "starknet_getClassHashAt",
+                "starknet_getNonce",
+                "starknet_getTransactionByHash",
+                "starknet_getTransactionReceipt",
+            ]
+        );
+        assert_eq!(deploy.rpc_methods(), Deploy::RPC_METHODS);
+
+        // The methods don't depend on the options
+        assert_eq!(
+            parse(&[
+                "deploy",
+                "--account",
+                "account.json",
+                "--watch",
+                "--count",
+                "2",
+                "0x1"
+            ])
+            .command
+            .rpc_methods(),
+            Deploy::RPC_METHODS
+        );
+
+        assert_eq!(
+            parse(&["block-number"]).command.rpc_methods(),
+            ["starknet_blockHashAndNumber"]
+        );
+        assert!(parse(&["selector", "transfer"])
+            .command
+            .rpc_methods()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_offline_rejects_network_commands() {
+        // Rejected before `--offline` takes effect, so other tests aren't affected
+        let err = run_command(parse(&["--offline", "block-number"]))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "this command always needs the network and can't be used with --offline"
+        );
+        assert!(!offline::is_enabled());
+    }
+}
@@ -0,0 +1,237 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::Result;
+use clap::Parser;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use starknet::core::{serde::unsigned_field_element::UfeHex, types::FieldElement};
+
+use crate::{output::persist, path::ExpandedPathbufParser};
+
+/// Version of the manifest file format, bumped on incompatible changes.
+const MANIFEST_VERSION: u64 = 1;
+
+#[derive(Debug, Clone, Parser)]
+pub struct ManifestArgs {
+    #[clap(
+        long,
+        value_name = "PATH",
+        value_parser = ExpandedPathbufParser,
+        requires = "manifest_name",
+        help = "Record the resulting resource in a JSON manifest file under the name given with \
+        --manifest-name. An existing entry with the same name is replaced"
+    )]
+    manifest_out: Option<PathBuf>,
+    #[clap(
+        long,
+        value_name = "NAME",
+        requires = "manifest_out",
+        help = "Logical name of the resource in the --manifest-out file"
+    )]
+    manifest_name: Option<String>,
+}
+
+/// A manifest file that a resource is recorded to once known.
+pub struct Manifest {
+    path: PathBuf,
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ManifestFile {
+    version: u64,
+    /// Sorted by name so that the file diffs cleanly between runs.
+    resources: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    Class,
+    Contract,
+}
+
+#[serde_as]
+#[derive(Serialize)]
+pub struct ManifestResource {
+    #[serde(rename = "type")]
+    pub kind: ResourceKind,
+    #[serde_as(as = "UfeHex")]
+    pub chain_id: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    pub class_hash: FieldElement,
+    /// Not known for contracts deployed with `--skip-address`.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<FieldElement>,
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub salt: Option<FieldElement>,
+    #[serde_as(as = "Option<Vec<UfeHex>>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub constructor_calldata: Option<Vec<FieldElement>>,
+    /// `None` for classes that were already declared, as nothing was sent.
+    #[serde_as(as = "Option<UfeHex>")]
+    pub transaction_hash: Option<FieldElement>,
+}
+
+impl ManifestArgs {
+    /// Validates the options before anything is sent, so that results are never lost to a typo.
+    pub fn into_manifest(self) -> Result<Option<Manifest>> {
+        let (path, name) = match (self.manifest_out, self.manifest_name) {
+            (Some(path), Some(name)) => (path, name),
+            _ => return Ok(None),
+        };
+
+        let regex_name = Regex::new("^[A-Za-z0-9_.-]+$").unwrap();
+        if !regex_name.is_match(&name) {
+            anyhow::bail!(
+                "invalid --manifest-name \"{}\": only letters, digits, \"_\", \".\", and \"-\" are \
+                allowed",
+                name
+            );
+        }
+
+        let manifest = Manifest { path, name };
+        // A broken file is reported now instead of after the transaction is sent
+        manifest.read()?;
+
+        Ok(Some(manifest))
+    }
+}
+
+impl Manifest {
+    /// Records `resource` under the manifest name, replacing any previous entry with that name.
+    /// Entries of other resources are kept as they are.
+    pub fn write(&self, resource: &ManifestResource) -> Result<()> {
+        let mut manifest = self.read()?;
+        manifest
+            .resources
+            .insert(self.name.clone(), serde_json::to_value(resource)?);
+
+        persist(
+            &self.path,
+            &format!("{}\n", serde_json::to_string_pretty(&manifest)?),
+        )
+    }
+
+    fn read(&self) -> Result<ManifestFile> {
+        if !self.path.exists() {
+            return Ok(ManifestFile {
+                version: MANIFEST_VERSION,
+                resources: BTreeMap::new(),
+            });
+        }
+
+        let manifest: ManifestFile = serde_json::from_reader(std::fs::File::open(&self.path)?)
+            .map_err(|err| {
+                anyhow::anyhow!("invalid manifest file {}: {}", self.path.display(), err)
+            })?;
+        if manifest.version != MANIFEST_VERSION {
+            anyhow::bail!(
+                "unsupported manifest file version {} in {}",
+                manifest.version,
+                self.path.display()
+            );
+        }
+
+        Ok(manifest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use starknet::macros::felt;
+
+    fn manifest(path: &std::path::Path, name: &str) -> Manifest {
+        Manifest {
+            path: path.to_owned(),
+            name: name.to_owned(),
+        }
+    }
+
+    fn contract(address: FieldElement) -> ManifestResource {
+        ManifestResource {
+            kind: ResourceKind::Contract,
+            chain_id: felt!("0x534e5f474f45524c49"),
+            class_hash: felt!("0x1234"),
+            address: Some(address),
+            salt: Some(felt!("0x1")),
+            constructor_calldata: Some(vec![felt!("0x2")]),
+            transaction_hash: Some(felt!("0xabc")),
+        }
+    }
+
+    #[test]
+    fn test_write_replaces_entry() {
+        let path = std::env::temp_dir().join(format!("starkli-test-{}.json", uuid::Uuid::new_v4()));
+
+        manifest(&path, "token")
+            .write(&contract(felt!("0x1")))
+            .unwrap();
+        manifest(&path, "class")
+            .write(&ManifestResource {
+                kind: ResourceKind::Class,
+                chain_id: felt!("0x534e5f474f45524c49"),
+                class_hash: felt!("0x1234"),
+                address: None,
+                salt: None,
+                constructor_calldata: None,
+                transaction_hash: None,
+            })
+            .unwrap();
+        // Redeploying under the same name updates the entry instead of adding another one
+        manifest(&path, "token")
+            .write(&contract(felt!("0x2")))
+            .unwrap();
+
+        let file = manifest(&path, "token").read().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(file.version, MANIFEST_VERSION);
+        assert_eq!(
+            file.resources.keys().collect::<Vec<_>>(),
+            ["class", "token"]
+        );
+        assert_eq!(
+            file.resources["token"],
+            serde_json::json!({
+                "type": "contract",
+                "chain_id": "0x534e5f474f45524c49",
+                "class_hash": "0x1234",
+                "address": "0x2",
+                "salt": "0x1",
+                "constructor_calldata": ["0x2"],
+                "transaction_hash": "0xabc"
+            })
+        );
+        assert_eq!(
+            file.resources["class"],
+            serde_json::json!({
+                "type": "class",
+                "chain_id": "0x534e5f474f45524c49",
+                "class_hash": "0x1234",
+                "transaction_hash": null
+            })
+        );
+    }
+
+    #[test]
+    fn test_read_rejects_other_versions() {
+        let path = std::env::temp_dir().join(format!("starkli-test-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, r#"{"version":2,"resources":{}}"#).unwrap();
+
+        let result = manifest(&path, "token").read();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("unsupported manifest file version 2"));
+    }
+}
@@ -0,0 +1,198 @@
+use std::{fmt::Write, path::PathBuf, time::Instant};
+
+use clap::Parser;
+use starknet::core::types::FieldElement;
+
+use crate::{
+    address_book::format_chain_id, output::persist, path::ExpandedPathbufParser, verbosity::warning,
+};
+
+#[derive(Debug, Clone, Parser)]
+pub struct MetricsArgs {
+    #[clap(
+        long,
+        value_name = "PATH",
+        value_parser = ExpandedPathbufParser,
+        help = "Write Prometheus metrics about the transactions sent to a file once the command \
+        ends, whether it succeeds or not"
+    )]
+    metrics_file: Option<PathBuf>,
+}
+
+/// Counters of the transactions a command submitted. They're written out when dropped, so that
+/// commands failing halfway through still report what was sent.
+pub struct Metrics {
+    path: Option<PathBuf>,
+    command: &'static str,
+    started_at: Instant,
+    chain_id: Option<FieldElement>,
+    sent: u64,
+    succeeded: u64,
+    max_fees: FieldElement,
+}
+
+impl MetricsArgs {
+    pub fn into_metrics(self, command: &'static str) -> Metrics {
+        Metrics {
+            path: self.metrics_file,
+            command,
+            started_at: Instant::now(),
+            chain_id: None,
+            sent: 0,
+            succeeded: 0,
+            max_fees: FieldElement::ZERO,
+        }
+    }
+}
+
+impl Metrics {
+    /// Counts a submission. Only transactions accepted by the node add to the fees.
+    pub fn record_send(&mut self, chain_id: FieldElement, max_fee: FieldElement, accepted: bool) {
+        self.chain_id = Some(chain_id);
+        self.sent += 1;
+        if accepted {
+            self.succeeded += 1;
+            self.max_fees += max_fee;
+        }
+    }
+
+    fn render(&self) -> String {
+        let labels = match self.chain_id {
+            Some(chain_id) => format!(
+                "{{chain_id=\"{}\",command=\"{}\"}}",
+                format_chain_id(chain_id),
+                self.command
+            ),
+            None => format!("{{command=\"{}\"}}", self.command),
+        };
+
+        let mut rendered = String::new();
+        for (name, r#type, help, value) in [
+            (
+                "starkli_transactions_sent_total",
+                "counter",
+                "Transactions submitted to the node.",
+                self.sent.to_string(),
+            ),
+            (
+                "starkli_transactions_succeeded_total",
+                "counter",
+                "Transactions accepted by the node.",
+                self.succeeded.to_string(),
+            ),
+            (
+                "starkli_transactions_failed_total",
+                "counter",
+                "Transactions rejected by the node or that failed to be submitted.",
+                (self.sent - self.succeeded).to_string(),
+            ),
+            (
+                "starkli_transaction_max_fees_wei_total",
+                "counter",
+                "Sum of the max fees of accepted transactions, an upper bound of the fees paid.",
+                self.max_fees.to_string(),
+            ),
+            (
+                "starkli_operation_duration_seconds",
+                "gauge",
+                "Time the command took, including waiting for confirmations.",
+                format!("{:.3}", self.started_at.elapsed().as_secs_f64()),
+            ),
+        ] {
+            // Writing to a `String` never fails
+            let _ = writeln!(rendered, "# HELP {} {}", name, help);
+            let _ = writeln!(rendered, "# TYPE {} {}", name, r#type);
+            let _ = writeln!(rendered, "{}{} {}", name, labels, value);
+        }
+        rendered
+    }
+}
+
+impl Drop for Metrics {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            // Replaced through a rename, so that collectors never read a partial file
+            if let Err(err) = persist(path, &self.render()) {
+                warning!(
+                    "WARNING: unable to write metrics to {}: {}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use starknet::macros::felt;
+
+    fn metrics() -> Metrics {
+        MetricsArgs { metrics_file: None }.into_metrics("invoke")
+    }
+
+    /// Rendered lines, except for the duration which depends on timing.
+    fn rendered_lines(metrics: &Metrics) -> Vec<String> {
+        metrics
+            .render()
+            .lines()
+            .filter(|line| !line.starts_with("starkli_operation_duration_seconds{"))
+            .map(|line| line.to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_render() {
+        let mut metrics = metrics();
+        metrics.record_send(felt!("0x534e5f474f45524c49"), felt!("1000"), true);
+        metrics.record_send(felt!("0x534e5f474f45524c49"), felt!("2000"), false);
+        metrics.record_send(felt!("0x534e5f474f45524c49"), felt!("500"), true);
+
+        assert_eq!(
+            rendered_lines(&metrics),
+            [
+                "# HELP starkli_transactions_sent_total Transactions submitted to the node.",
+                "# TYPE starkli_transactions_sent_total counter",
+                "starkli_transactions_sent_total{chain_id=\"SN_GOERLI\",command=\"invoke\"} 3",
+                "# HELP starkli_transactions_succeeded_total Transactions accepted by the node.",
+                "# TYPE starkli_transactions_succeeded_total counter",
+                "starkli_transactions_succeeded_total{chain_id=\"SN_GOERLI\",command=\"invoke\"} 2",
+                "# HELP starkli_transactions_failed_total Transactions rejected by the node or that \
+                failed to be submitted.",
+                "# TYPE starkli_transactions_failed_total counter",
+                "starkli_transactions_failed_total{chain_id=\"SN_GOERLI\",command=\"invoke\"} 1",
+                "# HELP starkli_transaction_max_fees_wei_total Sum of the max fees of accepted \
+                transactions, an upper bound of the fees paid.",
+                "# TYPE starkli_transaction_max_fees_wei_total counter",
+                "starkli_transaction_max_fees_wei_total{chain_id=\"SN_GOERLI\",command=\"invoke\"} \
+                1500",
+                "# HELP starkli_operation_duration_seconds Time the command took, including \
+                waiting for confirmations.",
+                "# TYPE starkli_operation_duration_seconds gauge",
+            ]
+        );
+
+        let duration = metrics
+            .render()
+            .lines()
+            .find_map(|line| {
+                line.strip_prefix(
+                    "starkli_operation_duration_seconds{chain_id=\"SN_GOERLI\",command=\"invoke\"} ",
+                )
+                .map(|value| value.to_owned())
+            })
+            .unwrap();
+        assert!(duration.parse::<f64>().is_ok(), "{duration}");
+    }
+
+    #[test]
+    fn test_render_nothing_sent() {
+        // The chain is only known once something is sent
+        let lines = rendered_lines(&metrics());
+        assert!(lines.contains(&"starkli_transactions_sent_total{command=\"invoke\"} 0".to_owned()));
+        assert!(lines
+            .contains(&"starkli_transaction_max_fees_wei_total{command=\"invoke\"} 0".to_owned()));
+    }
+}
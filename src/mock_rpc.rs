@@ -0,0 +1,204 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde_json::{json, Value};
+use url::Url;
+
+use crate::provider::ExtendedProvider;
+
+/// A JSON-RPC node served from a local port, for testing code that talks to a provider.
+///
+/// Answers each method with the responses queued for it, in order. The last response queued for
+/// a method keeps being used once the others are consumed. Methods without any response fail with
+/// the JSON-RPC "method not found" error.
+pub struct MockRpc {
+    url: Url,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    responses: HashMap<String, VecDeque<Response>>,
+    requests: Vec<(String, Value)>,
+}
+
+#[derive(Clone)]
+struct Response {
+    delay: Option<Duration>,
+    body: Result<Value, (i64, String)>,
+}
+
+impl MockRpc {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let server_state = state.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = server_state.clone();
+                // Connections are served concurrently so that a delayed response doesn't hold up
+                // the others
+                std::thread::spawn(move || Self::serve(stream, &state));
+            }
+        });
+
+        Self { url, state }
+    }
+
+    pub fn url(&self) -> Url {
+        self.url.clone()
+    }
+
+    pub fn provider(&self) -> ExtendedProvider {
+        ExtendedProvider::new_json_rpc(self.url())
+    }
+
+    /// Queues a successful response for `method`.
+    pub fn respond(&self, method: &str, result: Value) -> &Self {
+        self.push(
+            method,
+            Response {
+                delay: None,
+                body: Ok(result),
+            },
+        )
+    }
+
+    /// Queues a successful response for `method` that is only sent after `delay`.
+    pub fn respond_after(&self, method: &str, delay: Duration, result: Value) -> &Self {
+        self.push(
+            method,
+            Response {
+                delay: Some(delay),
+                body: Ok(result),
+            },
+        )
+    }
+
+    /// Queues a JSON-RPC error response for `method`.
+    pub fn respond_error(&self, method: &str, code: i64, message: &str) -> &Self {
+        self.push(
+            method,
+            Response {
+                delay: None,
+                body: Err((code, message.to_owned())),
+            },
+        )
+    }
+
+    /// Parameters of the `method` requests received so far, in order.
+    pub fn requests(&self, method: &str) -> Vec<Value> {
+        self.state
+            .lock()
+            .unwrap()
+            .requests
+            .iter()
+            .filter(|(name, _)| name == method)
+            .map(|(_, params)| params.clone())
+            .collect()
+    }
+
+    fn push(&self, method: &str, response: Response) -> &Self {
+        self.state
+            .lock()
+            .unwrap()
+            .responses
+            .entry(method.to_owned())
+            .or_default()
+            .push_back(response);
+        self
+    }
+
+    fn serve(stream: TcpStream, state: &Mutex<State>) {
+        let mut reader = BufReader::new(stream);
+
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or_default() == 0 {
+                return;
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap_or_default();
+                }
+            }
+        }
+        let mut body = vec![0u8; content_length];
+        if reader.read_exact(&mut body).is_err() {
+            return;
+        }
+
+        let request: Value = serde_json::from_slice(&body).unwrap_or_default();
+        let response = match &request {
+            Value::Array(items) => {
+                Value::Array(items.iter().map(|item| Self::answer(item, state)).collect())
+            }
+            item => Self::answer(item, state),
+        };
+        let response = response.to_string();
+
+        let mut stream = reader.into_inner();
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+            Connection: close\r\n\r\n{}",
+            response.len(),
+            response
+        );
+    }
+
+    fn answer(request: &Value, state: &Mutex<State>) -> Value {
+        let method = request["method"].as_str().unwrap_or_default().to_owned();
+        let id = request["id"].clone();
+
+        let response = {
+            let mut state = state.lock().unwrap();
+            state
+                .requests
+                .push((method.clone(), request["params"].clone()));
+            state
+                .responses
+                .get_mut(&method)
+                .and_then(|responses| match responses.len() {
+                    0 => None,
+                    1 => responses.front().cloned(),
+                    _ => responses.pop_front(),
+                })
+        };
+
+        let response = match response {
+            Some(response) => response,
+            None => {
+                return json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -32601, "message": "Method not found" },
+                })
+            }
+        };
+        if let Some(delay) = response.delay {
+            std::thread::sleep(delay);
+        }
+
+        match response.body {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        }
+    }
+}
@@ -4,7 +4,11 @@ use anyhow::Result;
 use async_trait::async_trait;
 use auto_impl::auto_impl;
 use clap::{builder::PossibleValue, ValueEnum};
-use starknet::providers::Provider;
+use starknet::{
+    core::{chain_id, types::FieldElement},
+    providers::Provider,
+};
+use url::Url;
 
 use crate::provider::ExtendedProvider;
 
@@ -23,6 +27,29 @@ pub trait NetworkSource {
     async fn get_network(&self) -> Result<Option<Network>>;
 }
 
+impl Network {
+    pub fn chain_id(&self) -> FieldElement {
+        match self {
+            Self::Mainnet => chain_id::MAINNET,
+            Self::Goerli1 | Self::Integration => chain_id::TESTNET,
+            Self::Goerli2 => chain_id::TESTNET2,
+        }
+    }
+
+    /// Default public JSON-RPC endpoint of the network, if one is known.
+    pub fn default_rpc_url(&self) -> Option<Url> {
+        match self {
+            Self::Mainnet => {
+                Some(Url::parse("https://starknet-mainnet.public.blastapi.io").unwrap())
+            }
+            Self::Goerli1 => {
+                Some(Url::parse("https://starknet-testnet.public.blastapi.io").unwrap())
+            }
+            Self::Goerli2 | Self::Integration => None,
+        }
+    }
+}
+
 impl ValueEnum for Network {
     fn value_variants<'a>() -> &'a [Self] {
         &[
@@ -0,0 +1,59 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::Result;
+
+/// Set by the global `--offline` option.
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Makes every later attempt to reach the network fail instead.
+pub fn enable() {
+    OFFLINE.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Fails with `--offline`, naming what would have been contacted. Called right before any
+/// network access that doesn't go through the provider.
+pub fn ensure_online(action: &str) -> Result<()> {
+    check_online(is_enabled(), action)
+}
+
+/// Same as [`ensure_online`], for callers that captured the option earlier.
+pub fn check_online(offline: bool, action: &str) -> Result<()> {
+    if offline {
+        anyhow::bail!("--offline is set, but the command attempted {}", action);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `enable` is never called in tests, as it would take every other test running alongside
+    // offline too
+
+    #[test]
+    fn test_check_online() {
+        check_online(false, "a starknet_chainId request").unwrap();
+        assert_eq!(
+            check_online(
+                true,
+                "downloading the contract artifact from https://example.com"
+            )
+            .unwrap_err()
+            .to_string(),
+            "--offline is set, but the command attempted downloading the contract artifact from \
+            https://example.com"
+        );
+    }
+
+    #[test]
+    fn test_ensure_online() {
+        assert!(!is_enabled());
+        ensure_online("fetching the address book").unwrap();
+    }
+}
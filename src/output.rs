@@ -0,0 +1,110 @@
+use std::{
+    path::Path,
+    sync::{Mutex, OnceLock},
+};
+
+use anyhow::Result;
+use regex::Regex;
+use starknet::core::types::FieldElement;
+
+/// Buffered primary output of the command when `--output-file` or `--json-envelope` is used.
+static BUFFER: Mutex<Option<String>> = Mutex::new(None);
+
+/// Colors are only meant for terminals, so they're stripped from captured output.
+static ANSI_ESCAPE: OnceLock<Regex> = OnceLock::new();
+
+/// Chain id last reported by a provider, for the `network` field of `--json-envelope`.
+static CHAIN_ID: Mutex<Option<FieldElement>> = Mutex::new(None);
+
+/// Prints the primary result of a command to stdout, or captures it when `--output-file` is used.
+/// Progress messages and warnings should go to stderr instead.
+macro_rules! output {
+    ($($arg:tt)*) => {
+        $crate::output::write_line(format!($($arg)*))
+    };
+}
+pub(crate) use output;
+
+/// Starts capturing output instead of printing it to stdout.
+pub fn capture() {
+    *BUFFER.lock().unwrap() = Some(String::new());
+}
+
+pub fn write_line(line: String) {
+    match BUFFER.lock().unwrap().as_mut() {
+        Some(buffer) => {
+            let regex_ansi_escape =
+                ANSI_ESCAPE.get_or_init(|| Regex::new("\x1b\\[[0-9;]*m").unwrap());
+            buffer.push_str(&regex_ansi_escape.replace_all(&line, ""));
+            buffer.push('\n');
+        }
+        None => println!("{line}"),
+    }
+}
+
+/// Stops capturing and returns the captured output.
+pub fn take_captured() -> String {
+    BUFFER.lock().unwrap().take().unwrap_or_default()
+}
+
+pub fn record_chain_id(chain_id: FieldElement) {
+    *CHAIN_ID.lock().unwrap() = Some(chain_id);
+}
+
+pub fn recorded_chain_id() -> Option<FieldElement> {
+    *CHAIN_ID.lock().unwrap()
+}
+
+/// Writes `content` to `path`. The file is written to a temporary sibling first and then renamed
+/// so that readers never observe a partially written file.
+pub fn persist(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    let mut temp_file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("invalid output file path: {}", path.display()))?
+        .to_owned();
+    temp_file_name.push(format!(".tmp-{}", std::process::id()));
+    let temp_path = path.with_file_name(temp_file_name);
+
+    std::fs::write(&temp_path, content)?;
+    std::fs::rename(&temp_path, path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_and_persist() {
+        capture();
+        write_line("\x1b[1;33m0x1234\x1b[0m".to_owned());
+        write_line("plain \x1b[35mmagenta\x1b[0m text".to_owned());
+        let captured = take_captured();
+
+        // Other tests might print while this one captures, but never in the middle of a line
+        assert!(captured.contains("0x1234\n"), "{captured:?}");
+        assert!(captured.contains("plain magenta text\n"), "{captured:?}");
+        assert!(!captured.contains('\x1b'));
+        assert_eq!(take_captured(), "");
+
+        let dir = std::env::temp_dir().join(format!("starkli-test-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("nested").join("output.txt");
+        persist(&path, &captured).unwrap();
+        persist(&path, &captured).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), captured);
+
+        // The temporary file is renamed away
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,28 +1,115 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
 use anyhow::Result;
 use async_trait::async_trait;
-use clap::Parser;
-use colored::Colorize;
+use clap::{Parser, ValueEnum};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Client,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use starknet::{
     core::{chain_id, types::*},
     providers::{
-        jsonrpc::HttpTransport, AnyProvider, JsonRpcClient, Provider, ProviderError,
+        jsonrpc::{HttpTransport, HttpTransportError, JsonRpcClientError},
+        AnyProvider, AnyProviderError, JsonRpcClient, Provider, ProviderError,
         SequencerGatewayProvider,
     },
 };
+use tokio::sync::OnceCell;
 use url::Url;
 
-use crate::network::Network;
+use crate::{
+    address_book::format_chain_id,
+    config::Config,
+    network::Network,
+    offline, output,
+    path::ExpandedPathbufParser,
+    timing::{self, Phase},
+    verbosity::{self, warning},
+};
 
 #[derive(Debug, Clone, Parser)]
 pub struct ProviderArgs {
     #[clap(
         long = "rpc",
         env = "STARKNET_RPC",
-        help = "Starknet JSON-RPC endpoint"
+        help = "Starknet JSON-RPC endpoint. Can be repeated to spread requests across endpoints \
+        of the same network"
+    )]
+    rpc: Vec<Url>,
+    #[clap(
+        long = "rpc-weight",
+        value_name = "WEIGHT",
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "Relative share of requests for each --rpc endpoint, given once per endpoint in the \
+        same order. All endpoints get the same share by default"
+    )]
+    rpc_weight: Vec<u32>,
+    #[clap(
+        long = "rpc-balance-sends",
+        help = "Spread transaction submissions and nonce lookups across --rpc endpoints too, \
+        instead of always sending them to the first one. Transactions are then looked up on the \
+        endpoint that accepted them"
+    )]
+    rpc_balance_sends: bool,
+    #[clap(
+        long = "rpc-file",
+        env = "STARKNET_RPC_FILE",
+        value_parser = ExpandedPathbufParser,
+        help = "Path to a file containing the Starknet JSON-RPC endpoint, optionally with HTTP \
+        headers. Takes precedence over --rpc"
+    )]
+    rpc_file: Option<PathBuf>,
+    #[clap(
+        long = "network",
+        env = "STARKNET_NETWORK",
+        help = "Starknet network (mainnet/goerli-1/goerli-2/integration), or a custom network \
+        defined in the config file"
+    )]
+    network: Option<String>,
+    #[clap(
+        long = "batch",
+        help = "Send the independent reads of multi-read commands as a single JSON-RPC batch \
+        request. Falls back to individual requests if the endpoint doesn't support batching"
+    )]
+    batch: bool,
+    #[clap(
+        long = "rpc-timeout",
+        env = "STARKNET_RPC_TIMEOUT",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Timeout in seconds for each request sent to the provider. Requests never time out \
+        by default"
+    )]
+    rpc_timeout: Option<u64>,
+    #[clap(
+        long = "rpc-timeout-default",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Timeout in seconds for requests without a more specific --rpc-timeout-* option. \
+        Overrides --rpc-timeout"
+    )]
+    rpc_timeout_default: Option<u64>,
+    #[clap(
+        long = "rpc-timeout-call",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Timeout in seconds for `starknet_call` requests. Overrides --rpc-timeout"
     )]
-    rpc: Option<Url>,
-    #[clap(long = "network", env = "STARKNET_NETWORK", help = "Starknet network")]
-    network: Option<Network>,
+    rpc_timeout_call: Option<u64>,
+    #[clap(
+        long = "rpc-timeout-estimate",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Timeout in seconds for `starknet_estimateFee` requests. Overrides --rpc-timeout"
+    )]
+    rpc_timeout_estimate: Option<u64>,
 }
 
 /// We need this because integration network has the same chain ID as `goerli-1`. We would otherwise
@@ -32,64 +119,215 @@ pub struct ProviderArgs {
 pub struct ExtendedProvider {
     provider: AnyProvider,
     is_integration: bool,
+    rpc_url: Option<Url>,
+    http_client: Client,
+    batch: bool,
+    timeouts: RpcTimeouts,
+    spec_version_checked: AtomicBool,
+    expected_chain_id: Option<FieldElement>,
+    chain_id_checked: AtomicBool,
+    load_balancer: Option<LoadBalancer>,
+    /// Whether `--offline` was set when the provider was created.
+    offline: bool,
+}
+
+/// Spreads requests across the endpoints of multiple `--rpc` options by smooth weighted
+/// round-robin, which interleaves endpoints instead of sending bursts to the heaviest one.
+struct LoadBalancer {
+    /// Endpoints after the first one, which is the provider itself.
+    rpcs: Vec<Url>,
+    providers: Vec<AnyProvider>,
+    /// Weight of each endpoint, starting with the first one.
+    weights: Vec<i64>,
+    current_weights: Mutex<Vec<i64>>,
+    /// Without it, submissions and the reads following up on them ([`PINNED_METHODS`]) all go
+    /// to the first endpoint, so that nonces and transactions are only ever asked about a node
+    /// that has seen every submission.
+    balance_sends: bool,
+    /// Endpoint that accepted each transaction submitted with `balance_sends`, which is then also
+    /// asked about the transaction.
+    accepted_by: Mutex<HashMap<FieldElement, usize>>,
+    /// Only set once all chains were found to match, so that concurrent requests wait for the
+    /// check in flight and a failed check is tried again.
+    chains_checked: OnceCell<()>,
+}
+
+/// Methods sent to the first endpoint unless `--rpc-balance-sends` is used.
+const PINNED_METHODS: &[&str] = &[
+    "starknet_addInvokeTransaction",
+    "starknet_addDeclareTransaction",
+    "starknet_addDeployAccountTransaction",
+    "starknet_getNonce",
+    "starknet_getTransactionByHash",
+    "starknet_getTransactionReceipt",
+];
+
+/// Request timeouts by method category. `None` means waiting indefinitely.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RpcTimeouts {
+    /// Methods without a category of their own.
+    pub default: Option<Duration>,
+    /// `starknet_call`.
+    pub call: Option<Duration>,
+    /// `starknet_estimateFee`, which is usually the slowest method as it executes transactions.
+    pub estimate: Option<Duration>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExtendedProviderError {
+    #[error(transparent)]
+    Provider(AnyProviderError),
+    #[error("{method} request timed out after {timeout}s")]
+    Timeout { method: &'static str, timeout: u64 },
+    #[error("--offline is set, but the command attempted a {method} request")]
+    Offline { method: &'static str },
+    #[error(
+        "--rpc endpoint {rpc} is on chain {}, but the first --rpc endpoint is on chain {}",
+        format_chain_id(*chain_id),
+        format_chain_id(*expected_chain_id)
+    )]
+    EndpointChainMismatch {
+        rpc: Url,
+        chain_id: FieldElement,
+        expected_chain_id: FieldElement,
+    },
+}
+
+/// The JSON-RPC specification version the types used by Starkli are built against.
+pub const JSONRPC_SPEC_VERSION: &str = "0.3.0";
+
+#[derive(Deserialize)]
+struct SpecVersionResponse {
+    result: Option<String>,
+}
+
+#[derive(Serialize)]
+struct BatchRequestItem<'a, P> {
+    jsonrpc: &'static str,
+    id: usize,
+    method: &'a str,
+    params: &'a P,
+}
+
+#[derive(Deserialize)]
+struct BatchResponseItem<R> {
+    id: usize,
+    result: Option<R>,
+    error: Option<BatchResponseError>,
+}
+
+#[derive(Deserialize)]
+struct BatchResponseError {
+    code: i64,
+    message: String,
+}
+
+/// Content of a `--rpc-file`: either a single URL, or a JSON object with `url` and `headers`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RpcFile {
+    url: Url,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+}
+
+enum ResolvedNetwork {
+    Builtin(Network),
+    Custom {
+        rpc: Url,
+        chain_id: Option<FieldElement>,
+    },
 }
 
 impl ProviderArgs {
-    pub fn into_provider(self) -> ExtendedProvider {
-        match (self.rpc, self.network) {
-            (Some(rpc), None) => ExtendedProvider::new(
-                AnyProvider::JsonRpcHttp(JsonRpcClient::new(HttpTransport::new(rpc))),
-                false,
-            ),
-            (Some(rpc), Some(_)) => {
-                eprintln!(
-                    "{}",
-                    "WARNING: when using JSON-RPC, the --network option and the STARKNET_NETWORK \
-                    environment variable are ignored, as those are for using the deprecated \
-                    sequencer gateway. See https://book.starkli.rs/providers for more details."
-                        .bright_magenta()
-                );
+    pub fn into_provider(self) -> Result<ExtendedProvider> {
+        timing::measure_sync(Phase::ProviderSetup, move || self.build_provider())
+    }
 
-                ExtendedProvider::new(
-                    AnyProvider::JsonRpcHttp(JsonRpcClient::new(HttpTransport::new(rpc))),
-                    false,
-                )
+    fn build_provider(self) -> Result<ExtendedProvider> {
+        let network = match &self.network {
+            Some(network) => Some(ResolvedNetwork::resolve(network)?),
+            None => None,
+        };
+
+        if !self.rpc_weight.is_empty() && self.rpc_weight.len() != self.rpc.len() {
+            anyhow::bail!(
+                "--rpc-weight must be given once for each --rpc endpoint: got {} weights for {} \
+                endpoints",
+                self.rpc_weight.len(),
+                self.rpc.len()
+            );
+        }
+        if self.rpc_file.is_some() && self.rpc.len() > 1 {
+            anyhow::bail!("--rpc-file cannot be used with multiple --rpc endpoints");
+        }
+
+        // All endpoints share the same client
+        let http_client = Client::new();
+        let load_balancer = match self.rpc.get(1..) {
+            Some(other_rpcs) if !other_rpcs.is_empty() => Some(LoadBalancer::new(
+                other_rpcs,
+                &http_client,
+                &self.rpc_weight,
+                self.rpc_balance_sends,
+            )),
+            _ => None,
+        };
+
+        let rpc = match (self.rpc_file, self.rpc.into_iter().next()) {
+            (Some(rpc_file), _) => Some(RpcFile::load(&rpc_file)?.into_endpoint()?),
+            (None, Some(rpc)) => Some((rpc, http_client)),
+            (None, None) => None,
+        };
+
+        let provider = match (rpc, network) {
+            // An explicit `--rpc` always wins, but the chain id of the network is still checked
+            (Some((rpc, http_client)), network) => {
+                ExtendedProvider::new_json_rpc_with_client(rpc, http_client)
+                    .with_expected_chain_id(network.and_then(|network| network.chain_id()))
             }
-            (None, Some(network)) => {
-                eprintln!(
-                    "{}",
-                    "WARNING: you're using the sequencer gateway instead of providing a JSON-RPC \
-                    endpoint. This is strongly discouraged. See https://book.starkli.rs/providers \
-                    for more details."
-                        .bright_magenta()
-                );
+            (None, Some(ResolvedNetwork::Custom { rpc, chain_id })) => {
+                ExtendedProvider::new_json_rpc(rpc).with_expected_chain_id(chain_id)
+            }
+            (None, Some(ResolvedNetwork::Builtin(network))) => match network.default_rpc_url() {
+                Some(rpc) => ExtendedProvider::new_json_rpc(rpc)
+                    .with_expected_chain_id(Some(network.chain_id())),
+                None => {
+                    warning!(
+                        "WARNING: no public JSON-RPC endpoint is known for the {} network. \
+                        Falling back to using the sequencer gateway, which is strongly \
+                        discouraged. See https://book.starkli.rs/providers for more details.",
+                        network
+                    );
 
-                ExtendedProvider::new(
-                    AnyProvider::SequencerGateway(match network {
-                        Network::Mainnet => SequencerGatewayProvider::starknet_alpha_mainnet(),
-                        Network::Goerli1 => SequencerGatewayProvider::starknet_alpha_goerli(),
-                        Network::Goerli2 => SequencerGatewayProvider::starknet_alpha_goerli_2(),
-                        Network::Integration => SequencerGatewayProvider::new(
-                            Url::parse("https://external.integration.starknet.io/gateway").unwrap(),
-                            Url::parse("https://external.integration.starknet.io/feeder_gateway")
+                    ExtendedProvider::new(
+                        AnyProvider::SequencerGateway(match network {
+                            Network::Mainnet => SequencerGatewayProvider::starknet_alpha_mainnet(),
+                            Network::Goerli1 => SequencerGatewayProvider::starknet_alpha_goerli(),
+                            Network::Goerli2 => SequencerGatewayProvider::starknet_alpha_goerli_2(),
+                            Network::Integration => SequencerGatewayProvider::new(
+                                Url::parse("https://external.integration.starknet.io/gateway")
+                                    .unwrap(),
+                                Url::parse(
+                                    "https://external.integration.starknet.io/feeder_gateway",
+                                )
                                 .unwrap(),
-                            chain_id::TESTNET,
-                        ),
-                    }),
-                    match network {
-                        Network::Mainnet | Network::Goerli1 | Network::Goerli2 => false,
-                        Network::Integration => true,
-                    },
-                )
-            }
+                                chain_id::TESTNET,
+                            ),
+                        }),
+                        match network {
+                            Network::Mainnet | Network::Goerli1 | Network::Goerli2 => false,
+                            Network::Integration => true,
+                        },
+                    )
+                }
+            },
             (None, None) => {
                 // If nothing is provided we fall back to using sequencer gateway for goerli-1
-                eprintln!(
-                    "{}",
+                warning!(
                     "WARNING: no valid provider option found. Falling back to using the sequencer \
                     gateway for the goerli-1 network. Doing this is discouraged. See \
                     https://book.starkli.rs/providers for more details."
-                        .bright_magenta()
                 );
 
                 ExtendedProvider::new(
@@ -97,6 +335,212 @@ impl ProviderArgs {
                     false,
                 )
             }
+        };
+
+        let category_timeout =
+            |timeout: Option<u64>| timeout.or(self.rpc_timeout).map(Duration::from_secs);
+
+        Ok(provider
+            .with_batch(self.batch)
+            .with_timeouts(RpcTimeouts {
+                default: category_timeout(self.rpc_timeout_default),
+                call: category_timeout(self.rpc_timeout_call),
+                estimate: category_timeout(self.rpc_timeout_estimate),
+            })
+            .with_load_balancer(load_balancer))
+    }
+
+    /// Options selecting the same endpoint, with networks resolved to their JSON-RPC endpoint so
+    /// that a change of defaults doesn't change what they point to. An `--rpc-file` is kept as is
+    /// instead, as its headers usually carry API keys.
+    pub fn explicit_args(&self) -> Result<Vec<String>> {
+        if let Some(rpc_file) = &self.rpc_file {
+            return Ok(vec![
+                "--rpc-file".into(),
+                rpc_file.to_string_lossy().into_owned(),
+            ]);
+        }
+
+        if !self.rpc.is_empty() {
+            let mut args = vec![];
+            for rpc in self.rpc.iter() {
+                args.push("--rpc".into());
+                args.push(rpc.to_string());
+            }
+            for weight in self.rpc_weight.iter() {
+                args.push("--rpc-weight".into());
+                args.push(weight.to_string());
+            }
+            if self.rpc_balance_sends {
+                args.push("--rpc-balance-sends".into());
+            }
+            return Ok(args);
+        }
+
+        let rpc = match &self.network {
+            Some(network) => match ResolvedNetwork::resolve(network)? {
+                ResolvedNetwork::Builtin(network) => network.default_rpc_url(),
+                ResolvedNetwork::Custom { rpc, .. } => Some(rpc),
+            },
+            None => None,
+        };
+
+        Ok(match (rpc, &self.network) {
+            (Some(rpc), _) => vec!["--rpc".into(), rpc.to_string()],
+            // Networks without a known endpoint use the sequencer gateway
+            (None, Some(network)) => vec!["--network".into(), network.clone()],
+            (None, None) => vec![],
+        })
+    }
+}
+
+impl RpcFile {
+    fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            anyhow::bail!("RPC file not found: {}", path.display());
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = std::fs::metadata(path)?.permissions().mode();
+            if mode & 0o004 != 0 {
+                warning!(
+                    "WARNING: the RPC file {} is readable by all users. Consider restricting \
+                    its permissions with `chmod 600`.",
+                    path.display()
+                );
+            }
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        let content = content.trim();
+
+        if content.starts_with('{') {
+            serde_json::from_str(content)
+                .map_err(|err| anyhow::anyhow!("invalid RPC file {}: {}", path.display(), err))
+        } else {
+            Ok(Self {
+                url: content.parse().map_err(|err| {
+                    anyhow::anyhow!("invalid RPC file {}: {}", path.display(), err)
+                })?,
+                headers: BTreeMap::new(),
+            })
+        }
+    }
+
+    fn into_endpoint(self) -> Result<(Url, Client)> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in self.headers.into_iter() {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| anyhow::anyhow!("invalid header name in RPC file: {}", name))?;
+            let mut value = HeaderValue::from_str(&value)
+                .map_err(|_| anyhow::anyhow!("invalid value for header {} in RPC file", name))?;
+
+            // Headers usually carry API keys
+            value.set_sensitive(true);
+
+            headers.insert(name, value);
+        }
+
+        Ok((
+            self.url,
+            Client::builder().default_headers(headers).build()?,
+        ))
+    }
+}
+
+impl ResolvedNetwork {
+    /// Built-in networks take precedence over custom networks from the config file.
+    fn resolve(name: &str) -> Result<Self> {
+        if let Ok(network) = name.parse::<Network>() {
+            return Ok(Self::Builtin(network));
+        }
+
+        let mut config = Config::load()?;
+        match config.networks.remove(name) {
+            Some(network) => Ok(Self::Custom {
+                rpc: network.rpc,
+                chain_id: network.chain_id,
+            }),
+            None => {
+                let mut known_networks = Network::value_variants()
+                    .iter()
+                    .map(|network| network.to_string())
+                    .collect::<Vec<_>>();
+                let mut custom_networks = config.networks.into_keys().collect::<Vec<_>>();
+                custom_networks.sort();
+                known_networks.append(&mut custom_networks);
+
+                anyhow::bail!(
+                    "unknown network: {}. Known networks are: {}",
+                    name,
+                    known_networks.join(", ")
+                )
+            }
+        }
+    }
+
+    fn chain_id(&self) -> Option<FieldElement> {
+        match self {
+            Self::Builtin(network) => Some(network.chain_id()),
+            Self::Custom { chain_id, .. } => *chain_id,
+        }
+    }
+}
+
+impl LoadBalancer {
+    fn new(other_rpcs: &[Url], http_client: &Client, weights: &[u32], balance_sends: bool) -> Self {
+        let weights = if weights.is_empty() {
+            vec![1; other_rpcs.len() + 1]
+        } else {
+            weights.iter().map(|weight| *weight as i64).collect()
+        };
+
+        Self {
+            rpcs: other_rpcs.to_vec(),
+            providers: other_rpcs
+                .iter()
+                .map(|rpc| {
+                    AnyProvider::JsonRpcHttp(JsonRpcClient::new(HttpTransport::new_with_client(
+                        rpc.clone(),
+                        http_client.clone(),
+                    )))
+                })
+                .collect(),
+            current_weights: Mutex::new(vec![0; weights.len()]),
+            weights,
+            balance_sends,
+            accepted_by: Mutex::new(HashMap::new()),
+            chains_checked: OnceCell::new(),
+        }
+    }
+
+    /// Index of the endpoint for the next request, where `0` is the first endpoint.
+    fn next_index(&self) -> usize {
+        let mut current_weights = self.current_weights.lock().unwrap();
+        let total_weight: i64 = self.weights.iter().sum();
+
+        let mut selected = 0;
+        for (ind, weight) in self.weights.iter().enumerate() {
+            current_weights[ind] += weight;
+            if current_weights[ind] > current_weights[selected] {
+                selected = ind;
+            }
+        }
+        current_weights[selected] -= total_weight;
+
+        selected
+    }
+}
+
+impl RpcTimeouts {
+    fn for_method(&self, method: &str) -> Option<Duration> {
+        match method {
+            "starknet_call" => self.call,
+            "starknet_estimateFee" => self.estimate,
+            _ => self.default,
         }
     }
 }
@@ -106,7 +550,242 @@ impl ExtendedProvider {
         Self {
             provider,
             is_integration,
+            rpc_url: None,
+            http_client: Client::new(),
+            batch: false,
+            timeouts: RpcTimeouts::default(),
+            spec_version_checked: AtomicBool::new(false),
+            expected_chain_id: None,
+            chain_id_checked: AtomicBool::new(false),
+            load_balancer: None,
+            offline: offline::is_enabled(),
+        }
+    }
+
+    pub fn new_json_rpc(rpc: Url) -> Self {
+        Self::new_json_rpc_with_client(rpc, Client::new())
+    }
+
+    /// Uses a preconfigured HTTP client, e.g. one that sends authentication headers.
+    pub fn new_json_rpc_with_client(rpc: Url, http_client: Client) -> Self {
+        Self {
+            provider: AnyProvider::JsonRpcHttp(JsonRpcClient::new(HttpTransport::new_with_client(
+                rpc.clone(),
+                http_client.clone(),
+            ))),
+            is_integration: false,
+            rpc_url: Some(rpc),
+            http_client,
+            batch: false,
+            timeouts: RpcTimeouts::default(),
+            spec_version_checked: AtomicBool::new(false),
+            expected_chain_id: None,
+            chain_id_checked: AtomicBool::new(false),
+            load_balancer: None,
+            offline: offline::is_enabled(),
+        }
+    }
+
+    /// Warns when the chain id reported by the provider is not what the selected network expects.
+    pub fn with_expected_chain_id(mut self, expected_chain_id: Option<FieldElement>) -> Self {
+        self.expected_chain_id = expected_chain_id;
+        self
+    }
+
+    pub fn with_batch(mut self, batch: bool) -> Self {
+        self.batch = batch;
+        self
+    }
+
+    pub fn with_timeouts(mut self, timeouts: RpcTimeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    fn with_load_balancer(mut self, load_balancer: Option<LoadBalancer>) -> Self {
+        self.load_balancer = load_balancer;
+        self
+    }
+
+    /// Provider to send a `method` request to. Batches and other requests sent manually always go
+    /// to the first endpoint.
+    fn endpoint(&self, method: &str) -> &AnyProvider {
+        self.provider_at(self.endpoint_index(method, None))
+    }
+
+    /// Index of the endpoint for a `method` request, where `0` is the first endpoint. Requests
+    /// about a transaction go to the endpoint that accepted it if known.
+    fn endpoint_index(&self, method: &str, transaction_hash: Option<FieldElement>) -> usize {
+        let load_balancer = match &self.load_balancer {
+            Some(load_balancer) => load_balancer,
+            None => return 0,
+        };
+        if let Some(index) = transaction_hash.and_then(|transaction_hash| {
+            load_balancer
+                .accepted_by
+                .lock()
+                .unwrap()
+                .get(&transaction_hash)
+                .copied()
+        }) {
+            return index;
+        }
+        if !load_balancer.balance_sends && PINNED_METHODS.contains(&method) {
+            return 0;
+        }
+
+        load_balancer.next_index()
+    }
+
+    fn provider_at(&self, index: usize) -> &AnyProvider {
+        match (index, &self.load_balancer) {
+            (0, _) | (_, None) => &self.provider,
+            (index, Some(load_balancer)) => &load_balancer.providers[index - 1],
+        }
+    }
+
+    /// Sends a transaction submission to the endpoint at `index`, remembering it as the one that
+    /// accepted the transaction.
+    async fn submit<T, F>(
+        &self,
+        method: &'static str,
+        index: usize,
+        submission: F,
+        transaction_hash: impl FnOnce(&T) -> FieldElement,
+    ) -> Result<T, ProviderError<ExtendedProviderError>>
+    where
+        F: Future<Output = Result<T, ProviderError<AnyProviderError>>>,
+    {
+        let result = self.request(method, submission).await?;
+
+        if let Some(load_balancer) = &self.load_balancer {
+            load_balancer
+                .accepted_by
+                .lock()
+                .unwrap()
+                .insert(transaction_hash(&result), index);
+        }
+
+        Ok(result)
+    }
+
+    /// Makes sure all `--rpc` endpoints are on the same chain before the first request goes out,
+    /// as responses from different networks would otherwise be mixed up silently.
+    async fn check_endpoint_chains(
+        &self,
+        load_balancer: &LoadBalancer,
+    ) -> Result<(), ProviderError<ExtendedProviderError>> {
+        load_balancer
+            .chains_checked
+            .get_or_try_init(|| async {
+                let expected_chain_id = self
+                    .send_request(
+                        "starknet_chainId",
+                        <AnyProvider as Provider>::chain_id(&self.provider),
+                    )
+                    .await?;
+                for (rpc, provider) in load_balancer
+                    .rpcs
+                    .iter()
+                    .zip(load_balancer.providers.iter())
+                {
+                    let chain_id = self
+                        .send_request(
+                            "starknet_chainId",
+                            <AnyProvider as Provider>::chain_id(provider),
+                        )
+                        .await?;
+                    if chain_id != expected_chain_id {
+                        return Err(ProviderError::Other(
+                            ExtendedProviderError::EndpointChainMismatch {
+                                rpc: rpc.clone(),
+                                chain_id,
+                                expected_chain_id,
+                            },
+                        ));
+                    }
+                }
+
+                Ok(())
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Sends one request per item in `params` as a single JSON-RPC batch, returning the results in
+    /// the same order. `None` is returned when batching is disabled or not supported by the
+    /// endpoint, in which case the caller is expected to send the requests individually.
+    pub async fn batch_request<P, R>(&self, method: &str, params: &[P]) -> Result<Option<Vec<R>>>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        if !self.batch {
+            return Ok(None);
+        }
+        offline::check_online(self.offline, &format!("a batch of {} requests", method))?;
+        let rpc_url = match &self.rpc_url {
+            Some(rpc_url) => rpc_url,
+            None => {
+                warning!(
+                    "WARNING: batch requests are only supported on JSON-RPC. Sending requests \
+                    individually instead."
+                );
+                return Ok(None);
+            }
+        };
+
+        let requests = params
+            .iter()
+            .enumerate()
+            .map(|(id, params)| BatchRequestItem {
+                jsonrpc: "2.0",
+                id,
+                method,
+                params,
+            })
+            .collect::<Vec<_>>();
+
+        let mut request = self.http_client.post(rpc_url.clone()).json(&requests);
+        if let Some(timeout) = self.timeouts.default {
+            request = request.timeout(timeout);
         }
+        let response = request.send().await?.json::<serde_json::Value>().await?;
+
+        // Endpoints without batch support usually respond with a single error object instead
+        let mut responses = match serde_json::from_value::<Vec<BatchResponseItem<R>>>(response) {
+            Ok(responses) if responses.len() == params.len() => responses,
+            _ => {
+                warning!(
+                    "WARNING: the JSON-RPC endpoint does not seem to support batch requests. \
+                    Sending requests individually instead."
+                );
+                return Ok(None);
+            }
+        };
+        responses.sort_by_key(|response| response.id);
+
+        responses
+            .into_iter()
+            .enumerate()
+            .map(
+                |(ind, response)| match (response.id == ind, response.result, response.error) {
+                    (false, _, _) => anyhow::bail!("invalid batch response: missing id {}", ind),
+                    (true, _, Some(error)) => anyhow::bail!(
+                        "JSON-RPC error in batch request {}: {} (code {})",
+                        ind,
+                        error.message,
+                        error.code
+                    ),
+                    (true, Some(result), None) => Ok(result),
+                    (true, None, None) => {
+                        anyhow::bail!("invalid batch response: no result for id {}", ind)
+                    }
+                },
+            )
+            .collect::<Result<Vec<_>>>()
+            .map(Some)
     }
 
     pub fn is_rpc(&self) -> bool {
@@ -116,12 +795,154 @@ impl ExtendedProvider {
     pub fn is_integration(&self) -> bool {
         self.is_integration
     }
+
+    /// Sends `request` to the underlying provider, once the endpoints are known to all be on the
+    /// same chain.
+    async fn request<T, F>(
+        &self,
+        method: &'static str,
+        request: F,
+    ) -> Result<T, ProviderError<ExtendedProviderError>>
+    where
+        F: Future<Output = Result<T, ProviderError<AnyProviderError>>>,
+    {
+        if self.offline {
+            return Err(ProviderError::Other(ExtendedProviderError::Offline {
+                method,
+            }));
+        }
+        if let Some(load_balancer) = &self.load_balancer {
+            self.check_endpoint_chains(load_balancer).await?;
+        }
+
+        self.send_request(method, request).await
+    }
+
+    /// Sends `request` right away, giving up after the timeout configured for the category of
+    /// `method`.
+    async fn send_request<T, F>(
+        &self,
+        method: &'static str,
+        request: F,
+    ) -> Result<T, ProviderError<ExtendedProviderError>>
+    where
+        F: Future<Output = Result<T, ProviderError<AnyProviderError>>>,
+    {
+        let request = async {
+            match timing_phase(method) {
+                Some(phase) => timing::measure(phase, request).await,
+                None => request.await,
+            }
+        };
+
+        let result = match self.timeouts.for_method(method) {
+            Some(timeout) => match tokio::time::timeout(timeout, request).await {
+                Ok(result) => result,
+                Err(_) => {
+                    verbosity::record_rpc_result(false);
+                    return Err(ProviderError::Other(ExtendedProviderError::Timeout {
+                        method,
+                        timeout: timeout.as_secs(),
+                    }));
+                }
+            },
+            None => request.await,
+        };
+
+        self.inspect_error(result).await
+    }
+
+    /// Deserialization failures are most likely caused by the node implementing a different
+    /// JSON-RPC spec version. When that happens, we look up the actual version the node uses (only
+    /// once per invocation) to turn the cryptic serde error into something actionable.
+    async fn inspect_error<T>(
+        &self,
+        result: Result<T, ProviderError<AnyProviderError>>,
+    ) -> Result<T, ProviderError<ExtendedProviderError>> {
+        if let Err(ProviderError::Other(AnyProviderError::JsonRpcHttp(
+            JsonRpcClientError::JsonError(_)
+            | JsonRpcClientError::TransportError(HttpTransportError::Json(_)),
+        ))) = &result
+        {
+            if !self.spec_version_checked.swap(true, Ordering::Relaxed) {
+                self.warn_on_spec_version_mismatch().await;
+            }
+        }
+
+        verbosity::record_rpc_result(result.is_ok());
+
+        result.map_err(|err| match err {
+            ProviderError::StarknetError(err) => ProviderError::StarknetError(err),
+            ProviderError::RateLimited => ProviderError::RateLimited,
+            ProviderError::ArrayLengthMismatch => ProviderError::ArrayLengthMismatch,
+            ProviderError::Other(err) => ProviderError::Other(ExtendedProviderError::Provider(err)),
+        })
+    }
+
+    /// Gets the JSON-RPC spec version implemented by the node.
+    pub async fn spec_version(&self) -> Result<String> {
+        let rpc_url = match &self.rpc_url {
+            Some(rpc_url) => rpc_url,
+            None => anyhow::bail!("spec version is only available on JSON-RPC"),
+        };
+        offline::check_online(self.offline, "a starknet_specVersion request")?;
+
+        // `starknet_specVersion` is not available in `starknet-rs` yet so we send it manually
+        let mut request = self
+            .http_client
+            .post(rpc_url.clone())
+            .header("Content-Type", "application/json")
+            .body(r#"{"jsonrpc":"2.0","id":1,"method":"starknet_specVersion","params":[]}"#);
+        if let Some(timeout) = self.timeouts.default {
+            request = request.timeout(timeout);
+        }
+
+        match request
+            .send()
+            .await?
+            .json::<SpecVersionResponse>()
+            .await?
+            .result
+        {
+            Some(spec_version) => Ok(spec_version),
+            None => anyhow::bail!("the node does not support starknet_specVersion"),
+        }
+    }
+
+    async fn warn_on_spec_version_mismatch(&self) {
+        if self.rpc_url.is_none() {
+            return;
+        }
+        let spec_version = self.spec_version().await.ok();
+
+        match spec_version {
+            Some(spec_version) if spec_version != JSONRPC_SPEC_VERSION => {
+                warning!(
+                    "WARNING: failed to parse the JSON-RPC response. The node implements \
+                    JSON-RPC spec version {}, but this version of Starkli expects {}. Use an \
+                    RPC endpoint for spec version {}, or a Starkli version matching the node.",
+                    spec_version,
+                    JSONRPC_SPEC_VERSION,
+                    JSONRPC_SPEC_VERSION
+                );
+            }
+            Some(_) => {}
+            None => {
+                warning!(
+                    "WARNING: failed to parse the JSON-RPC response. This usually happens \
+                    when the node implements a JSON-RPC spec version other than {}, which \
+                    is the one this version of Starkli expects.",
+                    JSONRPC_SPEC_VERSION
+                );
+            }
+        }
+    }
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl Provider for ExtendedProvider {
-    type Error = <AnyProvider as Provider>::Error;
+    type Error = ExtendedProviderError;
 
     async fn get_block_with_tx_hashes<B>(
         &self,
@@ -130,7 +951,14 @@ impl Provider for ExtendedProvider {
     where
         B: AsRef<BlockId> + Send + Sync,
     {
-        <AnyProvider as Provider>::get_block_with_tx_hashes(&self.provider, block_id).await
+        self.request(
+            "starknet_getBlockWithTxHashes",
+            <AnyProvider as Provider>::get_block_with_tx_hashes(
+                self.endpoint("starknet_getBlockWithTxHashes"),
+                block_id,
+            ),
+        )
+        .await
     }
 
     async fn get_block_with_txs<B>(
@@ -140,7 +968,14 @@ impl Provider for ExtendedProvider {
     where
         B: AsRef<BlockId> + Send + Sync,
     {
-        <AnyProvider as Provider>::get_block_with_txs(&self.provider, block_id).await
+        self.request(
+            "starknet_getBlockWithTxs",
+            <AnyProvider as Provider>::get_block_with_txs(
+                self.endpoint("starknet_getBlockWithTxs"),
+                block_id,
+            ),
+        )
+        .await
     }
 
     async fn get_state_update<B>(
@@ -150,7 +985,14 @@ impl Provider for ExtendedProvider {
     where
         B: AsRef<BlockId> + Send + Sync,
     {
-        <AnyProvider as Provider>::get_state_update(&self.provider, block_id).await
+        self.request(
+            "starknet_getStateUpdate",
+            <AnyProvider as Provider>::get_state_update(
+                self.endpoint("starknet_getStateUpdate"),
+                block_id,
+            ),
+        )
+        .await
     }
 
     async fn get_storage_at<A, K, B>(
@@ -164,8 +1006,16 @@ impl Provider for ExtendedProvider {
         K: AsRef<FieldElement> + Send + Sync,
         B: AsRef<BlockId> + Send + Sync,
     {
-        <AnyProvider as Provider>::get_storage_at(&self.provider, contract_address, key, block_id)
-            .await
+        self.request(
+            "starknet_getStorageAt",
+            <AnyProvider as Provider>::get_storage_at(
+                self.endpoint("starknet_getStorageAt"),
+                contract_address,
+                key,
+                block_id,
+            ),
+        )
+        .await
     }
 
     async fn get_transaction_by_hash<H>(
@@ -175,7 +1025,18 @@ impl Provider for ExtendedProvider {
     where
         H: AsRef<FieldElement> + Send + Sync,
     {
-        <AnyProvider as Provider>::get_transaction_by_hash(&self.provider, transaction_hash).await
+        let index = self.endpoint_index(
+            "starknet_getTransactionByHash",
+            Some(*transaction_hash.as_ref()),
+        );
+        self.request(
+            "starknet_getTransactionByHash",
+            <AnyProvider as Provider>::get_transaction_by_hash(
+                self.provider_at(index),
+                transaction_hash,
+            ),
+        )
+        .await
     }
 
     async fn get_transaction_by_block_id_and_index<B>(
@@ -186,10 +1047,13 @@ impl Provider for ExtendedProvider {
     where
         B: AsRef<BlockId> + Send + Sync,
     {
-        <AnyProvider as Provider>::get_transaction_by_block_id_and_index(
-            &self.provider,
-            block_id,
-            index,
+        self.request(
+            "starknet_getTransactionByBlockIdAndIndex",
+            <AnyProvider as Provider>::get_transaction_by_block_id_and_index(
+                self.endpoint("starknet_getTransactionByBlockIdAndIndex"),
+                block_id,
+                index,
+            ),
         )
         .await
     }
@@ -201,7 +1065,18 @@ impl Provider for ExtendedProvider {
     where
         H: AsRef<FieldElement> + Send + Sync,
     {
-        <AnyProvider as Provider>::get_transaction_receipt(&self.provider, transaction_hash).await
+        let index = self.endpoint_index(
+            "starknet_getTransactionReceipt",
+            Some(*transaction_hash.as_ref()),
+        );
+        self.request(
+            "starknet_getTransactionReceipt",
+            <AnyProvider as Provider>::get_transaction_receipt(
+                self.provider_at(index),
+                transaction_hash,
+            ),
+        )
+        .await
     }
 
     async fn get_class<B, H>(
@@ -213,7 +1088,15 @@ impl Provider for ExtendedProvider {
         B: AsRef<BlockId> + Send + Sync,
         H: AsRef<FieldElement> + Send + Sync,
     {
-        <AnyProvider as Provider>::get_class(&self.provider, block_id, class_hash).await
+        self.request(
+            "starknet_getClass",
+            <AnyProvider as Provider>::get_class(
+                self.endpoint("starknet_getClass"),
+                block_id,
+                class_hash,
+            ),
+        )
+        .await
     }
 
     async fn get_class_hash_at<B, A>(
@@ -225,8 +1108,15 @@ impl Provider for ExtendedProvider {
         B: AsRef<BlockId> + Send + Sync,
         A: AsRef<FieldElement> + Send + Sync,
     {
-        <AnyProvider as Provider>::get_class_hash_at(&self.provider, block_id, contract_address)
-            .await
+        self.request(
+            "starknet_getClassHashAt",
+            <AnyProvider as Provider>::get_class_hash_at(
+                self.endpoint("starknet_getClassHashAt"),
+                block_id,
+                contract_address,
+            ),
+        )
+        .await
     }
 
     async fn get_class_at<B, A>(
@@ -238,7 +1128,15 @@ impl Provider for ExtendedProvider {
         B: AsRef<BlockId> + Send + Sync,
         A: AsRef<FieldElement> + Send + Sync,
     {
-        <AnyProvider as Provider>::get_class_at(&self.provider, block_id, contract_address).await
+        self.request(
+            "starknet_getClassAt",
+            <AnyProvider as Provider>::get_class_at(
+                self.endpoint("starknet_getClassAt"),
+                block_id,
+                contract_address,
+            ),
+        )
+        .await
     }
 
     async fn get_block_transaction_count<B>(
@@ -248,7 +1146,14 @@ impl Provider for ExtendedProvider {
     where
         B: AsRef<BlockId> + Send + Sync,
     {
-        <AnyProvider as Provider>::get_block_transaction_count(&self.provider, block_id).await
+        self.request(
+            "starknet_getBlockTransactionCount",
+            <AnyProvider as Provider>::get_block_transaction_count(
+                self.endpoint("starknet_getBlockTransactionCount"),
+                block_id,
+            ),
+        )
+        .await
     }
 
     async fn call<R, B>(
@@ -260,7 +1165,11 @@ impl Provider for ExtendedProvider {
         R: AsRef<FunctionCall> + Send + Sync,
         B: AsRef<BlockId> + Send + Sync,
     {
-        <AnyProvider as Provider>::call(&self.provider, request, block_id).await
+        self.request(
+            "starknet_call",
+            <AnyProvider as Provider>::call(self.endpoint("starknet_call"), request, block_id),
+        )
+        .await
     }
 
     async fn estimate_fee<R, B>(
@@ -272,29 +1181,78 @@ impl Provider for ExtendedProvider {
         R: AsRef<[BroadcastedTransaction]> + Send + Sync,
         B: AsRef<BlockId> + Send + Sync,
     {
-        <AnyProvider as Provider>::estimate_fee(&self.provider, request, block_id).await
+        self.request(
+            "starknet_estimateFee",
+            <AnyProvider as Provider>::estimate_fee(
+                self.endpoint("starknet_estimateFee"),
+                request,
+                block_id,
+            ),
+        )
+        .await
     }
 
     async fn block_number(&self) -> Result<u64, ProviderError<Self::Error>> {
-        <AnyProvider as Provider>::block_number(&self.provider).await
+        self.request(
+            "starknet_blockNumber",
+            <AnyProvider as Provider>::block_number(self.endpoint("starknet_blockNumber")),
+        )
+        .await
     }
 
     async fn block_hash_and_number(
         &self,
     ) -> Result<BlockHashAndNumber, ProviderError<Self::Error>> {
-        <AnyProvider as Provider>::block_hash_and_number(&self.provider).await
+        self.request(
+            "starknet_blockHashAndNumber",
+            <AnyProvider as Provider>::block_hash_and_number(
+                self.endpoint("starknet_blockHashAndNumber"),
+            ),
+        )
+        .await
     }
 
     async fn chain_id(&self) -> Result<FieldElement, ProviderError<Self::Error>> {
-        <AnyProvider as Provider>::chain_id(&self.provider).await
+        let chain_id = self
+            .request(
+                "starknet_chainId",
+                <AnyProvider as Provider>::chain_id(self.endpoint("starknet_chainId")),
+            )
+            .await?;
+        output::record_chain_id(chain_id);
+
+        if let Some(expected_chain_id) = self.expected_chain_id {
+            if chain_id != expected_chain_id && !self.chain_id_checked.swap(true, Ordering::Relaxed)
+            {
+                warning!(
+                    "WARNING: the RPC endpoint reports chain id {:#x}, but the selected \
+                    network expects {:#x}. Make sure the RPC endpoint belongs to the network \
+                    you intend to use.",
+                    chain_id,
+                    expected_chain_id
+                );
+            }
+        }
+
+        Ok(chain_id)
     }
 
     async fn pending_transactions(&self) -> Result<Vec<Transaction>, ProviderError<Self::Error>> {
-        <AnyProvider as Provider>::pending_transactions(&self.provider).await
+        self.request(
+            "starknet_pendingTransactions",
+            <AnyProvider as Provider>::pending_transactions(
+                self.endpoint("starknet_pendingTransactions"),
+            ),
+        )
+        .await
     }
 
     async fn syncing(&self) -> Result<SyncStatusType, ProviderError<Self::Error>> {
-        <AnyProvider as Provider>::syncing(&self.provider).await
+        self.request(
+            "starknet_syncing",
+            <AnyProvider as Provider>::syncing(self.endpoint("starknet_syncing")),
+        )
+        .await
     }
 
     async fn get_events(
@@ -303,11 +1261,14 @@ impl Provider for ExtendedProvider {
         continuation_token: Option<String>,
         chunk_size: u64,
     ) -> Result<EventsPage, ProviderError<Self::Error>> {
-        <AnyProvider as Provider>::get_events(
-            &self.provider,
-            filter,
-            continuation_token,
-            chunk_size,
+        self.request(
+            "starknet_getEvents",
+            <AnyProvider as Provider>::get_events(
+                self.endpoint("starknet_getEvents"),
+                filter,
+                continuation_token,
+                chunk_size,
+            ),
         )
         .await
     }
@@ -321,7 +1282,15 @@ impl Provider for ExtendedProvider {
         B: AsRef<BlockId> + Send + Sync,
         A: AsRef<FieldElement> + Send + Sync,
     {
-        <AnyProvider as Provider>::get_nonce(&self.provider, block_id, contract_address).await
+        self.request(
+            "starknet_getNonce",
+            <AnyProvider as Provider>::get_nonce(
+                self.endpoint("starknet_getNonce"),
+                block_id,
+                contract_address,
+            ),
+        )
+        .await
     }
 
     async fn add_invoke_transaction<I>(
@@ -331,7 +1300,17 @@ impl Provider for ExtendedProvider {
     where
         I: AsRef<BroadcastedInvokeTransaction> + Send + Sync,
     {
-        <AnyProvider as Provider>::add_invoke_transaction(&self.provider, invoke_transaction).await
+        let index = self.endpoint_index("starknet_addInvokeTransaction", None);
+        self.submit(
+            "starknet_addInvokeTransaction",
+            index,
+            <AnyProvider as Provider>::add_invoke_transaction(
+                self.provider_at(index),
+                invoke_transaction,
+            ),
+            |result: &InvokeTransactionResult| result.transaction_hash,
+        )
+        .await
     }
 
     async fn add_declare_transaction<D>(
@@ -341,8 +1320,17 @@ impl Provider for ExtendedProvider {
     where
         D: AsRef<BroadcastedDeclareTransaction> + Send + Sync,
     {
-        <AnyProvider as Provider>::add_declare_transaction(&self.provider, declare_transaction)
-            .await
+        let index = self.endpoint_index("starknet_addDeclareTransaction", None);
+        self.submit(
+            "starknet_addDeclareTransaction",
+            index,
+            <AnyProvider as Provider>::add_declare_transaction(
+                self.provider_at(index),
+                declare_transaction,
+            ),
+            |result: &DeclareTransactionResult| result.transaction_hash,
+        )
+        .await
     }
 
     async fn add_deploy_account_transaction<D>(
@@ -352,10 +1340,230 @@ impl Provider for ExtendedProvider {
     where
         D: AsRef<BroadcastedDeployAccountTransaction> + Send + Sync,
     {
-        <AnyProvider as Provider>::add_deploy_account_transaction(
-            &self.provider,
-            deploy_account_transaction,
+        let index = self.endpoint_index("starknet_addDeployAccountTransaction", None);
+        self.submit(
+            "starknet_addDeployAccountTransaction",
+            index,
+            <AnyProvider as Provider>::add_deploy_account_transaction(
+                self.provider_at(index),
+                deploy_account_transaction,
+            ),
+            |result: &DeployAccountTransactionResult| result.transaction_hash,
         )
         .await
     }
 }
+
+/// The `--timing` phase that requests of `method` count towards. Other requests are only part of
+/// the total.
+fn timing_phase(method: &str) -> Option<Phase> {
+    match method {
+        "starknet_chainId" => Some(Phase::ChainId),
+        "starknet_estimateFee" => Some(Phase::Estimate),
+        "starknet_addInvokeTransaction"
+        | "starknet_addDeclareTransaction"
+        | "starknet_addDeployAccountTransaction" => Some(Phase::Send),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    use crate::mock_rpc::MockRpc;
+
+    fn provider_with_endpoints(weights: &[u32], balance_sends: bool) -> ExtendedProvider {
+        let rpcs = (1..weights.len())
+            .map(|ind| Url::parse(&format!("http://127.0.0.1:{}", 9000 + ind)).unwrap())
+            .collect::<Vec<_>>();
+
+        ExtendedProvider::new_json_rpc(Url::parse("http://127.0.0.1:9000").unwrap())
+            .with_load_balancer(Some(LoadBalancer::new(
+                &rpcs,
+                &Client::new(),
+                weights,
+                balance_sends,
+            )))
+    }
+
+    fn balanced_provider(first: &MockRpc, second: &MockRpc) -> ExtendedProvider {
+        first.provider().with_load_balancer(Some(LoadBalancer::new(
+            &[second.url()],
+            &Client::new(),
+            &[],
+            false,
+        )))
+    }
+
+    fn goerli_rpc() -> MockRpc {
+        let rpc = MockRpc::start();
+        rpc.respond("starknet_chainId", json!("0x534e5f474f45524c49"))
+            .respond("starknet_blockNumber", json!(10));
+        rpc
+    }
+
+    #[tokio::test]
+    async fn test_check_endpoint_chains_once() {
+        let (first, second) = (goerli_rpc(), goerli_rpc());
+        let provider = balanced_provider(&first, &second);
+
+        // Concurrent requests wait for the check in flight instead of skipping it
+        let results = futures_util::future::join_all((0..4).map(|_| provider.block_number())).await;
+        assert!(results.iter().all(|result| matches!(result, Ok(10))));
+        provider.block_number().await.unwrap();
+
+        assert_eq!(first.requests("starknet_chainId").len(), 1);
+        assert_eq!(second.requests("starknet_chainId").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_check_endpoint_chains_retried_after_failure() {
+        let (first, second) = (MockRpc::start(), goerli_rpc());
+        first
+            .respond_error("starknet_chainId", -32603, "Internal error")
+            .respond("starknet_chainId", json!("0x534e5f474f45524c49"))
+            .respond("starknet_blockNumber", json!(10));
+        let provider = balanced_provider(&first, &second);
+
+        assert!(provider.block_number().await.is_err());
+        assert_eq!(provider.block_number().await.unwrap(), 10);
+        assert_eq!(first.requests("starknet_chainId").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_check_endpoint_chains_timeout() {
+        let (first, second) = (goerli_rpc(), MockRpc::start());
+        second.respond_after(
+            "starknet_chainId",
+            Duration::from_secs(3),
+            json!("0x534e5f474f45524c49"),
+        );
+        let provider = balanced_provider(&first, &second).with_timeouts(RpcTimeouts {
+            default: Some(Duration::from_secs(1)),
+            ..Default::default()
+        });
+
+        assert!(matches!(
+            provider.block_number().await,
+            Err(ProviderError::Other(ExtendedProviderError::Timeout {
+                method: "starknet_chainId",
+                timeout: 1
+            }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_check_endpoint_chains_mismatch() {
+        let (first, second) = (goerli_rpc(), MockRpc::start());
+        second.respond("starknet_chainId", json!("0x534e5f4d41494e"));
+        let provider = balanced_provider(&first, &second);
+
+        for _ in 0..2 {
+            match provider.block_number().await {
+                Err(ProviderError::Other(ExtendedProviderError::EndpointChainMismatch {
+                    rpc,
+                    chain_id,
+                    expected_chain_id,
+                })) => {
+                    assert_eq!(rpc, second.url());
+                    assert_eq!(chain_id, chain_id::MAINNET);
+                    assert_eq!(expected_chain_id, chain_id::TESTNET);
+                }
+                result => panic!("unexpected result: {:?}", result),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_offline() {
+        let rpc = goerli_rpc();
+        let provider = ExtendedProvider {
+            offline: true,
+            ..rpc.provider().with_batch(true)
+        };
+
+        assert!(matches!(
+            provider.block_number().await,
+            Err(ProviderError::Other(ExtendedProviderError::Offline {
+                method: "starknet_blockNumber"
+            }))
+        ));
+        assert!(provider
+            .batch_request::<_, u64>("starknet_blockNumber", &[()])
+            .await
+            .is_err());
+        assert!(provider.spec_version().await.is_err());
+
+        // Nothing reached the node
+        assert!(rpc.requests("starknet_blockNumber").is_empty());
+        assert!(rpc.requests("starknet_specVersion").is_empty());
+    }
+
+    #[test]
+    fn test_next_index_distribution() {
+        let load_balancer = LoadBalancer::new(
+            &[Url::parse("http://127.0.0.1:9001").unwrap()],
+            &Client::new(),
+            &[3, 1],
+            false,
+        );
+        let picks = (0..8)
+            .map(|_| load_balancer.next_index())
+            .collect::<Vec<_>>();
+        assert_eq!(picks, [0, 0, 1, 0, 0, 0, 1, 0]);
+
+        let load_balancer = LoadBalancer::new(
+            &[
+                Url::parse("http://127.0.0.1:9001").unwrap(),
+                Url::parse("http://127.0.0.1:9002").unwrap(),
+            ],
+            &Client::new(),
+            &[],
+            false,
+        );
+        let picks = (0..6)
+            .map(|_| load_balancer.next_index())
+            .collect::<Vec<_>>();
+        assert_eq!(picks, [0, 1, 2, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_endpoint_index_pins_follow_up_reads() {
+        let provider = provider_with_endpoints(&[1, 1], false);
+        for method in PINNED_METHODS {
+            assert_eq!(provider.endpoint_index(method, None), 0);
+        }
+        let picks = (0..4)
+            .map(|_| provider.endpoint_index("starknet_call", None))
+            .collect::<Vec<_>>();
+        assert_eq!(picks, [0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_endpoint_index_follows_accepting_endpoint() {
+        let provider = provider_with_endpoints(&[1, 1, 1], true);
+        let transaction_hash = FieldElement::from_hex_be("0x1234").unwrap();
+        provider
+            .load_balancer
+            .as_ref()
+            .unwrap()
+            .accepted_by
+            .lock()
+            .unwrap()
+            .insert(transaction_hash, 2);
+
+        for _ in 0..3 {
+            assert_eq!(
+                provider.endpoint_index("starknet_getTransactionReceipt", Some(transaction_hash)),
+                2
+            );
+        }
+        let picks = (0..3)
+            .map(|_| provider.endpoint_index("starknet_getTransactionReceipt", None))
+            .collect::<Vec<_>>();
+        assert_eq!(picks, [0, 1, 2]);
+    }
+}
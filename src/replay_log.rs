@@ -0,0 +1,152 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use anyhow::Result;
+use clap::Parser;
+use serde_json::json;
+use starknet::{accounts::Call, core::types::FieldElement};
+
+use crate::{path::ExpandedPathbufParser, utils::unix_timestamp, verbosity::warning};
+
+/// Options whose values are secrets and never make it into the log.
+const REDACTED_OPTIONS: &[&str] = &["--private-key", "--keystore-password"];
+
+#[derive(Debug, Clone, Parser)]
+pub struct ReplayLogArgs {
+    #[clap(
+        long,
+        value_name = "PATH",
+        value_parser = ExpandedPathbufParser,
+        help = "Append a JSON line describing each transaction estimated or sent to a file, with \
+        the transaction hash once sent"
+    )]
+    replay_log: Option<PathBuf>,
+}
+
+/// Append-only log of the transactions a command attempted. The file is opened before anything
+/// is sent, so that a bad path never surfaces after a transaction is already out.
+pub struct ReplayLog {
+    file: Option<File>,
+    command: &'static str,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ReplayMode {
+    EstimateOnly,
+    SignatureOnly,
+    Send,
+}
+
+/// Fields of a replay log entry. Anything not known at the time of recording is left out.
+#[derive(Default)]
+pub struct ReplayRecord<'a> {
+    pub chain_id: FieldElement,
+    pub sender: FieldElement,
+    pub nonce: Option<FieldElement>,
+    pub calls: &'a [Call],
+    pub class_hash: Option<FieldElement>,
+    pub compiled_class_hash: Option<FieldElement>,
+    pub estimated_fee: Option<u64>,
+    pub max_fee: Option<FieldElement>,
+    pub transaction_hash: Option<FieldElement>,
+    pub error: Option<String>,
+}
+
+impl ReplayLogArgs {
+    pub fn into_replay_log(self, command: &'static str) -> Result<ReplayLog> {
+        let file = match &self.replay_log {
+            Some(path) => Some(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|err| {
+                        anyhow::anyhow!("unable to open replay log {}: {}", path.display(), err)
+                    })?,
+            ),
+            None => None,
+        };
+
+        Ok(ReplayLog { file, command })
+    }
+}
+
+impl ReplayLog {
+    /// Appends an entry. Failing to write only warns, as the transaction might already be out.
+    pub fn record(&self, mode: ReplayMode, record: ReplayRecord) {
+        let mut file = match &self.file {
+            Some(file) => file,
+            None => return,
+        };
+
+        let format_felt = |felt: FieldElement| format!("{:#064x}", felt);
+        let entry = json!({
+            "timestamp": unix_timestamp(),
+            "command": self.command,
+            "args": redacted_args(),
+            "mode": mode.name(),
+            "chain_id": format_felt(record.chain_id),
+            "sender": format_felt(record.sender),
+            "nonce": record.nonce.map(format_felt),
+            "calls": record
+                .calls
+                .iter()
+                .map(|call| {
+                    json!({
+                        "to": format_felt(call.to),
+                        "selector": format_felt(call.selector),
+                        "calldata": call
+                            .calldata
+                            .iter()
+                            .map(|element| format_felt(*element))
+                            .collect::<Vec<_>>(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+            "class_hash": record.class_hash.map(format_felt),
+            "compiled_class_hash": record.compiled_class_hash.map(format_felt),
+            "estimated_fee": record.estimated_fee.map(|fee| format!("{:#x}", fee)),
+            "max_fee": record.max_fee.map(|fee| format!("{:#x}", fee)),
+            "transaction_hash": record.transaction_hash.map(format_felt),
+            "error": record.error,
+        });
+
+        // A single write per line, so that entries from concurrent processes never interleave
+        if let Err(err) = file.write_all(format!("{}\n", entry).as_bytes()) {
+            warning!("WARNING: unable to write to the replay log: {}", err);
+        }
+    }
+}
+
+impl ReplayMode {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::EstimateOnly => "estimate-only",
+            Self::SignatureOnly => "signature-only",
+            Self::Send => "send",
+        }
+    }
+}
+
+fn redacted_args() -> Vec<String> {
+    let mut args = vec![];
+    let mut redact_next = false;
+    for arg in std::env::args().skip(1) {
+        if redact_next {
+            args.push("<redacted>".to_owned());
+            redact_next = false;
+        } else if let Some((option, _)) = arg
+            .split_once('=')
+            .filter(|(option, _)| REDACTED_OPTIONS.contains(option))
+        {
+            args.push(format!("{}=<redacted>", option));
+        } else {
+            redact_next = REDACTED_OPTIONS.contains(&arg.as_str());
+            args.push(arg);
+        }
+    }
+    args
+}
@@ -1,14 +1,20 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use async_trait::async_trait;
 use clap::Parser;
-use colored::Colorize;
 use starknet::{
     core::{crypto::Signature, types::FieldElement},
     signers::{LocalWallet, Signer, SigningKey, VerifyingKey},
 };
 
+use crate::{
+    account::AccountConfig, path::ExpandedPathbufParser, verbosity::warning, wallet::Wallet,
+};
+
+/// Placeholder for secrets in printed command lines.
+const REDACTED: &str = "<REDACTED>";
+
 #[derive(Debug)]
 pub enum AnySigner {
     LocalWallet(LocalWallet),
@@ -37,6 +43,14 @@ pub struct SignerArgs {
     keystore_password: Option<String>,
     #[clap(long, help = "Private key in hex in plain text")]
     private_key: Option<String>,
+    #[clap(
+        long,
+        value_parser = ExpandedPathbufParser,
+        conflicts_with_all = ["keystore", "private_key"],
+        help = "Path to a wallet file holding both the account config and its keystore, in place \
+        of --account and --keystore"
+    )]
+    wallet: Option<PathBuf>,
 }
 
 enum StringValue {
@@ -68,7 +82,19 @@ impl Signer for AnySigner {
 }
 
 impl SignerArgs {
+    /// Account config of the `--wallet` file, if one is given.
+    pub fn wallet_account(&self) -> Result<Option<AccountConfig>> {
+        match &self.wallet {
+            Some(wallet) => Ok(Some(Wallet::load(wallet)?.account)),
+            None => Ok(None),
+        }
+    }
+
     pub fn into_signer(self) -> Result<AnySigner> {
+        if let Some(wallet) = &self.wallet {
+            return Self::resolve_wallet(wallet, self.keystore_password);
+        }
+
         // We're not using the `env` derive from `clap` because we need to distinguish between
         // whether the value is supplied from the command line or the environment variable.
         //
@@ -102,16 +128,47 @@ impl SignerArgs {
         }
     }
 
+    /// Options selecting the same signer as [`into_signer`](Self::into_signer) would, with the
+    /// private key and keystore password masked as `<REDACTED>`.
+    pub fn explicit_args(&self) -> Vec<String> {
+        let mut args = vec![];
+
+        let keystore = self
+            .keystore
+            .clone()
+            .or_else(|| std::env::var("STARKNET_KEYSTORE").ok());
+        match (&self.wallet, &self.private_key, keystore) {
+            (Some(wallet), _, _) => {
+                args.push("--wallet".into());
+                args.push(wallet.to_string_lossy().into_owned());
+            }
+            // A private key takes precedence over a keystore from the environment
+            (None, Some(_), _) => {
+                args.push("--private-key".into());
+                args.push(REDACTED.into());
+            }
+            (None, None, Some(keystore)) => {
+                args.push("--keystore".into());
+                args.push(keystore);
+            }
+            (None, None, None) => {}
+        }
+        if self.keystore_password.is_some() && self.private_key.is_none() {
+            args.push("--keystore-password".into());
+            args.push(REDACTED.into());
+        }
+
+        args
+    }
+
     fn resolve_keystore(keystore: String, keystore_password: Option<String>) -> Result<AnySigner> {
         let keystore = PathBuf::from(&keystore);
 
         if keystore_password.is_some() {
-            eprintln!(
-                "{}",
+            warning!(
                 "WARNING: setting keystore passwords via --password is generally \
-                        considered insecure, as they will be stored in your shell history or other \
-                        log files."
-                    .bright_magenta()
+                considered insecure, as they will be stored in your shell history or other \
+                log files."
             );
         }
 
@@ -130,13 +187,25 @@ impl SignerArgs {
         Ok(AnySigner::LocalWallet(LocalWallet::from_signing_key(key)))
     }
 
+    fn resolve_wallet(wallet: &Path, keystore_password: Option<String>) -> Result<AnySigner> {
+        let wallet = Wallet::load(wallet)?;
+
+        let password = if let Some(password) = keystore_password {
+            password
+        } else {
+            rpassword::prompt_password("Enter keystore password: ")?
+        };
+
+        Ok(AnySigner::LocalWallet(LocalWallet::from_signing_key(
+            wallet.signing_key(&password)?,
+        )))
+    }
+
     fn resolve_private_key(private_key: String) -> Result<AnySigner> {
         // TODO: change to recommend hardware wallets when they become available
-        eprintln!(
-            "{}",
+        warning!(
             "WARNING: using private key in plain text is highly insecure, and you should \
-                    ONLY do this for development. Consider using an encrypted keystore instead."
-                .bright_magenta()
+            ONLY do this for development. Consider using an encrypted keystore instead."
         );
 
         let private_key = FieldElement::from_hex_be(&private_key)?;
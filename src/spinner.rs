@@ -0,0 +1,81 @@
+use std::{
+    future::Future,
+    io::{IsTerminal, Write},
+    time::{Duration, Instant},
+};
+
+use crate::verbosity::is_quiet;
+
+const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Only show the spinner for operations taking longer than this.
+const SHOW_AFTER: Duration = Duration::from_secs(1);
+
+const TICK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A progress indicator on stderr showing the elapsed time of a long-running operation. It's only
+/// ever drawn on a TTY so that scripted output is never polluted.
+pub struct Spinner {
+    started_at: Instant,
+    enabled: bool,
+}
+
+impl Spinner {
+    /// Creates a spinner whose elapsed time counts from now.
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            enabled: !is_quiet() && std::io::stderr().is_terminal(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Drives `future` to completion, drawing the spinner with `status` while waiting. The spinner
+    /// line is cleared before returning.
+    pub async fn run<F>(&self, status: &str, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        if !self.enabled {
+            return future.await;
+        }
+
+        tokio::pin!(future);
+
+        let mut ticker = tokio::time::interval(TICK_INTERVAL);
+        let mut drawn = false;
+
+        let output = loop {
+            tokio::select! {
+                output = &mut future => break output,
+                _ = ticker.tick() => {
+                    let elapsed = self.started_at.elapsed();
+                    if elapsed >= SHOW_AFTER {
+                        let frame =
+                            FRAMES[(elapsed.as_millis() / TICK_INTERVAL.as_millis()) as usize
+                                % FRAMES.len()];
+                        eprint!("\r\x1b[2K{} {} ({}s)", frame, status, elapsed.as_secs());
+                        let _ = std::io::stderr().flush();
+                        drawn = true;
+                    }
+                }
+            }
+        };
+
+        if drawn {
+            eprint!("\r\x1b[2K");
+            let _ = std::io::stderr().flush();
+        }
+
+        output
+    }
+}
+
+impl Default for Spinner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
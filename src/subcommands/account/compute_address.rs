@@ -0,0 +1,104 @@
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use colored::Colorize;
+use starknet::core::types::FieldElement;
+
+use crate::{
+    account::{AccountVariant, OzAccountConfig, OZ_ACCOUNT_CLASS_HASH},
+    output::output,
+    verbosity::VerbosityArgs,
+};
+
+#[derive(Debug, Parser)]
+pub struct ComputeAddress {
+    #[clap(long, help = "Public key of the account signer")]
+    public_key: String,
+    #[clap(long, value_enum, help = "Type of the account contract")]
+    account_type: AccountType,
+    #[clap(long, help = "Salt used when deploying the account")]
+    salt: String,
+    #[clap(
+        long,
+        help = "Class hash of the account contract. Defaults to the one used by Starkli for the \
+        account type"
+    )]
+    class_hash: Option<String>,
+    #[clap(flatten)]
+    verbosity: VerbosityArgs,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(super) enum AccountType {
+    /// OpenZeppelin account contract.
+    Oz,
+}
+
+impl AccountType {
+    /// Builds the account variant for `public_key`, along with the class hash Starkli uses for the
+    /// account type.
+    pub(super) fn variant(&self, public_key: FieldElement) -> (AccountVariant, FieldElement) {
+        match self {
+            Self::Oz => (
+                AccountVariant::OpenZeppelin(OzAccountConfig {
+                    version: 1,
+                    public_key,
+                }),
+                OZ_ACCOUNT_CLASS_HASH,
+            ),
+        }
+    }
+}
+
+impl ComputeAddress {
+    pub fn run(self) -> Result<()> {
+        self.verbosity.setup_logging();
+
+        let public_key = self.public_key.parse::<FieldElement>()?;
+        let salt = self.salt.parse::<FieldElement>()?;
+
+        let (variant, default_class_hash) = self.account_type.variant(public_key);
+        let class_hash = match &self.class_hash {
+            Some(class_hash) => class_hash.parse::<FieldElement>()?,
+            None => default_class_hash,
+        };
+
+        output!(
+            "{}",
+            format!("{:#064x}", variant.deployment_address(class_hash, salt)).bright_yellow()
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::macros::felt;
+
+    use super::*;
+
+    #[test]
+    fn test_oz_address() {
+        let (variant, class_hash) = AccountType::Oz.variant(felt!("0x1234"));
+        assert_eq!(class_hash, OZ_ACCOUNT_CLASS_HASH);
+        assert_eq!(
+            variant.deployment_address(class_hash, felt!("0x5678")),
+            felt!("0x0500f8271e106495091e46bc2be1b7a39923baefe750d58d613dd3270d28b94d")
+        );
+    }
+
+    #[test]
+    fn test_oz_address_custom_class() {
+        // Account deployed in transaction 0x750beaa8... on goerli-1
+        let (variant, _) = AccountType::Oz.variant(felt!(
+            "0x7d9b388e8ae71307ead5e71d2739ad6d29071f0a0e7bee7391421cc33c7e802"
+        ));
+        assert_eq!(
+            variant.deployment_address(
+                felt!("0x1fac3074c9d5282f0acc5c69a4781a1c711efea5e73c550c5d9fb253cf7fd3d"),
+                felt!("0x7a41671d0912dbcea3a95f79350bbe8ab61f2ab12c6e8bbfbf477a4a1121bdd")
+            ),
+            felt!("0x576f2f671483dbd3a98201a677015317dc508f4092dba1110292b65020570e2")
+        );
+    }
+}
@@ -11,15 +11,20 @@ use starknet::{
 };
 
 use crate::{
-    account::{AccountConfig, AccountVariant, DeployedStatus, DeploymentStatus},
-    fee::{FeeArgs, FeeSetting},
+    account::{AccountConfig, AccountVariant, DeployedStatus, DeploymentStatus, UndeployedStatus},
+    explorer::ExplorerArgs,
+    fee::{check_fee_ceiling, warn_on_low_fee, FeeArgs, FeeSetting, DEFAULT_FEE_BUFFER},
+    output::output,
     path::ExpandedPathbufParser,
     signer::SignerArgs,
-    utils::watch_tx,
-    verbosity::VerbosityArgs,
+    spinner::Spinner,
+    utils::{check_fee_balance, watch_tx, FeltFormat, WatchConditions},
+    verbosity::{progress, VerbosityArgs},
     ProviderArgs,
 };
 
+use super::compute_address::AccountType;
+
 #[derive(Debug, Parser)]
 pub struct Deploy {
     #[clap(flatten)]
@@ -28,9 +33,40 @@ pub struct Deploy {
     signer: SignerArgs,
     #[clap(flatten)]
     fee: FeeArgs,
+    #[clap(flatten)]
+    explorer: ExplorerArgs,
+    #[clap(
+        long,
+        value_enum,
+        requires = "salt",
+        help = "Deploy a new account of this type for the signer without an account config file. \
+        The config file is written once the deployment is confirmed"
+    )]
+    account_type: Option<AccountType>,
+    #[clap(
+        long,
+        requires = "account_type",
+        help = "Salt of the new account deployed with --account-type"
+    )]
+    salt: Option<String>,
+    #[clap(
+        long,
+        help = "Print only the transaction hash to stdout right after broadcasting. The \
+        transaction is still watched afterwards to update the account config file"
+    )]
+    broadcast_only: bool,
+    #[clap(
+        long,
+        value_enum,
+        default_value_t,
+        requires = "broadcast_only",
+        help = "Output format of the transaction hash printed with --broadcast-only"
+    )]
+    felt_format: FeltFormat,
     #[clap(
         value_parser = ExpandedPathbufParser,
-        help = "Path to the account config file"
+        help = "Path to the account config file. With --account-type, the path to write the new \
+        config file to"
     )]
     file: PathBuf,
     #[clap(flatten)]
@@ -48,20 +84,68 @@ enum MaxFeeType {
 }
 
 impl Deploy {
+    pub const RPC_METHODS: &'static [&'static str] = &[
+        "starknet_chainId",
+        "starknet_getNonce",
+        "starknet_estimateFee",
+        "starknet_call",
+        "starknet_addDeployAccountTransaction",
+        "starknet_getTransactionReceipt",
+        "starknet_blockNumber",
+    ];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
 
-        let fee_setting = self.fee.into_setting()?;
+        let fee_formatter = self.fee.formatter();
+        let skip_balance_check = self.fee.skip_balance_check();
+        let low_fee_threshold = self.fee.low_fee_threshold();
+        let max_fee_ceiling = self.fee.max_fee_ceiling()?;
+        let fee_buffer = self.fee.fee_buffer()?;
+        if fee_buffer.has_balance_percentage() {
+            anyhow::bail!(
+                "--max-fee-from-balance cannot be used for account deployment, as the account is \
+                only funded once the fee is known"
+            );
+        }
+        let explorer = self.explorer.into_explorer()?;
 
-        let provider = Arc::new(self.provider.into_provider());
+        let provider = Arc::new(self.provider.into_provider()?);
+        let fee_setting = self.fee.into_setting(&provider).await?;
         let signer = Arc::new(self.signer.into_signer()?);
 
-        if !self.file.exists() {
-            anyhow::bail!("account config file not found");
-        }
+        let mut account = match (self.account_type, &self.salt) {
+            (Some(account_type), Some(salt)) => {
+                if self.file.exists() {
+                    anyhow::bail!("account config file already exists");
+                }
+                // Checked upfront, as the file is only written after the deployment
+                if let Some(parent) = self.file.parent() {
+                    if !parent.as_os_str().is_empty() && !parent.exists() {
+                        anyhow::bail!("directory {} does not exist", parent.display());
+                    }
+                }
 
-        let mut account: AccountConfig =
-            serde_json::from_reader(&mut std::fs::File::open(&self.file)?)?;
+                let (variant, class_hash) =
+                    account_type.variant(signer.get_public_key().await?.scalar());
+                AccountConfig {
+                    version: 1,
+                    variant,
+                    deployment: DeploymentStatus::Undeployed(UndeployedStatus {
+                        class_hash,
+                        salt: salt.parse::<FieldElement>()?,
+                    }),
+                    chain_id: None,
+                }
+            }
+            _ => {
+                if !self.file.exists() {
+                    anyhow::bail!("account config file not found");
+                }
+
+                serde_json::from_reader(&mut std::fs::File::open(&self.file)?)?
+            }
+        };
 
         #[allow(clippy::infallible_destructuring_match)]
         let oz_config = match &account.variant {
@@ -107,24 +191,27 @@ impl Deploy {
         let max_fee = match fee_setting {
             FeeSetting::Manual(fee) => MaxFeeType::Manual { max_fee: fee },
             FeeSetting::EstimateOnly | FeeSetting::None => {
-                let estimated_fee = account_deployment.estimate_fee().await?.overall_fee;
+                let estimated_fee = Spinner::new()
+                    .run("Estimating fee", account_deployment.estimate_fee())
+                    .await?
+                    .overall_fee;
+                warn_on_low_fee(estimated_fee, low_fee_threshold);
 
-                // TODO: make buffer configurable
-                let estimated_fee_with_buffer = estimated_fee * 3 / 2;
+                let estimated_fee_with_buffer =
+                    fee_buffer.apply(estimated_fee, chain_id, DEFAULT_FEE_BUFFER);
 
                 let estimated_fee: FieldElement = estimated_fee.into();
 
                 if fee_setting.is_estimate_only() {
-                    println!(
-                        "{} ETH",
-                        format!("{}", estimated_fee.to_big_decimal(18)).bright_yellow(),
-                    );
+                    output!("{}", fee_formatter.format(estimated_fee).bright_yellow());
                     return Ok(());
                 }
 
+                check_fee_ceiling(estimated_fee.try_into()?, max_fee_ceiling, &fee_formatter)?;
+
                 MaxFeeType::Estimated {
                     estimate: estimated_fee,
-                    estimate_with_buffer: estimated_fee_with_buffer.into(),
+                    estimate_with_buffer: estimated_fee_with_buffer,
                 }
             }
         };
@@ -134,8 +221,8 @@ impl Deploy {
                 eprintln!(
                     "You've manually specified the account deployment fee to be {}. \
                     Therefore, fund at least:\n    {}",
-                    format!("{} ETH", max_fee.to_big_decimal(18)).bright_yellow(),
-                    format!("{} ETH", max_fee.to_big_decimal(18)).bright_yellow(),
+                    fee_formatter.format(max_fee).bright_yellow(),
+                    fee_formatter.format(max_fee).bright_yellow(),
                 );
             }
             MaxFeeType::Estimated {
@@ -145,8 +232,8 @@ impl Deploy {
                 eprintln!(
                     "The estimated account deployment fee is {}. \
                     However, to avoid failure, fund at least:\n    {}",
-                    format!("{} ETH", estimate.to_big_decimal(18)).bright_yellow(),
-                    format!("{} ETH", estimate_with_buffer.to_big_decimal(18)).bright_yellow()
+                    fee_formatter.format(estimate).bright_yellow(),
+                    fee_formatter.format(estimate_with_buffer).bright_yellow()
                 );
             }
         }
@@ -160,31 +247,66 @@ impl Deploy {
         eprint!("Press [ENTER] once you've funded the address.");
         std::io::stdin().read_line(&mut String::new())?;
 
-        // TODO: add option to check ETH balance before sending out tx
+        if !skip_balance_check {
+            check_fee_balance(
+                &provider,
+                chain_id,
+                target_deployment_address,
+                max_fee.max_fee(),
+                &fee_formatter,
+            )
+            .await?;
+        }
+
         let account_deployment_tx = account_deployment
             .max_fee(max_fee.max_fee())
             .send()
             .await?
             .transaction_hash;
-        eprintln!(
-            "Account deployment transaction: {}",
-            format!("{:#064x}", account_deployment_tx).bright_yellow()
-        );
+        if self.broadcast_only {
+            // Only the transaction hash goes to stdout so this can be easily scripted. It's printed
+            // before watching so that it can be picked up right away.
+            output!("{}", self.felt_format.format(account_deployment_tx));
+        } else {
+            progress!(
+                "Account deployment transaction: {}",
+                format!("{:#064x}", account_deployment_tx).bright_yellow()
+            );
+        }
+        explorer.print_link(chain_id, account_deployment_tx);
 
         // By default we wait for the tx to confirm so that we don't incorrectly mark the account
         // as deployed
-        eprintln!(
-            "Waiting for transaction {} to confirm. \
-            If this process is interrupted, you will need to run `{}` to update the account file.",
-            format!("{:#064x}", account_deployment_tx).bright_yellow(),
-            "starkli account fetch".bright_yellow(),
-        );
-        watch_tx(&provider, account_deployment_tx).await?;
+        if self.account_type.is_some() {
+            // There's no file to update yet when deploying without one
+            progress!(
+                "Waiting for transaction {} to confirm. \
+                If this process is interrupted, you will need to run `{}` to create the account \
+                file.",
+                format!("{:#064x}", account_deployment_tx).bright_yellow(),
+                format!(
+                    "starkli account fetch --output {} {:#064x}",
+                    self.file.display(),
+                    target_deployment_address
+                )
+                .bright_yellow(),
+            );
+        } else {
+            progress!(
+                "Waiting for transaction {} to confirm. \
+                If this process is interrupted, you will need to run `{}` to update the account \
+                file.",
+                format!("{:#064x}", account_deployment_tx).bright_yellow(),
+                "starkli account fetch".bright_yellow(),
+            );
+        }
+        watch_tx(&provider, account_deployment_tx, WatchConditions::default()).await?;
 
         account.deployment = DeploymentStatus::Deployed(DeployedStatus {
             class_hash: undeployed_status.class_hash,
             address: target_deployment_address,
         });
+        account.chain_id = Some(chain_id);
 
         // Never write directly to the original file to avoid data loss
         let mut temp_file_name = self
@@ -0,0 +1,90 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::Result;
+use clap::Parser;
+
+use crate::{
+    account::{AccountBundle, AccountConfig},
+    path::ExpandedPathbufParser,
+    verbosity::{progress, warning, VerbosityArgs},
+};
+
+#[derive(Debug, Parser)]
+pub struct Export {
+    #[clap(
+        long,
+        env = "STARKNET_ACCOUNT",
+        value_parser = ExpandedPathbufParser,
+        help = "Path to account config JSON file, \"-\" to read it from stdin, or the config \
+        JSON itself"
+    )]
+    account: PathBuf,
+    #[clap(
+        long,
+        env = "STARKNET_KEYSTORE",
+        value_parser = ExpandedPathbufParser,
+        help = "Path to the encrypted keystore to include with --include-keystore"
+    )]
+    keystore: Option<PathBuf>,
+    #[clap(
+        long,
+        help = "Include the encrypted keystore in the bundle. Anyone with the bundle can then \
+        attempt to brute force the keystore password"
+    )]
+    include_keystore: bool,
+    #[clap(long, help = "Overwrite the file if it already exists")]
+    force: bool,
+    #[clap(help = "Path to save the account bundle")]
+    output: PathBuf,
+    #[clap(flatten)]
+    verbosity: VerbosityArgs,
+}
+
+impl Export {
+    pub fn run(self) -> Result<()> {
+        self.verbosity.setup_logging();
+
+        if self.output.exists() && !self.force {
+            anyhow::bail!("account bundle file already exists");
+        }
+        let account = AccountConfig::load(&self.account)?;
+
+        let keystore = if self.include_keystore {
+            let keystore = match &self.keystore {
+                Some(keystore) => keystore,
+                None => anyhow::bail!("--include-keystore requires a keystore via --keystore"),
+            };
+            if !keystore.exists() {
+                anyhow::bail!("keystore file not found");
+            }
+
+            warning!(
+                "WARNING: the bundle includes your encrypted keystore. Only share it with \
+                parties you trust, and never together with the keystore password."
+            );
+
+            Some(serde_json::from_reader(&mut std::fs::File::open(
+                keystore,
+            )?)?)
+        } else {
+            None
+        };
+
+        let bundle = AccountBundle {
+            version: 1,
+            account,
+            keystore,
+        };
+
+        let mut file = std::fs::File::create(&self.output)?;
+        serde_json::to_writer_pretty(&mut file, &bundle)?;
+        file.write_all(b"\n")?;
+
+        progress!(
+            "Account bundle exported to: {}",
+            std::fs::canonicalize(&self.output)?.display()
+        );
+
+        Ok(())
+    }
+}
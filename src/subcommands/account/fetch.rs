@@ -14,7 +14,7 @@ use crate::{
         AccountConfig, AccountVariant, AccountVariantType, DeployedStatus, DeploymentStatus,
         OzAccountConfig, KNOWN_ACCOUNT_CLASSES,
     },
-    verbosity::VerbosityArgs,
+    verbosity::{progress, VerbosityArgs},
     ProviderArgs,
 };
 
@@ -33,6 +33,12 @@ pub struct Fetch {
 }
 
 impl Fetch {
+    pub const RPC_METHODS: &'static [&'static str] = &[
+        "starknet_getClassHashAt",
+        "starknet_call",
+        "starknet_chainId",
+    ];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
 
@@ -49,7 +55,7 @@ impl Fetch {
             anyhow::bail!("account config file already exists");
         }
 
-        let provider = self.provider.into_provider();
+        let provider = self.provider.into_provider()?;
         let address = FieldElement::from_hex_be(&self.address)?;
 
         let class_hash = provider
@@ -72,11 +78,11 @@ impl Fetch {
             }
         };
 
-        eprintln!(
+        progress!(
             "Account contract type identified as: {}",
             format!("{}", known_class.variant).bright_yellow()
         );
-        eprintln!("Description: {}", known_class.description.bright_yellow());
+        progress!("Description: {}", known_class.description.bright_yellow());
 
         // No need to proceed if the user doesn't even want to save the config
         let output = match self.output {
@@ -107,6 +113,7 @@ impl Fetch {
                         class_hash,
                         address,
                     }),
+                    chain_id: Some(provider.chain_id().await?),
                 }
             }
         };
@@ -115,7 +122,7 @@ impl Fetch {
         serde_json::to_writer_pretty(&mut file, &account)?;
         file.write_all(b"\n")?;
 
-        eprintln!(
+        progress!(
             "Downloaded new account config file: {}",
             std::fs::canonicalize(&output)?.display()
         );
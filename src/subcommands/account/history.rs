@@ -0,0 +1,188 @@
+use anyhow::Result;
+use clap::Parser;
+use colored_json::{ColorMode, Output};
+use futures_util::{StreamExt, TryStreamExt};
+use serde::Serialize;
+use serde_json::json;
+use starknet::{
+    core::{
+        types::{
+            BlockId, BlockStatus, BlockTag, DeclareTransaction, FieldElement, InvokeTransaction,
+            MaybePendingBlockWithTxs, Transaction,
+        },
+        utils::get_contract_address,
+    },
+    providers::Provider,
+};
+
+use crate::{
+    output::output,
+    verbosity::{progress, VerbosityArgs},
+    ProviderArgs,
+};
+
+/// Maximum number of blocks fetched at the same time.
+const MAX_CONCURRENT_READS: usize = 8;
+
+#[derive(Debug, Parser)]
+pub struct History {
+    #[clap(flatten)]
+    provider: ProviderArgs,
+    #[clap(
+        long,
+        default_value = "100",
+        help = "Number of most recent blocks to scan, in addition to the pending block"
+    )]
+    blocks: u64,
+    #[clap(long, help = "Print the transactions as JSON")]
+    json: bool,
+    #[clap(help = "Account contract address")]
+    address: String,
+    #[clap(flatten)]
+    verbosity: VerbosityArgs,
+}
+
+#[derive(Serialize)]
+struct HistoryEntry {
+    /// `None` for transactions in the pending block.
+    block_number: Option<u64>,
+    transaction_hash: String,
+    r#type: &'static str,
+    status: BlockStatus,
+}
+
+impl History {
+    pub const RPC_METHODS: &'static [&'static str] =
+        &["starknet_blockNumber", "starknet_getBlockWithTxs"];
+
+    pub async fn run(self) -> Result<()> {
+        self.verbosity.setup_logging();
+
+        if self.blocks == 0 {
+            anyhow::bail!("--blocks must be at least 1");
+        }
+
+        let provider = self.provider.into_provider()?;
+        let address = FieldElement::from_hex_be(&self.address)?;
+
+        let latest_block = provider.block_number().await?;
+        let first_block = latest_block.saturating_sub(self.blocks - 1);
+
+        progress!(
+            "Scanning the pending block and blocks {} to {} for transactions from {:#064x}...",
+            first_block,
+            latest_block,
+            address
+        );
+
+        // Newest first, starting with the pending block
+        let block_ids = std::iter::once(BlockId::Tag(BlockTag::Pending))
+            .chain((first_block..=latest_block).rev().map(BlockId::Number))
+            .collect::<Vec<_>>();
+
+        let batch_params = block_ids
+            .iter()
+            .map(|block_id| json!([block_id]))
+            .collect::<Vec<_>>();
+        let blocks = match provider
+            .batch_request::<_, MaybePendingBlockWithTxs>("starknet_getBlockWithTxs", &batch_params)
+            .await?
+        {
+            Some(blocks) => blocks,
+            None => {
+                futures_util::stream::iter(block_ids.iter())
+                    .map(|block_id| provider.get_block_with_txs(block_id))
+                    .buffered(MAX_CONCURRENT_READS)
+                    .try_collect::<Vec<_>>()
+                    .await?
+            }
+        };
+
+        let mut entries = vec![];
+        for block in blocks.into_iter() {
+            let (block_number, status, transactions) = match block {
+                MaybePendingBlockWithTxs::Block(block) => {
+                    (Some(block.block_number), block.status, block.transactions)
+                }
+                MaybePendingBlockWithTxs::PendingBlock(block) => {
+                    (None, BlockStatus::Pending, block.transactions)
+                }
+            };
+
+            for transaction in transactions.iter() {
+                if let Some((transaction_hash, r#type)) = Self::match_sender(transaction, address) {
+                    entries.push(HistoryEntry {
+                        block_number,
+                        transaction_hash: format!("{:#064x}", transaction_hash),
+                        r#type,
+                        status,
+                    });
+                }
+            }
+        }
+
+        if self.json {
+            let entries_json = colored_json::to_colored_json(
+                &serde_json::to_value(&entries)?,
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{entries_json}");
+        } else if entries.is_empty() {
+            progress!("No transactions found in the scanned blocks");
+        } else {
+            output!(
+                "{:<10} {:<66} {:<14} STATUS",
+                "BLOCK",
+                "TRANSACTION HASH",
+                "TYPE"
+            );
+            for entry in entries.iter() {
+                output!(
+                    "{:<10} {:<66} {:<14} {}",
+                    match entry.block_number {
+                        Some(block_number) => block_number.to_string(),
+                        None => "pending".into(),
+                    },
+                    entry.transaction_hash,
+                    entry.r#type,
+                    serde_json::to_value(entry.status)?
+                        .as_str()
+                        .unwrap_or_default()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the hash and type of `transaction` if it's sent by `address`.
+    fn match_sender(
+        transaction: &Transaction,
+        address: FieldElement,
+    ) -> Option<(FieldElement, &'static str)> {
+        match transaction {
+            Transaction::Invoke(InvokeTransaction::V1(tx)) if tx.sender_address == address => {
+                Some((tx.transaction_hash, "INVOKE"))
+            }
+            Transaction::Declare(DeclareTransaction::V1(tx)) if tx.sender_address == address => {
+                Some((tx.transaction_hash, "DECLARE"))
+            }
+            Transaction::Declare(DeclareTransaction::V2(tx)) if tx.sender_address == address => {
+                Some((tx.transaction_hash, "DECLARE"))
+            }
+            // Deploy account transactions don't carry the address, so it's computed instead
+            Transaction::DeployAccount(tx)
+                if get_contract_address(
+                    tx.contract_address_salt,
+                    tx.class_hash,
+                    &tx.constructor_calldata,
+                    FieldElement::ZERO,
+                ) == address =>
+            {
+                Some((tx.transaction_hash, "DEPLOY_ACCOUNT"))
+            }
+            // Other transactions are not sent from accounts
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+
+use crate::{
+    account::AccountBundle,
+    path::ExpandedPathbufParser,
+    verbosity::{progress, VerbosityArgs},
+};
+
+#[derive(Debug, Parser)]
+pub struct Import {
+    #[clap(
+        long,
+        value_parser = ExpandedPathbufParser,
+        help = "Path to save the account config file"
+    )]
+    output: PathBuf,
+    #[clap(
+        long,
+        value_parser = ExpandedPathbufParser,
+        help = "Path to save the encrypted keystore, if the bundle contains one"
+    )]
+    keystore_output: Option<PathBuf>,
+    #[clap(long, help = "Overwrite the files if they already exist")]
+    force: bool,
+    #[clap(
+        value_parser = ExpandedPathbufParser,
+        help = "Path to the account bundle created with `account export`"
+    )]
+    bundle: PathBuf,
+    #[clap(flatten)]
+    verbosity: VerbosityArgs,
+}
+
+impl Import {
+    pub fn run(self) -> Result<()> {
+        self.verbosity.setup_logging();
+
+        if !self.bundle.exists() {
+            anyhow::bail!("account bundle file not found");
+        }
+
+        let bundle: AccountBundle =
+            serde_json::from_reader(&mut std::fs::File::open(&self.bundle)?)?;
+        if bundle.version != 1 {
+            anyhow::bail!("unsupported account bundle version: {}", bundle.version);
+        }
+
+        if self.output.exists() && !self.force {
+            anyhow::bail!("account config file already exists");
+        }
+
+        let keystore_output = match (&bundle.keystore, &self.keystore_output) {
+            (Some(_), Some(keystore_output)) => {
+                if keystore_output.exists() && !self.force {
+                    anyhow::bail!("keystore file already exists");
+                }
+                Some(keystore_output)
+            }
+            (Some(_), None) => {
+                eprintln!(
+                    "{}",
+                    "NOTE: the bundle contains a keystore but --keystore-output is not supplied. \
+                    The keystore won't be imported."
+                        .bright_magenta()
+                );
+                None
+            }
+            (None, Some(_)) => anyhow::bail!("the bundle does not contain a keystore"),
+            (None, None) => None,
+        };
+
+        let mut file = std::fs::File::create(&self.output)?;
+        serde_json::to_writer_pretty(&mut file, &bundle.account)?;
+        file.write_all(b"\n")?;
+
+        progress!(
+            "Account config imported to: {}",
+            std::fs::canonicalize(&self.output)?.display()
+        );
+
+        if let (Some(keystore), Some(keystore_output)) = (&bundle.keystore, keystore_output) {
+            let mut file = std::fs::File::create(keystore_output)?;
+            serde_json::to_writer(&mut file, keystore)?;
+
+            progress!(
+                "Keystore imported to: {}",
+                std::fs::canonicalize(keystore_output)?.display()
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -7,6 +7,21 @@ use fetch::Fetch;
 mod deploy;
 use deploy::Deploy;
 
+mod export;
+use export::Export;
+
+mod import;
+use import::Import;
+
+mod history;
+use history::History;
+
+mod compute_address;
+use compute_address::ComputeAddress;
+
+mod signers;
+use signers::Signers;
+
 mod oz;
 use oz::Oz;
 
@@ -21,16 +36,44 @@ enum Subcommands {
     #[clap(about = "Fetch account config from an already deployed account contract")]
     Fetch(Fetch),
     #[clap(about = "Deploy account contract with a DeployAccount transaction")]
-    Deploy(Deploy),
+    Deploy(Box<Deploy>),
+    #[clap(about = "Export account config into a portable bundle")]
+    Export(Export),
+    #[clap(about = "Import account config from a bundle created with `account export`")]
+    Import(Import),
+    #[clap(about = "List recent transactions sent from an account by scanning recent blocks")]
+    History(History),
+    #[clap(about = "Compute the deployment address of an account from its public key")]
+    ComputeAddress(ComputeAddress),
+    #[clap(about = "Read the owner, guardians, or multisig signers set on an account contract")]
+    Signers(Signers),
     #[clap(about = "Create and manage OpenZeppelin account contracts")]
     Oz(Oz),
 }
 
 impl Account {
+    pub fn rpc_methods(&self) -> &'static [&'static str] {
+        match &self.command {
+            Subcommands::Fetch(_) => Fetch::RPC_METHODS,
+            Subcommands::Deploy(_) => Deploy::RPC_METHODS,
+            Subcommands::History(_) => History::RPC_METHODS,
+            Subcommands::Signers(_) => Signers::RPC_METHODS,
+            Subcommands::Export(_)
+            | Subcommands::Import(_)
+            | Subcommands::ComputeAddress(_)
+            | Subcommands::Oz(_) => &[],
+        }
+    }
+
     pub async fn run(self) -> Result<()> {
         match self.command {
             Subcommands::Fetch(cmd) => cmd.run().await,
             Subcommands::Deploy(cmd) => cmd.run().await,
+            Subcommands::Export(cmd) => cmd.run(),
+            Subcommands::Import(cmd) => cmd.run(),
+            Subcommands::History(cmd) => cmd.run().await,
+            Subcommands::ComputeAddress(cmd) => cmd.run(),
+            Subcommands::Signers(cmd) => cmd.run().await,
             Subcommands::Oz(cmd) => cmd.run().await,
         }
     }
@@ -3,22 +3,17 @@ use std::{io::Write, path::PathBuf};
 use anyhow::Result;
 use clap::Parser;
 use colored::Colorize;
-use starknet::{
-    core::types::FieldElement,
-    macros::felt,
-    signers::{Signer, SigningKey},
-};
+use starknet::signers::{Signer, SigningKey};
 
 use crate::{
-    account::{AccountConfig, AccountVariant, DeploymentStatus, OzAccountConfig, UndeployedStatus},
+    account::{
+        AccountConfig, AccountVariant, DeploymentStatus, OzAccountConfig, UndeployedStatus,
+        OZ_ACCOUNT_CLASS_HASH,
+    },
     path::ExpandedPathbufParser,
     signer::SignerArgs,
 };
 
-/// OpenZeppelin account contract v0.6.1 compiled with cairo-lang v0.11.0.2
-const OZ_ACCOUNT_CLASS_HASH: FieldElement =
-    felt!("0x048dd59fabc729a5db3afdf649ecaf388e931647ab2f53ca3c6183fa480aa292");
-
 #[derive(Debug, Parser)]
 pub struct Init {
     // TODO: allow manually specifying public key without using a signer
@@ -58,6 +53,7 @@ impl Init {
                 class_hash: OZ_ACCOUNT_CLASS_HASH,
                 salt,
             }),
+            chain_id: None,
         };
 
         let deployed_address = account_config.deploy_account_address()?;
@@ -0,0 +1,195 @@
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use colored_json::{ColorMode, Output};
+use serde::Serialize;
+use starknet::{
+    core::types::{BlockId, BlockTag, FieldElement, FunctionCall},
+    macros::selector,
+    providers::Provider,
+};
+
+use crate::{
+    output::output,
+    utils::PUBLIC_KEY_GETTERS,
+    verbosity::{progress, VerbosityArgs},
+    ProviderArgs,
+};
+
+/// Getters of the Argent X guardians, in both Cairo 1 and Cairo 0 naming.
+const GUARDIAN_GETTERS: [FieldElement; 2] = [selector!("get_guardian"), selector!("getGuardian")];
+const GUARDIAN_BACKUP_GETTERS: [FieldElement; 2] = [
+    selector!("get_guardian_backup"),
+    selector!("getGuardianBackup"),
+];
+
+/// Getters of the Argent multisig, which has no single owner.
+const MULTISIG_SIGNERS_GETTERS: [FieldElement; 1] = [selector!("get_signers")];
+const MULTISIG_THRESHOLD_GETTERS: [FieldElement; 1] = [selector!("get_threshold")];
+
+#[derive(Debug, Parser)]
+pub struct Signers {
+    #[clap(flatten)]
+    provider: ProviderArgs,
+    #[clap(long, help = "Print the signers as JSON")]
+    json: bool,
+    #[clap(help = "Account contract address")]
+    address: String,
+    #[clap(flatten)]
+    verbosity: VerbosityArgs,
+}
+
+/// Signer configuration read from the account. Roles the class doesn't have are left out, and
+/// guardians that are not set are `None`.
+#[derive(Default, Serialize)]
+struct SignerSet {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owner: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    guardian: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    guardian_backup: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signers: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    threshold: Option<u64>,
+}
+
+impl Signers {
+    pub const RPC_METHODS: &'static [&'static str] = &["starknet_getClassHashAt", "starknet_call"];
+
+    pub async fn run(self) -> Result<()> {
+        self.verbosity.setup_logging();
+
+        let provider = self.provider.into_provider()?;
+        let address = FieldElement::from_hex_be(&self.address)?;
+
+        // Makes sure the account exists at all, so that it's not reported as an unknown class
+        let class_hash = provider
+            .get_class_hash_at(BlockId::Tag(BlockTag::Pending), address)
+            .await?;
+        progress!(
+            "Reading signers of account {} with class {}",
+            format!("{:#064x}", address).bright_yellow(),
+            format!("{:#064x}", class_hash).bright_yellow()
+        );
+
+        let format_felt = |felt: FieldElement| format!("{:#064x}", felt);
+        // A zero guardian means that the account has no guardian
+        let format_guardian = |felt: FieldElement| {
+            if felt == FieldElement::ZERO {
+                None
+            } else {
+                Some(format_felt(felt))
+            }
+        };
+
+        let signer_set = SignerSet {
+            owner: Self::read(&provider, address, &PUBLIC_KEY_GETTERS)
+                .await
+                .and_then(|result| result.first().copied())
+                .map(format_felt),
+            guardian: Self::read(&provider, address, &GUARDIAN_GETTERS)
+                .await
+                .and_then(|result| result.first().copied())
+                .map(format_guardian),
+            guardian_backup: Self::read(&provider, address, &GUARDIAN_BACKUP_GETTERS)
+                .await
+                .and_then(|result| result.first().copied())
+                .map(format_guardian),
+            signers: Self::read(&provider, address, &MULTISIG_SIGNERS_GETTERS)
+                .await
+                .and_then(|result| Self::decode_array(&result))
+                .map(|signers| signers.into_iter().map(format_felt).collect()),
+            threshold: Self::read(&provider, address, &MULTISIG_THRESHOLD_GETTERS)
+                .await
+                .and_then(|result| result.first().copied())
+                .and_then(|threshold| u64::try_from(threshold).ok()),
+        };
+
+        if signer_set.owner.is_none() && signer_set.signers.is_none() {
+            anyhow::bail!(
+                "class {:#064x} exposes none of the known signer getters. Only the OpenZeppelin, \
+                Argent X, Braavos, and Argent multisig accounts are supported",
+                class_hash
+            );
+        }
+
+        if self.json {
+            let signers_json = colored_json::to_colored_json(
+                &serde_json::to_value(&signer_set)?,
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{signers_json}");
+            return Ok(());
+        }
+
+        let format_optional = |value: &Option<String>| match value {
+            Some(value) => value.to_owned(),
+            None => "none".to_owned(),
+        };
+
+        if let Some(owner) = &signer_set.owner {
+            output!("Owner:           {}", owner);
+        }
+        if let Some(guardian) = &signer_set.guardian {
+            output!("Guardian:        {}", format_optional(guardian));
+        }
+        if let Some(guardian_backup) = &signer_set.guardian_backup {
+            output!("Guardian backup: {}", format_optional(guardian_backup));
+        }
+        if let Some(signers) = &signer_set.signers {
+            for (ind_signer, signer) in signers.iter().enumerate() {
+                if ind_signer == 0 {
+                    output!("Signers:         {}", signer);
+                } else {
+                    output!("                 {}", signer);
+                }
+            }
+        }
+        if let Some(threshold) = &signer_set.threshold {
+            output!("Threshold:       {}", threshold);
+        }
+
+        Ok(())
+    }
+
+    /// Calls the first of `getters` the account class implements. An error most likely means the
+    /// getter doesn't exist on this class, in which case the next one is tried.
+    async fn read<P>(
+        provider: P,
+        address: FieldElement,
+        getters: &[FieldElement],
+    ) -> Option<Vec<FieldElement>>
+    where
+        P: Provider,
+    {
+        for getter in getters.iter() {
+            let result = provider
+                .call(
+                    FunctionCall {
+                        contract_address: address,
+                        entry_point_selector: *getter,
+                        calldata: vec![],
+                    },
+                    BlockId::Tag(BlockTag::Pending),
+                )
+                .await;
+            if let Ok(result) = result {
+                return Some(result);
+            }
+        }
+
+        None
+    }
+
+    /// Decodes an `Array<felt252>` return value, which is prefixed with its length.
+    fn decode_array(result: &[FieldElement]) -> Option<Vec<FieldElement>> {
+        let (len, elements) = result.split_first()?;
+        if u64::try_from(*len).ok()? != elements.len() as u64 {
+            return None;
+        }
+
+        Some(elements.to_vec())
+    }
+}
@@ -1,9 +1,14 @@
+use std::time::Duration;
+
 use anyhow::Result;
 use clap::Parser;
 use colored_json::{ColorMode, Output};
-use starknet::providers::Provider;
+use starknet::{
+    core::types::{BlockId, BlockTag},
+    providers::Provider,
+};
 
-use crate::{utils::parse_block_id, verbosity::VerbosityArgs, ProviderArgs};
+use crate::{output::output, utils::parse_block_id, verbosity::VerbosityArgs, ProviderArgs};
 
 #[derive(Debug, Parser)]
 pub struct Block {
@@ -11,6 +16,17 @@ pub struct Block {
     provider: ProviderArgs,
     #[clap(long, help = "Fetch full transactions instead of hashes only")]
     full: bool,
+    #[clap(
+        long,
+        help = "Keep printing new blocks as they're produced until interrupted"
+    )]
+    follow: bool,
+    #[clap(
+        long,
+        default_value = "5",
+        help = "Number of seconds between polls for new blocks in follow mode"
+    )]
+    poll_interval: u64,
     #[clap(
         default_value = "latest",
         help = "Block number, hash, or tag (latest/pending)"
@@ -21,14 +37,55 @@ pub struct Block {
 }
 
 impl Block {
+    pub const RPC_METHODS: &'static [&'static str] = &[
+        "starknet_getBlockWithTxHashes",
+        "starknet_getBlockWithTxs",
+        "starknet_blockNumber",
+    ];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
 
-        let provider = self.provider.into_provider();
+        let provider = self.provider.into_provider()?;
 
         let block_id = parse_block_id(&self.block_id)?;
 
-        let block_json = if self.full {
+        if !self.follow {
+            return Self::print_block(&provider, block_id, self.full).await;
+        }
+
+        if !matches!(block_id, BlockId::Tag(BlockTag::Latest)) {
+            anyhow::bail!("--follow can only be used with the latest block");
+        }
+        if self.poll_interval == 0 {
+            anyhow::bail!("--poll-interval must be at least 1 second");
+        }
+
+        // Only blocks with numbers are followed so that the ever-changing pending block never gets
+        // printed more than once.
+        let mut last_block_number = provider.block_number().await?;
+        Self::print_block(&provider, BlockId::Number(last_block_number), self.full).await?;
+
+        loop {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => return Ok(()),
+                _ = tokio::time::sleep(Duration::from_secs(self.poll_interval)) => {}
+            }
+
+            let latest_block_number = provider.block_number().await?;
+            while last_block_number < latest_block_number {
+                last_block_number += 1;
+                Self::print_block(&provider, BlockId::Number(last_block_number), self.full).await?;
+            }
+        }
+    }
+
+    async fn print_block<P>(provider: P, block_id: BlockId, full: bool) -> Result<()>
+    where
+        P: Provider,
+        P::Error: 'static,
+    {
+        let block_json = if full {
             serde_json::to_value(provider.get_block_with_txs(block_id).await?)?
         } else {
             serde_json::to_value(provider.get_block_with_tx_hashes(block_id).await?)?
@@ -36,7 +93,7 @@ impl Block {
 
         let block_json =
             colored_json::to_colored_json(&block_json, ColorMode::Auto(Output::StdOut))?;
-        println!("{block_json}");
+        output!("{block_json}");
 
         Ok(())
     }
@@ -2,7 +2,7 @@ use anyhow::Result;
 use clap::Parser;
 use starknet::providers::Provider;
 
-use crate::{verbosity::VerbosityArgs, ProviderArgs};
+use crate::{output::output, verbosity::VerbosityArgs, ProviderArgs};
 
 #[derive(Debug, Parser)]
 pub struct BlockHash {
@@ -13,14 +13,16 @@ pub struct BlockHash {
 }
 
 impl BlockHash {
+    pub const RPC_METHODS: &'static [&'static str] = &["starknet_blockHashAndNumber"];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
 
-        let provider = self.provider.into_provider();
+        let provider = self.provider.into_provider()?;
 
         let block = provider.block_hash_and_number().await?;
 
-        println!("{:#064x}", block.block_hash);
+        output!("{:#064x}", block.block_hash);
 
         Ok(())
     }
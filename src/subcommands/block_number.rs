@@ -2,7 +2,7 @@ use anyhow::Result;
 use clap::Parser;
 use starknet::providers::Provider;
 
-use crate::{verbosity::VerbosityArgs, ProviderArgs};
+use crate::{output::output, verbosity::VerbosityArgs, ProviderArgs};
 
 #[derive(Debug, Parser)]
 pub struct BlockNumber {
@@ -13,14 +13,16 @@ pub struct BlockNumber {
 }
 
 impl BlockNumber {
+    pub const RPC_METHODS: &'static [&'static str] = &["starknet_blockHashAndNumber"];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
 
-        let provider = self.provider.into_provider();
+        let provider = self.provider.into_provider()?;
 
         let block = provider.block_hash_and_number().await?;
 
-        println!("{}", block.block_number);
+        output!("{}", block.block_number);
 
         Ok(())
     }
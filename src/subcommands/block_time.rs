@@ -3,7 +3,7 @@ use chrono::{TimeZone, Utc};
 use clap::Parser;
 use starknet::{core::types::MaybePendingBlockWithTxHashes, providers::Provider};
 
-use crate::{utils::parse_block_id, verbosity::VerbosityArgs, ProviderArgs};
+use crate::{output::output, utils::parse_block_id, verbosity::VerbosityArgs, ProviderArgs};
 
 #[derive(Debug, Parser)]
 pub struct BlockTime {
@@ -31,10 +31,12 @@ pub struct BlockTime {
 }
 
 impl BlockTime {
+    pub const RPC_METHODS: &'static [&'static str] = &["starknet_getBlockWithTxHashes"];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
 
-        let provider = self.provider.into_provider();
+        let provider = self.provider.into_provider()?;
 
         let block_id = parse_block_id(&self.block_id)?;
 
@@ -45,7 +47,7 @@ impl BlockTime {
         };
 
         if self.unix {
-            println!("{timestamp}");
+            output!("{timestamp}");
         } else {
             let timestamp = Utc
                 .timestamp_opt(
@@ -56,9 +58,9 @@ impl BlockTime {
                 )
                 .unwrap();
             if self.rfc2822 {
-                println!("{}", timestamp.to_rfc2822())
+                output!("{}", timestamp.to_rfc2822())
             } else {
-                println!("{}", timestamp.to_rfc3339())
+                output!("{}", timestamp.to_rfc3339())
             }
         }
 
@@ -2,38 +2,84 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use clap::Parser;
+use colored_json::{ColorMode, Output};
+use serde_json::Value;
 use starknet::{
     core::{
-        types::{BlockId, BlockTag, FunctionCall},
+        types::{BlockId, BlockTag, FunctionCall, StarknetError},
         utils::get_selector_from_name,
     },
-    providers::Provider,
+    providers::{MaybeUnknownErrorCode, Provider, ProviderError, StarknetErrorWithMessage},
 };
 
 use crate::{
-    address_book::AddressBookResolver, decode::FeltDecoder, verbosity::VerbosityArgs, ProviderArgs,
+    abi::{format_suggestions, parse_type_list, AbiArgs, ContractAbi},
+    address_book::AliasArgs,
+    decode::FeltDecoder,
+    output::output,
+    utils::expand_stdin_args,
+    verbosity::{progress, warning, VerbosityArgs},
+    ProviderArgs,
 };
 
 #[derive(Debug, Parser)]
 pub struct Call {
     #[clap(flatten)]
     provider: ProviderArgs,
+    #[clap(
+        long,
+        help = "Annotate returned values matching known addresses with their address book names"
+    )]
+    resolve_names: bool,
+    #[clap(
+        long,
+        conflicts_with = "resolve_names",
+        help = "Decode the returned values into JSON using the contract ABI"
+    )]
+    json: bool,
+    #[clap(
+        long,
+        requires = "json",
+        help = "Comma-separated return types to decode with instead of the ABI, \
+        e.g. \"u256,Array<felt252>\""
+    )]
+    output_types: Option<String>,
+    #[clap(
+        long,
+        help = "Print the signature of the function from the ABI before the returned values. \
+        With --json, it's printed to stderr instead"
+    )]
+    show_signature: bool,
+    #[clap(flatten)]
+    abi: AbiArgs,
     #[clap(help = "Contract address")]
     contract_address: String,
     #[clap(help = "Name of the function being called")]
     selector: String,
     #[clap(help = "Raw function call arguments")]
     calldata: Vec<String>,
+    #[clap(
+        long,
+        help = "Read additional arguments from stdin, one per line. Alternatively, pass \"-\" \
+        as an argument to insert them at that position"
+    )]
+    args_stdin: bool,
+    #[clap(flatten)]
+    alias: AliasArgs,
     #[clap(flatten)]
     verbosity: VerbosityArgs,
 }
 
 impl Call {
+    pub const RPC_METHODS: &'static [&'static str] =
+        &["starknet_chainId", "starknet_call", "starknet_getClassAt"];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
 
-        let provider = Arc::new(self.provider.into_provider());
-        let felt_decoder = FeltDecoder::new(AddressBookResolver::new(provider.clone()));
+        let provider = Arc::new(self.provider.into_provider()?);
+        let abi_source = self.abi.into_source()?;
+        let felt_decoder = FeltDecoder::new(self.alias.into_resolver(provider.clone())?);
 
         let contract_address = felt_decoder
             .decode_single_with_addr_fallback(&self.contract_address)
@@ -41,11 +87,11 @@ impl Call {
         let selector = get_selector_from_name(&self.selector)?;
 
         let mut calldata = vec![];
-        for element in self.calldata.iter() {
+        for element in expand_stdin_args(&self.calldata, self.args_stdin)?.iter() {
             calldata.append(&mut felt_decoder.decode(element).await?);
         }
 
-        let result = provider
+        let result = match provider
             .call(
                 FunctionCall {
                     contract_address,
@@ -54,17 +100,102 @@ impl Call {
                 },
                 BlockId::Tag(BlockTag::Pending),
             )
-            .await?;
+            .await
+        {
+            Ok(result) => result,
+            // Calling a function that doesn't exist surfaces as a generic contract error, so the
+            // ABI is checked to turn typos into something actionable
+            Err(
+                err @ ProviderError::StarknetError(StarknetErrorWithMessage {
+                    code: MaybeUnknownErrorCode::Known(StarknetError::ContractError),
+                    ..
+                }),
+            ) => match abi_source.load(&provider, contract_address).await {
+                Ok(Some(abi)) if !abi.has_function(selector) => anyhow::bail!(
+                    "contract {:#064x} has no function named \"{}\".{}",
+                    contract_address,
+                    self.selector,
+                    format_suggestions(&abi.suggest_function_names(&self.selector))
+                ),
+                _ => return Err(err.into()),
+            },
+            Err(err) => return Err(err.into()),
+        };
+
+        // Loaded only once, as both the signature and the decoding might need it
+        let abi = if self.show_signature || (self.json && self.output_types.is_none()) {
+            abi_source.load(&provider, contract_address).await?
+        } else {
+            None
+        };
 
-        if result.is_empty() {
-            println!("[]");
+        if self.show_signature {
+            match abi
+                .as_ref()
+                .map(|abi| abi.find_function_signature(&self.selector))
+            {
+                Some(Some(signature)) if self.json => progress!("{}", signature),
+                Some(Some(signature)) => output!("{}", signature),
+                Some(None) => warning!(
+                    "WARNING: function {} not found in ABI. Not showing the signature.",
+                    self.selector
+                ),
+                None => warning!("WARNING: contract class has no ABI. Not showing the signature."),
+            }
+        }
+
+        if self.json {
+            let (abi, output_types) = match &self.output_types {
+                Some(output_types) => (ContractAbi::core_types(), parse_type_list(output_types)?),
+                None => {
+                    let abi = abi.ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "contract class has no ABI. \
+                            Use --output-types to specify the return types."
+                        )
+                    })?;
+                    let output_types =
+                        abi.find_function_outputs(&self.selector).ok_or_else(|| {
+                            anyhow::anyhow!("function not found in ABI: {}", self.selector)
+                        })?;
+
+                    (abi, output_types)
+                }
+            };
+
+            let decoded = abi.decode_values(&output_types, &result).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "returned values don't match the output types: {}",
+                    output_types.join(", ")
+                )
+            })?;
+
+            let decoded_json = colored_json::to_colored_json(
+                &Value::Array(decoded),
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{decoded_json}");
+        } else if result.is_empty() {
+            output!("[]");
         } else {
-            println!("[");
+            output!("[");
 
             for (ind_element, element) in result.iter().enumerate() {
-                println!(
-                    "    \"{:#064x}\"{}",
-                    element,
+                let name = if self.resolve_names {
+                    felt_decoder
+                        .address_book_resolver()
+                        .resolve_address(*element)
+                        .await?
+                } else {
+                    None
+                };
+
+                output!(
+                    "    \"{}\"{}",
+                    match name {
+                        Some(name) => format!("{} ({:#064x})", name, element),
+                        None => format!("{:#064x}", element),
+                    },
                     if ind_element == result.len() - 1 {
                         ""
                     } else {
@@ -73,7 +204,7 @@ impl Call {
                 );
             }
 
-            println!("]");
+            output!("]");
         }
 
         Ok(())
@@ -1,20 +1,28 @@
 use anyhow::Result;
 use clap::Parser;
+use colored_json::{ColorMode, Output};
 use starknet::{core::utils::parse_cairo_short_string, providers::Provider};
 
-use crate::{verbosity::VerbosityArgs, ProviderArgs};
+use crate::{output::output, verbosity::VerbosityArgs, ProviderArgs};
 
 #[derive(Debug, Parser)]
 pub struct ChainId {
     #[clap(flatten)]
     provider: ProviderArgs,
-    #[clap(long, help = "Do not show the decoded text")]
+    #[clap(long, alias = "raw", help = "Do not show the decoded text")]
     no_decode: bool,
     #[clap(
         long,
+        alias = "decimal",
         help = "Display the decimal instead of hexadecimal representation"
     )]
     dec: bool,
+    #[clap(
+        long,
+        conflicts_with_all = ["no_decode", "dec"],
+        help = "Print the chain id and its decoded name as JSON"
+    )]
+    json: bool,
     #[clap(
         default_value = "latest",
         help = "Block number, hash, or tag (latest/pending)"
@@ -25,14 +33,27 @@ pub struct ChainId {
 }
 
 impl ChainId {
+    pub const RPC_METHODS: &'static [&'static str] = &["starknet_chainId"];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
 
-        let provider = self.provider.into_provider();
+        let provider = self.provider.into_provider()?;
 
         let raw_chain_id = provider.chain_id().await?;
 
-        println!(
+        if self.json {
+            let json = serde_json::json!({
+                "chain_id": format!("{raw_chain_id:#x}"),
+                "name": parse_cairo_short_string(&raw_chain_id).ok(),
+            });
+            let json = colored_json::to_colored_json(&json, ColorMode::Auto(Output::StdOut))?;
+            output!("{json}");
+
+            return Ok(());
+        }
+
+        output!(
             "{}{}",
             if self.dec {
                 format!("{raw_chain_id}")
@@ -6,7 +6,7 @@ use starknet::{
     providers::Provider,
 };
 
-use crate::{verbosity::VerbosityArgs, ProviderArgs};
+use crate::{output::output, verbosity::VerbosityArgs, ProviderArgs};
 
 #[derive(Debug, Parser)]
 pub struct ClassAt {
@@ -19,10 +19,12 @@ pub struct ClassAt {
 }
 
 impl ClassAt {
+    pub const RPC_METHODS: &'static [&'static str] = &["starknet_getClassAt"];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
 
-        let provider = self.provider.into_provider();
+        let provider = self.provider.into_provider()?;
         let address = FieldElement::from_hex_be(&self.address)?;
 
         // TODO: allow custom block
@@ -33,7 +35,7 @@ impl ClassAt {
         let class_json = serde_json::to_value(class)?;
         let class_json =
             colored_json::to_colored_json(&class_json, ColorMode::Auto(Output::StdOut))?;
-        println!("{class_json}");
+        output!("{class_json}");
 
         Ok(())
     }
@@ -0,0 +1,346 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use colored_json::{ColorMode, Output};
+use regex::Regex;
+use serde::Serialize;
+use starknet::{core::types::FieldElement, providers::Provider};
+
+use crate::{
+    abi::{AbiItem, ContractAbi},
+    output::output,
+    verbosity::{warning, VerbosityArgs},
+    ProviderArgs,
+};
+
+#[derive(Debug, Parser)]
+pub struct ClassDiff {
+    #[clap(flatten)]
+    provider: ProviderArgs,
+    #[clap(long, help = "Print the diff as JSON")]
+    json: bool,
+    #[clap(help = "Hash of the class to compare from")]
+    old_hash: String,
+    #[clap(help = "Hash of the class to compare to")]
+    new_hash: String,
+    #[clap(flatten)]
+    verbosity: VerbosityArgs,
+}
+
+#[derive(Default, Serialize)]
+struct AbiDiff {
+    added: Vec<DiffItem>,
+    removed: Vec<DiffItem>,
+    changed: Vec<ChangedItem>,
+}
+
+#[derive(Serialize)]
+struct DiffItem {
+    kind: &'static str,
+    name: String,
+    signature: String,
+}
+
+#[derive(Serialize)]
+struct ChangedItem {
+    kind: &'static str,
+    name: String,
+    old_signature: String,
+    new_signature: String,
+}
+
+impl ClassDiff {
+    pub const RPC_METHODS: &'static [&'static str] = &["starknet_getClass"];
+
+    pub async fn run(self) -> Result<()> {
+        self.verbosity.setup_logging();
+
+        let provider = self.provider.into_provider()?;
+        let old_hash = FieldElement::from_hex_be(&self.old_hash)?;
+        let new_hash = FieldElement::from_hex_be(&self.new_hash)?;
+
+        let old_abi = fetch_abi(&provider, old_hash).await?;
+        let new_abi = fetch_abi(&provider, new_hash).await?;
+
+        // Cairo 0 and Cairo 1 name the same things differently. Items are matched by their last
+        // path segment, and the basic Cairo 0 types are written the Cairo 1 way.
+        let mixed = old_abi.is_legacy() != new_abi.is_legacy();
+        if mixed {
+            warning!(
+                "WARNING: comparing a legacy class with a Sierra class. Cairo 0 and Cairo 1 types \
+                other than felts and Uint256 are not comparable, so some items might show as \
+                changed even if they're called the same way."
+            );
+        }
+
+        let diff = diff_items(
+            normalize_items(&old_abi, mixed),
+            normalize_items(&new_abi, mixed),
+        );
+
+        if self.json {
+            let diff_json = colored_json::to_colored_json(
+                &serde_json::to_value(&diff)?,
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{diff_json}");
+            return Ok(());
+        }
+
+        if diff.added.is_empty() && diff.removed.is_empty() && diff.changed.is_empty() {
+            output!("No interface changes");
+            return Ok(());
+        }
+
+        for item in diff.removed.iter() {
+            output!("{}", format!("- {} {}", item.kind, item.signature).red());
+        }
+        for item in diff.added.iter() {
+            output!("{}", format!("+ {} {}", item.kind, item.signature).green());
+        }
+        for item in diff.changed.iter() {
+            output!(
+                "{}",
+                format!("~ {} {}", item.kind, item.name).bright_yellow()
+            );
+            output!("{}", format!("    - {}", item.old_signature).red());
+            output!("{}", format!("    + {}", item.new_signature).green());
+        }
+
+        Ok(())
+    }
+}
+
+async fn fetch_abi<P>(provider: P, class_hash: FieldElement) -> Result<ContractAbi>
+where
+    P: Provider,
+    P::Error: 'static,
+{
+    match ContractAbi::fetch_by_class_hash(provider, class_hash).await? {
+        Some(abi) => Ok(abi),
+        None => anyhow::bail!(
+            "class {:#064x} was declared without ABI and cannot be compared",
+            class_hash
+        ),
+    }
+}
+
+/// Keys items by kind and name, with names and legacy types adjusted for comparing a legacy class
+/// with a Sierra class when `mixed` is set.
+fn normalize_items(abi: &ContractAbi, mixed: bool) -> BTreeMap<(&'static str, String), AbiItem> {
+    let regex_felt = Regex::new(r"\bfelt\b").unwrap();
+    let regex_u256 = Regex::new(r"\bUint256\b").unwrap();
+
+    abi.interface_items()
+        .into_iter()
+        .map(|mut item| {
+            if mixed {
+                item.name = item.name.rsplit("::").next().unwrap_or_default().to_owned();
+                if abi.is_legacy() {
+                    let signature = regex_felt.replace_all(&item.signature, "felt252");
+                    item.signature = regex_u256.replace_all(&signature, "u256").into_owned();
+                    item.name = regex_u256.replace_all(&item.name, "u256").into_owned();
+                }
+            }
+            ((item.kind, item.name.clone()), item)
+        })
+        .collect()
+}
+
+fn diff_items(
+    mut old_items: BTreeMap<(&'static str, String), AbiItem>,
+    new_items: BTreeMap<(&'static str, String), AbiItem>,
+) -> AbiDiff {
+    let mut diff = AbiDiff::default();
+
+    for (key, new_item) in new_items.into_iter() {
+        match old_items.remove(&key) {
+            Some(old_item) => {
+                if old_item.signature != new_item.signature {
+                    diff.changed.push(ChangedItem {
+                        kind: new_item.kind,
+                        name: new_item.name,
+                        old_signature: old_item.signature,
+                        new_signature: new_item.signature,
+                    });
+                }
+            }
+            None => diff.added.push(DiffItem {
+                kind: new_item.kind,
+                name: new_item.name,
+                signature: new_item.signature,
+            }),
+        }
+    }
+
+    // Whatever is left was not found in the new class
+    diff.removed = old_items
+        .into_values()
+        .map(|old_item| DiffItem {
+            kind: old_item.kind,
+            name: old_item.name,
+            signature: old_item.signature,
+        })
+        .collect();
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::{json, Value};
+
+    fn sierra_function(name: &str, inputs: Value, mutability: &str) -> Value {
+        json!({
+            "type": "function",
+            "name": name,
+            "inputs": inputs,
+            "outputs": [],
+            "state_mutability": mutability
+        })
+    }
+
+    fn sierra_abi(entries: Value) -> ContractAbi {
+        ContractAbi::Sierra(serde_json::from_value(entries).unwrap())
+    }
+
+    fn diff(old_abi: &ContractAbi, new_abi: &ContractAbi) -> AbiDiff {
+        let mixed = old_abi.is_legacy() != new_abi.is_legacy();
+        diff_items(
+            normalize_items(old_abi, mixed),
+            normalize_items(new_abi, mixed),
+        )
+    }
+
+    fn diff_of_changed(old_abi: &ContractAbi, new_abi: &ContractAbi) -> Vec<String> {
+        diff(old_abi, new_abi)
+            .changed
+            .into_iter()
+            .map(|item| item.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_items() {
+        let old_abi = sierra_abi(json!([
+            sierra_function(
+                "transfer",
+                json!([{ "name": "amount", "type": "core::felt252" }]),
+                "external"
+            ),
+            sierra_function("burn", json!([]), "external"),
+            sierra_function("total_supply", json!([]), "view"),
+        ]));
+        let new_abi = sierra_abi(json!([
+            sierra_function(
+                "transfer",
+                json!([{ "name": "amount", "type": "core::integer::u256" }]),
+                "external"
+            ),
+            sierra_function("mint", json!([]), "external"),
+            sierra_function("total_supply", json!([]), "view"),
+        ]));
+
+        let diff = diff(&old_abi, &new_abi);
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].kind, "function");
+        assert_eq!(diff.added[0].name, "mint");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].name, "burn");
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].name, "transfer");
+        assert_eq!(diff.changed[0].old_signature, "transfer(amount: felt252)");
+        assert_eq!(diff.changed[0].new_signature, "transfer(amount: u256)");
+
+        assert_eq!(
+            serde_json::to_value(&diff).unwrap()["removed"],
+            json!([{ "kind": "function", "name": "burn", "signature": "burn()" }])
+        );
+    }
+
+    #[test]
+    fn test_diff_items_unchanged() {
+        let abi = sierra_abi(json!([sierra_function(
+            "transfer",
+            json!([{ "name": "amount", "type": "core::felt252" }]),
+            "external"
+        )]));
+
+        let diff = diff(&abi, &abi);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.changed.is_empty());
+
+        // Mutability is part of the signature
+        let view_abi = sierra_abi(json!([sierra_function(
+            "transfer",
+            json!([{ "name": "amount", "type": "core::felt252" }]),
+            "view"
+        )]));
+        assert_eq!(diff_of_changed(&abi, &view_abi), ["transfer"]);
+    }
+
+    #[test]
+    fn test_normalize_items_mixed() {
+        let legacy_abi = ContractAbi::Legacy(
+            serde_json::from_value(json!([
+                {
+                    "type": "function",
+                    "name": "set_total",
+                    "inputs": [
+                        { "name": "account", "type": "felt" },
+                        { "name": "total", "type": "Uint256" }
+                    ],
+                    "outputs": []
+                },
+                {
+                    "type": "function",
+                    "name": "get_total",
+                    "inputs": [],
+                    "outputs": [{ "name": "res", "type": "Uint256" }],
+                    "stateMutability": "view"
+                },
+            ]))
+            .unwrap(),
+        );
+
+        // Basic Cairo 0 types are written the Cairo 1 way
+        let items = normalize_items(&legacy_abi, true);
+        assert_eq!(
+            items[&("function", "set_total".to_owned())].signature,
+            "set_total(account: felt252, total: u256)"
+        );
+        assert_eq!(
+            items[&("function", "get_total".to_owned())].signature,
+            "get_total() -> (res: u256) [view]"
+        );
+        let items = normalize_items(&legacy_abi, false);
+        assert_eq!(
+            items[&("function", "set_total".to_owned())].signature,
+            "set_total(account: felt, total: Uint256)"
+        );
+
+        // So the same function in Cairo 1 compares equal, while outputs are only named in Cairo 0
+        let sierra_abi = sierra_abi(json!([
+            sierra_function(
+                "set_total",
+                json!([
+                    {
+                        "name": "account",
+                        "type": "core::felt252"
+                    },
+                    {
+                        "name": "total",
+                        "type": "core::integer::u256"
+                    },
+                ]),
+                "external"
+            ),
+            sierra_function("get_total", json!([]), "view"),
+        ]));
+        assert_eq!(diff_of_changed(&legacy_abi, &sierra_abi), ["get_total"]);
+    }
+}
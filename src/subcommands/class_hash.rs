@@ -4,39 +4,37 @@ use anyhow::Result;
 use clap::Parser;
 use starknet::core::types::contract::{legacy::LegacyContractClass, CompiledClass, SierraClass};
 
-use crate::path::ExpandedPathbufParser;
+use crate::{artifact::ArtifactArgs, output::output, path::ExpandedPathbufParser};
 
 #[derive(Debug, Parser)]
 pub struct ClassHash {
+    #[clap(flatten)]
+    artifact: ArtifactArgs,
     #[clap(
         value_parser = ExpandedPathbufParser,
-        help = "Path to contract artifact file"
+        help = "Path to contract artifact file, or an http(s):// URL to download it from"
     )]
     file: PathBuf,
 }
 
 impl ClassHash {
-    pub fn run(self) -> Result<()> {
+    pub async fn run(self) -> Result<()> {
+        let content = self.artifact.read(&self.file).await?;
+
         // Working around a deserialization bug in `starknet-rs`:
         //   https://github.com/xJonathanLEI/starknet-rs/issues/392
 
-        let class_hash = if let Ok(class) =
-            serde_json::from_reader::<_, SierraClass>(std::fs::File::open(&self.file)?)
-        {
+        let class_hash = if let Ok(class) = serde_json::from_slice::<SierraClass>(&content) {
             class.class_hash()?
-        } else if let Ok(class) =
-            serde_json::from_reader::<_, CompiledClass>(std::fs::File::open(&self.file)?)
-        {
+        } else if let Ok(class) = serde_json::from_slice::<CompiledClass>(&content) {
             class.class_hash()?
-        } else if let Ok(class) =
-            serde_json::from_reader::<_, LegacyContractClass>(std::fs::File::open(self.file)?)
-        {
+        } else if let Ok(class) = serde_json::from_slice::<LegacyContractClass>(&content) {
             class.class_hash()?
         } else {
             anyhow::bail!("failed to parse contract artifact");
         };
 
-        println!("{class_hash:#064x}");
+        output!("{class_hash:#064x}");
 
         Ok(())
     }
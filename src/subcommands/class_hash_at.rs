@@ -1,35 +1,86 @@
+use std::sync::Arc;
+
 use anyhow::Result;
 use clap::Parser;
+use colored_json::{ColorMode, Output};
+use serde_json::json;
 use starknet::{
-    core::types::{BlockId, BlockTag, FieldElement},
-    providers::Provider,
+    core::types::StarknetError,
+    providers::{MaybeUnknownErrorCode, Provider, ProviderError, StarknetErrorWithMessage},
 };
 
-use crate::{verbosity::VerbosityArgs, ProviderArgs};
+use crate::{
+    address_book::AliasArgs,
+    decode::FeltDecoder,
+    output::output,
+    utils::{parse_block_id, FeltFormat},
+    verbosity::VerbosityArgs,
+    ProviderArgs,
+};
 
 #[derive(Debug, Parser)]
 pub struct ClassHashAt {
     #[clap(flatten)]
     provider: ProviderArgs,
+    #[clap(
+        long,
+        default_value = "pending",
+        help = "Block number, hash, or tag (latest/pending) to look up the class hash at"
+    )]
+    block_id: String,
+    #[clap(long, help = "Print the address and class hash as JSON")]
+    json: bool,
+    #[clap(
+        long,
+        value_enum,
+        default_value_t,
+        help = "Output format for field elements"
+    )]
+    felt_format: FeltFormat,
     #[clap(help = "Contract address")]
     address: String,
     #[clap(flatten)]
+    alias: AliasArgs,
+    #[clap(flatten)]
     verbosity: VerbosityArgs,
 }
 
 impl ClassHashAt {
+    pub const RPC_METHODS: &'static [&'static str] =
+        &["starknet_chainId", "starknet_getClassHashAt"];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
 
-        let provider = self.provider.into_provider();
-        let address = FieldElement::from_hex_be(&self.address)?;
+        let provider = Arc::new(self.provider.into_provider()?);
+        let felt_decoder = FeltDecoder::new(self.alias.into_resolver(provider.clone())?);
 
-        // TODO: allow custom block
-        let class_hash = provider
-            .get_class_hash_at(BlockId::Tag(BlockTag::Pending), address)
+        let address = felt_decoder
+            .decode_single_with_addr_fallback(&self.address)
             .await?;
+        let block_id = parse_block_id(&self.block_id)?;
+
+        let class_hash = match provider.get_class_hash_at(block_id, address).await {
+            Ok(class_hash) => class_hash,
+            Err(ProviderError::StarknetError(StarknetErrorWithMessage {
+                code: MaybeUnknownErrorCode::Known(StarknetError::ContractNotFound),
+                ..
+            })) => anyhow::bail!("no contract deployed at address {:#064x}", address),
+            Err(err) => return Err(err.into()),
+        };
 
-        println!("{:#064x}", class_hash);
+        if self.json {
+            let result_json = colored_json::to_colored_json(
+                &json!({
+                    "address": self.felt_format.format(address),
+                    "class_hash": self.felt_format.format(class_hash),
+                }),
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{result_json}");
+        } else {
+            output!("{}", self.felt_format.format(class_hash));
+        }
 
         Ok(())
     }
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use colored_json::{ColorMode, Output};
+use serde::Serialize;
+use starknet::{
+    core::types::{BlockId, BlockTag, ContractClass, FieldElement},
+    providers::Provider,
+};
+
+use crate::{
+    abi::ContractAbi, compiler::SierraVersion, output::output, verbosity::VerbosityArgs,
+    ProviderArgs,
+};
+
+#[derive(Debug, Parser)]
+pub struct ClassInfo {
+    #[clap(flatten)]
+    provider: ProviderArgs,
+    #[clap(long, help = "Print the summary as JSON")]
+    json: bool,
+    #[clap(help = "Class hash")]
+    hash: String,
+    #[clap(flatten)]
+    verbosity: VerbosityArgs,
+}
+
+#[derive(Serialize)]
+struct ClassSummary {
+    r#type: &'static str,
+    /// Only for Sierra classes.
+    contract_class_version: Option<String>,
+    /// Only for Sierra classes from compilers that embed it.
+    sierra_version: Option<String>,
+    entry_points: EntryPointCounts,
+    /// `None` for legacy classes declared without ABI.
+    abi_items: Option<usize>,
+    view_functions: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct EntryPointCounts {
+    external: usize,
+    l1_handler: usize,
+    constructor: usize,
+}
+
+impl ClassInfo {
+    pub const RPC_METHODS: &'static [&'static str] = &["starknet_getClass"];
+
+    pub async fn run(self) -> Result<()> {
+        self.verbosity.setup_logging();
+
+        let provider = self.provider.into_provider()?;
+        let class_hash = FieldElement::from_hex_be(&self.hash)?;
+
+        // TODO: allow custom block
+        let class = provider
+            .get_class(BlockId::Tag(BlockTag::Pending), class_hash)
+            .await?;
+
+        let abi = ContractAbi::from_class(&class)?;
+        let summary = match &class {
+            ContractClass::Sierra(class) => ClassSummary {
+                r#type: "sierra",
+                contract_class_version: Some(class.contract_class_version.clone()),
+                sierra_version: SierraVersion::from_program(&class.sierra_program)
+                    .map(|version| version.to_string()),
+                entry_points: EntryPointCounts {
+                    external: class.entry_points_by_type.external.len(),
+                    l1_handler: class.entry_points_by_type.l1_handler.len(),
+                    constructor: class.entry_points_by_type.constructor.len(),
+                },
+                abi_items: abi.as_ref().map(|abi| abi.item_count()),
+                view_functions: abi.as_ref().map(|abi| abi.view_function_count()),
+            },
+            ContractClass::Legacy(class) => ClassSummary {
+                r#type: "legacy",
+                contract_class_version: None,
+                sierra_version: None,
+                entry_points: EntryPointCounts {
+                    external: class.entry_points_by_type.external.len(),
+                    l1_handler: class.entry_points_by_type.l1_handler.len(),
+                    constructor: class.entry_points_by_type.constructor.len(),
+                },
+                abi_items: abi.as_ref().map(|abi| abi.item_count()),
+                view_functions: abi.as_ref().map(|abi| abi.view_function_count()),
+            },
+        };
+
+        if self.json {
+            let summary_json = colored_json::to_colored_json(
+                &serde_json::to_value(&summary)?,
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{summary_json}");
+            return Ok(());
+        }
+
+        match (&summary.contract_class_version, &summary.sierra_version) {
+            (Some(class_version), Some(sierra_version)) => output!(
+                "Type:            {} (class version {}, Sierra {})",
+                "Sierra".bright_yellow(),
+                class_version,
+                sierra_version
+            ),
+            (Some(class_version), None) => output!(
+                "Type:            {} (class version {})",
+                "Sierra".bright_yellow(),
+                class_version
+            ),
+            _ => output!("Type:            {}", "Legacy (Cairo 0)".bright_yellow()),
+        }
+        output!("Entry points:");
+        output!("  External:      {}", summary.entry_points.external);
+        output!("  L1 handler:    {}", summary.entry_points.l1_handler);
+        output!("  Constructor:   {}", summary.entry_points.constructor);
+        match (summary.abi_items, summary.view_functions) {
+            (Some(abi_items), Some(view_functions)) => {
+                output!("ABI items:       {}", abi_items);
+                output!("View functions:  {}", view_functions);
+            }
+            _ => output!("ABI items:       none (class declared without ABI)"),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use starknet::core::types::contract::SierraClass;
+
+use crate::{
+    compiler::{BuiltInCompiler, CompilerVersion},
+    output::output,
+    path::ExpandedPathbufParser,
+};
+
+#[derive(Debug, Parser)]
+pub struct CompiledClassHash {
+    #[clap(long, help = "Statically-linked Sierra compiler version")]
+    compiler_version: Option<CompilerVersion>,
+    #[clap(
+        value_parser = ExpandedPathbufParser,
+        help = "Path to the Sierra contract artifact file"
+    )]
+    file: PathBuf,
+}
+
+impl CompiledClassHash {
+    pub fn run(self) -> Result<()> {
+        let class =
+            match serde_json::from_reader::<_, SierraClass>(std::fs::File::open(&self.file)?) {
+                Ok(class) => class,
+                Err(err) => anyhow::bail!("failed to parse Sierra class: {}", err),
+            };
+
+        let compiler: BuiltInCompiler = self.compiler_version.unwrap_or_default().into();
+        let casm_class_hash = compiler.compile(&class)?;
+
+        output!("{casm_class_hash:#064x}");
+
+        Ok(())
+    }
+}
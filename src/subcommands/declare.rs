@@ -1,25 +1,42 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{path::PathBuf, sync::Arc, time::Duration};
 
 use anyhow::Result;
 use clap::Parser;
 use colored::Colorize;
 use starknet::{
-    accounts::{Account, SingleOwnerAccount},
+    accounts::{Account, AccountError, ConnectedAccount, SingleOwnerAccount},
     core::types::{
         contract::{legacy::LegacyContractClass, CompiledClass, SierraClass},
-        BlockId, BlockTag, FieldElement, StarknetError,
+        BlockId, FieldElement, StarknetError,
     },
     providers::{MaybeUnknownErrorCode, Provider, ProviderError, StarknetErrorWithMessage},
+    signers::Signer,
 };
 
 use crate::{
     account::{AccountConfig, DeploymentStatus},
+    address_book::AddressBookResolver,
+    artifact::ArtifactArgs,
     casm::{CasmArgs, CasmHashSource},
-    fee::{FeeArgs, FeeSetting},
+    env_file::{EnvFile, EnvFileArgs},
+    explorer::ExplorerArgs,
+    fee::{
+        check_fee_ceiling, resolve_fee_token, warn_on_low_fee, FeeArgs, FeeSetting,
+        DEFAULT_FEE_BUFFER,
+    },
+    manifest::{Manifest, ManifestArgs, ManifestResource, ResourceKind},
+    metrics::MetricsArgs,
+    output::output,
     path::ExpandedPathbufParser,
+    replay_log::{ReplayLogArgs, ReplayMode, ReplayRecord},
     signer::SignerArgs,
-    utils::watch_tx,
-    verbosity::VerbosityArgs,
+    spinner::Spinner,
+    utils::{
+        check_fee_balance, check_signer_public_key, dump_transaction, parse_block_id,
+        print_parties, print_shell_exports, validate_export_prefix, watch_tx, FeltFormat, Finality,
+        WatchConditions,
+    },
+    verbosity::{progress, VerbosityArgs},
     ProviderArgs,
 };
 
@@ -35,226 +52,639 @@ pub struct Declare {
         long,
         env = "STARKNET_ACCOUNT",
         value_parser = ExpandedPathbufParser,
-        help = "Path to account config JSON file"
+        required_unless_present = "wallet",
+        help = "Path to account config JSON file, \"-\" to read it from stdin, the config JSON \
+        itself, or the name of an account in the address book"
+    )]
+    account: Option<PathBuf>,
+    #[clap(
+        long,
+        default_value = "pending",
+        help = "Block number, hash, or tag (latest/pending) the account fetches its nonce and \
+        estimates fees against"
     )]
-    account: PathBuf,
+    account_block: String,
     #[clap(flatten)]
     fee: FeeArgs,
+    #[clap(flatten)]
+    explorer: ExplorerArgs,
+    #[clap(flatten)]
+    replay_log: ReplayLogArgs,
+    #[clap(flatten)]
+    metrics: MetricsArgs,
+    #[clap(flatten)]
+    env_file: EnvFileArgs,
+    #[clap(flatten)]
+    manifest: ManifestArgs,
     #[clap(long, help = "Wait for the transaction to confirm")]
     watch: bool,
     #[clap(
+        long,
+        default_value = "0",
+        requires = "watch",
+        help = "Number of blocks to wait for on top of the one including the transaction when \
+                watching"
+    )]
+    min_confirmations: u64,
+    #[clap(
+        long,
+        value_enum,
+        requires = "watch",
+        help = "Transaction status to wait for when watching, on top of any confirmations"
+    )]
+    finality: Option<Finality>,
+    #[clap(
+        long,
+        value_name = "SECONDS",
+        requires = "watch",
+        help = "Give up watching after this many seconds, listing the conditions still pending"
+    )]
+    watch_timeout: Option<u64>,
+    #[clap(
+        long,
+        help = "Print only the transaction hash to stdout right after broadcasting. With --watch, \
+        the transaction is still watched afterwards"
+    )]
+    broadcast_only: bool,
+    #[clap(
+        long,
+        value_enum,
+        default_value_t,
+        requires = "broadcast_only",
+        help = "Output format of the transaction hash printed with --broadcast-only"
+    )]
+    felt_format: FeltFormat,
+    #[clap(
+        long,
+        value_name = "PREFIX",
+        conflicts_with = "estimate_only",
+        help = "Print \"export PREFIX_CLASS_HASH=...\" and \"export PREFIX_TX=...\" lines to \
+        stdout instead of the class hash, ready to be evaluated by a shell"
+    )]
+    export: Option<String>,
+    #[clap(
+        long,
+        help = "Send the declaration even if the class is already declared"
+    )]
+    force: bool,
+    #[clap(
+        long,
+        default_value = "pending",
+        help = "Block number, hash, or tag (latest/pending) to check whether the class is already \
+        declared at"
+    )]
+    block_id: String,
+    #[clap(
+        long,
+        value_name = "PATH",
         value_parser = ExpandedPathbufParser,
-        help = "Path to contract artifact file"
+        help = "Write the signed transaction as JSON to a file right before sending it. Use \"-\" \
+        to write to stderr instead"
+    )]
+    dump_transaction: Option<PathBuf>,
+    #[clap(
+        long,
+        help = "Skip checking that the signer's public key matches the one set on the account \
+        contract"
+    )]
+    skip_signer_check: bool,
+    #[clap(
+        long,
+        help = "Skip checking that the provider is on the chain recorded in the account config"
+    )]
+    skip_chain_check: bool,
+    #[clap(
+        long,
+        help = "Abort if the class hash computed from the artifact is not this one"
+    )]
+    expected_class_hash: Option<String>,
+    #[clap(
+        long,
+        help = "Abort if the CASM class hash computed from the artifact is not this one. Only \
+        applies to Cairo 1 classes"
+    )]
+    expected_compiled_class_hash: Option<String>,
+    #[clap(
+        long,
+        conflicts_with = "casm_hash",
+        help = "Compile the Sierra class to CASM a second time and abort if the compiled class \
+        hashes differ. Only applies to Cairo 1 classes"
+    )]
+    verify_deterministic: bool,
+    #[clap(
+        long,
+        help = "Declare the artifact as a Cairo 0 (deprecated) class instead of detecting its type"
+    )]
+    legacy: bool,
+    #[clap(
+        long,
+        help = "List every address the transaction involves before sending it, with address book \
+        names where known"
+    )]
+    show_parties: bool,
+    #[clap(flatten)]
+    artifact: ArtifactArgs,
+    #[clap(
+        value_parser = ExpandedPathbufParser,
+        help = "Path to contract artifact file, or an http(s):// URL to download it from"
     )]
     file: PathBuf,
     #[clap(flatten)]
     verbosity: VerbosityArgs,
 }
 
+enum ClassArtifact {
+    Sierra(SierraClass),
+    Legacy(LegacyContractClass),
+}
+
 impl Declare {
+    pub const RPC_METHODS: &'static [&'static str] = &[
+        "starknet_chainId",
+        "starknet_getNonce",
+        "starknet_call",
+        "starknet_getClass",
+        "starknet_estimateFee",
+        "starknet_addDeclareTransaction",
+        "starknet_getTransactionReceipt",
+        "starknet_blockNumber",
+    ];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
+        let watch_conditions = self.watch_conditions();
+
+        let fee_formatter = self.fee.formatter();
+        let skip_balance_check = self.fee.skip_balance_check();
+        let low_fee_threshold = self.fee.low_fee_threshold();
+        let max_fee_ceiling = self.fee.max_fee_ceiling()?;
+        let mut fee_buffer = self.fee.fee_buffer()?;
+        let explorer = self.explorer.into_explorer()?;
+        let replay_log = self.replay_log.into_replay_log("declare")?;
+        let mut metrics = self.metrics.into_metrics("declare");
+        let env_file = self.env_file.into_env_file()?;
+        let manifest = self.manifest.into_manifest()?;
+        if let Some(prefix) = &self.export {
+            validate_export_prefix(prefix)?;
+        }
 
-        let fee_setting = self.fee.into_setting()?;
-
-        let provider = Arc::new(self.provider.into_provider());
-
-        if !self.account.exists() {
-            anyhow::bail!("account config file not found");
+        // Hashes are verified before anything is sent to the network
+        let artifact = ClassArtifact::load(&self.artifact.read(&self.file).await?, self.legacy)?;
+        let class_hash = artifact.class_hash()?;
+        if let Some(expected_class_hash) = &self.expected_class_hash {
+            let expected_class_hash = expected_class_hash.parse::<FieldElement>()?;
+            if class_hash != expected_class_hash {
+                anyhow::bail!(
+                    "class hash mismatch: the artifact hashes to {:#064x}, but {:#064x} is expected",
+                    class_hash,
+                    expected_class_hash
+                );
+            }
         }
+        let expected_compiled_class_hash = match &self.expected_compiled_class_hash {
+            Some(_) if matches!(artifact, ClassArtifact::Legacy(_)) => {
+                anyhow::bail!("--expected-compiled-class-hash only applies to Cairo 1 classes")
+            }
+            Some(hash) => Some(hash.parse::<FieldElement>()?),
+            None => None,
+        };
+
+        let provider = Arc::new(self.provider.into_provider()?);
+        let fee_setting = self.fee.into_setting(&provider).await?;
+        let block_id = parse_block_id(&self.block_id)?;
 
-        let account_config: AccountConfig =
-            serde_json::from_reader(&mut std::fs::File::open(&self.account)?)?;
+        let account_config =
+            AccountConfig::load_from_args(self.account.as_deref(), &self.signer, &provider).await?;
 
-        let account_address = match account_config.deployment {
+        let account_address = match &account_config.deployment {
             DeploymentStatus::Undeployed(_) => anyhow::bail!("account not deployed"),
             DeploymentStatus::Deployed(inner) => inner.address,
         };
 
         let chain_id = provider.chain_id().await?;
 
+        if !self.skip_chain_check {
+            account_config.check_chain_id(chain_id)?;
+        }
+        fee_buffer
+            .read_balance_budget(&provider, chain_id, account_address)
+            .await?;
+
         let signer = self.signer.into_signer()?;
+        if !self.skip_signer_check {
+            check_signer_public_key(
+                &provider,
+                account_address,
+                signer.get_public_key().await?.scalar(),
+            )
+            .await?;
+        }
         let mut account =
             SingleOwnerAccount::new(provider.clone(), signer, account_address, chain_id);
-        account.set_block_id(BlockId::Tag(BlockTag::Pending));
+        account.set_block_id(parse_block_id(&self.account_block)?);
+
+        if self.show_parties {
+            print_parties(
+                &AddressBookResolver::with_config(provider.clone())?,
+                &[
+                    ("Sender", account_address),
+                    ("Fee token", resolve_fee_token(chain_id)?),
+                ],
+            )
+            .await?;
+        }
 
         // Workaround for issue:
         //   https://github.com/eqlabs/pathfinder/issues/1208
-        let (fee_multiplier_num, fee_multiplier_denom) =
-            if provider.is_rpc() { (5, 2) } else { (3, 2) };
-
-        // Working around a deserialization bug in `starknet-rs`:
-        //   https://github.com/xJonathanLEI/starknet-rs/issues/392
-
-        #[allow(clippy::redundant_pattern_matching)]
-        let (class_hash, declaration_tx_hash) = if let Ok(class) =
-            serde_json::from_reader::<_, SierraClass>(std::fs::File::open(&self.file)?)
-        {
-            // Declaring Cairo 1 class
-            let class_hash = class.class_hash()?;
-
-            // TODO: add option to skip checking
-            if Self::check_already_declared(&provider, class_hash).await? {
-                return Ok(());
-            }
+        let default_fee_buffer = if provider.is_rpc() {
+            150
+        } else {
+            DEFAULT_FEE_BUFFER
+        };
 
-            let casm_source = self.casm.into_casm_hash_source(&provider).await?;
+        let (declaration_tx_hash, nonce) = match artifact {
+            ClassArtifact::Sierra(class) => {
+                // Declaring Cairo 1 class
+                if !self.force
+                    && Self::check_already_declared(
+                        &provider,
+                        block_id,
+                        class_hash,
+                        self.export.as_deref(),
+                        env_file.as_ref(),
+                        manifest.as_ref().map(|manifest| (manifest, chain_id)),
+                    )
+                    .await?
+                {
+                    return Ok(());
+                }
 
-            if !fee_setting.is_estimate_only() {
-                eprintln!(
-                    "Declaring Cairo 1 class: {}",
-                    format!("{:#064x}", class_hash).bright_yellow()
-                );
+                let casm_source = self.casm.into_casm_hash_source(&provider).await?;
+
+                if !fee_setting.is_estimate_only() {
+                    progress!(
+                        "Declaring Cairo 1 class: {}",
+                        format!("{:#064x}", class_hash).bright_yellow()
+                    );
+
+                    match &casm_source {
+                        CasmHashSource::BuiltInCompiler(compiler) => {
+                            progress!(
+                                "Compiling Sierra class to CASM with compiler version {}...",
+                                format!("{}", compiler.version()).bright_yellow()
+                            );
+                        }
+                        CasmHashSource::Hash(hash) => {
+                            progress!(
+                                "Using the provided CASM hash: {}...",
+                                format!("{:#064x}", hash).bright_yellow()
+                            );
+                        }
+                    }
+                }
 
-                match &casm_source {
-                    CasmHashSource::BuiltInCompiler(compiler) => {
-                        eprintln!(
-                            "Compiling Sierra class to CASM with compiler version {}...",
-                            format!("{}", compiler.version()).bright_yellow()
+                let casm_class_hash = casm_source.get_casm_hash(&class)?;
+                if self.verify_deterministic {
+                    let recompiled_class_hash = casm_source.get_casm_hash(&class)?;
+                    if recompiled_class_hash != casm_class_hash {
+                        anyhow::bail!(
+                            "non-deterministic compilation: compiling the class twice gave CASM \
+                            class hashes {:#064x} and {:#064x}",
+                            casm_class_hash,
+                            recompiled_class_hash
                         );
                     }
-                    CasmHashSource::Hash(hash) => {
-                        eprintln!(
-                            "Using the provided CASM hash: {}...",
-                            format!("{:#064x}", hash).bright_yellow()
-                        );
+
+                    if !fee_setting.is_estimate_only() {
+                        progress!("Compilation verified to be deterministic");
+                    }
+                }
+                if let Some(expected_compiled_class_hash) = expected_compiled_class_hash {
+                    if casm_class_hash != expected_compiled_class_hash {
+                        anyhow::bail!(
+                        "CASM class hash mismatch: the artifact compiles to {:#064x}, but {:#064x} \
+                        is expected",
+                        casm_class_hash,
+                        expected_compiled_class_hash
+                    );
                     }
                 }
-            }
-
-            let casm_class_hash = casm_source.get_casm_hash(&class)?;
-
-            if !fee_setting.is_estimate_only() {
-                eprintln!(
-                    "CASM class hash: {}",
-                    format!("{:#064x}", casm_class_hash).bright_yellow()
-                );
-            }
-
-            // TODO: make buffer configurable
-            let declaration = account.declare(Arc::new(class.flatten()?), casm_class_hash);
 
-            let max_fee = match fee_setting {
-                FeeSetting::Manual(fee) => fee,
-                FeeSetting::EstimateOnly | FeeSetting::None => {
-                    let estimated_fee = declaration.estimate_fee().await?.overall_fee;
+                if !fee_setting.is_estimate_only() {
+                    progress!(
+                        "CASM class hash: {}",
+                        format!("{:#064x}", casm_class_hash).bright_yellow()
+                    );
+                }
 
-                    if fee_setting.is_estimate_only() {
-                        println!(
-                            "{} ETH",
-                            format!(
+                let declaration = account.declare(Arc::new(class.flatten()?), casm_class_hash);
+
+                let max_fee = match fee_setting {
+                    FeeSetting::Manual(fee) => fee,
+                    FeeSetting::EstimateOnly | FeeSetting::None => {
+                        let estimated_fee = Spinner::new()
+                            .run("Estimating fee", declaration.estimate_fee())
+                            .await?
+                            .overall_fee;
+                        warn_on_low_fee(estimated_fee, low_fee_threshold);
+
+                        if fee_setting.is_estimate_only() {
+                            replay_log.record(
+                                ReplayMode::EstimateOnly,
+                                ReplayRecord {
+                                    chain_id,
+                                    sender: account_address,
+                                    class_hash: Some(class_hash),
+                                    compiled_class_hash: Some(casm_class_hash),
+                                    estimated_fee: Some(estimated_fee),
+                                    ..Default::default()
+                                },
+                            );
+                            output!(
                                 "{}",
-                                <u64 as Into<FieldElement>>::into(estimated_fee).to_big_decimal(18)
-                            )
-                            .bright_yellow(),
-                        );
-                        return Ok(());
-                    }
+                                fee_formatter.format(estimated_fee.into()).bright_yellow()
+                            );
+                            return Ok(());
+                        }
 
-                    // TODO: make buffer configurable
-                    let estimated_fee_with_buffer =
-                        estimated_fee * fee_multiplier_num / fee_multiplier_denom;
+                        check_fee_ceiling(estimated_fee, max_fee_ceiling, &fee_formatter)?;
 
-                    estimated_fee_with_buffer.into()
+                        fee_buffer.apply(estimated_fee, chain_id, default_fee_buffer)
+                    }
+                };
+
+                if !skip_balance_check {
+                    check_fee_balance(
+                        &provider,
+                        chain_id,
+                        account_address,
+                        max_fee,
+                        &fee_formatter,
+                    )
+                    .await?;
                 }
-            };
-
-            (
-                class_hash,
-                declaration.max_fee(max_fee).send().await?.transaction_hash,
-            )
-        } else if let Ok(_) =
-            serde_json::from_reader::<_, CompiledClass>(std::fs::File::open(&self.file)?)
-        {
-            // TODO: add more helpful instructions to fix this
-            anyhow::bail!("unexpected CASM class");
-        } else if let Ok(class) =
-            serde_json::from_reader::<_, LegacyContractClass>(std::fs::File::open(self.file)?)
-        {
-            // Declaring Cairo 0 class
-            let class_hash = class.class_hash()?;
-
-            // TODO: add option to skip checking
-            if Self::check_already_declared(&provider, class_hash).await? {
-                return Ok(());
-            }
 
-            if !fee_setting.is_estimate_only() {
-                eprintln!(
-                    "Declaring Cairo 0 (deprecated) class: {}",
-                    format!("{:#064x}", class_hash).bright_yellow()
+                // Fetched explicitly so that the nonce consumed by the transaction can be reported
+                let nonce = account.get_nonce().await?;
+                let declaration = declaration.max_fee(max_fee).nonce(nonce);
+                let sent = match &self.dump_transaction {
+                    Some(path) => {
+                        let declaration = declaration.prepared()?;
+                        dump_transaction(&declaration.get_declare_request().await?, path)?;
+                        declaration.send().await
+                    }
+                    None => declaration.send().await,
+                };
+                metrics.record_send(chain_id, max_fee, sent.is_ok());
+                replay_log.record(
+                    ReplayMode::Send,
+                    ReplayRecord {
+                        chain_id,
+                        sender: account_address,
+                        nonce: Some(nonce),
+                        class_hash: Some(class_hash),
+                        compiled_class_hash: Some(casm_class_hash),
+                        max_fee: Some(max_fee),
+                        transaction_hash: sent.as_ref().ok().map(|sent| sent.transaction_hash),
+                        error: sent.as_ref().err().map(|err| err.to_string()),
+                        ..Default::default()
+                    },
                 );
+                (sent?.transaction_hash, nonce)
             }
+            ClassArtifact::Legacy(class) => {
+                // Declaring Cairo 0 class
+                if !self.force
+                    && Self::check_already_declared(
+                        &provider,
+                        block_id,
+                        class_hash,
+                        self.export.as_deref(),
+                        env_file.as_ref(),
+                        manifest.as_ref().map(|manifest| (manifest, chain_id)),
+                    )
+                    .await?
+                {
+                    return Ok(());
+                }
 
-            // TODO: make buffer configurable
-            let declaration = account.declare_legacy(Arc::new(class));
-
-            let max_fee = match fee_setting {
-                FeeSetting::Manual(fee) => fee,
-                FeeSetting::EstimateOnly | FeeSetting::None => {
-                    let estimated_fee = declaration.estimate_fee().await?.overall_fee;
+                if !fee_setting.is_estimate_only() {
+                    progress!(
+                        "Declaring Cairo 0 (deprecated) class: {}",
+                        format!("{:#064x}", class_hash).bright_yellow()
+                    );
+                }
 
-                    if fee_setting.is_estimate_only() {
-                        println!(
-                            "{} ETH",
-                            format!(
+                let declaration = account.declare_legacy(Arc::new(class));
+
+                let max_fee = match fee_setting {
+                    FeeSetting::Manual(fee) => fee,
+                    FeeSetting::EstimateOnly | FeeSetting::None => {
+                        let estimated_fee = Spinner::new()
+                            .run("Estimating fee", declaration.estimate_fee())
+                            .await
+                            .map_err(Self::legacy_declaration_error)?
+                            .overall_fee;
+                        warn_on_low_fee(estimated_fee, low_fee_threshold);
+
+                        if fee_setting.is_estimate_only() {
+                            replay_log.record(
+                                ReplayMode::EstimateOnly,
+                                ReplayRecord {
+                                    chain_id,
+                                    sender: account_address,
+                                    class_hash: Some(class_hash),
+                                    estimated_fee: Some(estimated_fee),
+                                    ..Default::default()
+                                },
+                            );
+                            output!(
                                 "{}",
-                                <u64 as Into<FieldElement>>::into(estimated_fee).to_big_decimal(18)
-                            )
-                            .bright_yellow(),
-                        );
-                        return Ok(());
-                    }
+                                fee_formatter.format(estimated_fee.into()).bright_yellow()
+                            );
+                            return Ok(());
+                        }
 
-                    // TODO: make buffer configurable
-                    let estimated_fee_with_buffer =
-                        estimated_fee * fee_multiplier_num / fee_multiplier_denom;
+                        check_fee_ceiling(estimated_fee, max_fee_ceiling, &fee_formatter)?;
 
-                    estimated_fee_with_buffer.into()
+                        fee_buffer.apply(estimated_fee, chain_id, default_fee_buffer)
+                    }
+                };
+
+                if !skip_balance_check {
+                    check_fee_balance(
+                        &provider,
+                        chain_id,
+                        account_address,
+                        max_fee,
+                        &fee_formatter,
+                    )
+                    .await?;
                 }
-            };
 
-            (
-                class_hash,
-                declaration.max_fee(max_fee).send().await?.transaction_hash,
-            )
-        } else {
-            anyhow::bail!("failed to parse contract artifact");
+                // Fetched explicitly so that the nonce consumed by the transaction can be reported
+                let nonce = account.get_nonce().await?;
+                let declaration = declaration.max_fee(max_fee).nonce(nonce);
+                let sent = match &self.dump_transaction {
+                    Some(path) => {
+                        let declaration = declaration.prepared()?;
+                        dump_transaction(&declaration.get_declare_request().await?, path)?;
+                        declaration.send().await
+                    }
+                    None => declaration.send().await,
+                }
+                .map_err(Self::legacy_declaration_error);
+                metrics.record_send(chain_id, max_fee, sent.is_ok());
+                replay_log.record(
+                    ReplayMode::Send,
+                    ReplayRecord {
+                        chain_id,
+                        sender: account_address,
+                        nonce: Some(nonce),
+                        class_hash: Some(class_hash),
+                        max_fee: Some(max_fee),
+                        transaction_hash: sent.as_ref().ok().map(|sent| sent.transaction_hash),
+                        error: sent.as_ref().err().map(|err| err.to_string()),
+                        ..Default::default()
+                    },
+                );
+                (sent?.transaction_hash, nonce)
+            }
+        };
+
+        progress!("Nonce used: {}", nonce.to_string().bright_yellow());
+        explorer.print_link(chain_id, declaration_tx_hash);
+
+        // With --watch, the env file is only written once the transaction is confirmed
+        let results = [
+            ("CLASS_HASH", class_hash),
+            ("DECLARE_TX", declaration_tx_hash),
+        ];
+
+        let manifest_resource = ManifestResource {
+            kind: ResourceKind::Class,
+            chain_id,
+            class_hash,
+            address: None,
+            salt: None,
+            constructor_calldata: None,
+            transaction_hash: Some(declaration_tx_hash),
         };
 
-        eprintln!(
-            "Contract declaration transaction: {}",
-            format!("{:#064x}", declaration_tx_hash).bright_yellow()
-        );
+        if self.broadcast_only {
+            match &self.export {
+                Some(prefix) => print_shell_exports(
+                    prefix,
+                    &[("CLASS_HASH", class_hash), ("TX", declaration_tx_hash)],
+                ),
+                // Only the transaction hash goes to stdout so this can be easily scripted. It's
+                // printed before watching so that it can be picked up right away.
+                None => output!("{}", self.felt_format.format(declaration_tx_hash)),
+            }
+        } else {
+            progress!(
+                "Contract declaration transaction: {}",
+                format!("{:#064x}", declaration_tx_hash).bright_yellow()
+            );
+        }
 
         if self.watch {
-            eprintln!(
+            progress!(
                 "Waiting for transaction {} to confirm...",
                 format!("{:#064x}", declaration_tx_hash).bright_yellow(),
             );
-            watch_tx(&provider, declaration_tx_hash).await?;
+            watch_tx(&provider, declaration_tx_hash, watch_conditions).await?;
+        }
+
+        if let Some(env_file) = &env_file {
+            env_file.write(&results)?;
+        }
+        if let Some(manifest) = &manifest {
+            manifest.write(&manifest_resource)?;
+        }
+        if self.broadcast_only {
+            return Ok(());
         }
 
-        eprintln!("Class hash declared:");
+        progress!("Class hash declared:");
 
-        // Only the class hash goes to stdout so this can be easily scripted
-        println!("{}", format!("{:#064x}", class_hash).bright_yellow());
+        match &self.export {
+            Some(prefix) => print_shell_exports(
+                prefix,
+                &[("CLASS_HASH", class_hash), ("TX", declaration_tx_hash)],
+            ),
+            // Only the class hash goes to stdout so this can be easily scripted
+            None => output!("{}", format!("{:#064x}", class_hash).bright_yellow()),
+        }
 
         Ok(())
     }
 
-    async fn check_already_declared<P>(provider: P, class_hash: FieldElement) -> Result<bool>
+    /// Networks can stop accepting Cairo 0 declarations, in which case the node just rejects the
+    /// transaction without saying why.
+    fn legacy_declaration_error<S, E>(err: AccountError<S, E>) -> anyhow::Error
+    where
+        S: std::error::Error + Send + Sync + 'static,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        match err {
+            AccountError::Provider(ProviderError::StarknetError(
+                err @ StarknetErrorWithMessage {
+                    code:
+                        MaybeUnknownErrorCode::Known(
+                            StarknetError::InvalidContractClass
+                            | StarknetError::FailedToReceiveTransaction,
+                        )
+                        | MaybeUnknownErrorCode::Unknown(_),
+                    ..
+                },
+            )) => anyhow::anyhow!(
+                "{}. The network might no longer accept Cairo 0 class declarations, in which case \
+                the contract needs to be migrated to Cairo 1",
+                err
+            ),
+            err => err.into(),
+        }
+    }
+
+    /// Nothing gets sent for classes that are already declared, so only the class hash is
+    /// exported or written to the env file and manifest in that case.
+    async fn check_already_declared<P>(
+        provider: P,
+        block_id: BlockId,
+        class_hash: FieldElement,
+        export: Option<&str>,
+        env_file: Option<&EnvFile>,
+        manifest: Option<(&Manifest, FieldElement)>,
+    ) -> Result<bool>
     where
         P: Provider,
         P::Error: 'static,
     {
-        match provider
-            .get_class(BlockId::Tag(BlockTag::Pending), class_hash)
-            .await
-        {
+        match provider.get_class(block_id, class_hash).await {
             Ok(_) => {
-                eprintln!("Not declaring class as it's already declared. Class hash:");
-                println!("{}", format!("{:#064x}", class_hash).bright_yellow());
+                progress!(
+                    "Not declaring class as it's already declared. Use --force to declare anyway. \
+                    Class hash:"
+                );
+                match export {
+                    Some(prefix) => print_shell_exports(prefix, &[("CLASS_HASH", class_hash)]),
+                    None => output!("{}", format!("{:#064x}", class_hash).bright_yellow()),
+                }
+                if let Some(env_file) = env_file {
+                    env_file.write(&[("CLASS_HASH", class_hash)])?;
+                }
+                if let Some((manifest, chain_id)) = manifest {
+                    manifest.write(&ManifestResource {
+                        kind: ResourceKind::Class,
+                        chain_id,
+                        class_hash,
+                        address: None,
+                        salt: None,
+                        constructor_calldata: None,
+                        transaction_hash: None,
+                    })?;
+                }
 
                 Ok(true)
             }
@@ -265,4 +695,44 @@ impl Declare {
             Err(err) => Err(err.into()),
         }
     }
+
+    fn watch_conditions(&self) -> WatchConditions {
+        WatchConditions {
+            min_confirmations: self.min_confirmations,
+            finality: self.finality,
+            timeout: self.watch_timeout.map(Duration::from_secs),
+        }
+    }
+}
+
+impl ClassArtifact {
+    fn load(content: &[u8], legacy: bool) -> Result<Self> {
+        if legacy {
+            return serde_json::from_slice::<LegacyContractClass>(content)
+                .map(Self::Legacy)
+                .map_err(|err| anyhow::anyhow!("failed to parse Cairo 0 class artifact: {}", err));
+        }
+
+        // Working around a deserialization bug in `starknet-rs`:
+        //   https://github.com/xJonathanLEI/starknet-rs/issues/392
+
+        #[allow(clippy::redundant_pattern_matching)]
+        if let Ok(class) = serde_json::from_slice::<SierraClass>(content) {
+            Ok(Self::Sierra(class))
+        } else if let Ok(_) = serde_json::from_slice::<CompiledClass>(content) {
+            // TODO: add more helpful instructions to fix this
+            anyhow::bail!("unexpected CASM class");
+        } else if let Ok(class) = serde_json::from_slice::<LegacyContractClass>(content) {
+            Ok(Self::Legacy(class))
+        } else {
+            anyhow::bail!("failed to parse contract artifact");
+        }
+    }
+
+    fn class_hash(&self) -> Result<FieldElement> {
+        Ok(match self {
+            Self::Sierra(class) => class.class_hash()?,
+            Self::Legacy(class) => class.class_hash()?,
+        })
+    }
 }
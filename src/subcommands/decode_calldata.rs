@@ -0,0 +1,657 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use colored::Colorize;
+use colored_json::{ColorMode, Output};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use starknet::{
+    core::{
+        types::{FieldElement, InvokeTransaction, Transaction},
+        utils::get_selector_from_name,
+    },
+    providers::Provider,
+};
+
+use crate::{
+    abi::{ContractAbi, TypedMember},
+    address_book::AliasArgs,
+    output::output,
+    path::ExpandedPathbufParser,
+    utils::{expand_stdin_args, parse_felt_value},
+    verbosity::{progress, warning, VerbosityArgs},
+    ProviderArgs,
+};
+
+#[derive(Debug, Parser)]
+pub struct DecodeCalldata {
+    #[clap(flatten)]
+    provider: ProviderArgs,
+    #[clap(
+        long,
+        value_enum,
+        default_value_t,
+        help = "Calldata encoding used by the account's __execute__ entrypoint"
+    )]
+    encoding: CalldataEncoding,
+    #[clap(
+        long,
+        help = "Don't look up function names from on-chain ABIs and address book names. ABIs from \
+        --abi-dir are still used"
+    )]
+    no_resolve: bool,
+    #[clap(
+        long,
+        value_name = "PATH",
+        value_parser = ExpandedPathbufParser,
+        help = "Directory of local ABI or contract class JSON files to look up functions by \
+        selector, whatever contract they're called on. Takes precedence over on-chain ABIs"
+    )]
+    abi_dir: Option<PathBuf>,
+    #[clap(long, help = "Print the decoded calls as JSON")]
+    json: bool,
+    #[clap(
+        required = true,
+        help = "Hash of an invoke transaction, or the raw calldata of a multicall. Pass \"-\" to \
+        read the calldata from stdin"
+    )]
+    input: Vec<String>,
+    #[clap(flatten)]
+    alias: AliasArgs,
+    #[clap(flatten)]
+    verbosity: VerbosityArgs,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum CalldataEncoding {
+    /// Tries the legacy encoding first, then the new one.
+    #[default]
+    Auto,
+    /// Call array followed by the concatenated calldata, as used by Cairo 0 accounts.
+    Legacy,
+    /// Calls serialized one after another, as used by Cairo 1 accounts.
+    New,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct RawCall {
+    to: FieldElement,
+    selector: FieldElement,
+    calldata: Vec<FieldElement>,
+}
+
+#[derive(Serialize)]
+struct DecodedCall {
+    to: String,
+    to_name: Option<String>,
+    selector: String,
+    function: Option<String>,
+    /// Only known when a single function signature matches the calldata.
+    arguments: Option<Map<String, Value>>,
+    /// Functions from `--abi-dir` with the same selector but different signatures, when the
+    /// calldata doesn't tell them apart.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    candidates: Vec<String>,
+    calldata: Vec<String>,
+}
+
+/// Functions of the ABIs in `--abi-dir`, indexed by selector.
+struct LocalAbis {
+    abis: Vec<(String, ContractAbi)>,
+    functions: HashMap<FieldElement, Vec<(usize, String)>>,
+}
+
+/// A function from `--abi-dir` that a selector could refer to.
+struct Candidate<'a> {
+    signature: String,
+    function: &'a str,
+    file_name: &'a str,
+    arguments: Option<Map<String, Value>>,
+}
+
+/// What the local ABIs say about a call.
+struct LocalMatch {
+    function: String,
+    arguments: Option<Map<String, Value>>,
+    candidates: Vec<String>,
+}
+
+impl DecodeCalldata {
+    pub const RPC_METHODS: &'static [&'static str] = &[
+        "starknet_chainId",
+        "starknet_getTransactionByHash",
+        "starknet_getClassAt",
+    ];
+
+    pub async fn run(self) -> Result<()> {
+        self.verbosity.setup_logging();
+
+        let provider = Arc::new(self.provider.into_provider()?);
+        let address_book_resolver = self.alias.into_resolver(provider.clone())?;
+
+        let local_abis = match &self.abi_dir {
+            Some(abi_dir) => Some(LocalAbis::load(abi_dir)?),
+            None => None,
+        };
+
+        let input = expand_stdin_args(&self.input, false)?;
+
+        // A multicall always has at least 2 elements, so a single one must be a transaction hash
+        let calldata = if input.len() == 1 {
+            let transaction_hash = FieldElement::from_hex_be(&input[0])?;
+            match provider.get_transaction_by_hash(transaction_hash).await? {
+                Transaction::Invoke(InvokeTransaction::V1(tx)) => {
+                    progress!("Sender: {:#064x}", tx.sender_address);
+                    tx.calldata
+                }
+                Transaction::Invoke(InvokeTransaction::V0(_)) => anyhow::bail!(
+                    "transaction {:#064x} is a version 0 invoke, which doesn't go through an \
+                    account and has no multicall",
+                    transaction_hash
+                ),
+                _ => anyhow::bail!(
+                    "transaction {:#064x} is not an invoke transaction",
+                    transaction_hash
+                ),
+            }
+        } else {
+            input
+                .iter()
+                .map(|element| parse_felt_value(element))
+                .collect::<Result<Vec<_>>>()?
+        };
+
+        let calls = match self.encoding {
+            CalldataEncoding::Legacy => Self::decode_legacy(&calldata).ok_or_else(|| {
+                anyhow::anyhow!("calldata is not a valid multicall in the legacy encoding")
+            })?,
+            CalldataEncoding::New => Self::decode_new(&calldata).ok_or_else(|| {
+                anyhow::anyhow!("calldata is not a valid multicall in the new encoding")
+            })?,
+            CalldataEncoding::Auto => {
+                match (Self::decode_legacy(&calldata), Self::decode_new(&calldata)) {
+                    (Some(calls), Some(_)) => {
+                        warning!(
+                            "WARNING: calldata is valid in both the legacy and the new \
+                            encodings. Decoding as legacy. Use --encoding to choose explicitly."
+                        );
+                        calls
+                    }
+                    (Some(calls), None) | (None, Some(calls)) => calls,
+                    (None, None) => {
+                        anyhow::bail!("calldata is not a valid multicall in any known encoding")
+                    }
+                }
+            }
+        };
+
+        let mut abis: HashMap<FieldElement, Option<ContractAbi>> = HashMap::new();
+        let mut decoded_calls = vec![];
+        for call in calls.into_iter() {
+            let to_name = if self.no_resolve {
+                None
+            } else {
+                address_book_resolver
+                    .resolve_address(call.to)
+                    .await?
+                    .map(|name| name.to_owned())
+            };
+
+            let local_match = local_abis
+                .as_ref()
+                .and_then(|local_abis| local_abis.find(call.selector, &call.calldata));
+
+            let (function, arguments, candidates) = if let Some(local_match) = local_match {
+                (
+                    Some(local_match.function),
+                    local_match.arguments,
+                    local_match.candidates,
+                )
+            } else if self.no_resolve {
+                (None, None, vec![])
+            } else {
+                let abi = match abis.entry(call.to) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let abi = match ContractAbi::fetch(&provider, call.to).await {
+                            Ok(abi) => abi,
+                            Err(err) => {
+                                warning!(
+                                    "WARNING: unable to fetch the ABI of {:#064x}: {}",
+                                    call.to,
+                                    err
+                                );
+                                None
+                            }
+                        };
+                        entry.insert(abi)
+                    }
+                };
+                let function = abi
+                    .as_ref()
+                    .and_then(|abi| abi.find_function_name(call.selector));
+                let arguments = match (abi.as_ref(), &function) {
+                    (Some(abi), Some(function)) => decode_arguments(abi, function, &call.calldata),
+                    _ => None,
+                };
+
+                (function, arguments, vec![])
+            };
+
+            decoded_calls.push(DecodedCall {
+                to: format!("{:#064x}", call.to),
+                to_name,
+                selector: format!("{:#064x}", call.selector),
+                function,
+                arguments,
+                candidates,
+                calldata: call
+                    .calldata
+                    .iter()
+                    .map(|element| format!("{:#064x}", element))
+                    .collect(),
+            });
+        }
+
+        if self.json {
+            let calls_json = colored_json::to_colored_json(
+                &serde_json::to_value(&decoded_calls)?,
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{calls_json}");
+        } else {
+            for (ind_call, call) in decoded_calls.iter().enumerate() {
+                if ind_call > 0 {
+                    output!("");
+                }
+
+                output!(
+                    "Call {} of {}",
+                    (ind_call + 1).to_string().bright_yellow(),
+                    decoded_calls.len()
+                );
+                output!(
+                    "  To:       {}",
+                    match &call.to_name {
+                        Some(name) => format!("{} ({})", call.to, name),
+                        None => call.to.clone(),
+                    }
+                );
+                output!(
+                    "  Selector: {}",
+                    match &call.function {
+                        Some(function) => format!("{} ({})", call.selector, function),
+                        None => call.selector.clone(),
+                    }
+                );
+                if !call.candidates.is_empty() {
+                    output!("  Candidates:");
+                    for candidate in call.candidates.iter() {
+                        output!("    {}", candidate);
+                    }
+                }
+                if let Some(arguments) = &call.arguments {
+                    output!("  Arguments:");
+                    for (name, value) in arguments.iter() {
+                        output!("    {}: {}", name, serde_json::to_string(value)?);
+                    }
+                }
+                if call.calldata.is_empty() {
+                    output!("  Calldata: []");
+                } else {
+                    output!("  Calldata:");
+                    for element in call.calldata.iter() {
+                        output!("    {}", element);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decodes `[call_count, (to, selector, data_offset, data_len)..., calldata_len, calldata...]`.
+    fn decode_legacy(calldata: &[FieldElement]) -> Option<Vec<RawCall>> {
+        let (call_count, rest) = calldata.split_first()?;
+        let call_count = felt_to_usize(*call_count)?;
+
+        let call_array_len = call_count.checked_mul(4)?;
+        if rest.len() <= call_array_len {
+            return None;
+        }
+        let (call_array, rest) = rest.split_at(call_array_len);
+
+        let (calldata_len, concatenated) = rest.split_first()?;
+        if felt_to_usize(*calldata_len)? != concatenated.len() {
+            return None;
+        }
+
+        call_array
+            .chunks_exact(4)
+            .map(|call| {
+                let offset = felt_to_usize(call[2])?;
+                let len = felt_to_usize(call[3])?;
+
+                Some(RawCall {
+                    to: call[0],
+                    selector: call[1],
+                    calldata: concatenated.get(offset..offset.checked_add(len)?)?.to_vec(),
+                })
+            })
+            .collect()
+    }
+
+    /// Decodes `[call_count, (to, selector, calldata_len, calldata...)...]`.
+    fn decode_new(calldata: &[FieldElement]) -> Option<Vec<RawCall>> {
+        let (call_count, mut rest) = calldata.split_first()?;
+        let call_count = felt_to_usize(*call_count)?;
+
+        let mut calls = vec![];
+        for _ in 0..call_count {
+            let (header, remaining) = (rest.get(..3)?, &rest[3..]);
+            let len = felt_to_usize(header[2])?;
+            if remaining.len() < len {
+                return None;
+            }
+            let (call_calldata, remaining) = remaining.split_at(len);
+
+            calls.push(RawCall {
+                to: header[0],
+                selector: header[1],
+                calldata: call_calldata.to_vec(),
+            });
+            rest = remaining;
+        }
+
+        if rest.is_empty() {
+            Some(calls)
+        } else {
+            None
+        }
+    }
+}
+
+impl LocalAbis {
+    /// Loads every JSON file directly in `dir`. Files that aren't ABIs, like build manifests, are
+    /// skipped with a warning.
+    fn load(dir: &Path) -> Result<Self> {
+        if !dir.is_dir() {
+            anyhow::bail!("ABI directory {} not found", dir.display());
+        }
+
+        let mut paths = std::fs::read_dir(dir)?
+            .map(|entry| Ok(entry?.path()))
+            .collect::<Result<Vec<_>>>()?;
+        paths.retain(|path| path.is_file() && path.extension().map_or(false, |ext| ext == "json"));
+        paths.sort();
+
+        let mut abis = vec![];
+        let mut functions: HashMap<FieldElement, Vec<(usize, String)>> = HashMap::new();
+        for path in paths.iter() {
+            let abi = match ContractAbi::from_file(path) {
+                Ok(abi) => abi,
+                Err(err) => {
+                    warning!("WARNING: skipping {}: {}", path.display(), err);
+                    continue;
+                }
+            };
+
+            for entrypoint in abi.entrypoints().into_iter() {
+                // Only these can be the target of an account call
+                if entrypoint.kind != "external" && entrypoint.kind != "view" {
+                    continue;
+                }
+                functions
+                    .entry(get_selector_from_name(&entrypoint.name)?)
+                    .or_default()
+                    .push((abis.len(), entrypoint.name));
+            }
+
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            abis.push((file_name, abi));
+        }
+
+        if abis.is_empty() {
+            anyhow::bail!("no ABI found in {}", dir.display());
+        }
+        progress!(
+            "Indexed {} functions from {} ABIs in {}",
+            functions
+                .values()
+                .map(|functions| functions.len())
+                .sum::<usize>(),
+            abis.len(),
+            dir.display()
+        );
+
+        Ok(Self { abis, functions })
+    }
+
+    /// Looks up the function with `selector`. When several ABIs define it with different
+    /// signatures, only the ones the calldata decodes against are kept, and those left are all
+    /// returned as candidates unless there's a single one.
+    fn find(&self, selector: FieldElement, calldata: &[FieldElement]) -> Option<LocalMatch> {
+        let functions = self.functions.get(&selector)?;
+
+        // Signatures are deduplicated, as the same interface is often in many ABIs
+        let mut candidates: Vec<Candidate> = vec![];
+        for (ind_abi, name) in functions.iter() {
+            let (file_name, abi) = &self.abis[*ind_abi];
+            let signature = abi
+                .find_function_signature(name)
+                .unwrap_or_else(|| name.clone());
+            if candidates
+                .iter()
+                .all(|candidate| candidate.signature != signature)
+            {
+                candidates.push(Candidate {
+                    signature,
+                    function: name,
+                    file_name,
+                    arguments: decode_arguments(abi, name, calldata),
+                });
+            }
+        }
+        if candidates
+            .iter()
+            .any(|candidate| candidate.arguments.is_some())
+        {
+            candidates.retain(|candidate| candidate.arguments.is_some());
+        }
+
+        let function = candidates[0].function.to_owned();
+        if candidates.len() == 1 {
+            return Some(LocalMatch {
+                function,
+                arguments: candidates.pop().and_then(|candidate| candidate.arguments),
+                candidates: vec![],
+            });
+        }
+
+        Some(LocalMatch {
+            function,
+            arguments: None,
+            candidates: candidates
+                .iter()
+                .map(|candidate| format!("{} ({})", candidate.signature, candidate.file_name))
+                .collect(),
+        })
+    }
+}
+
+/// Decodes calldata into the named arguments of `function`, requiring all of it to be consumed.
+fn decode_arguments(
+    abi: &ContractAbi,
+    function: &str,
+    calldata: &[FieldElement],
+) -> Option<Map<String, Value>> {
+    let parameters = abi
+        .find_function_parameters(function)?
+        .into_iter()
+        .map(|(name, r#type)| TypedMember { name, r#type })
+        .collect::<Vec<_>>();
+
+    let mut arguments = Map::new();
+    abi.decode_members(&parameters, calldata, &mut arguments)?;
+
+    Some(arguments)
+}
+
+fn felt_to_usize(felt: FieldElement) -> Option<usize> {
+    u64::try_from(felt).ok()?.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::macros::{felt, selector};
+
+    use super::*;
+
+    const ETH: FieldElement =
+        felt!("0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7");
+    const SPENDER: FieldElement = felt!("0x1234");
+    const RECIPIENT: FieldElement = felt!("0x5678");
+
+    /// An `approve` followed by a `transfer` of the same amount.
+    fn approve_and_transfer() -> Vec<RawCall> {
+        vec![
+            RawCall {
+                to: ETH,
+                selector: selector!("approve"),
+                calldata: vec![SPENDER, felt!("1000"), FieldElement::ZERO],
+            },
+            RawCall {
+                to: ETH,
+                selector: selector!("transfer"),
+                calldata: vec![RECIPIENT, felt!("1000"), FieldElement::ZERO],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_decode_legacy() {
+        let calldata = [
+            felt!("2"),
+            ETH,
+            selector!("approve"),
+            felt!("0"),
+            felt!("3"),
+            ETH,
+            selector!("transfer"),
+            felt!("3"),
+            felt!("3"),
+            felt!("6"),
+            SPENDER,
+            felt!("1000"),
+            felt!("0"),
+            RECIPIENT,
+            felt!("1000"),
+            felt!("0"),
+        ];
+        assert_eq!(
+            DecodeCalldata::decode_legacy(&calldata).unwrap(),
+            approve_and_transfer()
+        );
+        assert_eq!(DecodeCalldata::decode_new(&calldata), None);
+
+        // Calldata length not matching the concatenated calldata
+        assert_eq!(
+            DecodeCalldata::decode_legacy(&calldata[..calldata.len() - 1]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_decode_new() {
+        let calldata = [
+            felt!("2"),
+            ETH,
+            selector!("approve"),
+            felt!("3"),
+            SPENDER,
+            felt!("1000"),
+            felt!("0"),
+            ETH,
+            selector!("transfer"),
+            felt!("3"),
+            RECIPIENT,
+            felt!("1000"),
+            felt!("0"),
+        ];
+        assert_eq!(
+            DecodeCalldata::decode_new(&calldata).unwrap(),
+            approve_and_transfer()
+        );
+        assert_eq!(DecodeCalldata::decode_legacy(&calldata), None);
+
+        // Trailing or missing felts
+        assert_eq!(
+            DecodeCalldata::decode_new(&[&calldata[..], &[felt!("1")]].concat()),
+            None
+        );
+        assert_eq!(
+            DecodeCalldata::decode_new(&calldata[..calldata.len() - 1]),
+            None
+        );
+        assert_eq!(DecodeCalldata::decode_new(&[felt!("0")]).unwrap(), []);
+    }
+
+    #[test]
+    fn test_decode_ambiguous() {
+        let calldata = [
+            felt!("2"),
+            ETH,
+            selector!("approve"),
+            felt!("3"),
+            felt!("1"),
+            ETH,
+            selector!("transfer"),
+            felt!("0"),
+            felt!("2"),
+            felt!("4"),
+            RECIPIENT,
+            felt!("1000"),
+            felt!("0"),
+            SPENDER,
+        ];
+
+        assert_eq!(
+            DecodeCalldata::decode_legacy(&calldata).unwrap(),
+            [
+                RawCall {
+                    to: ETH,
+                    selector: selector!("approve"),
+                    calldata: vec![SPENDER],
+                },
+                RawCall {
+                    to: ETH,
+                    selector: selector!("transfer"),
+                    calldata: vec![RECIPIENT, felt!("1000")],
+                },
+            ]
+        );
+        assert_eq!(
+            DecodeCalldata::decode_new(&calldata).unwrap(),
+            [
+                RawCall {
+                    to: ETH,
+                    selector: selector!("approve"),
+                    calldata: vec![felt!("1"), ETH, selector!("transfer")],
+                },
+                RawCall {
+                    to: felt!("0"),
+                    selector: felt!("2"),
+                    calldata: vec![RECIPIENT, felt!("1000"), felt!("0"), SPENDER],
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+use clap::Parser;
+
+use crate::{
+    decode::decode_error_message,
+    output::output,
+    utils::{expand_stdin_args, parse_felt_value},
+    verbosity::warning,
+};
+
+#[derive(Debug, Parser)]
+pub struct DecodeRevert {
+    #[clap(
+        required = true,
+        help = "Felts of the revert reason, in decimal or hexadecimal representation. Pass \"-\" \
+        to read them from stdin"
+    )]
+    felts: Vec<String>,
+}
+
+impl DecodeRevert {
+    pub fn run(self) -> Result<()> {
+        let felts = expand_stdin_args(&self.felts, false)?
+            .iter()
+            .map(|felt| parse_felt_value(felt))
+            .collect::<Result<Vec<_>>>()?;
+
+        match decode_error_message(&felts) {
+            Some(message) => output!("{message}"),
+            None => {
+                warning!(
+                    "WARNING: felts are neither short strings nor a ByteArray. Printing them as is."
+                );
+                for felt in felts.iter() {
+                    output!("{:#064x}", felt);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,39 +1,91 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    collections::HashMap,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use anyhow::Result;
-use clap::Parser;
+use bigdecimal::BigDecimal;
+use clap::{Parser, ValueEnum};
 use colored::Colorize;
+use colored_json::{ColorMode, Output};
+use futures_util::{stream, Stream, StreamExt};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
 use starknet::{
-    accounts::SingleOwnerAccount,
-    contract::ContractFactory,
+    accounts::{Account, Call, ConnectedAccount, SingleOwnerAccount},
     core::{
-        types::{BlockId, BlockTag, FieldElement},
-        utils::{get_udc_deployed_address, UdcUniqueSettings, UdcUniqueness},
+        crypto::{compute_hash_on_elements, pedersen_hash},
+        serde::unsigned_field_element::UfeHex,
+        types::{
+            BlockId, BlockTag, ContractClass, FieldElement, MaybePendingTransactionReceipt,
+            PendingTransactionReceipt, StarknetError, TransactionReceipt,
+        },
+        utils::{
+            get_selector_from_name, get_udc_deployed_address, starknet_keccak, UdcUniqueSettings,
+            UdcUniqueness,
+        },
     },
-    providers::Provider,
-    signers::SigningKey,
+    macros::selector,
+    providers::{MaybeUnknownErrorCode, Provider, ProviderError, StarknetErrorWithMessage},
+    signers::{Signer, SigningKey},
 };
 
 use crate::{
+    abi::ContractAbi,
     account::{AccountConfig, DeploymentStatus},
-    address_book::AddressBookResolver,
+    address_book::AliasArgs,
+    chain_id::ChainIdSource,
     decode::FeltDecoder,
-    fee::{FeeArgs, FeeSetting},
+    env_file::EnvFileArgs,
+    explorer::ExplorerArgs,
+    fee::{
+        check_fee_ceiling, receipt_actual_fee, resolve_fee_token, warn_on_low_fee, FeeArgs,
+        FeeFormatter, FeeSetting, DEFAULT_FEE_BUFFER,
+    },
+    interactive,
+    manifest::{ManifestArgs, ManifestResource, ResourceKind},
+    metrics::MetricsArgs,
+    output::{output, persist},
     path::ExpandedPathbufParser,
+    replay_log::{ReplayLogArgs, ReplayMode, ReplayRecord},
     signer::SignerArgs,
-    utils::watch_tx,
-    verbosity::VerbosityArgs,
+    spinner::Spinner,
+    utils::{
+        bigdecimal_to_felt, check_fee_balance, check_signer_public_key, dump_transaction,
+        expand_stdin_args, parse_block_id, parse_felt_value, parse_salt, print_parties,
+        print_shell_exports, send_execution, shell_command, unix_timestamp, validate_export_prefix,
+        watch_tx, FeltFormat, Finality, NonceRetryArgs, PreflightReport, WatchConditions,
+    },
+    verbosity::{progress, silence, warning, VerbosityArgs},
     ProviderArgs,
 };
 
+use super::find_deployment::parse_contract_deployed;
+
 /// The default UDC address: 0x041a78e741e5af2fec34b695679bc6891742439f7afb8484ecd7766661ad02bf.
-const DEFAULT_UDC_ADDRESS: FieldElement = FieldElement::from_mont([
+pub(crate) const DEFAULT_UDC_ADDRESS: FieldElement = FieldElement::from_mont([
     15144800532519055890,
     15685625669053253235,
     9333317513348225193,
     121672436446604875,
 ]);
 
+/// How the address of the deployed contract is found, set with `--udc-compute`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum UdcCompute {
+    /// Computed upfront with the UDC address scheme.
+    Standard,
+    /// Read from the `ContractDeployed` event once the deployment confirms.
+    None,
+}
+
 #[derive(Debug, Parser)]
 pub struct Deploy {
     #[clap(flatten)]
@@ -42,165 +94,3320 @@ pub struct Deploy {
     signer: SignerArgs,
     #[clap(long, help = "Do not derive contract address from deployer address")]
     not_unique: bool,
+    #[clap(
+        long,
+        value_name = "ADDRESS",
+        help = "Deploy through a custom deployer contract instead of the Universal Deployer \
+        Contract"
+    )]
+    deployer: Option<String>,
+    #[clap(
+        long,
+        value_name = "NAME",
+        default_value = "deployContract",
+        requires = "deployer",
+        help = "Deployment function of the custom deployer. It must take the same arguments as \
+        the UDC's deployContract: class hash, salt, unique flag, and constructor arguments"
+    )]
+    deployer_selector: String,
+    #[clap(
+        long,
+        requires = "deployer",
+        conflicts_with_all = [
+            "mine_prefix",
+            "deployed_address",
+            "post_deploy_call",
+            "atomic_init",
+            "batch_state",
+            "output_address_only"
+        ],
+        help = "Do not compute the contract address, for custom deployers that don't follow the \
+        UDC address scheme. The transaction hash is printed instead"
+    )]
+    skip_address: bool,
+    #[clap(
+        long,
+        value_enum,
+        default_value = "standard",
+        requires = "deployer",
+        conflicts_with = "skip_address",
+        help = "How the contract address is found. With none, the address is not computed \
+        upfront, and is read from the deployment event once the transaction confirms instead"
+    )]
+    udc_compute: UdcCompute,
     #[clap(
         long,
         env = "STARKNET_ACCOUNT",
         value_parser = ExpandedPathbufParser,
-        help = "Path to account config JSON file"
+        required_unless_present = "wallet",
+        help = "Path to account config JSON file, \"-\" to read it from stdin, the config JSON \
+        itself, or the name of an account in the address book"
+    )]
+    account: Option<PathBuf>,
+    #[clap(
+        long,
+        default_value = "pending",
+        help = "Block number, hash, or tag (latest/pending) the account fetches its nonce and \
+        estimates fees against"
     )]
-    account: PathBuf,
+    account_block: String,
     #[clap(flatten)]
     fee: FeeArgs,
-    #[clap(long, help = "Use the given salt to compute contract deploy address")]
+    #[clap(flatten)]
+    explorer: ExplorerArgs,
+    #[clap(flatten)]
+    nonce_retry: NonceRetryArgs,
+    #[clap(flatten)]
+    replay_log: ReplayLogArgs,
+    #[clap(flatten)]
+    metrics: MetricsArgs,
+    #[clap(flatten)]
+    env_file: EnvFileArgs,
+    #[clap(flatten)]
+    manifest: ManifestArgs,
+    #[clap(
+        long,
+        conflicts_with_all = ["random_salt", "mine_prefix", "salt_from"],
+        help = "Use the given salt to compute contract deploy address, in hex (0x-prefixed) or \
+        decimal"
+    )]
     salt: Option<String>,
+    #[clap(
+        long,
+        conflicts_with_all = ["random_salt", "mine_prefix"],
+        help = "Derive the salt from a seed string by hashing it the same way as function \
+        selectors"
+    )]
+    salt_from: Option<String>,
+    #[clap(
+        long,
+        conflicts_with = "mine_prefix",
+        help = "Use a random salt to compute contract deploy address (default)"
+    )]
+    random_salt: bool,
+    #[clap(
+        long,
+        help = "Search for a salt that yields a contract address starting with the given hex prefix"
+    )]
+    mine_prefix: Option<String>,
+    #[clap(
+        long,
+        value_name = "N",
+        default_value = "100000000",
+        requires = "mine_prefix",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Give up mining a salt for --mine-prefix after this many attempts"
+    )]
+    mine_max_attempts: u64,
+    #[clap(
+        long,
+        value_name = "ADDRESS",
+        conflicts_with = "random_salt",
+        help = "Abort if the contract would be deployed at an address other than this one, e.g. \
+        because the constructor arguments changed"
+    )]
+    deployed_address: Option<String>,
+    #[clap(
+        long,
+        default_value = "1",
+        help = "Number of instances to deploy, with the salt incremented by one for each"
+    )]
+    count: u64,
+    #[clap(
+        long,
+        help = "Keep deploying the remaining instances when one of them fails with --count"
+    )]
+    continue_on_error: bool,
+    #[clap(
+        long,
+        value_name = "N",
+        default_value = "1",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with_all = [
+            "post_deploy_call",
+            "reuse_fee",
+            "batch_fee_cap",
+            "retry_on_nonce_error",
+            "dump_transaction",
+            "show_parties",
+            "check_only"
+        ],
+        help = "Send up to N deployments of a --count batch at the same time, with consecutive \
+        nonces assigned upfront. The fee is estimated once for the whole batch"
+    )]
+    parallel: u64,
+    #[clap(
+        long,
+        help = "Don't print the table summarizing a --count batch once it ends"
+    )]
+    no_summary: bool,
+    #[clap(
+        long,
+        conflicts_with = "no_summary",
+        help = "Print the summary of a --count batch as JSON to stdout instead of a table, in place \
+        of the line per deployment"
+    )]
+    json_summary: bool,
+    #[clap(
+        long,
+        value_name = "NONCE",
+        help = "Abort before sending anything unless the account nonce is exactly this value, in \
+        hex (0x-prefixed) or decimal, e.g. to make sure nobody else used a shared account"
+    )]
+    expect_nonce: Option<String>,
+    #[clap(
+        long,
+        value_name = "PATH",
+        value_parser = ExpandedPathbufParser,
+        help = "Record completed deployments to a state file, and skip the ones already recorded \
+        there when re-running an interrupted --count deployment"
+    )]
+    batch_state: Option<PathBuf>,
+    #[clap(
+        long,
+        value_name = "AMOUNT",
+        conflicts_with = "estimate_only",
+        help = "Stop a --count deployment before sending the instance that would bring the total \
+        of max fees in Ether (18 decimals) above this amount"
+    )]
+    batch_fee_cap: Option<BigDecimal>,
+    #[clap(
+        long,
+        value_name = "PATH",
+        value_parser = ExpandedPathbufParser,
+        conflicts_with_all = ["max_fee", "max_fee_raw", "max_fee_from", "estimate_only"],
+        help = "Cache the estimated max fee in a file and reuse it for later deployments of the \
+        same class with the same calldata size, instead of estimating again"
+    )]
+    reuse_fee: Option<PathBuf>,
+    #[clap(
+        long,
+        default_value = "10",
+        requires = "reuse_fee",
+        help = "Number of times a cached fee is reused before it's estimated again"
+    )]
+    reuse_fee_max_uses: u64,
     #[clap(long, help = "Wait for the transaction to confirm")]
     watch: bool,
+    #[clap(
+        long,
+        default_value = "0",
+        requires = "watch",
+        help = "Number of blocks to wait for on top of the one including the transaction when \
+                watching"
+    )]
+    min_confirmations: u64,
+    #[clap(
+        long,
+        value_enum,
+        requires = "watch",
+        help = "Transaction status to wait for when watching, on top of any confirmations"
+    )]
+    finality: Option<Finality>,
+    #[clap(
+        long,
+        value_name = "SECONDS",
+        requires = "watch",
+        help = "Give up watching after this many seconds, listing the conditions still pending"
+    )]
+    watch_timeout: Option<u64>,
+    #[clap(
+        long,
+        help = "Print only the transaction hash to stdout right after broadcasting. With --watch, \
+        the transaction is still watched afterwards"
+    )]
+    broadcast_only: bool,
+    #[clap(
+        long,
+        value_enum,
+        default_value_t,
+        requires = "broadcast_only",
+        help = "Output format of the transaction hash printed with --broadcast-only"
+    )]
+    felt_format: FeltFormat,
+    #[clap(
+        long,
+        conflicts_with_all = ["broadcast_only", "estimate_only"],
+        help = "Print only the plain deployed contract address to stdout, and nothing else to \
+        stderr except fatal errors"
+    )]
+    output_address_only: bool,
+    #[clap(
+        long,
+        conflicts_with_all = [
+            "watch",
+            "broadcast_only",
+            "output_address_only",
+            "export",
+            "estimate_only"
+        ],
+        help = "Run all pre-flight checks and print a pass/fail report for each without sending \
+        anything. Exits with an error if any check fails"
+    )]
+    check_only: bool,
+    #[clap(
+        long,
+        conflicts_with_all = [
+            "watch",
+            "broadcast_only",
+            "output_address_only",
+            "export",
+            "estimate_only",
+            "check_only"
+        ],
+        help = "Print everything about the deployment as a single JSON object without sending \
+        anything: address, class hash, salt, constructor calldata, fee estimate, nonce, and chain \
+        ID"
+    )]
+    plan: bool,
+    #[clap(
+        long,
+        conflicts_with_all = [
+            "watch",
+            "broadcast_only",
+            "output_address_only",
+            "export",
+            "estimate_only",
+            "check_only",
+            "plan",
+            "skip_address"
+        ],
+        help = "Print the inputs the deployment address is computed from, and what changing each \
+        of them does, without sending anything"
+    )]
+    explain_address: bool,
+    #[clap(
+        long,
+        conflicts_with = "output_address_only",
+        help = "List every address each deployment involves before sending it, with address book \
+        names where known"
+    )]
+    show_parties: bool,
+    #[clap(
+        long,
+        conflicts_with_all = [
+            "output_address_only",
+            "estimate_only",
+            "check_only",
+            "plan",
+            "explain_address"
+        ],
+        help = "Once deployed, print to stderr an explicit command performing the same \
+        deployment, with the endpoint, salt, constructor calldata, and max fee resolved. Secrets \
+        are masked"
+    )]
+    print_command: bool,
+    #[clap(
+        long,
+        value_name = "PREFIX",
+        conflicts_with_all = ["output_address_only", "skip_address", "estimate_only"],
+        help = "Print \"export PREFIX_ADDRESS=...\" and \"export PREFIX_TX=...\" lines to stdout \
+        instead of the address, ready to be evaluated by a shell"
+    )]
+    export: Option<String>,
+    #[clap(
+        long,
+        value_name = "PATH",
+        value_parser = ExpandedPathbufParser,
+        help = "Write the signed transaction as JSON to a file right before sending it. Use \"-\" \
+        to write to stderr instead"
+    )]
+    dump_transaction: Option<PathBuf>,
+    #[clap(
+        long,
+        help = "Skip checking that the account contract is actually deployed on the network"
+    )]
+    skip_account_check: bool,
+    #[clap(
+        long,
+        help = "Skip checking that the signer's public key matches the one set on the account \
+        contract"
+    )]
+    skip_signer_check: bool,
+    #[clap(
+        long,
+        help = "Skip checking that the provider is on the chain recorded in the account config"
+    )]
+    skip_chain_check: bool,
+    #[clap(
+        long,
+        help = "Skip checking that the class has been declared on the network"
+    )]
+    allow_undeclared: bool,
+    #[clap(
+        long,
+        conflicts_with = "allow_undeclared",
+        help = "Check that the constructor arguments decode against the constructor inputs in the \
+        class ABI before mining a salt or sending anything"
+    )]
+    check_ctor_args: bool,
+    #[clap(
+        long,
+        conflicts_with_all = [
+            "ctor_args",
+            "args_stdin",
+            "ctor_args_file",
+            "ctor_template",
+            "allow_undeclared"
+        ],
+        help = "Prompt for each constructor argument by name and type, as found in the class ABI"
+    )]
+    interactive: bool,
+    #[clap(
+        long,
+        help = "Substitute {name} placeholders in constructor arguments with values from --arg"
+    )]
+    ctor_template: bool,
+    #[clap(
+        long = "arg",
+        requires = "ctor_template",
+        help = "Named value for constructor argument templates, in the form of name=value"
+    )]
+    template_args: Vec<String>,
+    #[clap(
+        long,
+        num_args = 1..,
+        value_names = ["SELECTOR", "CALLDATA"],
+        conflicts_with = "broadcast_only",
+        help = "Invoke a function on the deployed contract once the deployment confirms, \
+        followed by raw calldata. Must come after all positional arguments"
+    )]
+    post_deploy_call: Vec<String>,
+    #[clap(
+        long,
+        num_args = 1..,
+        value_names = ["SELECTOR", "CALLDATA"],
+        conflicts_with = "post_deploy_call",
+        help = "Invoke a function on the deployed contract in the same transaction as the \
+        deployment, followed by raw calldata. Must come after all positional arguments"
+    )]
+    atomic_init: Vec<String>,
     #[clap(help = "Class hash")]
     class_hash: String,
     #[clap(help = "Raw constructor arguments")]
     ctor_args: Vec<String>,
+    #[clap(
+        long,
+        help = "Read additional arguments from stdin, one per line. Alternatively, pass \"-\" \
+        as an argument to insert them at that position"
+    )]
+    args_stdin: bool,
+    #[clap(
+        long,
+        value_name = "PATH",
+        value_parser = ExpandedPathbufParser,
+        help = "Read additional constructor arguments from a JSON array in a file, appended after \
+        the other arguments. Can be repeated to concatenate files in order"
+    )]
+    ctor_args_file: Vec<PathBuf>,
+    #[clap(flatten)]
+    alias: AliasArgs,
     #[clap(flatten)]
     verbosity: VerbosityArgs,
 }
 
-fn left_pad_with_zeros(input_string: &str, n: usize) -> String {
-    if input_string.len() >= n {
-        input_string.to_string()
-    } else {
-        let zeros_to_pad = n - input_string.len();
-        let padded_string = format!("{}{}", "0".repeat(zeros_to_pad), input_string);
-        padded_string
-    }
+/// Progress of a `--count` deployment, persisted after each deployment with `--batch-state`.
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchState {
+    #[serde_as(as = "UfeHex")]
+    class_hash: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    base_salt: FieldElement,
+    deployments: Vec<BatchDeployment>,
+}
+
+/// Fee estimate of an earlier deployment, persisted with `--reuse-fee`. The buffer and fee checks
+/// are applied again on every use.
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FeeCache {
+    #[serde_as(as = "UfeHex")]
+    chain_id: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    class_hash: FieldElement,
+    calldata_len: usize,
+    estimated_fee: u64,
+    /// Unix timestamp of the estimate.
+    estimated_at: u64,
+    uses: u64,
+}
+
+/// Max fees of the deployments sent so far in a `--count` batch, which bound what the batch can be
+/// charged, checked against `--batch-fee-cap`.
+struct BatchFees {
+    cap: FieldElement,
+    used: FieldElement,
+}
+
+/// Preview of a deployment printed with `--plan`.
+#[serde_as]
+#[derive(Serialize)]
+struct DeploymentPlan {
+    #[serde_as(as = "UfeHex")]
+    chain_id: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    account: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    nonce: FieldElement,
+    /// Not known upfront with `--skip-address` or `--udc-compute none`.
+    #[serde_as(as = "Option<UfeHex>")]
+    address: Option<FieldElement>,
+    #[serde_as(as = "UfeHex")]
+    class_hash: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    salt: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    deployer: FieldElement,
+    unique: bool,
+    #[serde_as(as = "Vec<UfeHex>")]
+    constructor_calldata: Vec<FieldElement>,
+    /// Fees can only be paid in ETH with the transaction versions supported.
+    fee_token: &'static str,
+    /// Not estimated when the max fee is set manually.
+    #[serde_as(as = "Option<UfeHex>")]
+    estimated_fee: Option<FieldElement>,
+    #[serde_as(as = "UfeHex")]
+    max_fee: FieldElement,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchDeployment {
+    index: u64,
+    #[serde_as(as = "UfeHex")]
+    salt: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    address: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    transaction_hash: FieldElement,
+}
+
+/// Outcome of each deployment of a `--count` batch, printed once the batch ends.
+struct BatchSummary {
+    /// Single deployments and `--no-summary` don't get a summary.
+    enabled: bool,
+    json: bool,
+    count: u64,
+    rows: Vec<BatchSummaryRow>,
+}
+
+#[serde_as]
+#[derive(Serialize)]
+struct BatchSummaryRow {
+    index: u64,
+    /// Not known with `--skip-address`, or for deployments that never went through.
+    #[serde_as(as = "Option<UfeHex>")]
+    address: Option<FieldElement>,
+    #[serde_as(as = "Option<UfeHex>")]
+    transaction_hash: Option<FieldElement>,
+    status: BatchStatus,
+    /// Only known for deployments that were watched.
+    #[serde_as(as = "Option<UfeHex>")]
+    actual_fee: Option<FieldElement>,
+    #[serde_as(as = "Option<UfeHex>")]
+    max_fee: Option<FieldElement>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum BatchStatus {
+    /// Watched until confirmed.
+    Deployed,
+    /// Accepted by the node but not watched.
+    Sent,
+    /// Already completed according to `--batch-state`.
+    Skipped,
+    Failed,
+    NotSent,
 }
 
 impl Deploy {
+    pub const RPC_METHODS: &'static [&'static str] = &[
+        "starknet_chainId",
+        "starknet_getNonce",
+        "starknet_call",
+        "starknet_getClass",
+        "starknet_getClassHashAt",
+        "starknet_estimateFee",
+        "starknet_addInvokeTransaction",
+        "starknet_getTransactionByHash",
+        "starknet_getTransactionReceipt",
+        "starknet_blockNumber",
+    ];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
+        let watch_conditions = self.watch_conditions();
 
-        let fee_setting = self.fee.into_setting()?;
+        // Without computing the address, nothing that needs it before the deployment confirms
+        // can be used
+        let compute_address = self.udc_compute == UdcCompute::Standard;
+        if !compute_address {
+            for (name, is_set) in [
+                ("--mine-prefix", self.mine_prefix.is_some()),
+                ("--deployed-address", self.deployed_address.is_some()),
+                ("--post-deploy-call", !self.post_deploy_call.is_empty()),
+                ("--atomic-init", !self.atomic_init.is_empty()),
+                ("--batch-state", self.batch_state.is_some()),
+                ("--output-address-only", self.output_address_only),
+                ("--export", self.export.is_some()),
+            ] {
+                if is_set {
+                    anyhow::bail!("{} cannot be used with --udc-compute none", name);
+                }
+            }
+        }
+        let skip_address = self.skip_address || !compute_address;
 
-        let provider = Arc::new(self.provider.into_provider());
-        let felt_decoder = FeltDecoder::new(AddressBookResolver::new(provider.clone()));
+        // Stricter than `--quiet`: warnings are suppressed too, and colors are never emitted
+        if self.output_address_only {
+            if self.count > 1 {
+                anyhow::bail!("--output-address-only cannot be used with --count");
+            }
+            silence();
+            colored::control::set_override(false);
+        }
 
-        if !self.account.exists() {
-            anyhow::bail!("account config file not found");
+        // Checks are ordered from cheapest to most expensive so that bad input fails fast: options
+        // first, then the account and class on the network, then the constructor arguments
+        // against the class. Only then is a salt mined and the address computed, right before
+        // the fee is estimated and the transaction sent.
+        if self.count == 0 {
+            anyhow::bail!("--count must be at least 1");
+        }
+        if let Some(prefix) = &self.export {
+            if self.count > 1 {
+                anyhow::bail!("--export cannot be used with --count");
+            }
+            validate_export_prefix(prefix)?;
+        }
+        if self.parallel > 1 && self.count == 1 {
+            anyhow::bail!("--parallel requires --count");
+        }
+        if self.plan && self.count > 1 {
+            anyhow::bail!("--plan cannot be used with --count");
+        }
+        if self.explain_address && self.count > 1 {
+            anyhow::bail!("--explain-address cannot be used with --count");
+        }
+        if self.print_command && self.count > 1 {
+            anyhow::bail!("--print-command cannot be used with --count");
+        }
+        if self.explain_address && self.udc_compute != UdcCompute::Standard {
+            anyhow::bail!(
+                "--explain-address cannot be used with --udc-compute none, as the address is only \
+                known once deployed"
+            );
+        }
+        if self.mine_prefix.is_some() && self.count > 1 {
+            anyhow::bail!("--mine-prefix cannot be used with --count");
+        }
+        if !self.post_deploy_call.is_empty() && self.count > 1 {
+            anyhow::bail!("--post-deploy-call cannot be used with --count");
+        }
+        if self.batch_fee_cap.is_some() && self.count == 1 {
+            anyhow::bail!("--batch-fee-cap requires --count");
+        }
+        if self.deployed_address.is_some() {
+            if self.count > 1 {
+                anyhow::bail!("--deployed-address cannot be used with --count");
+            }
+            if self.salt.is_none() && self.salt_from.is_none() && self.mine_prefix.is_none() {
+                anyhow::bail!("--deployed-address requires a deterministic salt");
+            }
         }
 
-        let class_hash = FieldElement::from_hex_be(&self.class_hash)?;
-        let mut ctor_args = vec![];
-        for element in self.ctor_args.iter() {
-            ctor_args.append(&mut felt_decoder.decode(element).await?);
+        let expected_nonce = self
+            .expect_nonce
+            .as_deref()
+            .map(Self::parse_expected_nonce)
+            .transpose()?;
+
+        let fee_formatter = self.fee.formatter();
+        let skip_balance_check = self.fee.skip_balance_check();
+        let low_fee_threshold = self.fee.low_fee_threshold();
+        let max_fee_ceiling = self.fee.max_fee_ceiling()?;
+        let mut fee_buffer = self.fee.fee_buffer()?;
+        let batch_fee_cap = self
+            .batch_fee_cap
+            .as_ref()
+            .map(|cap| bigdecimal_to_felt(cap, 18))
+            .transpose()?;
+        let explorer = self.explorer.into_explorer()?;
+        let replay_log = self.replay_log.into_replay_log("deploy")?;
+        let mut metrics = self.metrics.into_metrics("deploy");
+        let env_file = self.env_file.into_env_file()?;
+        if env_file.is_some() && self.count > 1 {
+            anyhow::bail!("--env-out cannot be used with --count");
+        }
+        let manifest = self.manifest.into_manifest()?;
+        if manifest.is_some() && self.count > 1 {
+            anyhow::bail!("--manifest-out cannot be used with --count");
+        }
+
+        // Options of the printed command are collected as they're resolved, before the provider
+        // and signer options are used up. Positional arguments come last.
+        let mut explicit_options = vec![];
+        let mut explicit_positionals = vec![];
+        if self.print_command {
+            explicit_options.append(&mut self.provider.explicit_args()?);
+            if let Some(account) = &self.account {
+                explicit_options.push("--account".into());
+                explicit_options.push(account.to_string_lossy().into_owned());
+            }
+            explicit_options.append(&mut self.signer.explicit_args());
         }
 
-        let mut salt = 0;
+        let provider = Arc::new(self.provider.into_provider()?);
+        let fee_setting = self.fee.into_setting(&provider).await?;
+
         // TODO: refactor account & signer loading
 
-        let account_config: AccountConfig =
-            serde_json::from_reader(&mut std::fs::File::open(&self.account)?)?;
+        if self.interactive {
+            interactive::ensure_terminal()?;
+        }
+        if self.account.as_deref() == Some(Path::new("-"))
+            && (self.args_stdin || self.interactive || self.ctor_args.iter().any(|arg| arg == "-"))
+        {
+            anyhow::bail!(
+                "the account config and constructor arguments can't both be read from stdin"
+            );
+        }
+        let account_config =
+            AccountConfig::load_from_args(self.account.as_deref(), &self.signer, &provider).await?;
 
-        let account_address = match account_config.deployment {
+        let account_address = match &account_config.deployment {
             DeploymentStatus::Undeployed(_) => anyhow::bail!("account not deployed"),
             DeploymentStatus::Deployed(inner) => inner.address,
         };
 
-        
-        let mut deployed_address: FieldElement;
-        loop {
-                deployed_address = get_udc_deployed_address(
-                    FieldElement::from_dec_str(salt.to_string().as_str()).unwrap(),
-                    class_hash,
-                    &if self.not_unique {
-                        UdcUniqueness::NotUnique
-                    } else {
-                        UdcUniqueness::Unique(UdcUniqueSettings {
-                            deployer_address: account_address,
-                            udc_contract_address: DEFAULT_UDC_ADDRESS,
-                        })
-                    },
-                    &ctor_args,
-                );
-                
-                let mut formated = format!("{:x}", deployed_address);
-                formated = left_pad_with_zeros(&formated, 64);
-                if formated.as_str().starts_with("04515") {
-                    println!("Right salt is: {:?}", salt);
-                    println!("Associated address: {:?}", formated);
-                    break;
-                }
-                salt += 1;
-        }
-        let salt = FieldElement::from_dec_str(salt.to_string().as_str()).unwrap();
-        let chain_id = provider.chain_id().await?;
+        let felt_decoder = FeltDecoder::new(self.alias.into_resolver(provider.clone())?)
+            .with_account_address(account_address);
 
-        let signer = Arc::new(self.signer.into_signer()?);
-        let mut account =
-            SingleOwnerAccount::new(provider.clone(), signer.clone(), account_address, chain_id);
-        account.set_block_id(BlockId::Tag(BlockTag::Pending));
+        let class_hash = Self::parse_class_hash(&self.class_hash)?;
+        let raw_ctor_args = expand_stdin_args(&self.ctor_args, self.args_stdin)?;
+        let raw_ctor_args = if self.ctor_template {
+            Self::render_ctor_template(&raw_ctor_args, &self.template_args)?
+        } else {
+            raw_ctor_args
+        };
 
-        // TODO: allow custom UDC
-        let factory = ContractFactory::new_with_udc(class_hash, account, DEFAULT_UDC_ADDRESS);
+        let mut ctor_args = vec![];
+        for element in raw_ctor_args.iter() {
+            ctor_args.append(&mut felt_decoder.decode(element).await?);
+        }
+        for path in self.ctor_args_file.iter() {
+            let file_args = Self::read_ctor_args_file(path)?;
+            let file_args = if self.ctor_template {
+                Self::render_ctor_template(&file_args, &self.template_args)
+                    .map_err(|err| anyhow::anyhow!("{}: {}", path.display(), err))?
+            } else {
+                file_args
+            };
 
-        // TODO: pre-compute and show target deployment address
+            for (ind_element, element) in file_args.iter().enumerate() {
+                ctor_args.append(&mut felt_decoder.decode(element).await.map_err(|err| {
+                    anyhow::anyhow!(
+                        "failed to decode element {} of {} (\"{}\"): {}",
+                        ind_element,
+                        path.display(),
+                        element,
+                        err
+                    )
+                })?);
+            }
+        }
 
-        let contract_deployment = factory.deploy(&ctor_args, salt, !self.not_unique);
+        // With `--check-only`, failing checks are reported at the end instead of aborting
+        let mut report = PreflightReport::new(self.check_only);
 
-        let max_fee = match fee_setting {
-            FeeSetting::Manual(fee) => fee,
-            FeeSetting::EstimateOnly | FeeSetting::None => {
-                let estimated_fee = contract_deployment.estimate_fee().await?.overall_fee;
+        // The account config file could be stale or simply belong to another network. Catching it
+        // here gives a much clearer error than a failed transaction later on.
+        if self.skip_account_check {
+            report.skip("Account deployed", "--skip-account-check");
+        } else {
+            report.check(
+                "Account deployed",
+                Self::check_account_deployed(&provider, account_address).await,
+            )?;
+        }
+        if self.allow_undeclared {
+            report.skip("Class declared", "--allow-undeclared");
+            report.skip("Constructor arguments", "--allow-undeclared");
+        } else {
+            match report.check(
+                "Class declared",
+                Self::check_class_declared(&provider, class_hash).await,
+            )? {
+                Some(class) => {
+                    if self.interactive {
+                        ctor_args =
+                            Self::prompt_ctor_args(&class, class_hash, &felt_decoder).await?;
+                    }
+                    if self.check_ctor_args || report.is_enabled() {
+                        report.check(
+                            "Constructor arguments",
+                            Self::check_ctor_args(&class, class_hash, &ctor_args),
+                        )?;
+                    }
+                }
+                None => report.skip("Constructor arguments", "class not declared"),
+            }
+        }
 
-                if fee_setting.is_estimate_only() {
-                    eprintln!(
-                        "{} ETH",
-                        format!(
-                            "{}",
-                            <u64 as Into<FieldElement>>::into(estimated_fee).to_big_decimal(18)
-                        )
-                        .bright_yellow(),
-                    );
-                    return Ok(());
+        // Resolved upfront so that invalid input fails before anything is sent
+        let post_deploy_call = match self.post_deploy_call.split_first() {
+            Some((selector, raw_calldata)) => {
+                let selector = get_selector_from_name(selector)?;
+
+                let mut calldata = vec![];
+                for element in raw_calldata.iter() {
+                    calldata.append(&mut felt_decoder.decode(element).await?);
                 }
 
-                // TODO: make buffer configurable
-                let estimated_fee_with_buffer = estimated_fee * 3 / 2;
+                Some((selector, calldata))
+            }
+            None => None,
+        };
+
+        let atomic_init = match self.atomic_init.split_first() {
+            Some((selector, raw_calldata)) => {
+                let selector = get_selector_from_name(selector)?;
+
+                let mut calldata = vec![];
+                for element in raw_calldata.iter() {
+                    calldata.append(&mut felt_decoder.decode(element).await?);
+                }
 
-                estimated_fee_with_buffer.into()
+                Some((selector, calldata))
             }
+            None => None,
         };
 
-        eprintln!(
-            "Deploying class {} with salt {}...",
-            format!("{:#064x}", class_hash).bright_yellow(),
-            format!("{:#064x}", salt).bright_yellow()
-        );
-        eprintln!(
-            "The contract will be deployed at address {}",
-            format!("{:#064x}", deployed_address).bright_yellow()
-        );
+        let deployer_address = match &self.deployer {
+            Some(deployer) => {
+                felt_decoder
+                    .decode_single_with_addr_fallback(deployer)
+                    .await?
+            }
+            None => DEFAULT_UDC_ADDRESS,
+        };
+        let deployer_selector = get_selector_from_name(&self.deployer_selector)?;
 
-        let deployment_tx = contract_deployment
-            .max_fee(max_fee)
-            .send()
-            .await?
-            .transaction_hash;
-        eprintln!(
-            "Contract deployment transaction: {}",
-            format!("{:#064x}", deployment_tx).bright_yellow()
-        );
+        if self.print_command {
+            if self.deployer.is_some() {
+                explicit_options.push("--deployer".into());
+                explicit_options.push(format!("{:#064x}", deployer_address));
+                explicit_options.push("--deployer-selector".into());
+                explicit_options.push(self.deployer_selector.clone());
+            }
+            if self.skip_address {
+                explicit_options.push("--skip-address".into());
+            }
+            if !compute_address {
+                explicit_options.push("--udc-compute".into());
+                explicit_options.push("none".into());
+            }
+            if self.not_unique {
+                explicit_options.push("--not-unique".into());
+            }
 
-        if self.watch {
-            eprintln!(
-                "Waiting for transaction {} to confirm...",
-                format!("{:#064x}", deployment_tx).bright_yellow(),
-            );
-            watch_tx(&provider, deployment_tx).await?;
+            // Prompted, templated, and file arguments all end up as plain calldata
+            explicit_positionals.push(format!("{:#064x}", class_hash));
+            explicit_positionals.extend(ctor_args.iter().map(|arg| format!("{:#064x}", arg)));
+            for (option, raw_call, call) in [
+                ("--atomic-init", &self.atomic_init, &atomic_init),
+                (
+                    "--post-deploy-call",
+                    &self.post_deploy_call,
+                    &post_deploy_call,
+                ),
+            ] {
+                if let (Some(function_name), Some((_, calldata))) = (raw_call.first(), call) {
+                    explicit_positionals.push(option.into());
+                    explicit_positionals.push(function_name.clone());
+                    explicit_positionals
+                        .extend(calldata.iter().map(|element| format!("{:#064x}", element)));
+                }
+            }
         }
 
-        eprintln!("Contract deployed:");
+        // Custom deployers are assumed to compute addresses the same way as the UDC does
+        let uniqueness = if self.not_unique {
+            UdcUniqueness::NotUnique
+        } else {
+            UdcUniqueness::Unique(UdcUniqueSettings {
+                deployer_address: account_address,
+                udc_contract_address: deployer_address,
+            })
+        };
+
+        let base_salt = match (&self.salt, &self.salt_from, &self.mine_prefix) {
+            (Some(salt), _, _) => parse_salt(salt)?,
+            (None, Some(seed), _) => {
+                let salt = starknet_keccak(seed.as_bytes());
+                progress!(
+                    "Salt derived from {}: {}",
+                    format!("{:?}", seed).bright_yellow(),
+                    format!("{:#064x}", salt).bright_yellow()
+                );
+                salt
+            }
+            (None, None, Some(prefix)) => Self::mine_salt(
+                prefix,
+                self.mine_max_attempts,
+                class_hash,
+                &uniqueness,
+                &ctor_args,
+            )?,
+            (None, None, None) => SigningKey::from_random().secret_scalar(),
+        };
 
-        // Only the contract goes to stdout so this can be easily scripted
-        println!("{}", format!("{:#064x}", deployed_address).bright_yellow());
+        let mut batch_state = match &self.batch_state {
+            Some(path) if path.exists() => {
+                let state: BatchState = serde_json::from_reader(std::fs::File::open(path)?)
+                    .map_err(|err| anyhow::anyhow!("invalid batch state file: {}", err))?;
 
-        Ok(())
+                if state.class_hash != class_hash {
+                    anyhow::bail!(
+                        "batch state file is for class {:#064x}, not {:#064x}",
+                        state.class_hash,
+                        class_hash
+                    );
+                }
+                if (self.salt.is_some() || self.salt_from.is_some()) && state.base_salt != base_salt
+                {
+                    anyhow::bail!(
+                        "batch state file starts from salt {:#064x}, not {:#064x}",
+                        state.base_salt,
+                        base_salt
+                    );
+                }
+
+                progress!(
+                    "Resuming batch with {} of {} deployments already completed",
+                    state
+                        .deployments
+                        .iter()
+                        .filter(|deployment| deployment.index < self.count)
+                        .count(),
+                    self.count
+                );
+                Some(state)
+            }
+            Some(_) => Some(BatchState {
+                class_hash,
+                base_salt,
+                deployments: vec![],
+            }),
+            None => None,
+        };
+        // A random salt would give different addresses, so the recorded one is used instead
+        let base_salt = match &batch_state {
+            Some(state) => state.base_salt,
+            None => base_salt,
+        };
+        Self::check_salt_range(base_salt, self.count)?;
+
+        // Guards against argument drift in deterministic deployments before anything is sent
+        if let Some(expected_address) = &self.deployed_address {
+            let expected_address = felt_decoder
+                .decode_single_with_addr_fallback(expected_address)
+                .await?;
+            Self::check_deployed_address(
+                get_udc_deployed_address(base_salt, class_hash, &uniqueness, &ctor_args),
+                expected_address,
+            )?;
+        }
+
+        if self.explain_address {
+            let salt_source = match (&self.salt, &self.salt_from, &self.mine_prefix) {
+                _ if batch_state.is_some() && self.salt.is_none() && self.salt_from.is_none() => {
+                    "recorded in --batch-state"
+                }
+                (Some(_), _, _) => "--salt",
+                (None, Some(_), _) => "derived from --salt-from",
+                (None, None, Some(_)) => "mined for --mine-prefix",
+                (None, None, None) => "random, so the address changes on every run",
+            };
+            Self::explain_address(base_salt, salt_source, class_hash, &uniqueness, &ctor_args);
+            return Ok(());
+        }
+
+        let chain_id = provider.chain_id().await?;
+        if self.skip_chain_check {
+            report.skip("Account chain", "--skip-chain-check");
+        } else {
+            report.check("Account chain", account_config.check_chain_id(chain_id))?;
+        }
+        fee_buffer
+            .read_balance_budget(&provider, chain_id, account_address)
+            .await?;
+
+        let mut fee_cache = match &self.reuse_fee {
+            Some(path) if path.exists() => Some(
+                serde_json::from_reader::<_, FeeCache>(std::fs::File::open(path)?)
+                    .map_err(|err| anyhow::anyhow!("invalid fee cache file: {}", err))?,
+            ),
+            _ => None,
+        };
+
+        let signer = Arc::new(self.signer.into_signer()?);
+        if self.skip_signer_check {
+            report.skip("Signer matches account", "--skip-signer-check");
+        } else {
+            report.check(
+                "Signer matches account",
+                check_signer_public_key(
+                    &provider,
+                    account_address,
+                    signer.get_public_key().await?.scalar(),
+                )
+                .await,
+            )?;
+        }
+        let mut account =
+            SingleOwnerAccount::new(provider.clone(), signer.clone(), account_address, chain_id);
+        account.set_block_id(parse_block_id(&self.account_block)?);
+
+        // Nonces are tracked locally as the pending nonce reported by the node might lag behind
+        // when sending transactions back to back.
+        let mut nonce = account.get_nonce().await?;
+        if let Some(expected_nonce) = expected_nonce {
+            report.check(
+                "Account nonce as expected",
+                Self::check_expected_nonce(nonce, expected_nonce),
+            )?;
+        }
+        if report.is_enabled() {
+            report.check(
+                "No pending transactions",
+                Self::check_nonce_gap(&provider, account_address).await,
+            )?;
+        }
+
+        // The address is known upfront, so the contract can be initialized atomically by calling it
+        // right after the UDC in the same multicall
+        let build_calls = |salt: FieldElement, deployed_address: FieldElement| {
+            let mut calls = vec![Call {
+                to: deployer_address,
+                selector: deployer_selector,
+                calldata: Self::deployment_calldata(class_hash, salt, !self.not_unique, &ctor_args),
+            }];
+            if let Some((selector, calldata)) = &atomic_init {
+                calls.push(Call {
+                    to: deployed_address,
+                    selector: *selector,
+                    calldata: calldata.clone(),
+                });
+            }
+            calls
+        };
+
+        let estimate_fee = {
+            let account = &account;
+            move |calls: Vec<Call>, nonce: FieldElement| async move {
+                let estimated_fee = Spinner::new()
+                    .run(
+                        "Estimating fee",
+                        account.execute(calls).nonce(nonce).estimate_fee(),
+                    )
+                    .await?
+                    .overall_fee;
+                warn_on_low_fee(estimated_fee, low_fee_threshold);
+                Ok::<_, anyhow::Error>(estimated_fee)
+            }
+        };
+
+        // Checks a fee estimate against `--max-fee-ceiling` before applying the buffer, which
+        // also keeps the max fee within the `--max-fee-from-balance` budget.
+        let max_fee_from_estimate = {
+            let (fee_buffer, fee_formatter) = (&fee_buffer, &fee_formatter);
+            move |estimated_fee: u64| {
+                check_fee_ceiling(estimated_fee, max_fee_ceiling, fee_formatter)?;
+                Ok::<_, anyhow::Error>(fee_buffer.apply(
+                    estimated_fee,
+                    chain_id,
+                    DEFAULT_FEE_BUFFER,
+                ))
+            }
+        };
+
+        // Estimates the fee unless it's set manually. With `--estimate-only`, the estimate is
+        // printed instead, as applying to each of the `deployments`, and `None` is returned.
+        let resolve_max_fee = {
+            let (fee_setting, fee_formatter, replay_log) =
+                (&fee_setting, &fee_formatter, &replay_log);
+            move |calls: Vec<Call>, nonce: FieldElement, deployments: u64| async move {
+                let estimated_fee = match fee_setting {
+                    FeeSetting::Manual(fee) => return Ok(Some(*fee)),
+                    FeeSetting::EstimateOnly | FeeSetting::None => {
+                        estimate_fee(calls.clone(), nonce).await?
+                    }
+                };
+
+                if fee_setting.is_estimate_only() {
+                    replay_log.record(
+                        ReplayMode::EstimateOnly,
+                        ReplayRecord {
+                            chain_id,
+                            sender: account_address,
+                            calls: &calls,
+                            estimated_fee: Some(estimated_fee),
+                            ..Default::default()
+                        },
+                    );
+                    output!(
+                        "{}",
+                        fee_formatter.format(estimated_fee.into()).bright_yellow()
+                    );
+                    if deployments > 1 {
+                        progress!("Fees apply to each of the {} deployments", deployments);
+                    }
+                    return Ok(None);
+                }
+
+                Ok::<_, anyhow::Error>(Some(max_fee_from_estimate(estimated_fee)?))
+            }
+        };
+
+        // Sends the calls and returns the transaction hash along with the nonce used. Timed out
+        // submissions are looked up before being sent again, and nonce errors are retried with
+        // `--retry-on-nonce-error`. With `dump`, the transaction also goes to `--dump-transaction`.
+        let max_nonce_retries = self.nonce_retry.max_retries();
+        let send_calls = {
+            let (provider, account, replay_log, dump_path) =
+                (&provider, &account, &replay_log, &self.dump_transaction);
+            move |calls: Vec<Call>, nonce: FieldElement, max_fee: FieldElement, dump: bool| async move {
+                let execution = account
+                    .execute(calls.clone())
+                    .nonce(nonce)
+                    .max_fee(max_fee)
+                    .prepared()?;
+                if let (true, Some(path)) = (dump, dump_path) {
+                    dump_transaction(&execution.get_invoke_request().await?, path)?;
+                }
+
+                let sent = send_execution(
+                    provider,
+                    account,
+                    execution,
+                    nonce,
+                    max_nonce_retries,
+                    |nonce| {
+                        Ok(account
+                            .execute(calls.clone())
+                            .nonce(nonce)
+                            .max_fee(max_fee)
+                            .prepared()?)
+                    },
+                )
+                .await;
+                replay_log.record(
+                    ReplayMode::Send,
+                    ReplayRecord {
+                        chain_id,
+                        sender: account_address,
+                        nonce: Some(sent.as_ref().map_or(nonce, |(_, nonce)| *nonce)),
+                        calls: &calls,
+                        max_fee: Some(max_fee),
+                        transaction_hash: sent
+                            .as_ref()
+                            .ok()
+                            .map(|(transaction_hash, _)| *transaction_hash),
+                        error: sent.as_ref().err().map(|err| err.to_string()),
+                        ..Default::default()
+                    },
+                );
+                sent
+            }
+        };
+
+        let mut summary = BatchSummary {
+            enabled: self.count > 1 && !self.no_summary,
+            json: self.json_summary,
+            count: self.count,
+            rows: vec![],
+        };
+
+        if self.parallel > 1 {
+            let mut pending = vec![];
+            for index in 0..self.count {
+                let salt = base_salt + FieldElement::from(index);
+                let deployed_address =
+                    get_udc_deployed_address(salt, class_hash, &uniqueness, &ctor_args);
+
+                let recorded = match &batch_state {
+                    Some(state) => state.recorded(index, deployed_address)?,
+                    None => None,
+                };
+                match recorded {
+                    Some(deployment) => {
+                        if !summary.replaces_output_lines() {
+                            output!(
+                                "{:#064x} {:#064x}",
+                                deployed_address,
+                                deployment.transaction_hash
+                            );
+                        }
+                        summary.record(
+                            index,
+                            BatchStatus::Skipped,
+                            (!skip_address).then_some(deployed_address),
+                            Some(deployment.transaction_hash),
+                            None,
+                        );
+                    }
+                    None => pending.push((index, salt, deployed_address)),
+                }
+            }
+            if pending.len() as u64 != self.count {
+                progress!(
+                    "Skipping {} deployments already completed",
+                    self.count - pending.len() as u64
+                );
+            }
+            let (first_salt, first_address) = match pending.first() {
+                Some((_, salt, deployed_address)) => (*salt, *deployed_address),
+                None => return summary.print(&fee_formatter),
+            };
+
+            // Deployments only differ in their salt, so a single estimate covers all of them
+            let max_fee = match resolve_max_fee(
+                build_calls(first_salt, first_address),
+                nonce,
+                pending.len() as u64,
+            )
+            .await?
+            {
+                Some(max_fee) => max_fee,
+                None => return Ok(()),
+            };
+            if !skip_balance_check {
+                check_fee_balance(
+                    &provider,
+                    chain_id,
+                    account_address,
+                    max_fee * FieldElement::from(pending.len() as u64),
+                    &fee_formatter,
+                )
+                .await?;
+            }
+
+            progress!(
+                "Deploying {} instances of class {} with up to {} at a time, using nonces {} to {}",
+                pending.len(),
+                format!("{:#064x}", class_hash).bright_yellow(),
+                self.parallel,
+                nonce,
+                nonce + FieldElement::from(pending.len() as u64 - 1)
+            );
+
+            // Deployments that haven't started yet are not sent once one fails, unless asked to
+            let stop = AtomicBool::new(false);
+            let send_calls = &send_calls;
+            let send_deployment =
+                |&(_, salt, deployed_address): &(u64, FieldElement, FieldElement), nonce| {
+                    let calls = build_calls(salt, deployed_address);
+                    async move { send_calls(calls, nonce, max_fee, false).await }
+                };
+            let mut deployments = Self::send_parallel(
+                &pending,
+                nonce,
+                self.parallel as usize,
+                &stop,
+                &send_deployment,
+            );
+
+            let mut sent_deployments = vec![];
+            let mut failed_count = 0;
+            while let Some((&(index, salt, deployed_address), deployment_nonce, sent)) =
+                deployments.next().await
+            {
+                let sent = match sent {
+                    Some(sent) => sent,
+                    None => continue,
+                };
+                metrics.record_send(chain_id, max_fee, sent.is_ok());
+
+                match sent {
+                    Ok((deployment_tx, _)) => {
+                        progress!(
+                            "Deployment #{} sent with nonce {}: {}",
+                            index + 1,
+                            deployment_nonce.to_string().bright_yellow(),
+                            format!("{:#064x}", deployment_tx).bright_yellow()
+                        );
+                        explorer.print_link(chain_id, deployment_tx);
+
+                        if let (Some(state), Some(path)) = (&mut batch_state, &self.batch_state) {
+                            state.record(
+                                path,
+                                BatchDeployment {
+                                    index,
+                                    salt,
+                                    address: deployed_address,
+                                    transaction_hash: deployment_tx,
+                                },
+                            )?;
+                        }
+                        sent_deployments.push((index, salt, deployed_address, deployment_tx));
+                    }
+                    Err(err) => {
+                        // Later nonces are already taken, so the gap has to be filled first
+                        warning!(
+                            "WARNING: deployment #{} with nonce {} failed: {}. Deployments sent \
+                            with later nonces are only included once nonce {} is used.",
+                            index + 1,
+                            deployment_nonce,
+                            err,
+                            deployment_nonce
+                        );
+                        failed_count += 1;
+                        summary.record(index, BatchStatus::Failed, None, None, None);
+                        if !self.continue_on_error {
+                            stop.store(true, Ordering::Relaxed);
+                        }
+                    }
+                }
+            }
+
+            let unsent_count = pending.len() - sent_deployments.len() - failed_count;
+
+            // The transactions are all in flight already, so waiting for them in order doesn't
+            // take longer than waiting for the last one. For the same reason, the others are still
+            // watched after one fails.
+            sent_deployments.sort_by_key(|(index, _, _, _)| *index);
+            for (index, salt, deployed_address, deployment_tx) in sent_deployments.iter() {
+                let watched = self.watch || !compute_address;
+                let reported_address = async {
+                    if watched {
+                        watch_tx(&provider, *deployment_tx, watch_conditions).await?;
+                    }
+
+                    if compute_address {
+                        Ok((!skip_address).then_some(*deployed_address))
+                    } else {
+                        Ok::<_, anyhow::Error>(Some(
+                            Self::find_deployed_address(
+                                &provider,
+                                *deployment_tx,
+                                deployer_address,
+                                class_hash,
+                                *salt,
+                            )
+                            .await?,
+                        ))
+                    }
+                }
+                .await;
+                let reported_address = match reported_address {
+                    Ok(reported_address) => reported_address,
+                    Err(err) => {
+                        warning!(
+                            "WARNING: deployment #{} in transaction {:#064x} failed: {}",
+                            index + 1,
+                            deployment_tx,
+                            err
+                        );
+                        failed_count += 1;
+                        summary.record(
+                            *index,
+                            BatchStatus::Failed,
+                            (compute_address && !skip_address).then_some(*deployed_address),
+                            Some(*deployment_tx),
+                            Some(max_fee),
+                        );
+                        continue;
+                    }
+                };
+                match reported_address {
+                    _ if summary.replaces_output_lines() => {}
+                    Some(address) => output!("{:#064x} {:#064x}", address, deployment_tx),
+                    None => output!("{:#064x}", deployment_tx),
+                }
+                summary
+                    .record_sent(
+                        &provider,
+                        *index,
+                        watched,
+                        reported_address,
+                        *deployment_tx,
+                        max_fee,
+                    )
+                    .await;
+            }
+            summary.print(&fee_formatter)?;
+
+            if failed_count > 0 {
+                anyhow::bail!(
+                    "{} of {} deployments failed, and {} were not sent",
+                    failed_count,
+                    pending.len(),
+                    unsent_count
+                );
+            }
+
+            return Ok(());
+        }
+
+        let is_batch = self.count > 1;
+        let mut failed_count = 0;
+        let mut batch_fees = batch_fee_cap.map(BatchFees::new);
+        let mut batch_fee_cap_reached = false;
+
+        for index in 0..self.count {
+            let salt = base_salt + FieldElement::from(index);
+            let deployed_address =
+                get_udc_deployed_address(salt, class_hash, &uniqueness, &ctor_args);
+
+            let recorded = match &batch_state {
+                Some(state) => state.recorded(index, deployed_address)?,
+                None => None,
+            };
+            if let Some(deployment) = recorded {
+                progress!(
+                    "Skipping deployment #{} already completed in transaction {}",
+                    index + 1,
+                    format!("{:#064x}", deployment.transaction_hash).bright_yellow()
+                );
+                if let Some(env_file) = &env_file {
+                    env_file.write(&Self::env_results(
+                        (!skip_address).then_some(deployed_address),
+                        deployment.transaction_hash,
+                    ))?;
+                }
+                if let Some(manifest) = &manifest {
+                    manifest.write(&Self::manifest_resource(
+                        chain_id,
+                        class_hash,
+                        (!skip_address).then_some(deployed_address),
+                        salt,
+                        &ctor_args,
+                        deployment.transaction_hash,
+                    ))?;
+                }
+                if let Some(prefix) = &self.export {
+                    print_shell_exports(
+                        prefix,
+                        &[
+                            ("ADDRESS", deployed_address),
+                            ("TX", deployment.transaction_hash),
+                        ],
+                    );
+                } else if is_batch {
+                    if !summary.replaces_output_lines() {
+                        output!(
+                            "{:#064x} {:#064x}",
+                            deployed_address,
+                            deployment.transaction_hash
+                        );
+                    }
+                } else {
+                    output!("{}", format!("{:#064x}", deployed_address).bright_yellow());
+                }
+                summary.record(
+                    index,
+                    BatchStatus::Skipped,
+                    (!skip_address).then_some(deployed_address),
+                    Some(deployment.transaction_hash),
+                    None,
+                );
+                continue;
+            }
+
+            let calls = build_calls(salt, deployed_address);
+            let contract_deployment = account.execute(calls.clone()).nonce(nonce);
+            let calldata_len = calls.iter().map(|call| call.calldata.len()).sum();
+
+            if self.plan {
+                let (estimated_fee, max_fee) = match &fee_setting {
+                    FeeSetting::Manual(fee) => (None, *fee),
+                    FeeSetting::EstimateOnly | FeeSetting::None => {
+                        let estimated_fee = Spinner::new()
+                            .run("Estimating fee", contract_deployment.estimate_fee())
+                            .await?
+                            .overall_fee;
+                        (
+                            Some(estimated_fee.into()),
+                            fee_buffer.apply(estimated_fee, chain_id, DEFAULT_FEE_BUFFER),
+                        )
+                    }
+                };
+
+                let plan = DeploymentPlan {
+                    chain_id,
+                    account: account_address,
+                    nonce,
+                    address: (!skip_address).then_some(deployed_address),
+                    class_hash,
+                    salt,
+                    deployer: deployer_address,
+                    unique: !self.not_unique,
+                    constructor_calldata: ctor_args.clone(),
+                    fee_token: "ETH",
+                    estimated_fee,
+                    max_fee,
+                };
+                let plan_json = colored_json::to_colored_json(
+                    &serde_json::to_value(plan)?,
+                    ColorMode::Auto(Output::StdOut),
+                )?;
+                output!("{plan_json}");
+                return Ok(());
+            }
+
+            // Other deployments in a batch only differ in the salt, so checking the first one is
+            // enough
+            if report.is_enabled() {
+                let max_fee = match &fee_setting {
+                    FeeSetting::Manual(fee) => {
+                        report.skip("Fee within ceiling", "max fee set manually");
+                        Some(*fee)
+                    }
+                    FeeSetting::EstimateOnly | FeeSetting::None => {
+                        match report.check(
+                            "Fee estimate",
+                            contract_deployment
+                                .estimate_fee()
+                                .await
+                                .map_err(anyhow::Error::from),
+                        )? {
+                            Some(estimate) => {
+                                if max_fee_ceiling.is_some() {
+                                    report.check(
+                                        "Fee within ceiling",
+                                        check_fee_ceiling(
+                                            estimate.overall_fee,
+                                            max_fee_ceiling,
+                                            &fee_formatter,
+                                        ),
+                                    )?;
+                                } else {
+                                    report.skip("Fee within ceiling", "no --max-fee-ceiling");
+                                }
+                                Some(fee_buffer.apply(
+                                    estimate.overall_fee,
+                                    chain_id,
+                                    DEFAULT_FEE_BUFFER,
+                                ))
+                            }
+                            None => {
+                                report.skip("Fee within ceiling", "no fee estimate");
+                                None
+                            }
+                        }
+                    }
+                };
+
+                match max_fee {
+                    _ if skip_balance_check => {
+                        report.skip("Sufficient balance", "--skip-balance-check")
+                    }
+                    Some(max_fee) => {
+                        report.check(
+                            "Sufficient balance",
+                            check_fee_balance(
+                                &provider,
+                                chain_id,
+                                account_address,
+                                max_fee,
+                                &fee_formatter,
+                            )
+                            .await,
+                        )?;
+                    }
+                    None => report.skip("Sufficient balance", "no max fee"),
+                }
+
+                return report.finish();
+            }
+
+            let result: Result<Option<(FieldElement, FieldElement, FieldElement)>> = async {
+                let cached_fee = fee_cache.as_ref().and_then(|cache| {
+                    cache.reusable_fee(chain_id, class_hash, calldata_len, self.reuse_fee_max_uses)
+                });
+
+                let max_fee = match (&fee_setting, &self.reuse_fee, cached_fee) {
+                    // The cached estimate goes through the same checks as a fresh one
+                    (FeeSetting::None, Some(path), Some(cached_fee)) => {
+                        warn_on_low_fee(cached_fee, low_fee_threshold);
+                        let max_fee = max_fee_from_estimate(cached_fee)?;
+
+                        // Unwrapping is safe as there's only a cached fee with a cache
+                        let cache = fee_cache.as_mut().unwrap();
+                        cache.uses += 1;
+                        progress!(
+                            "Reusing cached fee estimate {} ({} of {} uses)",
+                            fee_formatter.format(cached_fee.into()).bright_yellow(),
+                            cache.uses,
+                            self.reuse_fee_max_uses
+                        );
+                        cache.save(path)?;
+
+                        max_fee
+                    }
+                    // Manual fees are not cached, as they are set on every run anyway
+                    (FeeSetting::None, Some(path), None) => {
+                        let estimated_fee = estimate_fee(calls.clone(), nonce).await?;
+                        let cache = FeeCache {
+                            chain_id,
+                            class_hash,
+                            calldata_len,
+                            estimated_fee,
+                            estimated_at: unix_timestamp(),
+                            uses: 0,
+                        };
+                        cache.save(path)?;
+                        fee_cache = Some(cache);
+
+                        max_fee_from_estimate(estimated_fee)?
+                    }
+                    _ => match resolve_max_fee(calls.clone(), nonce, self.count).await? {
+                        Some(max_fee) => max_fee,
+                        None => return Ok(None),
+                    },
+                };
+
+                if let Some(batch_fees) = &batch_fees {
+                    if !batch_fees.allows(max_fee) {
+                        batch_fee_cap_reached = true;
+                        anyhow::bail!(
+                            "deployment #{} with a max fee of {} would bring the batch above the \
+                            --batch-fee-cap of {} ({} used). The transaction was not sent.",
+                            index + 1,
+                            fee_formatter.format(max_fee),
+                            fee_formatter.format(batch_fees.cap),
+                            fee_formatter.format(batch_fees.used)
+                        );
+                    }
+                }
+
+                if self.show_parties {
+                    let mut parties = vec![
+                        ("Sender", account_address),
+                        (
+                            if deployer_address == DEFAULT_UDC_ADDRESS {
+                                "UDC"
+                            } else {
+                                "Deployer"
+                            },
+                            deployer_address,
+                        ),
+                    ];
+                    // The computed address can't be trusted with custom address schemes
+                    if !skip_address {
+                        parties.push(("Deployed contract", deployed_address));
+                    }
+                    parties.push(("Fee token", resolve_fee_token(chain_id)?));
+                    print_parties(felt_decoder.address_book_resolver(), &parties).await?;
+                }
+
+                progress!(
+                    "Deploying class {} with salt {}...",
+                    format!("{:#064x}", class_hash).bright_yellow(),
+                    format!("{:#064x}", salt).bright_yellow()
+                );
+                if !skip_address {
+                    progress!(
+                        "The contract will be deployed at address {}",
+                        format!("{:#064x}", deployed_address).bright_yellow()
+                    );
+                } else if !compute_address {
+                    progress!("The contract address will be read from the deployment event");
+                }
+                if let Some(function_name) = self.atomic_init.first() {
+                    progress!(
+                        "The contract will be initialized with {} in the same transaction",
+                        function_name.bright_yellow()
+                    );
+                }
+
+                if !skip_balance_check {
+                    check_fee_balance(
+                        &provider,
+                        chain_id,
+                        account_address,
+                        max_fee,
+                        &fee_formatter,
+                    )
+                    .await?;
+                }
+                let sent = send_calls(calls.clone(), nonce, max_fee, true).await;
+                metrics.record_send(chain_id, max_fee, sent.is_ok());
+                let (deployment_tx, used_nonce) = sent?;
+                if let Some(batch_fees) = &mut batch_fees {
+                    batch_fees.record(max_fee);
+                }
+
+                Ok(Some((deployment_tx, used_nonce, max_fee)))
+            }
+            .await;
+
+            let (deployment_tx, max_fee) = match result {
+                Ok(Some((deployment_tx, used_nonce, max_fee))) => {
+                    nonce = used_nonce;
+                    (deployment_tx, max_fee)
+                }
+                Ok(None) => return Ok(()),
+                // Hitting the cap stops the batch, as the remaining deployments would hit it too
+                Err(err) if self.continue_on_error && is_batch && !batch_fee_cap_reached => {
+                    warning!(
+                        "WARNING: deployment #{} with salt {:#064x} failed: {}",
+                        index + 1,
+                        salt,
+                        err
+                    );
+                    failed_count += 1;
+                    summary.record(index, BatchStatus::Failed, None, None, None);
+                    continue;
+                }
+                Err(err) => {
+                    summary.record(index, BatchStatus::Failed, None, None, None);
+                    summary.print(&fee_formatter)?;
+                    return Err(err);
+                }
+            };
+
+            // The transaction has been accepted by the node so the nonce is consumed
+            progress!("Nonce used: {}", nonce.to_string().bright_yellow());
+            explorer.print_link(chain_id, deployment_tx);
+            nonce += FieldElement::ONE;
+
+            if let (Some(state), Some(path)) = (&mut batch_state, &self.batch_state) {
+                state.record(
+                    path,
+                    BatchDeployment {
+                        index,
+                        salt,
+                        address: deployed_address,
+                        transaction_hash: deployment_tx,
+                    },
+                )?;
+            }
+
+            // Batches keep printing their own line per deployment instead
+            let hash_printed = self.broadcast_only && !is_batch;
+            if hash_printed {
+                match &self.export {
+                    Some(prefix) => print_shell_exports(
+                        prefix,
+                        &[("ADDRESS", deployed_address), ("TX", deployment_tx)],
+                    ),
+                    // Only the transaction hash goes to stdout so this can be easily scripted.
+                    // It's printed before watching so that it can be picked up right away.
+                    None => output!("{}", self.felt_format.format(deployment_tx)),
+                }
+            } else {
+                progress!(
+                    "Contract deployment transaction: {}",
+                    format!("{:#064x}", deployment_tx).bright_yellow()
+                );
+            }
+            if hash_printed && !self.watch {
+                if self.print_command {
+                    Self::print_command(&explicit_options, salt, max_fee, &explicit_positionals);
+                }
+                if let Some(env_file) = &env_file {
+                    env_file.write(&Self::env_results(
+                        (!skip_address).then_some(deployed_address),
+                        deployment_tx,
+                    ))?;
+                }
+                if let Some(manifest) = &manifest {
+                    manifest.write(&Self::manifest_resource(
+                        chain_id,
+                        class_hash,
+                        (!skip_address).then_some(deployed_address),
+                        salt,
+                        &ctor_args,
+                        deployment_tx,
+                    ))?;
+                }
+                return Ok(());
+            }
+
+            // The post-deploy call would fail if the contract isn't deployed yet, and the
+            // deployment event is only there once the transaction is included
+            let watched = self.watch || post_deploy_call.is_some() || !compute_address;
+            let reported_address = async {
+                if watched {
+                    progress!(
+                        "Waiting for transaction {} to confirm...",
+                        format!("{:#064x}", deployment_tx).bright_yellow(),
+                    );
+                    watch_tx(&provider, deployment_tx, watch_conditions).await?;
+                }
+
+                if compute_address {
+                    Ok((!skip_address).then_some(deployed_address))
+                } else {
+                    Ok::<_, anyhow::Error>(Some(
+                        Self::find_deployed_address(
+                            &provider,
+                            deployment_tx,
+                            deployer_address,
+                            class_hash,
+                            salt,
+                        )
+                        .await?,
+                    ))
+                }
+            }
+            .await;
+            // The deployment is sent either way, so it's reported with its transaction hash
+            let reported_address = match reported_address {
+                Ok(reported_address) => reported_address,
+                Err(err) => {
+                    summary.record(
+                        index,
+                        BatchStatus::Failed,
+                        (compute_address && !skip_address).then_some(deployed_address),
+                        Some(deployment_tx),
+                        Some(max_fee),
+                    );
+                    if self.continue_on_error && is_batch {
+                        warning!(
+                            "WARNING: deployment #{} in transaction {:#064x} failed: {}",
+                            index + 1,
+                            deployment_tx,
+                            err
+                        );
+                        failed_count += 1;
+                        continue;
+                    }
+                    summary.print(&fee_formatter)?;
+                    return Err(err);
+                }
+            };
+
+            if let Some((selector, calldata)) = &post_deploy_call {
+                let call = Call {
+                    to: deployed_address,
+                    selector: *selector,
+                    calldata: calldata.clone(),
+                };
+                // Never reached with `--estimate-only`, which returns before anything is sent
+                let max_fee = match resolve_max_fee(vec![call.clone()], nonce, 1).await? {
+                    Some(max_fee) => max_fee,
+                    None => return Ok(()),
+                };
+
+                if !skip_balance_check {
+                    check_fee_balance(
+                        &provider,
+                        chain_id,
+                        account_address,
+                        max_fee,
+                        &fee_formatter,
+                    )
+                    .await?;
+                }
+                let sent = send_calls(vec![call], nonce, max_fee, false).await;
+                metrics.record_send(chain_id, max_fee, sent.is_ok());
+                let (call_tx, used_nonce) = sent?;
+                nonce = used_nonce;
+                progress!("Nonce used: {}", nonce.to_string().bright_yellow());
+                explorer.print_link(chain_id, call_tx);
+                nonce += FieldElement::ONE;
+
+                progress!(
+                    "Post-deploy call transaction: {}",
+                    format!("{:#064x}", call_tx).bright_yellow()
+                );
+
+                if self.watch {
+                    progress!(
+                        "Waiting for transaction {} to confirm...",
+                        format!("{:#064x}", call_tx).bright_yellow(),
+                    );
+                    watch_tx(&provider, call_tx, watch_conditions).await?;
+                }
+            }
+
+            if let Some(env_file) = &env_file {
+                env_file.write(&Self::env_results(reported_address, deployment_tx))?;
+            }
+            if let Some(manifest) = &manifest {
+                manifest.write(&Self::manifest_resource(
+                    chain_id,
+                    class_hash,
+                    reported_address,
+                    salt,
+                    &ctor_args,
+                    deployment_tx,
+                ))?;
+            }
+
+            if hash_printed {
+                // Nothing else goes to stdout after the transaction hash
+            } else if let Some(prefix) = &self.export {
+                progress!("Contract deployed:");
+
+                print_shell_exports(
+                    prefix,
+                    &[("ADDRESS", deployed_address), ("TX", deployment_tx)],
+                );
+            } else {
+                match reported_address {
+                    _ if is_batch && summary.replaces_output_lines() => {}
+                    None => output!("{:#064x}", deployment_tx),
+                    // One line per deployment with the address and transaction hash for scripting
+                    Some(address) if is_batch => {
+                        output!("{:#064x} {:#064x}", address, deployment_tx)
+                    }
+                    Some(address) => {
+                        progress!("Contract deployed:");
+
+                        // Only the contract goes to stdout so this can be easily scripted
+                        output!("{}", format!("{:#064x}", address).bright_yellow());
+                    }
+                }
+            }
+            if self.print_command {
+                Self::print_command(&explicit_options, salt, max_fee, &explicit_positionals);
+            }
+            summary
+                .record_sent(
+                    &provider,
+                    index,
+                    watched,
+                    reported_address,
+                    deployment_tx,
+                    max_fee,
+                )
+                .await;
+        }
+        summary.print(&fee_formatter)?;
+
+        if let Some(batch_fees) = &batch_fees {
+            progress!(
+                "Batch fee cap used: {} of {}",
+                fee_formatter.format(batch_fees.used).bright_yellow(),
+                fee_formatter.format(batch_fees.cap)
+            );
+        }
+        if failed_count > 0 {
+            anyhow::bail!("{} of {} deployments failed", failed_count, self.count);
+        }
+
+        Ok(())
+    }
+
+    /// Sends the `pending` deployments of `--parallel` with up to `parallel` of them in flight at a
+    /// time. Nonces are assigned upfront, counting up from `first_nonce` in the order of
+    /// `pending`. Deployments that haven't started yet are skipped once `stop` is set, and yield
+    /// `None` instead of the result of `send`. Results come in the order the sends complete.
+    fn send_parallel<'a, T, F, Fut>(
+        pending: &'a [T],
+        first_nonce: FieldElement,
+        parallel: usize,
+        stop: &'a AtomicBool,
+        send: &'a F,
+    ) -> impl Stream<Item = (&'a T, FieldElement, Option<Fut::Output>)> + Unpin + 'a
+    where
+        F: Fn(&'a T, FieldElement) -> Fut,
+        Fut: Future + 'a,
+    {
+        stream::iter(pending.iter().enumerate().map(move |(ind, item)| {
+            let nonce = first_nonce + FieldElement::from(ind as u64);
+            async move {
+                let sent = if stop.load(Ordering::Relaxed) {
+                    None
+                } else {
+                    Some(send(item, nonce).await)
+                };
+                (item, nonce, sent)
+            }
+        }))
+        .buffer_unordered(parallel)
+    }
+
+    /// Prints the command for `--print-command`. The salt and max fee are only final once the
+    /// deployment is sent.
+    fn print_command(
+        options: &[String],
+        salt: FieldElement,
+        max_fee: FieldElement,
+        positionals: &[String],
+    ) {
+        // Not silenced by `--quiet`, as it's explicitly asked for
+        eprintln!("Equivalent command:");
+        eprintln!(
+            "  {}",
+            Self::equivalent_command(options, salt, max_fee, positionals)
+        );
+    }
+
+    fn equivalent_command(
+        options: &[String],
+        salt: FieldElement,
+        max_fee: FieldElement,
+        positionals: &[String],
+    ) -> String {
+        let mut command = vec!["starkli".to_owned(), "deploy".to_owned()];
+        command.extend_from_slice(options);
+        command.push("--salt".into());
+        command.push(format!("{:#064x}", salt));
+        command.push("--max-fee-raw".into());
+        command.push(max_fee.to_string());
+        command.extend_from_slice(positionals);
+
+        shell_command(&command)
+    }
+
+    /// Prints every input of the UDC address computation for `--explain-address`, following
+    /// `get_udc_deployed_address`.
+    fn explain_address(
+        salt: FieldElement,
+        salt_source: &str,
+        class_hash: FieldElement,
+        uniqueness: &UdcUniqueness,
+        ctor_args: &[FieldElement],
+    ) {
+        for line in Self::address_explanation(salt, salt_source, class_hash, uniqueness, ctor_args)
+        {
+            output!("{}", line);
+        }
+    }
+
+    fn address_explanation(
+        salt: FieldElement,
+        salt_source: &str,
+        class_hash: FieldElement,
+        uniqueness: &UdcUniqueness,
+        ctor_args: &[FieldElement],
+    ) -> Vec<String> {
+        let deployed_address = get_udc_deployed_address(salt, class_hash, uniqueness, ctor_args);
+        let (contract_address_salt, deployer_address) = match uniqueness {
+            UdcUniqueness::NotUnique => (salt, FieldElement::ZERO),
+            UdcUniqueness::Unique(settings) => (
+                pedersen_hash(&settings.deployer_address, &salt),
+                settings.udc_contract_address,
+            ),
+        };
+
+        let mut lines = vec![
+            format!(
+                "Deployment address:      {}",
+                format!("{:#064x}", deployed_address).bright_yellow()
+            ),
+            String::new(),
+            "Computed from:".to_owned(),
+            format!("  Salt:                  {:#064x} ({})", salt, salt_source),
+        ];
+        match uniqueness {
+            UdcUniqueness::NotUnique => {
+                lines.push("  Uniqueness:            not unique (--not-unique)".to_owned());
+            }
+            UdcUniqueness::Unique(settings) => {
+                lines.push("  Uniqueness:            unique".to_owned());
+                lines.push(format!(
+                    "  Account address:       {:#064x}",
+                    settings.deployer_address
+                ));
+                lines.push(format!(
+                    "  Unique salt:           {:#064x} (hash of the account address and salt)",
+                    contract_address_salt
+                ));
+            }
+        }
+        lines.push(format!("  Class hash:            {:#064x}", class_hash));
+        lines.push(format!(
+            "  Constructor calldata:  {:#064x} (hash of {} elements)",
+            compute_hash_on_elements(ctor_args),
+            ctor_args.len()
+        ));
+        lines.push(format!(
+            "  Deployer address:      {:#064x}{}",
+            deployer_address,
+            match uniqueness {
+                UdcUniqueness::NotUnique => " (always zero when not unique)",
+                UdcUniqueness::Unique(_) => "",
+            }
+        ));
+        lines.push(String::new());
+        lines.push("The address changes with any of these inputs:".to_owned());
+        lines.push("  - a different salt".to_owned());
+        match uniqueness {
+            UdcUniqueness::NotUnique => lines.push(
+                "  - deploying with uniqueness, which also ties the address to the account and \
+                deployer"
+                    .to_owned(),
+            ),
+            UdcUniqueness::Unique(_) => {
+                lines.push(
+                    "  - a different sending account, or deploying with --not-unique".to_owned(),
+                );
+                lines.push("  - a different deployer contract (--deployer)".to_owned());
+            }
+        }
+        lines.push("  - a different class hash, e.g. after recompiling the contract".to_owned());
+        lines.push(
+            "  - any change to the constructor arguments, including their order and encoding"
+                .to_owned(),
+        );
+
+        lines
+    }
+
+    /// Values written with `--env-out`. The computed address can't be trusted with custom
+    /// address schemes, so it's left out with `--skip-address`.
+    fn env_results(
+        deployed_address: Option<FieldElement>,
+        deployment_tx: FieldElement,
+    ) -> Vec<(&'static str, FieldElement)> {
+        match deployed_address {
+            Some(deployed_address) => vec![
+                ("CONTRACT_ADDRESS", deployed_address),
+                ("DEPLOY_TX", deployment_tx),
+            ],
+            None => vec![("DEPLOY_TX", deployment_tx)],
+        }
+    }
+
+    fn manifest_resource(
+        chain_id: FieldElement,
+        class_hash: FieldElement,
+        deployed_address: Option<FieldElement>,
+        salt: FieldElement,
+        ctor_args: &[FieldElement],
+        deployment_tx: FieldElement,
+    ) -> ManifestResource {
+        ManifestResource {
+            kind: ResourceKind::Contract,
+            chain_id,
+            class_hash,
+            address: deployed_address,
+            salt: Some(salt),
+            constructor_calldata: Some(ctor_args.to_vec()),
+            transaction_hash: Some(deployment_tx),
+        }
+    }
+
+    /// Reads the address from the `ContractDeployed` event the deployer emitted, for
+    /// `--udc-compute none`.
+    async fn find_deployed_address<P>(
+        provider: P,
+        transaction_hash: FieldElement,
+        deployer_address: FieldElement,
+        class_hash: FieldElement,
+        salt: FieldElement,
+    ) -> Result<FieldElement>
+    where
+        P: Provider,
+        P::Error: 'static,
+    {
+        let events = match provider.get_transaction_receipt(transaction_hash).await? {
+            MaybePendingTransactionReceipt::Receipt(TransactionReceipt::Invoke(receipt)) => {
+                receipt.events
+            }
+            MaybePendingTransactionReceipt::PendingReceipt(PendingTransactionReceipt::Invoke(
+                receipt,
+            )) => receipt.events,
+            _ => anyhow::bail!(
+                "transaction {:#064x} is not an invoke transaction",
+                transaction_hash
+            ),
+        };
+
+        events
+            .iter()
+            .filter(|event| {
+                event.from_address == deployer_address
+                    && event.keys.first() == Some(&selector!("ContractDeployed"))
+            })
+            .find_map(|event| parse_contract_deployed(&event.data, class_hash, salt))
+            .map(|deployed| deployed.address)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "the deployer emitted no ContractDeployed event for class {:#064x} with salt \
+                    {:#064x} in transaction {:#064x}, so the contract address is unknown",
+                    class_hash,
+                    salt,
+                    transaction_hash
+                )
+            })
+    }
+
+    /// Arguments of the UDC's `deployContract`, which custom deployers are expected to share.
+    fn deployment_calldata(
+        class_hash: FieldElement,
+        salt: FieldElement,
+        unique: bool,
+        ctor_args: &[FieldElement],
+    ) -> Vec<FieldElement> {
+        let mut calldata = vec![
+            class_hash,
+            salt,
+            if unique {
+                FieldElement::ONE
+            } else {
+                FieldElement::ZERO
+            },
+            ctor_args.len().into(),
+        ];
+        calldata.extend_from_slice(ctor_args);
+        calldata
+    }
+
+    /// Searches for the first integer salt that yields a contract address whose 64-digit hex
+    /// representation starts with `prefix`.
+    fn mine_salt(
+        prefix: &str,
+        max_attempts: u64,
+        class_hash: FieldElement,
+        uniqueness: &UdcUniqueness,
+        ctor_args: &[FieldElement],
+    ) -> Result<FieldElement> {
+        let prefix = Self::parse_address_prefix(prefix)?;
+
+        progress!(
+            "Searching for a salt with address prefix {}...",
+            prefix.bright_yellow()
+        );
+
+        for salt in 0..max_attempts {
+            let salt_felt = FieldElement::from(salt);
+            let deployed_address =
+                get_udc_deployed_address(salt_felt, class_hash, uniqueness, ctor_args);
+
+            if format!("{:064x}", deployed_address).starts_with(&prefix) {
+                progress!(
+                    "Found salt: {}",
+                    format!("{:#x}", salt_felt).bright_yellow()
+                );
+                return Ok(salt_felt);
+            }
+        }
+
+        anyhow::bail!(
+            "no salt found for address prefix {} after {} attempts. Use a shorter prefix or a \
+            larger --mine-max-attempts",
+            prefix,
+            max_attempts
+        )
+    }
+
+    /// Normalizes a `--mine-prefix` to lowercase hex digits without `0x`, rejecting prefixes no
+    /// address can start with. Addresses are below 2^251, so their 64-digit hex form always
+    /// starts with `0` followed by a digit up to `7`.
+    fn parse_address_prefix(prefix: &str) -> Result<String> {
+        let prefix = prefix.trim_start_matches("0x").to_lowercase();
+        if prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+            anyhow::bail!("invalid address prefix: expected hex digits");
+        }
+        if prefix.len() > 64 {
+            anyhow::bail!("address prefix too long");
+        }
+
+        let mut digits = prefix.chars();
+        if digits.next() != Some('0') {
+            anyhow::bail!(
+                "no address can start with {}: the first of the 64 hex digits is always 0",
+                prefix
+            );
+        }
+        if digits.next().map_or(false, |digit| digit > '7') {
+            anyhow::bail!(
+                "no address can start with {}: the second of the 64 hex digits is at most 7",
+                prefix
+            );
+        }
+
+        Ok(prefix)
+    }
+
+    async fn check_account_deployed<P>(provider: P, account_address: FieldElement) -> Result<()>
+    where
+        P: Provider,
+        P::Error: 'static,
+    {
+        match provider
+            .get_class_hash_at(BlockId::Tag(BlockTag::Pending), account_address)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(ProviderError::StarknetError(StarknetErrorWithMessage {
+                code: MaybeUnknownErrorCode::Known(StarknetError::ContractNotFound),
+                ..
+            })) => Err(anyhow::anyhow!(
+                "account config says deployed but no code found on this network — wrong RPC? \
+                Use --skip-account-check to bypass this check."
+            )),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn check_class_declared<P>(provider: P, class_hash: FieldElement) -> Result<ContractClass>
+    where
+        P: Provider,
+        P::Error: 'static,
+    {
+        match provider
+            .get_class(BlockId::Tag(BlockTag::Pending), class_hash)
+            .await
+        {
+            Ok(class) => Ok(class),
+            Err(ProviderError::StarknetError(StarknetErrorWithMessage {
+                code: MaybeUnknownErrorCode::Known(StarknetError::ClassHashNotFound),
+                ..
+            })) => Err(anyhow::anyhow!(
+                "class {:#064x} is not declared on this network. \
+                Use --allow-undeclared to bypass this check.",
+                class_hash
+            )),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn prompt_ctor_args<S>(
+        class: &ContractClass,
+        class_hash: FieldElement,
+        felt_decoder: &FeltDecoder<S>,
+    ) -> Result<Vec<FieldElement>>
+    where
+        S: ChainIdSource,
+    {
+        let abi = match ContractAbi::from_class(class)? {
+            Some(abi) => abi,
+            None => anyhow::bail!(
+                "class {:#064x} has no ABI to prompt for constructor arguments with",
+                class_hash
+            ),
+        };
+
+        let ctor_args =
+            interactive::prompt_calldata(felt_decoder, &abi.constructor_parameters()).await?;
+        eprintln!(
+            "Constructor calldata: [{}]",
+            ctor_args
+                .iter()
+                .map(|element| format!("{:#x}", element))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        if !interactive::confirm("Deploy with these constructor arguments?")? {
+            anyhow::bail!("deployment cancelled");
+        }
+
+        Ok(ctor_args)
+    }
+
+    /// Transactions from the account that haven't made it into a block yet are usually fine, but
+    /// could still be stuck or fail and shift the nonce.
+    async fn check_nonce_gap<P>(provider: P, account_address: FieldElement) -> Result<()>
+    where
+        P: Provider,
+        P::Error: 'static,
+    {
+        let latest_nonce = provider
+            .get_nonce(BlockId::Tag(BlockTag::Latest), account_address)
+            .await?;
+        let pending_nonce = provider
+            .get_nonce(BlockId::Tag(BlockTag::Pending), account_address)
+            .await?;
+
+        if pending_nonce != latest_nonce {
+            anyhow::bail!(
+                "the account has pending transactions: nonce {} in the latest block, {} with \
+                pending ones",
+                latest_nonce,
+                pending_nonce
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Salts of a `--count` deployment are incremented from the base salt. Wrapping around the
+    /// field would silently restart from zero, deploying at addresses nobody asked for.
+    fn check_salt_range(base_salt: FieldElement, count: u64) -> Result<()> {
+        let last_salt = base_salt + FieldElement::from(count - 1);
+        if last_salt < base_salt {
+            anyhow::bail!(
+                "salt {:#064x} is too large for --count {}: the salts of the later deployments \
+                would exceed the maximum felt value {:#x}",
+                base_salt,
+                count,
+                FieldElement::MAX
+            );
+        }
+
+        Ok(())
+    }
+
+    fn check_deployed_address(
+        deployed_address: FieldElement,
+        expected_address: FieldElement,
+    ) -> Result<()> {
+        if deployed_address != expected_address {
+            anyhow::bail!(
+                "the contract would be deployed at {:#064x} instead of the expected address \
+                {:#064x}. Check the class hash, salt, constructor arguments, and --not-unique",
+                deployed_address,
+                expected_address
+            );
+        }
+
+        Ok(())
+    }
+
+    fn parse_expected_nonce(nonce: &str) -> Result<FieldElement> {
+        parse_felt_value(nonce)
+            .map_err(|err| anyhow::anyhow!("invalid --expect-nonce \"{}\": {}", nonce, err))
+    }
+
+    /// Guards against another sender having used the account since `--expect-nonce` was picked.
+    /// Only the starting nonce is checked, as the batch itself moves it forward.
+    fn check_expected_nonce(nonce: FieldElement, expected_nonce: FieldElement) -> Result<()> {
+        if nonce != expected_nonce {
+            anyhow::bail!(
+                "the account nonce is {} but {} is expected. Was the account used by someone else?",
+                nonce,
+                expected_nonce
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Makes sure the constructor arguments are exactly what the constructor takes, so that no
+    /// time is spent mining a salt for a deployment that would fail anyway.
+    fn check_ctor_args(
+        class: &ContractClass,
+        class_hash: FieldElement,
+        ctor_args: &[FieldElement],
+    ) -> Result<()> {
+        let abi = match ContractAbi::from_class(class)? {
+            Some(abi) => abi,
+            None => anyhow::bail!(
+                "class {:#064x} has no ABI to check the constructor arguments against",
+                class_hash
+            ),
+        };
+
+        let inputs = abi.constructor_inputs();
+        if abi.decode_values(&inputs, ctor_args).is_none() {
+            anyhow::bail!(
+                "{} constructor argument(s) don't match the constructor inputs ({}) of class \
+                {:#064x}",
+                ctor_args.len(),
+                inputs.join(", "),
+                class_hash
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Parses a class hash with or without the `0x` prefix, rejecting anything that can't
+    /// possibly be a valid hash instead of failing obscurely later on.
+    fn parse_class_hash(raw: &str) -> Result<FieldElement> {
+        let digits = raw.strip_prefix("0x").unwrap_or(raw);
+
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            anyhow::bail!("invalid class hash \"{}\": expected hex digits", raw);
+        }
+        if digits.len() > 64 {
+            anyhow::bail!("invalid class hash \"{}\": too many hex digits", raw);
+        }
+
+        FieldElement::from_hex_be(digits)
+            .map_err(|_| anyhow::anyhow!("invalid class hash \"{}\": out of felt range", raw))
+    }
+
+    /// Reads a JSON array of arguments. Numbers are taken as decimal values, and anything else must
+    /// be a string going through argument resolution like on the command line.
+    fn read_ctor_args_file(path: &Path) -> Result<Vec<String>> {
+        let values: Vec<serde_json::Value> = serde_json::from_reader(
+            std::fs::File::open(path)
+                .map_err(|err| anyhow::anyhow!("unable to open {}: {}", path.display(), err))?,
+        )
+        .map_err(|err| {
+            anyhow::anyhow!(
+                "invalid constructor arguments file {}: expected a JSON array: {}",
+                path.display(),
+                err
+            )
+        })?;
+
+        values
+            .into_iter()
+            .enumerate()
+            .map(|(ind_element, value)| match value {
+                serde_json::Value::String(value) => Ok(value),
+                serde_json::Value::Number(value) if value.is_u64() => Ok(value.to_string()),
+                _ => Err(anyhow::anyhow!(
+                    "invalid element {} of {}: expected a string or a non-negative integer",
+                    ind_element,
+                    path.display()
+                )),
+            })
+            .collect()
+    }
+
+    /// Expands `{name}` placeholders before argument resolution takes place, so that substituted
+    /// values can still make use of schemes like `u256:` or `str:`.
+    fn render_ctor_template(args: &[String], template_args: &[String]) -> Result<Vec<String>> {
+        let mut values = HashMap::new();
+        for template_arg in template_args.iter() {
+            let (name, value) = template_arg.split_once('=').ok_or_else(|| {
+                anyhow::anyhow!(
+                    "invalid template argument \"{}\": expected name=value",
+                    template_arg
+                )
+            })?;
+            if values.insert(name, value).is_some() {
+                anyhow::bail!("duplicate template argument: {}", name);
+            }
+        }
+
+        let regex_placeholder = Regex::new(r"\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap();
+
+        args.iter()
+            .map(|arg| {
+                if let Some(unresolved) = regex_placeholder
+                    .captures_iter(arg)
+                    .map(|captures| captures[1].to_owned())
+                    .find(|name| !values.contains_key(name.as_str()))
+                {
+                    anyhow::bail!(
+                        "unresolved constructor argument placeholder: {{{}}}",
+                        unresolved
+                    );
+                }
+
+                Ok(regex_placeholder
+                    .replace_all(arg, |captures: &regex::Captures| values[&captures[1]])
+                    .into_owned())
+            })
+            .collect()
+    }
+
+    fn watch_conditions(&self) -> WatchConditions {
+        WatchConditions {
+            min_confirmations: self.min_confirmations,
+            finality: self.finality,
+            timeout: self.watch_timeout.map(Duration::from_secs),
+        }
+    }
+}
+
+impl FeeCache {
+    /// Fees can change quickly, so a cached fee isn't reused once it's this old no matter how few
+    /// times it has been used.
+    const MAX_AGE_SECS: u64 = 600;
+
+    fn reusable_fee(
+        &self,
+        chain_id: FieldElement,
+        class_hash: FieldElement,
+        calldata_len: usize,
+        max_uses: u64,
+    ) -> Option<u64> {
+        let is_fresh = unix_timestamp().saturating_sub(self.estimated_at) < Self::MAX_AGE_SECS;
+
+        if self.chain_id == chain_id
+            && self.class_hash == class_hash
+            && self.calldata_len == calldata_len
+            && self.uses < max_uses
+            && is_fresh
+        {
+            Some(self.estimated_fee)
+        } else {
+            None
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        persist(path, &format!("{}\n", serde_json::to_string_pretty(self)?))
+    }
+}
+
+impl BatchState {
+    /// Finds the deployment recorded at `index`, which is skipped when the batch is resumed. Fails
+    /// if it doesn't match the address the deployment would now have.
+    fn recorded(
+        &self,
+        index: u64,
+        deployed_address: FieldElement,
+    ) -> Result<Option<&BatchDeployment>> {
+        match self
+            .deployments
+            .iter()
+            .find(|deployment| deployment.index == index)
+        {
+            Some(deployment) if deployment.address != deployed_address => anyhow::bail!(
+                "deployment #{} was recorded at address {:#064x} but would now be deployed at \
+                {:#064x}. Did the constructor arguments change?",
+                index + 1,
+                deployment.address,
+                deployed_address
+            ),
+            deployment => Ok(deployment),
+        }
+    }
+
+    /// Records a deployment accepted by the node, so that it's skipped when the batch is resumed.
+    fn record(&mut self, path: &Path, deployment: BatchDeployment) -> Result<()> {
+        self.deployments.push(deployment);
+        self.save(path)
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        persist(path, &format!("{}\n", serde_json::to_string_pretty(self)?))
+    }
+}
+
+impl BatchFees {
+    fn new(cap: FieldElement) -> Self {
+        Self {
+            cap,
+            used: FieldElement::ZERO,
+        }
+    }
+
+    /// Whether a deployment with this max fee can be sent without going above the cap.
+    fn allows(&self, max_fee: FieldElement) -> bool {
+        self.used + max_fee <= self.cap
+    }
+
+    fn record(&mut self, max_fee: FieldElement) {
+        self.used += max_fee;
+    }
+}
+
+impl BatchSummary {
+    /// The JSON summary already has the address and transaction hash of every deployment, so it's
+    /// printed to stdout on its own instead of the line per deployment.
+    fn replaces_output_lines(&self) -> bool {
+        self.enabled && self.json
+    }
+
+    /// Records a deployment accepted by the node. The fee actually paid is read from the receipt
+    /// of watched deployments.
+    async fn record_sent<P>(
+        &mut self,
+        provider: P,
+        index: u64,
+        watched: bool,
+        address: Option<FieldElement>,
+        transaction_hash: FieldElement,
+        max_fee: FieldElement,
+    ) where
+        P: Provider,
+    {
+        if !self.enabled {
+            return;
+        }
+
+        let (status, actual_fee) = if watched {
+            let actual_fee = provider
+                .get_transaction_receipt(transaction_hash)
+                .await
+                .ok()
+                .map(|receipt| receipt_actual_fee(&receipt));
+            (BatchStatus::Deployed, actual_fee)
+        } else {
+            (BatchStatus::Sent, None)
+        };
+
+        self.rows.push(BatchSummaryRow {
+            index,
+            address,
+            transaction_hash: Some(transaction_hash),
+            status,
+            actual_fee,
+            max_fee: Some(max_fee),
+        });
+    }
+
+    fn record(
+        &mut self,
+        index: u64,
+        status: BatchStatus,
+        address: Option<FieldElement>,
+        transaction_hash: Option<FieldElement>,
+        max_fee: Option<FieldElement>,
+    ) {
+        self.rows.push(BatchSummaryRow {
+            index,
+            address,
+            transaction_hash,
+            status,
+            actual_fee: None,
+            max_fee,
+        });
+    }
+
+    /// Deployments that were never recorded are reported as not sent.
+    fn print(&mut self, fee_formatter: &FeeFormatter) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        for index in 0..self.count {
+            if !self.rows.iter().any(|row| row.index == index) {
+                self.record(index, BatchStatus::NotSent, None, None, None);
+            }
+        }
+        self.rows.sort_by_key(|row| row.index);
+
+        let count_of =
+            |status: BatchStatus| self.rows.iter().filter(|row| row.status == status).count();
+        // Only reported if any deployment was watched, as it'd be misleading otherwise
+        let actual_fees = self
+            .rows
+            .iter()
+            .filter_map(|row| row.actual_fee)
+            .reduce(|acc, fee| acc + fee);
+        let max_fees = self
+            .rows
+            .iter()
+            .filter_map(|row| row.max_fee)
+            .fold(FieldElement::ZERO, |acc, fee| acc + fee);
+
+        if self.json {
+            let summary_json = colored_json::to_colored_json(
+                &serde_json::json!({
+                    "deployments": &self.rows,
+                    "totals": {
+                        "deployed": count_of(BatchStatus::Deployed),
+                        "sent": count_of(BatchStatus::Sent),
+                        "skipped": count_of(BatchStatus::Skipped),
+                        "failed": count_of(BatchStatus::Failed),
+                        "not_sent": count_of(BatchStatus::NotSent),
+                        "actual_fees": actual_fees.map(|fees| format!("{:#x}", fees)),
+                        "max_fees": format!("{:#x}", max_fees),
+                    },
+                }),
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{summary_json}");
+            return Ok(());
+        }
+
+        let format_felt = |felt: Option<FieldElement>| match felt {
+            Some(felt) => format!("{:#064x}", felt),
+            None => "-".to_owned(),
+        };
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                [
+                    (row.index + 1).to_string(),
+                    format_felt(row.address),
+                    format_felt(row.transaction_hash),
+                    match row.status {
+                        BatchStatus::Deployed => "deployed",
+                        BatchStatus::Sent => "sent",
+                        BatchStatus::Skipped => "skipped",
+                        BatchStatus::Failed => "failed",
+                        BatchStatus::NotSent => "not sent",
+                    }
+                    .to_owned(),
+                    match (row.actual_fee, row.max_fee) {
+                        (Some(actual_fee), _) => fee_formatter.format(actual_fee),
+                        (None, Some(max_fee)) => format!("max {}", fee_formatter.format(max_fee)),
+                        (None, None) => "-".to_owned(),
+                    },
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let header = ["#", "Address", "Transaction", "Status", "Fee"];
+        let mut widths = header.map(|column| column.len());
+        for row in rows.iter() {
+            for (width, cell) in widths.iter_mut().zip(row.iter()) {
+                *width = (*width).max(cell.len());
+            }
+        }
+        let format_row = |cells: &[String]| {
+            cells
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_owned()
+        };
+
+        eprintln!();
+        eprintln!(
+            "{}",
+            format_row(&header.map(|column| column.to_owned())).bold()
+        );
+        for row in rows.iter() {
+            eprintln!("{}", format_row(row));
+        }
+        let fees = match actual_fees {
+            Some(actual_fees) => format!(
+                "Fees paid: {}. Max fees: {}.",
+                fee_formatter.format(actual_fees),
+                fee_formatter.format(max_fees)
+            ),
+            None => format!("Max fees: {}.", fee_formatter.format(max_fees)),
+        };
+        eprintln!(
+            "{} deployed, {} sent, {} skipped, {} failed, {} not sent. {}",
+            count_of(BatchStatus::Deployed),
+            count_of(BatchStatus::Sent),
+            count_of(BatchStatus::Skipped),
+            count_of(BatchStatus::Failed),
+            count_of(BatchStatus::NotSent),
+            fees
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use starknet::core::chain_id;
+
+    use crate::mock_rpc::MockRpc;
+
+    #[test]
+    fn test_address_explanation() {
+        let salt = FieldElement::from(0x1u64);
+        let class_hash = FieldElement::from(0x2u64);
+        let ctor_args = [FieldElement::from(0x3u64), FieldElement::from(0x4u64)];
+        let account_address = FieldElement::from(0x1234u64);
+        let unique = UdcUniqueness::Unique(UdcUniqueSettings {
+            deployer_address: account_address,
+            udc_contract_address: DEFAULT_UDC_ADDRESS,
+        });
+
+        let lines = Deploy::address_explanation(salt, "--salt", class_hash, &unique, &ctor_args);
+        // The address itself may be colored
+        assert!(lines[0].starts_with("Deployment address:"));
+        assert!(lines[0].contains(&format!(
+            "{:#064x}",
+            get_udc_deployed_address(salt, class_hash, &unique, &ctor_args)
+        )));
+        for expected in [
+            format!("  Salt:                  {:#064x} (--salt)", salt),
+            "  Uniqueness:            unique".to_owned(),
+            format!("  Account address:       {:#064x}", account_address),
+            format!(
+                "  Unique salt:           {:#064x} (hash of the account address and salt)",
+                pedersen_hash(&account_address, &salt)
+            ),
+            format!("  Class hash:            {:#064x}", class_hash),
+            format!(
+                "  Constructor calldata:  {:#064x} (hash of 2 elements)",
+                compute_hash_on_elements(&ctor_args)
+            ),
+            format!("  Deployer address:      {:#064x}", DEFAULT_UDC_ADDRESS),
+            "  - a different deployer contract (--deployer)".to_owned(),
+        ] {
+            assert!(lines.contains(&expected), "{expected}");
+        }
+
+        // Without uniqueness, the salt is used as is and the deployer is zero
+        let lines = Deploy::address_explanation(
+            salt,
+            "random, so the address changes on every run",
+            class_hash,
+            &UdcUniqueness::NotUnique,
+            &[],
+        );
+        assert!(lines[0].contains(&format!(
+            "{:#064x}",
+            get_udc_deployed_address(salt, class_hash, &UdcUniqueness::NotUnique, &[])
+        )));
+        for expected in [
+            format!(
+                "  Salt:                  {:#064x} (random, so the address changes on every run)",
+                salt
+            ),
+            "  Uniqueness:            not unique (--not-unique)".to_owned(),
+            format!(
+                "  Constructor calldata:  {:#064x} (hash of 0 elements)",
+                compute_hash_on_elements(&[])
+            ),
+            format!(
+                "  Deployer address:      {:#064x} (always zero when not unique)",
+                FieldElement::ZERO
+            ),
+        ] {
+            assert!(lines.contains(&expected), "{expected}");
+        }
+        assert!(!lines.iter().any(|line| line.contains("Unique salt")
+            || line.contains("Account address")
+            || line.contains("--deployer")));
+    }
+
+    #[test]
+    fn test_equivalent_command() {
+        let to_strings = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+        let command = Deploy::equivalent_command(
+            &to_strings(&[
+                "--rpc",
+                "https://example.com/rpc?key=<masked>",
+                "--account",
+                "/home/me/my accounts/deployer.json",
+                "--keystore",
+                "it's.json",
+            ]),
+            FieldElement::from(0x1234u64),
+            FieldElement::from(1000u64),
+            &to_strings(&["0x2", ""]),
+        );
+
+        // Options come first, then the resolved salt and max fee, then the positionals. Anything
+        // a shell would split or expand is quoted
+        assert_eq!(
+            command,
+            format!(
+                "starkli deploy --rpc 'https://example.com/rpc?key=<masked>' \
+                --account '/home/me/my accounts/deployer.json' --keystore 'it'\\''s.json' \
+                --salt {:#064x} --max-fee-raw 1000 0x2 ''",
+                FieldElement::from(0x1234u64)
+            )
+        );
+    }
+
+    #[test]
+    fn test_batch_fees() {
+        let mut batch_fees = BatchFees::new(FieldElement::from(300u64));
+
+        assert!(batch_fees.allows(FieldElement::from(300u64)));
+        batch_fees.record(FieldElement::from(150u64));
+        assert!(batch_fees.allows(FieldElement::from(150u64)));
+        batch_fees.record(FieldElement::from(150u64));
+
+        // The cap is reached exactly, so nothing else fits
+        assert_eq!(batch_fees.used, FieldElement::from(300u64));
+        assert!(!batch_fees.allows(FieldElement::ONE));
+        assert!(batch_fees.allows(FieldElement::ZERO));
+
+        let batch_fees = BatchFees::new(FieldElement::from(100u64));
+        assert!(!batch_fees.allows(FieldElement::from(101u64)));
+    }
+
+    #[tokio::test]
+    async fn test_batch_summary() {
+        let rpc = MockRpc::start();
+        let mut summary = BatchSummary {
+            enabled: true,
+            json: false,
+            count: 4,
+            rows: vec![],
+        };
+
+        // Recorded out of order, as deployments are with `--parallel`
+        summary.record(
+            2,
+            BatchStatus::Failed,
+            Some(FieldElement::from(0x302u64)),
+            Some(FieldElement::from(0x202u64)),
+            Some(FieldElement::from(100u64)),
+        );
+        summary
+            .record_sent(
+                rpc.provider(),
+                1,
+                false,
+                Some(FieldElement::from(0x301u64)),
+                FieldElement::from(0x201u64),
+                FieldElement::from(100u64),
+            )
+            .await;
+        summary.record(
+            0,
+            BatchStatus::Skipped,
+            Some(FieldElement::from(0x300u64)),
+            Some(FieldElement::from(0x200u64)),
+            None,
+        );
+        summary.print(&FeeFormatter::default()).unwrap();
+
+        assert_eq!(
+            summary
+                .rows
+                .iter()
+                .map(|row| (row.index, row.status, row.transaction_hash))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, BatchStatus::Skipped, Some(FieldElement::from(0x200u64))),
+                (1, BatchStatus::Sent, Some(FieldElement::from(0x201u64))),
+                (2, BatchStatus::Failed, Some(FieldElement::from(0x202u64))),
+                (3, BatchStatus::NotSent, None),
+            ]
+        );
+        // Unwatched deployments don't touch the provider
+        assert!(rpc.requests("starknet_getTransactionReceipt").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_batch_summary_disabled() {
+        let mut summary = BatchSummary {
+            enabled: false,
+            json: true,
+            count: 2,
+            rows: vec![],
+        };
+        summary
+            .record_sent(
+                MockRpc::start().provider(),
+                0,
+                true,
+                None,
+                FieldElement::ONE,
+                FieldElement::ONE,
+            )
+            .await;
+        summary.print(&FeeFormatter::default()).unwrap();
+
+        assert!(summary.rows.is_empty());
+        assert!(!summary.replaces_output_lines());
+    }
+
+    fn batch_state_fixture() -> BatchState {
+        BatchState {
+            class_hash: FieldElement::from(0x1234u64),
+            base_salt: FieldElement::from(0x10u64),
+            deployments: [0, 2]
+                .into_iter()
+                .map(|index| BatchDeployment {
+                    index,
+                    salt: FieldElement::from(0x10 + index),
+                    address: FieldElement::from(0x300 + index),
+                    transaction_hash: FieldElement::from(0x200 + index),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_batch_state_recorded() {
+        let state = batch_state_fixture();
+
+        let skipped = state
+            .recorded(0, FieldElement::from(0x300u64))
+            .unwrap()
+            .unwrap();
+        assert_eq!(skipped.transaction_hash, FieldElement::from(0x200u64));
+        assert!(state
+            .recorded(1, FieldElement::from(0x301u64))
+            .unwrap()
+            .is_none());
+        assert!(state
+            .recorded(3, FieldElement::from(0x303u64))
+            .unwrap()
+            .is_none());
+
+        // The constructor arguments changed since the deployment was recorded
+        let err = state
+            .recorded(2, FieldElement::from(0x999u64))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.starts_with("deployment #3 was recorded at address"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_batch_state_resume() {
+        let path = std::env::temp_dir().join(format!("starkli-test-{}.json", uuid::Uuid::new_v4()));
+
+        let mut state = batch_state_fixture();
+        state.save(&path).unwrap();
+        state
+            .record(
+                &path,
+                BatchDeployment {
+                    index: 1,
+                    salt: FieldElement::from(0x11u64),
+                    address: FieldElement::from(0x301u64),
+                    transaction_hash: FieldElement::from(0x201u64),
+                },
+            )
+            .unwrap();
+
+        let resumed: BatchState =
+            serde_json::from_reader(std::fs::File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(resumed.base_salt, FieldElement::from(0x10u64));
+        for index in 0..3 {
+            assert!(resumed
+                .recorded(index, FieldElement::from(0x300 + index))
+                .unwrap()
+                .is_some());
+        }
+        assert!(resumed
+            .recorded(3, FieldElement::from(0x303u64))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_fee_cache_reusable_fee() {
+        let class_hash = FieldElement::from(0x1234u64);
+        let cache = FeeCache {
+            chain_id: chain_id::TESTNET,
+            class_hash,
+            calldata_len: 3,
+            estimated_fee: 1000,
+            estimated_at: unix_timestamp(),
+            uses: 2,
+        };
+        assert_eq!(
+            cache.reusable_fee(chain_id::TESTNET, class_hash, 3, 10),
+            Some(1000)
+        );
+
+        // Used up
+        assert_eq!(
+            cache.reusable_fee(chain_id::TESTNET, class_hash, 3, 2),
+            None
+        );
+        // Another chain, class, or amount of calldata
+        assert_eq!(
+            cache.reusable_fee(chain_id::MAINNET, class_hash, 3, 10),
+            None
+        );
+        assert_eq!(
+            cache.reusable_fee(chain_id::TESTNET, FieldElement::ONE, 3, 10),
+            None
+        );
+        assert_eq!(
+            cache.reusable_fee(chain_id::TESTNET, class_hash, 4, 10),
+            None
+        );
+
+        // Too old
+        let cache = FeeCache {
+            estimated_at: unix_timestamp() - FeeCache::MAX_AGE_SECS,
+            ..cache
+        };
+        assert_eq!(
+            cache.reusable_fee(chain_id::TESTNET, class_hash, 3, 10),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_address_prefix() {
+        assert_eq!(Deploy::parse_address_prefix("0x07AB").unwrap(), "07ab");
+        assert_eq!(Deploy::parse_address_prefix("07").unwrap(), "07");
+        assert_eq!(Deploy::parse_address_prefix("0").unwrap(), "0");
+
+        for prefix in [
+            "1",
+            "8",
+            "ff",
+            "08",
+            "09",
+            "0f",
+            "",
+            "0x",
+            "0g",
+            &"0".repeat(65),
+        ] {
+            assert!(
+                Deploy::parse_address_prefix(prefix).is_err(),
+                "prefix {:?} accepted",
+                prefix
+            );
+        }
+    }
+
+    #[test]
+    fn test_mine_salt() {
+        let class_hash = FieldElement::from(0x1234u64);
+        let ctor_args = [FieldElement::ONE];
+
+        // Every address starts with 0
+        assert_eq!(
+            Deploy::mine_salt("0", 1, class_hash, &UdcUniqueness::NotUnique, &ctor_args).unwrap(),
+            FieldElement::ZERO
+        );
+
+        let salt = Deploy::mine_salt(
+            "00",
+            10_000,
+            class_hash,
+            &UdcUniqueness::NotUnique,
+            &ctor_args,
+        )
+        .unwrap();
+        let address =
+            get_udc_deployed_address(salt, class_hash, &UdcUniqueness::NotUnique, &ctor_args);
+        assert!(format!("{:064x}", address).starts_with("00"));
+
+        // Matching 32 digits within 10 attempts is practically impossible
+        let err = Deploy::mine_salt(
+            &"0".repeat(32),
+            10,
+            class_hash,
+            &UdcUniqueness::NotUnique,
+            &ctor_args,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("after 10 attempts"));
+    }
+
+    #[tokio::test]
+    async fn test_send_parallel_nonces() {
+        let pending = (0..20u64).collect::<Vec<_>>();
+        let first_nonce = FieldElement::from(7u64);
+        let stop = AtomicBool::new(false);
+        let in_flight = std::sync::atomic::AtomicUsize::new(0);
+        let max_in_flight = std::sync::atomic::AtomicUsize::new(0);
+
+        // Later deployments complete first, so results come back out of order
+        let send = |&index: &u64, nonce: FieldElement| {
+            let (in_flight, max_in_flight) = (&in_flight, &max_in_flight);
+            async move {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20 - index)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                nonce
+            }
+        };
+        let results = Deploy::send_parallel(&pending, first_nonce, 4, &stop, &send)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(results.len(), pending.len());
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 4);
+        let mut nonces = results
+            .iter()
+            .map(|(&index, nonce, sent)| {
+                assert_eq!(*nonce, first_nonce + FieldElement::from(index));
+                assert_eq!(sent, &Some(*nonce));
+                *nonce
+            })
+            .collect::<Vec<_>>();
+        assert_ne!(nonces, {
+            let mut sorted = nonces.clone();
+            sorted.sort();
+            sorted
+        });
+        nonces.sort();
+        assert_eq!(
+            nonces,
+            (7..27u64).map(FieldElement::from).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_parallel_stop() {
+        let pending = (0..10u64).collect::<Vec<_>>();
+        let stop = AtomicBool::new(false);
+
+        // The first deployment fails while two others are in flight, so only those are sent
+        let send = |&index: &u64, _| {
+            let stop = &stop;
+            async move {
+                if index == 0 {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    stop.store(true, Ordering::Relaxed);
+                } else {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+            }
+        };
+        let results = Deploy::send_parallel(&pending, FieldElement::ZERO, 3, &stop, &send)
+            .collect::<Vec<_>>()
+            .await;
+
+        let sent = results
+            .iter()
+            .filter(|(_, _, sent)| sent.is_some())
+            .map(|(&index, _, _)| index)
+            .collect::<Vec<_>>();
+        assert_eq!(sent, [0, 1, 2]);
+        assert_eq!(results.len(), pending.len());
+    }
+
+    #[test]
+    fn test_check_salt_range() {
+        // The last salt is exactly the maximum felt value
+        assert!(Deploy::check_salt_range(FieldElement::MAX, 1).is_ok());
+        assert!(Deploy::check_salt_range(FieldElement::MAX - FieldElement::from(9u64), 10).is_ok());
+
+        assert!(Deploy::check_salt_range(FieldElement::MAX, 2).is_err());
+        assert!(
+            Deploy::check_salt_range(FieldElement::MAX - FieldElement::from(9u64), 11).is_err()
+        );
+
+        assert!(Deploy::check_salt_range(FieldElement::ZERO, 1).is_ok());
+        assert!(Deploy::check_salt_range(FieldElement::ZERO, u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_expected_nonce() {
+        // Both radixes give the same nonce
+        let expected_nonce = Deploy::parse_expected_nonce("0x1a").unwrap();
+        assert_eq!(expected_nonce, FieldElement::from(26u64));
+        assert_eq!(Deploy::parse_expected_nonce("26").unwrap(), expected_nonce);
+        assert!(Deploy::parse_expected_nonce("nonce")
+            .unwrap_err()
+            .to_string()
+            .starts_with("invalid --expect-nonce \"nonce\""));
+
+        assert!(Deploy::check_expected_nonce(FieldElement::from(26u64), expected_nonce).is_ok());
+        // The account nonce moving either way means it was used elsewhere
+        for nonce in [25u64, 27] {
+            assert_eq!(
+                Deploy::check_expected_nonce(FieldElement::from(nonce), expected_nonce)
+                    .unwrap_err()
+                    .to_string(),
+                format!(
+                    "the account nonce is {} but 26 is expected. Was the account used by someone \
+                    else?",
+                    nonce
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_deployed_address() {
+        let salt = FieldElement::from(0x1u64);
+        let class_hash = FieldElement::from(0x2u64);
+        let ctor_args = [FieldElement::from(0x3u64)];
+        let unique = UdcUniqueness::Unique(UdcUniqueSettings {
+            deployer_address: FieldElement::from(0x1234u64),
+            udc_contract_address: DEFAULT_UDC_ADDRESS,
+        });
+        let expected_address = get_udc_deployed_address(salt, class_hash, &unique, &ctor_args);
+
+        assert!(Deploy::check_deployed_address(expected_address, expected_address).is_ok());
+
+        // Any drift in the inputs moves the address away from the expected one
+        for deployed_address in [
+            get_udc_deployed_address(salt, class_hash, &unique, &[]),
+            get_udc_deployed_address(salt, class_hash, &UdcUniqueness::NotUnique, &ctor_args),
+            get_udc_deployed_address(FieldElement::ZERO, class_hash, &unique, &ctor_args),
+        ] {
+            assert_eq!(
+                Deploy::check_deployed_address(deployed_address, expected_address)
+                    .unwrap_err()
+                    .to_string(),
+                format!(
+                    "the contract would be deployed at {:#064x} instead of the expected address \
+                    {:#064x}. Check the class hash, salt, constructor arguments, and --not-unique",
+                    deployed_address, expected_address
+                )
+            );
+        }
     }
 }
@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use colored_json::{ColorMode, Output};
+use serde::Serialize;
+use starknet::core::utils::get_selector_from_name;
+
+use crate::{abi::ContractAbi, output::output, path::ExpandedPathbufParser};
+
+#[derive(Debug, Parser)]
+pub struct Entrypoints {
+    #[clap(long, help = "Print the entrypoints as JSON")]
+    json: bool,
+    #[clap(
+        long,
+        value_name = "TEXT",
+        help = "Only list entrypoints whose name contains this text, ignoring case"
+    )]
+    filter: Option<String>,
+    #[clap(
+        value_parser = ExpandedPathbufParser,
+        help = "Path to contract artifact file, or a file with a bare ABI array"
+    )]
+    file: PathBuf,
+}
+
+#[derive(Serialize)]
+struct EntrypointEntry {
+    name: String,
+    r#type: &'static str,
+    selector: String,
+    signature: String,
+}
+
+impl Entrypoints {
+    pub fn run(self) -> Result<()> {
+        let abi = ContractAbi::from_file(&self.file)?;
+        let filter = self.filter.map(|filter| filter.to_lowercase());
+
+        let entrypoints = abi
+            .entrypoints()
+            .into_iter()
+            .filter(|entrypoint| match &filter {
+                Some(filter) => entrypoint.name.to_lowercase().contains(filter),
+                None => true,
+            })
+            .map(|entrypoint| {
+                Ok(EntrypointEntry {
+                    selector: format!("{:#064x}", get_selector_from_name(&entrypoint.name)?),
+                    name: entrypoint.name,
+                    r#type: entrypoint.kind,
+                    signature: entrypoint.signature,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if self.json {
+            let entrypoints_json = colored_json::to_colored_json(
+                &serde_json::to_value(&entrypoints)?,
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{entrypoints_json}");
+            return Ok(());
+        }
+
+        if entrypoints.is_empty() {
+            output!("No entrypoints found");
+            return Ok(());
+        }
+
+        for entrypoint in entrypoints.iter() {
+            output!(
+                "{:<11} {} {}",
+                entrypoint.r#type,
+                entrypoint.selector.bright_yellow(),
+                entrypoint.signature
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+use std::{io::Read, path::PathBuf};
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use starknet::{
+    core::types::{
+        BroadcastedDeclareTransaction, BroadcastedInvokeTransaction, BroadcastedTransaction,
+        FieldElement,
+    },
+    providers::Provider,
+};
+
+use crate::{
+    fee::FeeFormatter, output::output, path::ExpandedPathbufParser, utils::parse_block_id,
+    verbosity::VerbosityArgs, ProviderArgs,
+};
+
+#[derive(Debug, Parser)]
+pub struct Estimate {
+    #[clap(flatten)]
+    provider: ProviderArgs,
+    #[clap(
+        long,
+        default_value = "pending",
+        help = "Block number, hash, or tag (latest/pending) to estimate the fee against"
+    )]
+    block_id: String,
+    #[clap(long, help = "Print the overall fee in Wei instead of Ether")]
+    raw: bool,
+    #[clap(
+        value_parser = ExpandedPathbufParser,
+        help = "Path to a signed transaction JSON file, as written by --dump-transaction. Use \
+        \"-\" to read it from stdin"
+    )]
+    file: PathBuf,
+    #[clap(flatten)]
+    verbosity: VerbosityArgs,
+}
+
+impl Estimate {
+    pub const RPC_METHODS: &'static [&'static str] = &["starknet_estimateFee"];
+
+    pub async fn run(self) -> Result<()> {
+        self.verbosity.setup_logging();
+
+        let raw_json = if self.file.as_os_str() == "-" {
+            let mut raw_json = String::new();
+            std::io::stdin().read_to_string(&mut raw_json)?;
+            raw_json
+        } else {
+            std::fs::read_to_string(&self.file)?
+        };
+        let transaction = parse_transaction(serde_json::from_str(&raw_json)?)?;
+
+        let provider = self.provider.into_provider()?;
+        let block_id = parse_block_id(&self.block_id)?;
+
+        let estimate = provider.estimate_fee_single(transaction, block_id).await?;
+
+        if self.raw {
+            output!("{}", estimate.overall_fee);
+        } else {
+            output!(
+                "{}",
+                FeeFormatter::default()
+                    .format(estimate.overall_fee.into())
+                    .bright_yellow()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Picks the transaction shape from the `type` and `version` fields first, so that malformed
+/// transactions are reported against the shape they're meant to have.
+fn parse_transaction(tx_json: Value) -> Result<BroadcastedTransaction> {
+    let tx_type = tx_json
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow::anyhow!("transaction JSON is missing the \"type\" field"))?
+        .to_owned();
+    let version = tx_json
+        .get("version")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow::anyhow!("transaction JSON is missing the \"version\" field"))?;
+    let version = FieldElement::from_hex_be(version)
+        .ok()
+        .and_then(|version| u64::try_from(version).ok())
+        .ok_or_else(|| anyhow::anyhow!("invalid transaction version: {}", version))?;
+
+    Ok(match (tx_type.as_str(), version) {
+        ("INVOKE", 0) => BroadcastedTransaction::Invoke(BroadcastedInvokeTransaction::V0(
+            parse_shape(tx_json, &tx_type, version)?,
+        )),
+        ("INVOKE", 1) => BroadcastedTransaction::Invoke(BroadcastedInvokeTransaction::V1(
+            parse_shape(tx_json, &tx_type, version)?,
+        )),
+        ("DECLARE", 1) => BroadcastedTransaction::Declare(BroadcastedDeclareTransaction::V1(
+            parse_shape(tx_json, &tx_type, version)?,
+        )),
+        ("DECLARE", 2) => BroadcastedTransaction::Declare(BroadcastedDeclareTransaction::V2(
+            parse_shape(tx_json, &tx_type, version)?,
+        )),
+        ("DEPLOY_ACCOUNT", 1) => {
+            BroadcastedTransaction::DeployAccount(parse_shape(tx_json, &tx_type, version)?)
+        }
+        ("INVOKE" | "DECLARE" | "DEPLOY_ACCOUNT", _) => {
+            anyhow::bail!("unsupported {} transaction version {}", tx_type, version)
+        }
+        _ => anyhow::bail!(
+            "unknown transaction type \"{}\". Expected INVOKE, DECLARE, or DEPLOY_ACCOUNT",
+            tx_type
+        ),
+    })
+}
+
+fn parse_shape<T>(tx_json: Value, tx_type: &str, version: u64) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    serde_json::from_value(tx_json)
+        .map_err(|err| anyhow::anyhow!("invalid {} v{} transaction: {}", tx_type, version, err))
+}
@@ -0,0 +1,305 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use colored_json::{ColorMode, Output};
+use serde_json::{Map, Value};
+use starknet::{
+    core::{
+        types::{BlockId, EmittedEvent, EventFilter, FieldElement},
+        utils::get_selector_from_name,
+    },
+    providers::Provider,
+};
+
+use crate::{
+    abi::{AbiArgs, AbiSource, ContractAbi, DecodedEvent},
+    address_book::AliasArgs,
+    chain_id::ChainIdSource,
+    decode::FeltDecoder,
+    output::output,
+    utils::parse_block_id,
+    verbosity::{progress, warning, VerbosityArgs},
+    ProviderArgs,
+};
+
+#[derive(Debug, Parser)]
+pub struct Events {
+    #[clap(flatten)]
+    provider: ProviderArgs,
+    #[clap(long, help = "Only include events emitted by this contract")]
+    address: Option<String>,
+    #[clap(
+        long,
+        num_args = 1..,
+        help = "Keys to match, one value per key position. A position can list alternatives \
+        separated by commas, or be \"*\" to match anything. Values that aren't felts are taken \
+        as event names"
+    )]
+    keys: Vec<String>,
+    #[clap(
+        long,
+        help = "First block to scan: a number, a hash, \"latest\", or \"pending\""
+    )]
+    from_block: String,
+    #[clap(
+        long,
+        default_value = "latest",
+        help = "Last block to scan: a number, a hash, \"latest\", or \"pending\""
+    )]
+    to_block: String,
+    #[clap(long, help = "Stop after this many events")]
+    limit: Option<u64>,
+    #[clap(
+        long,
+        default_value = "100",
+        help = "Number of events requested per starknet_getEvents page"
+    )]
+    chunk_size: u64,
+    #[clap(
+        long,
+        help = "Decode events against the ABI of the emitting contracts where possible"
+    )]
+    decode: bool,
+    #[clap(flatten)]
+    abi: AbiArgs,
+    #[clap(long, help = "Print the events as JSON")]
+    json: bool,
+    #[clap(flatten)]
+    alias: AliasArgs,
+    #[clap(flatten)]
+    verbosity: VerbosityArgs,
+}
+
+impl Events {
+    pub const RPC_METHODS: &'static [&'static str] = &[
+        "starknet_chainId",
+        "starknet_getEvents",
+        "starknet_getClassAt",
+        "starknet_getClass",
+        "starknet_getClassHashAt",
+        "starknet_call",
+    ];
+
+    pub async fn run(self) -> Result<()> {
+        self.verbosity.setup_logging();
+
+        if self.chunk_size == 0 {
+            anyhow::bail!("--chunk-size must be at least 1");
+        }
+        if self.limit == Some(0) {
+            anyhow::bail!("--limit must be at least 1");
+        }
+
+        let from_block = parse_block_id(&self.from_block).map_err(|err| {
+            anyhow::anyhow!("invalid --from-block \"{}\": {}", self.from_block, err)
+        })?;
+        let to_block = parse_block_id(&self.to_block)
+            .map_err(|err| anyhow::anyhow!("invalid --to-block \"{}\": {}", self.to_block, err))?;
+        if let (BlockId::Number(from), BlockId::Number(to)) = (&from_block, &to_block) {
+            if from > to {
+                anyhow::bail!("--from-block {} is after --to-block {}", from, to);
+            }
+        }
+
+        let provider = Arc::new(self.provider.into_provider()?);
+        let abi_source = self.abi.into_source()?;
+        // Supplying an ABI only makes sense for decoding
+        let decode = self.decode || abi_source.is_local();
+
+        let felt_decoder = FeltDecoder::new(self.alias.into_resolver(provider.clone())?);
+
+        let address = match &self.address {
+            Some(address) => Some(
+                felt_decoder
+                    .decode_single_with_addr_fallback(address)
+                    .await?,
+            ),
+            None => None,
+        };
+
+        let mut keys = vec![];
+        for position in self.keys.iter() {
+            keys.push(Self::parse_key_position(&felt_decoder, position).await?);
+        }
+
+        let filter = EventFilter {
+            from_block: Some(from_block),
+            to_block: Some(to_block),
+            address,
+            keys: if keys.is_empty() { None } else { Some(keys) },
+        };
+
+        progress!(
+            "Scanning blocks {} to {} for events...",
+            self.from_block,
+            self.to_block
+        );
+
+        let mut abis: HashMap<FieldElement, Option<ContractAbi>> = HashMap::new();
+        let mut events_json = vec![];
+        let mut event_count: u64 = 0;
+        let mut truncated = false;
+        let mut continuation_token = None;
+        'pages: loop {
+            let page = provider
+                .get_events(filter.clone(), continuation_token, self.chunk_size)
+                .await?;
+
+            for event in page.events.into_iter() {
+                if self.limit.map_or(false, |limit| event_count >= limit) {
+                    truncated = true;
+                    break 'pages;
+                }
+
+                let decoded = if decode {
+                    Self::decode_event(&provider, &abi_source, &mut abis, &event).await
+                } else {
+                    None
+                };
+
+                if self.json {
+                    let mut event_json = serde_json::to_value(&event)?;
+                    if let (Some(decoded), Some(event_json)) = (decoded, event_json.as_object_mut())
+                    {
+                        let mut decoded_json = Map::new();
+                        decoded_json.insert("name".into(), Value::String(decoded.name));
+                        decoded_json.insert("fields".into(), Value::Object(decoded.fields));
+
+                        event_json.insert("decoded".into(), Value::Object(decoded_json));
+                    }
+                    events_json.push(event_json);
+                } else {
+                    // Printed as the pages come in, as scanning a large range can take a while
+                    if event_count > 0 {
+                        output!("");
+                    }
+                    Self::print_event(&event, decoded.as_ref())?;
+                }
+
+                event_count += 1;
+            }
+
+            match page.continuation_token {
+                Some(token) => continuation_token = Some(token),
+                None => break,
+            }
+        }
+
+        if self.json {
+            let events_json = colored_json::to_colored_json(
+                &Value::Array(events_json),
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{events_json}");
+        } else if event_count == 0 {
+            progress!("No matching event found in the scanned blocks.");
+        }
+
+        if truncated {
+            progress!(
+                "Stopped after {} events. Use a larger --limit to see more.",
+                event_count
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Parses one `--keys` value into the alternatives accepted at that position. An empty list
+    /// matches any key.
+    async fn parse_key_position<S>(
+        felt_decoder: &FeltDecoder<S>,
+        position: &str,
+    ) -> Result<Vec<FieldElement>>
+    where
+        S: ChainIdSource,
+    {
+        if position == "*" {
+            return Ok(vec![]);
+        }
+
+        let mut alternatives = vec![];
+        for key in position.split(',') {
+            let key = key.trim();
+            match felt_decoder.decode(key).await {
+                Ok(decoded) => match decoded.as_slice() {
+                    [single] => alternatives.push(*single),
+                    _ => anyhow::bail!("key \"{}\" must be a single felt", key),
+                },
+                // Event names are the most common first key
+                Err(err) => match get_selector_from_name(key) {
+                    Ok(selector)
+                        if !key.is_empty()
+                            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+                    {
+                        alternatives.push(selector)
+                    }
+                    _ => anyhow::bail!("invalid key \"{}\": {}", key, err),
+                },
+            }
+        }
+
+        Ok(alternatives)
+    }
+
+    /// Decodes the event against the ABI of its emitter, fetching each ABI only once.
+    async fn decode_event<P>(
+        provider: P,
+        abi_source: &AbiSource,
+        abis: &mut HashMap<FieldElement, Option<ContractAbi>>,
+        event: &EmittedEvent,
+    ) -> Option<DecodedEvent>
+    where
+        P: Provider + Sync,
+        P::Error: 'static,
+    {
+        if let Entry::Vacant(entry) = abis.entry(event.from_address) {
+            let abi = match abi_source.load(&provider, event.from_address).await {
+                Ok(abi) => abi,
+                Err(err) => {
+                    warning!(
+                        "WARNING: unable to fetch ABI for {:#064x}: {}",
+                        event.from_address,
+                        err
+                    );
+                    None
+                }
+            };
+            entry.insert(abi);
+        }
+
+        abis[&event.from_address]
+            .as_ref()
+            .and_then(|abi| abi.decode_event(&event.keys, &event.data))
+    }
+
+    fn print_event(event: &EmittedEvent, decoded: Option<&DecodedEvent>) -> Result<()> {
+        output!("Block:       {}", event.block_number);
+        output!("Transaction: {:#064x}", event.transaction_hash);
+        output!("From:        {:#064x}", event.from_address);
+        if let Some(decoded) = decoded {
+            output!("Event:       {}", decoded.name.bright_yellow());
+            for (name, value) in decoded.fields.iter() {
+                output!("  {}: {}", name, serde_json::to_string(value)?);
+            }
+        } else {
+            output!("Keys:        {}", join_felts(&event.keys));
+            output!("Data:        {}", join_felts(&event.data));
+        }
+
+        Ok(())
+    }
+}
+
+fn join_felts(felts: &[FieldElement]) -> String {
+    felts
+        .iter()
+        .map(|felt| format!("{:#064x}", felt))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,244 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use colored_json::{ColorMode, Output};
+use serde::Serialize;
+use starknet::{
+    core::{
+        types::{BlockId, BlockTag, EmittedEvent, EventFilter, FieldElement},
+        utils::{get_udc_deployed_address, UdcUniqueSettings, UdcUniqueness},
+    },
+    macros::selector,
+    providers::Provider,
+};
+
+use crate::{
+    address_book::AliasArgs,
+    decode::FeltDecoder,
+    output::output,
+    utils::parse_salt,
+    verbosity::{progress, VerbosityArgs},
+    ProviderArgs,
+};
+
+use super::deploy::DEFAULT_UDC_ADDRESS;
+
+/// Number of events requested per `starknet_getEvents` page.
+const EVENTS_CHUNK_SIZE: u64 = 100;
+
+#[derive(Debug, Parser)]
+pub struct FindDeployment {
+    #[clap(flatten)]
+    provider: ProviderArgs,
+    #[clap(long, help = "Class hash of the deployed contract")]
+    class_hash: String,
+    #[clap(
+        long,
+        help = "Salt used for the deployment, in hex (0x-prefixed) or decimal"
+    )]
+    salt: String,
+    #[clap(
+        long,
+        help = "Address of the account that sent the deployment, for computing the expected \
+        address of a unique deployment"
+    )]
+    sender: Option<String>,
+    #[clap(long, help = "The deployment was made with --not-unique")]
+    not_unique: bool,
+    #[clap(
+        long,
+        default_value = "1000",
+        help = "Number of most recent blocks to scan for deployment events"
+    )]
+    blocks: u64,
+    #[clap(long, help = "Print the found deployments as JSON")]
+    json: bool,
+    #[clap(help = "Constructor arguments of the deployment, for computing the expected address")]
+    ctor_args: Vec<String>,
+    #[clap(flatten)]
+    alias: AliasArgs,
+    #[clap(flatten)]
+    verbosity: VerbosityArgs,
+}
+
+pub(crate) struct ContractDeployed<'a> {
+    pub address: FieldElement,
+    pub deployer: FieldElement,
+    pub unique: bool,
+    pub calldata: &'a [FieldElement],
+}
+
+#[derive(Serialize)]
+struct FoundDeployment {
+    address: String,
+    transaction_hash: String,
+    block_number: u64,
+    deployer: String,
+    unique: bool,
+    constructor_calldata: Vec<String>,
+}
+
+impl FindDeployment {
+    pub const RPC_METHODS: &'static [&'static str] = &[
+        "starknet_chainId",
+        "starknet_blockNumber",
+        "starknet_getEvents",
+    ];
+
+    pub async fn run(self) -> Result<()> {
+        self.verbosity.setup_logging();
+
+        if self.blocks == 0 {
+            anyhow::bail!("--blocks must be at least 1");
+        }
+
+        let provider = Arc::new(self.provider.into_provider()?);
+        let felt_decoder = FeltDecoder::new(self.alias.into_resolver(provider.clone())?);
+
+        let class_hash = FieldElement::from_hex_be(&self.class_hash)?;
+        let salt = parse_salt(&self.salt)?;
+
+        let mut ctor_args = vec![];
+        for element in self.ctor_args.iter() {
+            ctor_args.append(&mut felt_decoder.decode(element).await?);
+        }
+
+        // Unique deployments mix the sender into the salt, so it must be known
+        let uniqueness = match (&self.sender, self.not_unique) {
+            (_, true) => Some(UdcUniqueness::NotUnique),
+            (Some(sender), false) => Some(UdcUniqueness::Unique(UdcUniqueSettings {
+                deployer_address: felt_decoder
+                    .decode_single_with_addr_fallback(sender)
+                    .await?,
+                udc_contract_address: DEFAULT_UDC_ADDRESS,
+            })),
+            (None, false) => None,
+        };
+        let expected_address = uniqueness
+            .map(|uniqueness| get_udc_deployed_address(salt, class_hash, &uniqueness, &ctor_args));
+
+        match expected_address {
+            Some(address) => progress!(
+                "Expected address: {}",
+                format!("{:#064x}", address).bright_yellow()
+            ),
+            None => progress!(
+                "Pass --sender or --not-unique to compute the expected address. Scanning events \
+                only."
+            ),
+        }
+
+        let latest_block = provider.block_number().await?;
+        let first_block = latest_block.saturating_sub(self.blocks - 1);
+
+        progress!(
+            "Scanning blocks {} to {} for UDC deployments of class {:#064x} with salt {:#064x}...",
+            first_block,
+            latest_block,
+            class_hash,
+            salt
+        );
+
+        // Events from the pending block don't have a block number, so they're left out
+        let filter = EventFilter {
+            from_block: Some(BlockId::Number(first_block)),
+            to_block: Some(BlockId::Tag(BlockTag::Latest)),
+            address: Some(DEFAULT_UDC_ADDRESS),
+            keys: Some(vec![vec![selector!("ContractDeployed")]]),
+        };
+
+        let mut deployments = vec![];
+        let mut continuation_token = None;
+        loop {
+            let page = provider
+                .get_events(filter.clone(), continuation_token, EVENTS_CHUNK_SIZE)
+                .await?;
+
+            deployments.extend(
+                page.events
+                    .iter()
+                    .filter_map(|event| Self::match_deployment(event, class_hash, salt)),
+            );
+
+            match page.continuation_token {
+                Some(token) => continuation_token = Some(token),
+                None => break,
+            }
+        }
+
+        if self.json {
+            let deployments_json = colored_json::to_colored_json(
+                &serde_json::to_value(&deployments)?,
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{deployments_json}");
+        } else if deployments.is_empty() {
+            progress!(
+                "No matching deployment found in the scanned blocks. Use --blocks to scan further \
+                back."
+            );
+        } else {
+            for (ind_deployment, deployment) in deployments.iter().enumerate() {
+                if ind_deployment > 0 {
+                    output!("");
+                }
+
+                output!("Address:     {}", deployment.address.bright_yellow());
+                output!("Transaction: {}", deployment.transaction_hash);
+                output!("Block:       {}", deployment.block_number);
+                output!("Deployer:    {}", deployment.deployer);
+                output!("Unique:      {}", deployment.unique);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn match_deployment(
+        event: &EmittedEvent,
+        class_hash: FieldElement,
+        salt: FieldElement,
+    ) -> Option<FoundDeployment> {
+        let deployed = parse_contract_deployed(&event.data, class_hash, salt)?;
+
+        Some(FoundDeployment {
+            address: format!("{:#064x}", deployed.address),
+            transaction_hash: format!("{:#064x}", event.transaction_hash),
+            block_number: event.block_number,
+            deployer: format!("{:#064x}", deployed.deployer),
+            unique: deployed.unique,
+            constructor_calldata: deployed
+                .calldata
+                .iter()
+                .map(|element| format!("{:#064x}", element))
+                .collect(),
+        })
+    }
+}
+
+/// Decodes the data of `ContractDeployed(address, deployer, unique, classHash, calldata_len,
+/// calldata..., salt)` if it's a deployment of `class_hash` with `salt`.
+pub(crate) fn parse_contract_deployed(
+    data: &[FieldElement],
+    class_hash: FieldElement,
+    salt: FieldElement,
+) -> Option<ContractDeployed<'_>> {
+    let (event_salt, data) = data.split_last()?;
+    let (header, calldata) = (data.get(..5)?, &data[5..]);
+
+    if header[3] != class_hash
+        || *event_salt != salt
+        || u64::try_from(header[4]).ok()? != calldata.len() as u64
+    {
+        return None;
+    }
+
+    Some(ContractDeployed {
+        address: header[0],
+        deployer: header[1],
+        unique: header[2] != FieldElement::ZERO,
+        calldata,
+    })
+}
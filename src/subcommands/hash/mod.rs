@@ -0,0 +1,98 @@
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use colored_json::{ColorMode, Output};
+use serde_json::json;
+use starknet::core::types::FieldElement;
+
+use crate::{
+    address_book::AddressBookResolver, chain_id::NoChainIdSource, decode::FeltDecoder,
+    output::output, utils::expand_stdin_args,
+};
+
+mod pedersen;
+use pedersen::Pedersen;
+
+mod poseidon;
+use poseidon::Poseidon;
+
+#[derive(Debug, Parser)]
+pub struct Hash {
+    #[clap(subcommand)]
+    command: Subcommands,
+}
+
+#[derive(Debug, Subcommand)]
+enum Subcommands {
+    #[clap(about = "Compute the Pedersen hash of field elements")]
+    Pedersen(Pedersen),
+    #[clap(about = "Compute the Poseidon hash of field elements")]
+    Poseidon(Poseidon),
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct HashArgs {
+    #[clap(
+        long,
+        help = "Hash exactly 2 elements with the raw hash function instead of hashing them as an \
+        array"
+    )]
+    raw: bool,
+    #[clap(long, help = "Print the elements and the hash as JSON")]
+    json: bool,
+    #[clap(help = "Elements to hash. Pass \"-\" to read them from stdin")]
+    elements: Vec<String>,
+}
+
+impl Hash {
+    pub async fn run(self) -> Result<()> {
+        match self.command {
+            Subcommands::Pedersen(cmd) => cmd.run().await,
+            Subcommands::Poseidon(cmd) => cmd.run().await,
+        }
+    }
+}
+
+impl HashArgs {
+    /// Decodes the elements the same way as calldata, with the exception of address book names.
+    pub async fn decode_elements(&self) -> Result<Vec<FieldElement>> {
+        let felt_decoder = FeltDecoder::new(AddressBookResolver::new(NoChainIdSource));
+
+        let mut elements = vec![];
+        for element in expand_stdin_args(&self.elements, false)?.iter() {
+            elements.append(&mut felt_decoder.decode(element).await?);
+        }
+
+        if self.raw && elements.len() != 2 {
+            anyhow::bail!(
+                "--raw expects exactly 2 elements but found {}",
+                elements.len()
+            );
+        }
+
+        Ok(elements)
+    }
+
+    pub fn raw(&self) -> bool {
+        self.raw
+    }
+
+    pub fn print_hash(&self, elements: &[FieldElement], hash: FieldElement) -> Result<()> {
+        if self.json {
+            let result_json = colored_json::to_colored_json(
+                &json!({
+                    "elements": elements
+                        .iter()
+                        .map(|element| format!("{:#064x}", element))
+                        .collect::<Vec<_>>(),
+                    "hash": format!("{:#064x}", hash),
+                }),
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{result_json}");
+        } else {
+            output!("{:#064x}", hash);
+        }
+
+        Ok(())
+    }
+}
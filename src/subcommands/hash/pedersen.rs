@@ -0,0 +1,80 @@
+use anyhow::Result;
+use clap::Parser;
+use starknet::core::{
+    crypto::{compute_hash_on_elements, pedersen_hash},
+    types::FieldElement,
+};
+
+use super::HashArgs;
+
+#[derive(Debug, Parser)]
+pub struct Pedersen {
+    #[clap(flatten)]
+    args: HashArgs,
+}
+
+impl Pedersen {
+    pub async fn run(self) -> Result<()> {
+        let elements = self.args.decode_elements().await?;
+        let hash = Self::hash(self.args.raw(), &elements);
+
+        self.args.print_hash(&elements, hash)
+    }
+
+    /// Arrays are hashed the way Starknet does for transaction hashes and contract addresses:
+    /// chained from 0, with the array length hashed in last.
+    fn hash(raw: bool, elements: &[FieldElement]) -> FieldElement {
+        if raw {
+            pedersen_hash(&elements[0], &elements[1])
+        } else {
+            compute_hash_on_elements(elements)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use starknet::macros::felt;
+
+    async fn hash_of(args: &[&str]) -> FieldElement {
+        let cmd = Pedersen::try_parse_from(std::iter::once("pedersen").chain(args.iter().copied()))
+            .unwrap();
+        let elements = cmd.args.decode_elements().await.unwrap();
+        Pedersen::hash(cmd.args.raw(), &elements)
+    }
+
+    #[tokio::test]
+    async fn test_pedersen_raw() {
+        // Generated with `cairo-lang`
+        assert_eq!(
+            hash_of(&[
+                "--raw",
+                "0x03d937c035c878245caf64531a5756109c53068da139362728feb561405371cb",
+                "0x0208a0a10250e382e1e4bbe2880906c2791bf6275695e02fbbc6aeff9cd8b31a",
+            ])
+            .await,
+            felt!("0x030e480bed5fe53fa909cc0f8c4d99b8f9f2c016be4c41e13a4848797979c662")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pedersen_array() {
+        // Generated with `cairo-lang`
+        assert_eq!(
+            hash_of(&["0xaa", "0xbb", "0xcc", "0xdd"]).await,
+            felt!("0x025cde77210b1c223b2c6e69db6e9021aa1599177ab177474d5326cd2a62cb69")
+        );
+        assert_eq!(
+            hash_of(&[]).await,
+            felt!("0x049ee3eba8c1600700ee1b87eb599f16716b0b1022947733551fde4050ca6804")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pedersen_raw_needs_2_elements() {
+        let cmd = Pedersen::try_parse_from(["pedersen", "--raw", "0x1", "0x2", "0x3"]).unwrap();
+        assert!(cmd.args.decode_elements().await.is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use clap::Parser;
+use starknet::core::types::FieldElement;
+use starknet_crypto::{poseidon_hash, poseidon_hash_many};
+
+use super::HashArgs;
+
+#[derive(Debug, Parser)]
+pub struct Poseidon {
+    #[clap(flatten)]
+    args: HashArgs,
+}
+
+impl Poseidon {
+    pub async fn run(self) -> Result<()> {
+        let elements = self.args.decode_elements().await?;
+        let hash = Self::hash(self.args.raw(), &elements);
+
+        self.args.print_hash(&elements, hash)
+    }
+
+    /// Arrays are hashed like `poseidon_hash_span` in Cairo.
+    fn hash(raw: bool, elements: &[FieldElement]) -> FieldElement {
+        if raw {
+            poseidon_hash(elements[0], elements[1])
+        } else {
+            poseidon_hash_many(elements)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use starknet::macros::felt;
+
+    async fn hash_of(args: &[&str]) -> FieldElement {
+        let cmd = Poseidon::try_parse_from(std::iter::once("poseidon").chain(args.iter().copied()))
+            .unwrap();
+        let elements = cmd.args.decode_elements().await.unwrap();
+        Poseidon::hash(cmd.args.raw(), &elements)
+    }
+
+    #[tokio::test]
+    async fn test_poseidon_raw() {
+        // Generated with `cairo-lang` v0.11.0
+        assert_eq!(
+            hash_of(&[
+                "--raw",
+                "0xb662f9017fa7956fd70e26129b1833e10ad000fd37b4d9f4e0ce6884b7bbe",
+                "0x1fe356bf76102cdae1bfbdc173602ead228b12904c00dad9cf16e035468bea",
+            ])
+            .await,
+            felt!("0x75540825a6ecc5dc7d7c2f5f868164182742227f1367d66c43ee51ec7937a81")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_poseidon_array() {
+        // Generated with `cairo-lang` v0.11.0
+        assert_eq!(
+            hash_of(&[
+                "0x9bf52404586087391c5fbb42538692e7ca2149bac13c145ae4230a51a6fc47",
+                "0x40304159ee9d2d611120fbd7c7fb8020cc8f7a599bfa108e0e085222b862c0",
+                "0x46286e4f3c450761d960d6a151a9c0988f9e16f8a48d4c0a85817c009f806a",
+            ])
+            .await,
+            felt!("0x1ec38b38dc88bac7b0ed6ff6326f975a06a59ac601b417745fd412a5d38e4f7")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_poseidon_raw_needs_2_elements() {
+        let cmd = Poseidon::try_parse_from(["poseidon", "--raw", "0x1"]).unwrap();
+        assert!(cmd.args.decode_elements().await.is_err());
+    }
+}
@@ -1,26 +1,45 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 use anyhow::Result;
 use clap::Parser;
 use colored::Colorize;
+use colored_json::{ColorMode, Output};
 use starknet::{
-    accounts::{Account, Call, SingleOwnerAccount},
-    core::{
-        types::{BlockId, BlockTag, FieldElement},
-        utils::get_selector_from_name,
-    },
+    accounts::{Account, Call, ConnectedAccount, SingleOwnerAccount},
+    core::{types::FieldElement, utils::get_selector_from_name},
     providers::Provider,
+    signers::Signer,
 };
 
 use crate::{
+    abi::{format_suggestions, ContractAbi},
     account::{AccountConfig, DeploymentStatus},
-    address_book::AddressBookResolver,
+    address_book::AliasArgs,
+    chain_id::ChainIdSource,
     decode::FeltDecoder,
-    fee::{FeeArgs, FeeSetting},
+    explorer::ExplorerArgs,
+    fee::{
+        check_fee_ceiling, resolve_fee_token, warn_on_low_fee, FeeArgs, FeeSetting,
+        DEFAULT_FEE_BUFFER,
+    },
+    interactive,
+    metrics::MetricsArgs,
+    output::output,
     path::ExpandedPathbufParser,
+    replay_log::{ReplayLogArgs, ReplayMode, ReplayRecord},
     signer::SignerArgs,
-    utils::watch_tx,
-    verbosity::VerbosityArgs,
+    spinner::Spinner,
+    utils::{
+        check_fee_balance, check_signer_public_key, dump_transaction, parse_block_id,
+        print_parties, read_stdin_args, send_execution, watch_tx, FeltFormat, Finality,
+        NonceRetryArgs, WatchConditions,
+    },
+    verbosity::{progress, warning, VerbosityArgs},
     ProviderArgs,
 };
 
@@ -34,129 +53,538 @@ pub struct Invoke {
         long,
         env = "STARKNET_ACCOUNT",
         value_parser = ExpandedPathbufParser,
-        help = "Path to account config JSON file"
+        required_unless_present = "wallet",
+        help = "Path to account config JSON file, \"-\" to read it from stdin, the config JSON \
+        itself, or the name of an account in the address book"
     )]
-    account: PathBuf,
+    account: Option<PathBuf>,
+    #[clap(
+        long,
+        default_value = "pending",
+        help = "Block number, hash, or tag (latest/pending) the account fetches its nonce and \
+        estimates fees against"
+    )]
+    account_block: String,
     #[clap(flatten)]
     fee: FeeArgs,
+    #[clap(flatten)]
+    explorer: ExplorerArgs,
+    #[clap(flatten)]
+    nonce_retry: NonceRetryArgs,
+    #[clap(flatten)]
+    replay_log: ReplayLogArgs,
+    #[clap(flatten)]
+    metrics: MetricsArgs,
     #[clap(long, help = "Wait for the transaction to confirm")]
     watch: bool,
+    #[clap(
+        long,
+        default_value = "0",
+        requires = "watch",
+        help = "Number of blocks to wait for on top of the one including the transaction when \
+                watching"
+    )]
+    min_confirmations: u64,
+    #[clap(
+        long,
+        value_enum,
+        requires = "watch",
+        help = "Transaction status to wait for when watching, on top of any confirmations"
+    )]
+    finality: Option<Finality>,
+    #[clap(
+        long,
+        value_name = "SECONDS",
+        requires = "watch",
+        help = "Give up watching after this many seconds, listing the conditions still pending"
+    )]
+    watch_timeout: Option<u64>,
+    #[clap(
+        long,
+        help = "Print only the transaction hash to stdout right after broadcasting. With --watch, \
+        the transaction is still watched afterwards"
+    )]
+    broadcast_only: bool,
+    #[clap(
+        long,
+        value_enum,
+        default_value_t,
+        requires = "broadcast_only",
+        help = "Output format of the transaction hash printed with --broadcast-only"
+    )]
+    felt_format: FeltFormat,
+    #[clap(
+        long,
+        value_name = "PATH",
+        value_parser = ExpandedPathbufParser,
+        help = "Write the signed transaction as JSON to a file right before sending it. Use \"-\" \
+        to write to stderr instead"
+    )]
+    dump_transaction: Option<PathBuf>,
+    #[clap(
+        long,
+        conflicts_with_all = ["watch", "broadcast_only", "estimate_only", "retry_on_nonce_error"],
+        help = "Sign the transaction without sending it, and print only its hash and signature \
+        as JSON, e.g. for broadcasting it elsewhere"
+    )]
+    signature_only: bool,
+    #[clap(
+        long,
+        help = "Skip checking that the signer's public key matches the one set on the account \
+        contract"
+    )]
+    skip_signer_check: bool,
+    #[clap(
+        long,
+        help = "Skip checking that the provider is on the chain recorded in the account config"
+    )]
+    skip_chain_check: bool,
+    #[clap(
+        long,
+        help = "Check that each target contract exposes the function being invoked by fetching \
+        its ABI before sending"
+    )]
+    check_functions: bool,
+    #[clap(
+        long,
+        requires = "check_functions",
+        help = "Fail instead of warning when a function is not found by --check-functions"
+    )]
+    strict: bool,
+    #[clap(
+        long,
+        help = "List every address the transaction involves before sending it, with address book \
+        names where known"
+    )]
+    show_parties: bool,
     #[clap(help = "One or more contract calls. See documentation for more details")]
     calls: Vec<String>,
+    #[clap(
+        long,
+        help = "Read additional call arguments from stdin, one per line, appended after the ones \
+        given on the command line"
+    )]
+    args_stdin: bool,
+    #[clap(
+        long,
+        conflicts_with = "args_stdin",
+        help = "Prompt for the arguments of each call by name and type, as found in the contract \
+        ABI, instead of taking calldata from the command line"
+    )]
+    interactive: bool,
+    #[clap(flatten)]
+    alias: AliasArgs,
     #[clap(flatten)]
     verbosity: VerbosityArgs,
 }
 
 impl Invoke {
+    pub const RPC_METHODS: &'static [&'static str] = &[
+        "starknet_chainId",
+        "starknet_getNonce",
+        "starknet_call",
+        "starknet_getClassAt",
+        "starknet_estimateFee",
+        "starknet_addInvokeTransaction",
+        "starknet_getTransactionByHash",
+        "starknet_getTransactionReceipt",
+        "starknet_blockNumber",
+    ];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
+        let watch_conditions = self.watch_conditions();
 
-        let fee_setting = self.fee.into_setting()?;
+        let fee_formatter = self.fee.formatter();
+        let skip_balance_check = self.fee.skip_balance_check();
+        let low_fee_threshold = self.fee.low_fee_threshold();
+        let max_fee_ceiling = self.fee.max_fee_ceiling()?;
+        let mut fee_buffer = self.fee.fee_buffer()?;
+        let explorer = self.explorer.into_explorer()?;
+        let replay_log = self.replay_log.into_replay_log("invoke")?;
+        let mut metrics = self.metrics.into_metrics("invoke");
 
-        let provider = Arc::new(self.provider.into_provider());
-        let felt_decoder = FeltDecoder::new(AddressBookResolver::new(provider.clone()));
+        let provider = Arc::new(self.provider.into_provider()?);
+        let fee_setting = self.fee.into_setting(&provider).await?;
 
-        if !self.account.exists() {
-            anyhow::bail!("account config file not found");
-        }
+        // TODO: refactor account & signer loading
 
-        // Parses and resolves the calls
-        let calls = {
-            // TODO: show more helpful message
-            let unexpected_end_of_args = || anyhow::anyhow!("unexpected end of arguments");
+        if self.interactive {
+            interactive::ensure_terminal()?;
+            if self.account.as_deref() == Some(Path::new("-")) {
+                anyhow::bail!("the account config can't be read from stdin with --interactive");
+            }
+        }
+        let account_config =
+            AccountConfig::load_from_args(self.account.as_deref(), &self.signer, &provider).await?;
 
-            let mut buffer = vec![];
+        let account_address = match &account_config.deployment {
+            DeploymentStatus::Undeployed(_) => anyhow::bail!("account not deployed"),
+            DeploymentStatus::Deployed(inner) => inner.address,
+        };
 
-            let mut arg_iter = self.calls.into_iter();
+        let felt_decoder = FeltDecoder::new(self.alias.into_resolver(provider.clone())?)
+            .with_account_address(account_address);
 
-            while let Some(first_arg) = arg_iter.next() {
-                let contract_address = felt_decoder
-                    .decode_single_with_addr_fallback(&first_arg)
-                    .await?;
+        // `-` is a call separator here, so stdin can only be opted into with the flag
+        let mut raw_calls = self.calls;
+        if self.args_stdin {
+            raw_calls.extend(read_stdin_args()?);
+        }
 
-                let next_arg = arg_iter.next().ok_or_else(unexpected_end_of_args)?;
-                let selector = get_selector_from_name(&next_arg)?;
+        // Function names are kept for the function check
+        let (mut calls, function_names) = parse_calls(&felt_decoder, raw_calls).await?;
 
-                let mut calldata = vec![];
-                for arg in &mut arg_iter {
-                    let mut arg = match arg.as_str() {
-                        "/" | "-" | "\\" => break,
-                        _ => felt_decoder.decode(&arg).await?,
-                    };
-                    calldata.append(&mut arg);
+        if self.interactive {
+            for (call, function_name) in calls.iter_mut().zip(function_names.iter()) {
+                if !call.calldata.is_empty() {
+                    anyhow::bail!("calldata can't be given on the command line with --interactive");
                 }
-
-                buffer.push(Call {
-                    to: contract_address,
-                    selector,
-                    calldata,
-                });
+                call.calldata =
+                    Self::prompt_calldata(&provider, &felt_decoder, call.to, function_name).await?;
             }
-
-            buffer
-        };
+        }
 
         if calls.is_empty() {
             anyhow::bail!("empty execution");
         }
 
-        // TODO: refactor account & signer loading
-
-        let account_config: AccountConfig =
-            serde_json::from_reader(&mut std::fs::File::open(&self.account)?)?;
+        if self.interactive {
+            for call in calls.iter() {
+                eprintln!(
+                    "Call to {:#064x}: [{}]",
+                    call.to,
+                    call.calldata
+                        .iter()
+                        .map(|element| format!("{:#x}", element))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+            if !interactive::confirm("Send with this calldata?")? {
+                anyhow::bail!("invoke cancelled");
+            }
+        }
 
-        let account_address = match account_config.deployment {
-            DeploymentStatus::Undeployed(_) => anyhow::bail!("account not deployed"),
-            DeploymentStatus::Deployed(inner) => inner.address,
-        };
+        if self.check_functions {
+            Self::check_functions(&provider, &calls, &function_names, self.strict).await?;
+        }
 
         let chain_id = provider.chain_id().await?;
 
+        if !self.skip_chain_check {
+            account_config.check_chain_id(chain_id)?;
+        }
+        fee_buffer
+            .read_balance_budget(&provider, chain_id, account_address)
+            .await?;
+
         let signer = Arc::new(self.signer.into_signer()?);
+        if !self.skip_signer_check {
+            check_signer_public_key(
+                &provider,
+                account_address,
+                signer.get_public_key().await?.scalar(),
+            )
+            .await?;
+        }
         let mut account =
             SingleOwnerAccount::new(provider.clone(), signer, account_address, chain_id);
-        account.set_block_id(BlockId::Tag(BlockTag::Pending));
+        account.set_block_id(parse_block_id(&self.account_block)?);
+
+        if self.show_parties {
+            let mut parties = vec![("Sender", account_address)];
+            for call in calls.iter() {
+                if !parties.contains(&("Target", call.to)) {
+                    parties.push(("Target", call.to));
+                }
+            }
+            parties.push(("Fee token", resolve_fee_token(chain_id)?));
+            print_parties(felt_decoder.address_book_resolver(), &parties).await?;
+        }
 
-        let execution = account.execute(calls).fee_estimate_multiplier(1.5f64);
+        let execution = account
+            .execute(calls.clone())
+            .fee_estimate_multiplier(1.5f64);
 
         let max_fee = match fee_setting {
             FeeSetting::Manual(fee) => fee,
             FeeSetting::EstimateOnly | FeeSetting::None => {
-                let estimated_fee = execution.estimate_fee().await?.overall_fee;
+                let estimated_fee = Spinner::new()
+                    .run("Estimating fee", execution.estimate_fee())
+                    .await?
+                    .overall_fee;
+                warn_on_low_fee(estimated_fee, low_fee_threshold);
 
                 if fee_setting.is_estimate_only() {
-                    println!(
-                        "{} ETH",
-                        format!(
-                            "{}",
-                            <u64 as Into<FieldElement>>::into(estimated_fee).to_big_decimal(18)
-                        )
-                        .bright_yellow(),
+                    replay_log.record(
+                        ReplayMode::EstimateOnly,
+                        ReplayRecord {
+                            chain_id,
+                            sender: account_address,
+                            calls: &calls,
+                            estimated_fee: Some(estimated_fee),
+                            ..Default::default()
+                        },
+                    );
+                    output!(
+                        "{}",
+                        fee_formatter.format(estimated_fee.into()).bright_yellow()
                     );
                     return Ok(());
                 }
 
-                // TODO: make buffer configurable
-                let estimated_fee_with_buffer = estimated_fee * 3 / 2;
+                check_fee_ceiling(estimated_fee, max_fee_ceiling, &fee_formatter)?;
 
-                estimated_fee_with_buffer.into()
+                fee_buffer.apply(estimated_fee, chain_id, DEFAULT_FEE_BUFFER)
             }
         };
 
-        let invoke_tx = execution.max_fee(max_fee).send().await?.transaction_hash;
-        eprintln!(
-            "Invoke transaction: {}",
-            format!("{:#064x}", invoke_tx).bright_yellow()
+        if !skip_balance_check {
+            check_fee_balance(
+                &provider,
+                chain_id,
+                account_address,
+                max_fee,
+                &fee_formatter,
+            )
+            .await?;
+        }
+        // Fetched explicitly so that the nonce consumed by the transaction can be reported
+        let nonce = account.get_nonce().await?;
+        let execution = execution.nonce(nonce).max_fee(max_fee).prepared()?;
+        if let Some(path) = &self.dump_transaction {
+            dump_transaction(&execution.get_invoke_request().await?, path)?;
+        }
+        if self.signature_only {
+            replay_log.record(
+                ReplayMode::SignatureOnly,
+                ReplayRecord {
+                    chain_id,
+                    sender: account_address,
+                    nonce: Some(nonce),
+                    calls: &calls,
+                    max_fee: Some(max_fee),
+                    transaction_hash: Some(execution.transaction_hash()),
+                    ..Default::default()
+                },
+            );
+            let signature = execution.get_invoke_request().await?.signature;
+            let signature_json = colored_json::to_colored_json(
+                &serde_json::json!({
+                    "transaction_hash": format!("{:#064x}", execution.transaction_hash()),
+                    "nonce": format!("{:#x}", nonce),
+                    "max_fee": format!("{:#x}", max_fee),
+                    "signature": signature
+                        .iter()
+                        .map(|element| format!("{:#064x}", element))
+                        .collect::<Vec<_>>(),
+                }),
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{signature_json}");
+            return Ok(());
+        }
+        let sent = send_execution(
+            &provider,
+            &account,
+            execution,
+            nonce,
+            self.nonce_retry.max_retries(),
+            |nonce| {
+                Ok(account
+                    .execute(calls.clone())
+                    .nonce(nonce)
+                    .max_fee(max_fee)
+                    .prepared()?)
+            },
+        )
+        .await;
+        metrics.record_send(chain_id, max_fee, sent.is_ok());
+        replay_log.record(
+            ReplayMode::Send,
+            ReplayRecord {
+                chain_id,
+                sender: account_address,
+                nonce: Some(sent.as_ref().map_or(nonce, |(_, nonce)| *nonce)),
+                calls: &calls,
+                max_fee: Some(max_fee),
+                transaction_hash: sent
+                    .as_ref()
+                    .ok()
+                    .map(|(transaction_hash, _)| *transaction_hash),
+                error: sent.as_ref().err().map(|err| err.to_string()),
+                ..Default::default()
+            },
         );
+        let (invoke_tx, nonce) = sent?;
+        progress!("Nonce used: {}", nonce.to_string().bright_yellow());
+        explorer.print_link(chain_id, invoke_tx);
+        if self.broadcast_only {
+            // Only the transaction hash goes to stdout so this can be easily scripted. It's printed
+            // before watching so that it can be picked up right away.
+            output!("{}", self.felt_format.format(invoke_tx));
+        } else {
+            progress!(
+                "Invoke transaction: {}",
+                format!("{:#064x}", invoke_tx).bright_yellow()
+            );
+        }
 
         if self.watch {
-            eprintln!(
+            progress!(
                 "Waiting for transaction {} to confirm...",
                 format!("{:#064x}", invoke_tx).bright_yellow(),
             );
-            watch_tx(&provider, invoke_tx).await?;
+            watch_tx(&provider, invoke_tx, watch_conditions).await?;
         }
 
         Ok(())
     }
+
+    async fn prompt_calldata<P, S>(
+        provider: P,
+        felt_decoder: &FeltDecoder<S>,
+        contract_address: FieldElement,
+        function_name: &str,
+    ) -> Result<Vec<FieldElement>>
+    where
+        P: Provider,
+        P::Error: 'static,
+        S: ChainIdSource,
+    {
+        let abi = match ContractAbi::fetch(provider, contract_address).await? {
+            Some(abi) => abi,
+            None => anyhow::bail!(
+                "contract {:#064x} has no ABI to prompt for arguments with",
+                contract_address
+            ),
+        };
+        let parameters = match abi.find_function_parameters(function_name) {
+            Some(parameters) => parameters,
+            None => anyhow::bail!(
+                "contract {:#064x} has no function named \"{}\".{}",
+                contract_address,
+                function_name,
+                format_suggestions(&abi.suggest_function_names(function_name))
+            ),
+        };
+
+        progress!(
+            "Arguments for {} on {:#064x}:",
+            function_name.bright_yellow(),
+            contract_address
+        );
+        interactive::prompt_calldata(felt_decoder, &parameters).await
+    }
+
+    /// Catches mistyped function names before paying for a transaction that can only fail with
+    /// `ENTRYPOINT_NOT_FOUND`. Contracts whose ABI can't be fetched are skipped.
+    async fn check_functions<P>(
+        provider: &P,
+        calls: &[Call],
+        function_names: &[String],
+        strict: bool,
+    ) -> Result<()>
+    where
+        P: Provider + Sync,
+        P::Error: 'static,
+    {
+        let mut abis = HashMap::new();
+
+        for (call, function_name) in calls.iter().zip(function_names.iter()) {
+            if let Entry::Vacant(entry) = abis.entry(call.to) {
+                let abi = match ContractAbi::fetch(provider, call.to).await {
+                    Ok(abi) => abi,
+                    Err(err) => {
+                        warning!(
+                            "WARNING: unable to fetch ABI for {:#064x}: {}. Skipping the function \
+                            check.",
+                            call.to,
+                            err
+                        );
+                        None
+                    }
+                };
+                entry.insert(abi);
+            }
+
+            if let Some(abi) = &abis[&call.to] {
+                if !abi.has_function(call.selector) {
+                    let suggestions =
+                        format_suggestions(&abi.suggest_function_names(function_name));
+                    if strict {
+                        anyhow::bail!(
+                            "contract {:#064x} has no function named \"{}\".{}",
+                            call.to,
+                            function_name,
+                            suggestions
+                        );
+                    }
+                    warning!(
+                        "WARNING: contract {:#064x} has no function named \"{}\".{} The \
+                        transaction is likely to fail.",
+                        call.to,
+                        function_name,
+                        suggestions
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn watch_conditions(&self) -> WatchConditions {
+        WatchConditions {
+            min_confirmations: self.min_confirmations,
+            finality: self.finality,
+            timeout: self.watch_timeout.map(Duration::from_secs),
+        }
+    }
+}
+
+/// Parses calls given as `<ADDRESS> <FUNCTION> [CALLDATA...]`, separated by `/`, `-`, or `\`.
+/// Returns the function names along with the calls.
+pub(super) async fn parse_calls<S>(
+    felt_decoder: &FeltDecoder<S>,
+    raw_calls: Vec<String>,
+) -> Result<(Vec<Call>, Vec<String>)>
+where
+    S: ChainIdSource,
+{
+    // TODO: show more helpful message
+    let unexpected_end_of_args = || anyhow::anyhow!("unexpected end of arguments");
+
+    let mut calls = vec![];
+    let mut function_names = vec![];
+
+    let mut arg_iter = raw_calls.into_iter();
+
+    while let Some(first_arg) = arg_iter.next() {
+        let contract_address = felt_decoder
+            .decode_single_with_addr_fallback(&first_arg)
+            .await?;
+
+        let next_arg = arg_iter.next().ok_or_else(unexpected_end_of_args)?;
+        let selector = get_selector_from_name(&next_arg)?;
+        function_names.push(next_arg);
+
+        let mut calldata = vec![];
+        for arg in &mut arg_iter {
+            let mut arg = match arg.as_str() {
+                "/" | "-" | "\\" => break,
+                _ => felt_decoder.decode(&arg).await?,
+            };
+            calldata.append(&mut arg);
+        }
+
+        calls.push(Call {
+            to: contract_address,
+            selector,
+            calldata,
+        });
+    }
+
+    Ok((calls, function_names))
 }
@@ -0,0 +1,44 @@
+use anyhow::Result;
+use clap::Parser;
+use colored_json::{ColorMode, Output};
+use serde_json::json;
+
+use crate::{decode::join_u256, output::output, utils::parse_felt_value};
+
+#[derive(Debug, Parser)]
+pub struct JoinU256 {
+    #[clap(
+        long,
+        help = "Display the decimal instead of hexadecimal representation"
+    )]
+    dec: bool,
+    #[clap(long, help = "Print the joined value as JSON")]
+    json: bool,
+    #[clap(help = "Low 128 bits, in decimal or hexadecimal representation")]
+    low: String,
+    #[clap(help = "High 128 bits, in decimal or hexadecimal representation")]
+    high: String,
+}
+
+impl JoinU256 {
+    pub fn run(self) -> Result<()> {
+        let value = join_u256(parse_felt_value(&self.low)?, parse_felt_value(&self.high)?)?;
+        let value = if self.dec {
+            value.to_string()
+        } else {
+            format!("{:#x}", value)
+        };
+
+        if self.json {
+            let result_json = colored_json::to_colored_json(
+                &json!({ "value": value }),
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{result_json}");
+        } else {
+            output!("{value}");
+        }
+
+        Ok(())
+    }
+}
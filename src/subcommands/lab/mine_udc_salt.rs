@@ -9,6 +9,8 @@ use starknet::core::{
     utils::{normalize_address, UdcUniqueSettings, UdcUniqueness},
 };
 
+use crate::{output::output, verbosity::warning};
+
 /// The default UDC address: 0x041a78e741e5af2fec34b695679bc6891742439f7afb8484ecd7766661ad02bf.
 const DEFAULT_UDC_ADDRESS: FieldElement = FieldElement::from_mont([
     15144800532519055890,
@@ -58,12 +60,10 @@ impl MineUdcSalt {
             }
             (true, None) => UdcUniqueness::NotUnique,
             (false, Some(deployer_address)) => {
-                eprintln!(
-                    "{}",
+                warning!(
                     "WARNING: mining without --not-unique is slower. \
                     Try using --no-unique instead \
                     (you need to also use this option for the deploy command)."
-                        .bright_magenta()
                 );
 
                 UdcUniqueness::Unique(UdcUniqueSettings {
@@ -149,13 +149,13 @@ impl MineUdcSalt {
 
         let duration = end_time.duration_since(start_time)?;
 
-        println!(
+        output!(
             "Time spent: {}",
             format!("{}s", duration.as_secs()).bright_yellow()
         );
 
-        println!("Salt: {}", format!("{:#064x}", nonce).bright_yellow());
-        println!(
+        output!("Salt: {}", format!("{:#064x}", nonce).bright_yellow());
+        output!(
             "Address: {}",
             format!("{:#064x}", resulting_address).bright_yellow()
         );
@@ -7,6 +7,12 @@ pub use completions::Completions;
 mod class_hash;
 pub use class_hash::ClassHash;
 
+mod compiled_class_hash;
+pub use compiled_class_hash::CompiledClassHash;
+
+mod entrypoints;
+pub use entrypoints::Entrypoints;
+
 mod transaction;
 pub use transaction::Transaction;
 
@@ -25,6 +31,18 @@ pub use block_time::BlockTime;
 mod transaction_receipt;
 pub use transaction_receipt::TransactionReceipt;
 
+mod decode_calldata;
+pub use decode_calldata::DecodeCalldata;
+
+mod find_deployment;
+pub use find_deployment::FindDeployment;
+
+mod events;
+pub use events::Events;
+
+mod resolve;
+pub use resolve::Resolve;
+
 mod chain_id;
 pub use chain_id::ChainId;
 
@@ -34,18 +52,36 @@ pub use to_cairo_string::ToCairoString;
 mod parse_cairo_string;
 pub use parse_cairo_string::ParseCairoString;
 
+mod decode_revert;
+pub use decode_revert::DecodeRevert;
+
 mod mont;
 pub use mont::Mont;
 
+mod split_u256;
+pub use split_u256::SplitU256;
+
+mod join_u256;
+pub use join_u256::JoinU256;
+
 mod class_by_hash;
 pub use class_by_hash::ClassByHash;
 
 mod syncing;
 pub use syncing::Syncing;
 
+mod network_probe;
+pub use network_probe::NetworkProbe;
+
 mod class_at;
 pub use class_at::ClassAt;
 
+mod class_info;
+pub use class_info::ClassInfo;
+
+mod class_diff;
+pub use class_diff::ClassDiff;
+
 mod class_hash_at;
 pub use class_hash_at::ClassHashAt;
 
@@ -55,6 +91,9 @@ pub use nonce::Nonce;
 mod storage;
 pub use storage::Storage;
 
+mod storage_address;
+pub use storage_address::StorageAddress;
+
 mod state_update;
 pub use state_update::StateUpdate;
 
@@ -73,8 +112,23 @@ pub use declare::Declare;
 mod call;
 pub use call::Call;
 
+mod estimate;
+pub use estimate::Estimate;
+
 mod invoke;
 pub use invoke::Invoke;
 
+mod replace_tx;
+pub use replace_tx::ReplaceTx;
+
+mod units;
+pub use units::Units;
+
+mod hash;
+pub use hash::Hash;
+
+mod tx_hash;
+pub use tx_hash::TxHash;
+
 mod lab;
 pub use lab::Lab;
@@ -0,0 +1,158 @@
+use std::time::Instant;
+
+use anyhow::Result;
+use clap::Parser;
+use colored_json::{ColorMode, Output};
+use serde::Serialize;
+use starknet::{core::utils::parse_cairo_short_string, providers::Provider};
+
+use crate::{output::output, verbosity::VerbosityArgs, ProviderArgs};
+
+/// First JSON-RPC spec version with v3 transactions, which pay fees in STRK.
+const V3_SPEC_VERSION: (u64, u64) = (0, 6);
+
+#[derive(Debug, Parser)]
+pub struct NetworkProbe {
+    #[clap(flatten)]
+    provider: ProviderArgs,
+    #[clap(long, help = "Print the report as JSON")]
+    json: bool,
+    #[clap(flatten)]
+    verbosity: VerbosityArgs,
+}
+
+/// Outcome of a single probe. Probes fail independently so that the report is always complete.
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Probe<T> {
+    Ok(T),
+    Error(String),
+}
+
+#[derive(Serialize)]
+struct ProbeReport {
+    reachable: bool,
+    latency_ms: Option<u128>,
+    spec_version: Probe<String>,
+    chain_id: Probe<String>,
+    chain_name: Option<String>,
+    latest_block: Probe<u64>,
+    v3_transactions: Option<bool>,
+}
+
+impl NetworkProbe {
+    pub const RPC_METHODS: &'static [&'static str] = &[
+        "starknet_chainId",
+        "starknet_blockNumber",
+        "starknet_specVersion",
+    ];
+
+    pub async fn run(self) -> Result<()> {
+        self.verbosity.setup_logging();
+
+        let provider = self.provider.into_provider()?;
+
+        // The first request is timed as it's the cheapest one every node supports
+        let start = Instant::now();
+        let chain_id = provider.chain_id().await;
+        let latency = start.elapsed();
+
+        let (chain_id, chain_name) = match chain_id {
+            Ok(chain_id) => (
+                Probe::Ok(format!("{:#x}", chain_id)),
+                parse_cairo_short_string(&chain_id).ok(),
+            ),
+            Err(err) => (Probe::Error(err.to_string()), None),
+        };
+        let latest_block = match provider.block_number().await {
+            Ok(block_number) => Probe::Ok(block_number),
+            Err(err) => Probe::Error(err.to_string()),
+        };
+        let spec_version = match provider.spec_version().await {
+            Ok(spec_version) => Probe::Ok(spec_version),
+            Err(err) => Probe::Error(err.to_string()),
+        };
+
+        let reachable = matches!(chain_id, Probe::Ok(_))
+            || matches!(latest_block, Probe::Ok(_))
+            || matches!(spec_version, Probe::Ok(_));
+        let v3_transactions = match &spec_version {
+            Probe::Ok(spec_version) => {
+                Self::parse_major_minor(spec_version).map(|version| version >= V3_SPEC_VERSION)
+            }
+            Probe::Error(_) => None,
+        };
+
+        let report = ProbeReport {
+            reachable,
+            latency_ms: if reachable {
+                Some(latency.as_millis())
+            } else {
+                None
+            },
+            spec_version,
+            chain_id,
+            chain_name,
+            latest_block,
+            v3_transactions,
+        };
+
+        if self.json {
+            let report_json = colored_json::to_colored_json(
+                &serde_json::to_value(&report)?,
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{report_json}");
+        } else {
+            output!(
+                "Reachable:       {}",
+                if report.reachable { "yes" } else { "no" }
+            );
+            if let Some(latency_ms) = report.latency_ms {
+                output!("Latency:         {} ms", latency_ms);
+            }
+            output!("Spec version:    {}", report.spec_version);
+            match &report.chain_name {
+                Some(chain_name) => {
+                    output!("Chain ID:        {} ({})", report.chain_id, chain_name)
+                }
+                None => output!("Chain ID:        {}", report.chain_id),
+            }
+            output!("Latest block:    {}", report.latest_block);
+            output!(
+                "V3 transactions: {}",
+                match report.v3_transactions {
+                    Some(true) => "supported by the node, but not by this version of Starkli",
+                    Some(false) => "not supported by the node",
+                    None => "unknown",
+                }
+            );
+        }
+
+        if !report.reachable {
+            anyhow::bail!("the provider is unreachable");
+        }
+
+        Ok(())
+    }
+
+    fn parse_major_minor(version: &str) -> Option<(u64, u64)> {
+        let mut parts = version.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+
+        Some((major, minor))
+    }
+}
+
+impl<T> std::fmt::Display for Probe<T>
+where
+    T: std::fmt::Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ok(value) => write!(f, "{}", value),
+            Self::Error(err) => write!(f, "error: {}", err),
+        }
+    }
+}
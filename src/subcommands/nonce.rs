@@ -5,7 +5,7 @@ use starknet::{
     providers::Provider,
 };
 
-use crate::{verbosity::VerbosityArgs, ProviderArgs};
+use crate::{output::output, verbosity::VerbosityArgs, ProviderArgs};
 
 #[derive(Debug, Parser)]
 pub struct Nonce {
@@ -18,10 +18,12 @@ pub struct Nonce {
 }
 
 impl Nonce {
+    pub const RPC_METHODS: &'static [&'static str] = &["starknet_getNonce"];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
 
-        let provider = self.provider.into_provider();
+        let provider = self.provider.into_provider()?;
         let address = FieldElement::from_hex_be(&self.address)?;
 
         // TODO: allow custom block
@@ -29,7 +31,7 @@ impl Nonce {
             .get_nonce(BlockId::Tag(BlockTag::Pending), address)
             .await?;
 
-        println!("{}", nonce);
+        output!("{}", nonce);
 
         Ok(())
     }
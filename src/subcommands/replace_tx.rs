@@ -0,0 +1,462 @@
+use std::{cmp::Ordering, path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use starknet::{
+    accounts::{Call, ConnectedAccount, SingleOwnerAccount},
+    core::types::{
+        BlockId, BlockTag, FieldElement, InvokeTransaction, MaybePendingTransactionReceipt,
+        StarknetError, Transaction,
+    },
+    macros::selector,
+    providers::{MaybeUnknownErrorCode, Provider, ProviderError, StarknetErrorWithMessage},
+    signers::Signer,
+};
+
+use crate::{
+    account::{AccountConfig, DeploymentStatus},
+    address_book::AliasArgs,
+    decode::FeltDecoder,
+    explorer::ExplorerArgs,
+    fee::{resolve_fee_token, FeeArgs, FeeFormatter, FeeSetting},
+    output::output,
+    path::ExpandedPathbufParser,
+    provider::ExtendedProviderError,
+    signer::SignerArgs,
+    utils::{
+        check_fee_balance, check_signer_public_key, parse_felt_value, send_execution, watch_tx,
+        FeltFormat,
+    },
+    verbosity::{progress, warning, VerbosityArgs},
+    ProviderArgs,
+};
+
+use super::invoke::parse_calls;
+
+#[derive(Debug, Parser)]
+pub struct ReplaceTx {
+    #[clap(flatten)]
+    provider: ProviderArgs,
+    #[clap(flatten)]
+    signer: SignerArgs,
+    #[clap(
+        long,
+        env = "STARKNET_ACCOUNT",
+        value_parser = ExpandedPathbufParser,
+        required_unless_present = "wallet",
+        help = "Path to account config JSON file, \"-\" to read it from stdin, the config JSON \
+        itself, or the name of an account in the address book"
+    )]
+    account: Option<PathBuf>,
+    #[clap(
+        long,
+        help = "Nonce of the stuck transaction to replace, in hex (0x-prefixed) or decimal"
+    )]
+    nonce: String,
+    #[clap(
+        long,
+        value_name = "TX_HASH",
+        help = "Hash of the stuck transaction. Makes sure it's from the account, uses the same \
+        nonce, and has a lower max fee than the replacement"
+    )]
+    replaces: Option<FieldElement>,
+    #[clap(flatten)]
+    fee: FeeArgs,
+    #[clap(flatten)]
+    explorer: ExplorerArgs,
+    #[clap(long, help = "Wait for the replacement transaction to confirm")]
+    watch: bool,
+    #[clap(
+        long,
+        help = "Print only the transaction hash to stdout right after broadcasting. With --watch, \
+        the transaction is still watched afterwards"
+    )]
+    broadcast_only: bool,
+    #[clap(
+        long,
+        value_enum,
+        default_value_t,
+        requires = "broadcast_only",
+        help = "Output format of the transaction hash printed with --broadcast-only"
+    )]
+    felt_format: FeltFormat,
+    #[clap(
+        long,
+        help = "Skip checking that the signer's public key matches the one set on the account \
+        contract"
+    )]
+    skip_signer_check: bool,
+    #[clap(
+        long,
+        help = "Skip checking that the provider is on the chain recorded in the account config"
+    )]
+    skip_chain_check: bool,
+    #[clap(
+        help = "Calls to send instead of the stuck transaction, in the same format as for \
+        invoke. Defaults to a transfer of 0 fee tokens to the account itself"
+    )]
+    calls: Vec<String>,
+    #[clap(flatten)]
+    alias: AliasArgs,
+    #[clap(flatten)]
+    verbosity: VerbosityArgs,
+}
+
+impl ReplaceTx {
+    pub const RPC_METHODS: &'static [&'static str] = &[
+        "starknet_chainId",
+        "starknet_getNonce",
+        "starknet_call",
+        "starknet_getTransactionByHash",
+        "starknet_getTransactionReceipt",
+        "starknet_addInvokeTransaction",
+        "starknet_blockNumber",
+    ];
+
+    pub async fn run(self) -> Result<()> {
+        self.verbosity.setup_logging();
+
+        let fee_formatter = self.fee.formatter();
+        let skip_balance_check = self.fee.skip_balance_check();
+        let explorer = self.explorer.into_explorer()?;
+
+        let nonce = parse_felt_value(&self.nonce)
+            .map_err(|err| anyhow::anyhow!("invalid --nonce \"{}\": {}", self.nonce, err))?;
+
+        let provider = Arc::new(self.provider.into_provider()?);
+
+        // An estimate is based on the current gas price, which says nothing about the fee of the
+        // stuck transaction
+        let max_fee = match self.fee.into_setting(&provider).await? {
+            FeeSetting::Manual(max_fee) => max_fee,
+            FeeSetting::EstimateOnly | FeeSetting::None => anyhow::bail!(
+                "replace-tx needs the max fee of the replacement given explicitly with --max-fee, \
+                --max-fee-raw, or --max-fee-from. It must be higher than the max fee of the stuck \
+                transaction"
+            ),
+        };
+
+        let account_config =
+            AccountConfig::load_from_args(self.account.as_deref(), &self.signer, &provider).await?;
+        let account_address = match &account_config.deployment {
+            DeploymentStatus::Undeployed(_) => anyhow::bail!("account not deployed"),
+            DeploymentStatus::Deployed(inner) => inner.address,
+        };
+
+        let chain_id = provider.chain_id().await?;
+
+        if !self.skip_chain_check {
+            account_config.check_chain_id(chain_id)?;
+        }
+
+        let calls = if self.calls.is_empty() {
+            vec![Call {
+                to: resolve_fee_token(chain_id)?,
+                selector: selector!("transfer"),
+                calldata: vec![account_address, FieldElement::ZERO, FieldElement::ZERO],
+            }]
+        } else {
+            let felt_decoder = FeltDecoder::new(self.alias.into_resolver(provider.clone())?)
+                .with_account_address(account_address);
+            parse_calls(&felt_decoder, self.calls).await?.0
+        };
+
+        Self::check_nonce(&provider, account_address, nonce).await?;
+        if let Some(transaction_hash) = self.replaces {
+            Self::check_replaced(
+                &provider,
+                transaction_hash,
+                account_address,
+                nonce,
+                max_fee,
+                &fee_formatter,
+            )
+            .await?;
+        }
+
+        let signer = Arc::new(self.signer.into_signer()?);
+        if !self.skip_signer_check {
+            check_signer_public_key(
+                &provider,
+                account_address,
+                signer.get_public_key().await?.scalar(),
+            )
+            .await?;
+        }
+
+        if !skip_balance_check {
+            check_fee_balance(
+                &provider,
+                chain_id,
+                account_address,
+                max_fee,
+                &fee_formatter,
+            )
+            .await?;
+        }
+
+        let account = SingleOwnerAccount::new(provider.clone(), signer, account_address, chain_id);
+
+        progress!(
+            "Sending replacement with nonce {} and max fee {}",
+            nonce.to_string().bright_yellow(),
+            fee_formatter.format(max_fee).bright_yellow()
+        );
+
+        let replacement_tx =
+            Self::send_replacement(&provider, &account, calls, nonce, max_fee).await?;
+        explorer.print_link(chain_id, replacement_tx);
+
+        if self.broadcast_only {
+            // Printed before watching so that it can be picked up right away
+            output!("{}", self.felt_format.format(replacement_tx));
+        } else {
+            progress!(
+                "Replacement transaction: {}",
+                format!("{:#064x}", replacement_tx).bright_yellow()
+            );
+        }
+
+        if self.watch {
+            progress!(
+                "Waiting for transaction {} to confirm...",
+                format!("{:#064x}", replacement_tx).bright_yellow(),
+            );
+            watch_tx(&provider, replacement_tx, Default::default()).await?;
+        }
+
+        if !self.broadcast_only {
+            output!("{:#064x}", replacement_tx);
+        }
+
+        Ok(())
+    }
+
+    /// Sends the replacement like any other transaction, so that it's looked up before being sent
+    /// again on timeouts. Nonce errors are never retried, as the nonce is what's being replaced.
+    async fn send_replacement<A, P>(
+        provider: P,
+        account: &A,
+        calls: Vec<Call>,
+        nonce: FieldElement,
+        max_fee: FieldElement,
+    ) -> Result<FieldElement>
+    where
+        A: ConnectedAccount + Sync,
+        A::SignError: 'static,
+        A::Provider: Provider<Error = ExtendedProviderError>,
+        P: Provider,
+        P::Error: 'static,
+    {
+        let prepare = |nonce| {
+            Ok(account
+                .execute(calls.clone())
+                .nonce(nonce)
+                .max_fee(max_fee)
+                .prepared()?)
+        };
+
+        let (replacement_tx, _) =
+            send_execution(provider, account, prepare(nonce)?, nonce, 0, prepare).await?;
+        Ok(replacement_tx)
+    }
+
+    /// Makes sure the nonce hasn't been used yet, as a replacement could only be rejected then.
+    async fn check_nonce<P>(
+        provider: P,
+        account_address: FieldElement,
+        nonce: FieldElement,
+    ) -> Result<()>
+    where
+        P: Provider,
+        P::Error: 'static,
+    {
+        let latest_nonce = provider
+            .get_nonce(BlockId::Tag(BlockTag::Latest), account_address)
+            .await?;
+        if nonce < latest_nonce {
+            anyhow::bail!(
+                "nonce {} was already used by a transaction included in a block. The account \
+                nonce is now {}, so there's nothing left to replace",
+                nonce,
+                latest_nonce
+            );
+        }
+
+        let pending_nonce = provider
+            .get_nonce(BlockId::Tag(BlockTag::Pending), account_address)
+            .await?;
+        match nonce.cmp(&pending_nonce) {
+            Ordering::Less => warning!(
+                "WARNING: a transaction with nonce {} is already in the pending block, so it's \
+                most likely to be included. The replacement is then rejected.",
+                nonce
+            ),
+            Ordering::Greater => warning!(
+                "WARNING: nonce {} is ahead of the account nonce {}. The replacement can only be \
+                included once the nonces before it are used.",
+                nonce,
+                pending_nonce
+            ),
+            Ordering::Equal => {}
+        }
+
+        Ok(())
+    }
+
+    /// Checks the transaction given with `--replaces` against the replacement, so that a typo in
+    /// the nonce doesn't go unnoticed.
+    async fn check_replaced<P>(
+        provider: P,
+        transaction_hash: FieldElement,
+        account_address: FieldElement,
+        nonce: FieldElement,
+        max_fee: FieldElement,
+        fee_formatter: &FeeFormatter,
+    ) -> Result<()>
+    where
+        P: Provider,
+        P::Error: 'static,
+    {
+        let transaction = match provider.get_transaction_by_hash(transaction_hash).await {
+            Ok(transaction) => transaction,
+            Err(ProviderError::StarknetError(StarknetErrorWithMessage {
+                code: MaybeUnknownErrorCode::Known(StarknetError::TransactionHashNotFound),
+                ..
+            })) => {
+                warning!(
+                    "WARNING: transaction {:#064x} is not known to the node. Unable to check it \
+                    against the replacement.",
+                    transaction_hash
+                );
+                return Ok(());
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let transaction = match transaction {
+            Transaction::Invoke(InvokeTransaction::V1(transaction)) => transaction,
+            _ => anyhow::bail!(
+                "transaction {:#064x} is not an invoke transaction sent from an account",
+                transaction_hash
+            ),
+        };
+        if transaction.sender_address != account_address {
+            anyhow::bail!(
+                "transaction {:#064x} was sent by {:#064x}, not by the account",
+                transaction_hash,
+                transaction.sender_address
+            );
+        }
+        if transaction.nonce != nonce {
+            anyhow::bail!(
+                "transaction {:#064x} uses nonce {}, not {}",
+                transaction_hash,
+                transaction.nonce,
+                nonce
+            );
+        }
+        if max_fee <= transaction.max_fee {
+            anyhow::bail!(
+                "the replacement max fee must be higher than the max fee of {:#064x}, which is {}",
+                transaction_hash,
+                fee_formatter.format(transaction.max_fee)
+            );
+        }
+
+        // Being in the pending block is already warned about by the nonce check
+        if let Ok(MaybePendingTransactionReceipt::Receipt(_)) =
+            provider.get_transaction_receipt(transaction_hash).await
+        {
+            anyhow::bail!(
+                "transaction {:#064x} is already included in a block and can't be replaced",
+                transaction_hash
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+    use starknet::{
+        macros::felt,
+        signers::{LocalWallet, SigningKey},
+    };
+
+    use crate::{mock_rpc::MockRpc, provider::ExtendedProvider};
+
+    fn test_account(rpc: &MockRpc) -> SingleOwnerAccount<ExtendedProvider, LocalWallet> {
+        SingleOwnerAccount::new(
+            rpc.provider(),
+            LocalWallet::from_signing_key(SigningKey::from_secret_scalar(felt!("0x1"))),
+            felt!("0x1234"),
+            felt!("0x534e5f474f45524c49"),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_send_replacement() {
+        let rpc = MockRpc::start();
+        rpc.respond(
+            "starknet_addInvokeTransaction",
+            json!({ "transaction_hash": "0xabc" }),
+        );
+        let account = test_account(&rpc);
+        let calls = vec![Call {
+            to: felt!("0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7"),
+            selector: selector!("transfer"),
+            calldata: vec![felt!("0x1234"), FieldElement::ZERO, FieldElement::ZERO],
+        }];
+
+        let replacement_tx = ReplaceTx::send_replacement(
+            &rpc.provider(),
+            &account,
+            calls,
+            felt!("0x5"),
+            felt!("0x2000"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(replacement_tx, felt!("0xabc"));
+
+        let submissions = rpc.requests("starknet_addInvokeTransaction");
+        assert_eq!(submissions.len(), 1);
+        let transaction = &submissions[0][0];
+        assert_eq!(transaction["sender_address"], "0x1234");
+        assert_eq!(transaction["nonce"], "0x5");
+        assert_eq!(transaction["max_fee"], "0x2000");
+
+        // The account nonce is never asked for, as the nonce to replace is given
+        assert!(rpc.requests("starknet_getNonce").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_send_replacement_nonce_error() {
+        let rpc = MockRpc::start();
+        rpc.respond_error(
+            "starknet_addInvokeTransaction",
+            52,
+            "Invalid transaction nonce",
+        );
+        let account = test_account(&rpc);
+
+        // Replacing a nonce that's already used can't be fixed by sending with another one
+        assert!(ReplaceTx::send_replacement(
+            &rpc.provider(),
+            &account,
+            vec![],
+            felt!("0x5"),
+            felt!("0x2000"),
+        )
+        .await
+        .is_err());
+        assert_eq!(rpc.requests("starknet_addInvokeTransaction").len(), 1);
+        assert!(rpc.requests("starknet_getNonce").is_empty());
+    }
+}
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use colored_json::{ColorMode, Output};
+use serde::Serialize;
+use starknet::core::types::FieldElement;
+
+use crate::{
+    address_book::AliasArgs, decode::FeltDecoder, output::output, verbosity::VerbosityArgs,
+    ProviderArgs,
+};
+
+#[derive(Debug, Parser)]
+pub struct Resolve {
+    #[clap(flatten)]
+    provider: ProviderArgs,
+    #[clap(flatten)]
+    alias: AliasArgs,
+    #[clap(long, help = "Print the resolved names as JSON")]
+    json: bool,
+    #[clap(
+        long,
+        help = "Exit successfully even if some of the names could not be resolved"
+    )]
+    allow_unresolved: bool,
+    #[clap(
+        required = true,
+        help = "Names to resolve, as address book names, addresses, or any other value taking a \
+        single felt"
+    )]
+    names: Vec<String>,
+    #[clap(flatten)]
+    verbosity: VerbosityArgs,
+}
+
+#[derive(Serialize)]
+struct Resolution {
+    name: String,
+    address: Option<String>,
+    error: Option<String>,
+}
+
+impl Resolve {
+    pub const RPC_METHODS: &'static [&'static str] = &["starknet_chainId"];
+
+    pub async fn run(self) -> Result<()> {
+        self.verbosity.setup_logging();
+
+        let provider = Arc::new(self.provider.into_provider()?);
+        let felt_decoder = FeltDecoder::new(self.alias.into_resolver(provider)?);
+
+        // Names are resolved one by one, so that a single failure doesn't hide the others
+        let mut resolutions = vec![];
+        for name in self.names.into_iter() {
+            // Bare names go through the `addr` scheme, which reports missing entries clearly
+            let raw = if name.contains(':') || name.parse::<FieldElement>().is_ok() {
+                name.clone()
+            } else {
+                format!("addr:{}", name)
+            };
+
+            let resolution = match felt_decoder.decode_single_with_addr_fallback(&raw).await {
+                Ok(address) => Resolution {
+                    name,
+                    address: Some(format!("{:#064x}", address)),
+                    error: None,
+                },
+                Err(err) => Resolution {
+                    name,
+                    address: None,
+                    error: Some(err.to_string()),
+                },
+            };
+            resolutions.push(resolution);
+        }
+
+        if self.json {
+            let resolutions_json = colored_json::to_colored_json(
+                &serde_json::to_value(&resolutions)?,
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{resolutions_json}");
+        } else {
+            let name_width = resolutions
+                .iter()
+                .map(|resolution| resolution.name.len())
+                .max()
+                .unwrap_or_default();
+
+            for resolution in resolutions.iter() {
+                match (&resolution.address, &resolution.error) {
+                    (Some(address), _) => {
+                        output!("{:<name_width$}  {}", resolution.name, address)
+                    }
+                    (None, error) => output!(
+                        "{:<name_width$}  {}",
+                        resolution.name,
+                        format!("unresolved: {}", error.as_deref().unwrap_or_default()).red()
+                    ),
+                }
+            }
+        }
+
+        let unresolved_count = resolutions
+            .iter()
+            .filter(|resolution| resolution.address.is_none())
+            .count();
+        if unresolved_count > 0 && !self.allow_unresolved {
+            anyhow::bail!(
+                "{} of {} names could not be resolved",
+                unresolved_count,
+                resolutions.len()
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -2,6 +2,8 @@ use anyhow::{anyhow, Result};
 use clap::Parser;
 use starknet::core::utils::get_selector_from_name;
 
+use crate::output::output;
+
 #[derive(Debug, Parser)]
 pub struct Selector {
     #[clap(help = "Selector name")]
@@ -19,7 +21,7 @@ impl Selector {
         }
 
         let selector = get_selector_from_name(trimmed_name)?;
-        println!("{selector:#064x}");
+        output!("{selector:#064x}");
 
         Ok(())
     }
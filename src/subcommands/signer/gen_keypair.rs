@@ -2,6 +2,8 @@ use anyhow::Result;
 use clap::Parser;
 use starknet::signers::SigningKey;
 
+use crate::output::output;
+
 #[derive(Debug, Parser)]
 pub struct GenKeypair {}
 
@@ -9,8 +11,8 @@ impl GenKeypair {
     pub fn run(self) -> Result<()> {
         let key = SigningKey::from_random();
 
-        println!("Private key : {:#064x}", key.secret_scalar());
-        println!("Public key  : {:#064x}", key.verifying_key().scalar());
+        output!("Private key : {:#064x}", key.secret_scalar());
+        output!("Public key  : {:#064x}", key.verifying_key().scalar());
 
         Ok(())
     }
@@ -2,10 +2,9 @@ use std::{io::Read, path::PathBuf};
 
 use anyhow::Result;
 use clap::Parser;
-use colored::Colorize;
 use starknet::{core::types::FieldElement, signers::SigningKey};
 
-use crate::path::ExpandedPathbufParser;
+use crate::{keystore::KdfArgs, output::output, path::ExpandedPathbufParser, verbosity::warning};
 
 #[derive(Debug, Parser)]
 pub struct FromKey {
@@ -23,16 +22,16 @@ pub struct FromKey {
         help = "Path to save the JSON keystore"
     )]
     file: PathBuf,
+    #[clap(flatten)]
+    kdf: KdfArgs,
 }
 
 impl FromKey {
     pub fn run(self) -> Result<()> {
         if self.password.is_some() {
-            eprintln!(
-                "{}",
+            warning!(
                 "WARNING: setting passwords via --password is generally considered insecure, \
                 as they will be stored in your shell history or other log files."
-                    .bright_magenta()
             );
         }
 
@@ -57,13 +56,13 @@ impl FromKey {
         };
 
         let key = SigningKey::from_secret_scalar(private_key);
-        key.save_as_keystore(&self.file, &password)?;
+        self.kdf.save_keystore(&key, &self.file, &password)?;
 
-        println!(
+        output!(
             "Created new encrypted keystore file: {}",
             std::fs::canonicalize(self.file)?.display()
         );
-        println!("Public key: {:#064x}", key.verifying_key().scalar());
+        output!("Public key: {:#064x}", key.verifying_key().scalar());
 
         Ok(())
     }
@@ -2,10 +2,9 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 use clap::Parser;
-use colored::Colorize;
 use starknet::signers::SigningKey;
 
-use crate::path::ExpandedPathbufParser;
+use crate::{output::output, path::ExpandedPathbufParser, verbosity::warning};
 
 #[derive(Debug, Parser)]
 pub struct Inspect {
@@ -26,11 +25,9 @@ pub struct Inspect {
 impl Inspect {
     pub fn run(self) -> Result<()> {
         if self.password.is_some() {
-            eprintln!(
-                "{}",
+            warning!(
                 "WARNING: setting passwords via --password is generally considered insecure, \
                 as they will be stored in your shell history or other log files."
-                    .bright_magenta()
             );
         }
 
@@ -47,9 +44,9 @@ impl Inspect {
         let key = SigningKey::from_keystore(self.file, &password)?;
 
         if self.raw {
-            println!("{:#064x}", key.verifying_key().scalar());
+            output!("{:#064x}", key.verifying_key().scalar());
         } else {
-            println!("Public key: {:#064x}", key.verifying_key().scalar());
+            output!("Public key: {:#064x}", key.verifying_key().scalar());
         }
 
         Ok(())
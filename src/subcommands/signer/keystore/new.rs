@@ -5,7 +5,7 @@ use clap::Parser;
 use colored::Colorize;
 use starknet::signers::SigningKey;
 
-use crate::path::ExpandedPathbufParser;
+use crate::{keystore::KdfArgs, output::output, path::ExpandedPathbufParser, verbosity::warning};
 
 #[derive(Debug, Parser)]
 pub struct New {
@@ -21,16 +21,16 @@ pub struct New {
         help = "Path to save the JSON keystore"
     )]
     file: PathBuf,
+    #[clap(flatten)]
+    kdf: KdfArgs,
 }
 
 impl New {
     pub fn run(self) -> Result<()> {
         if self.password.is_some() {
-            eprintln!(
-                "{}",
+            warning!(
                 "WARNING: setting passwords via --password is generally considered insecure, \
                 as they will be stored in your shell history or other log files."
-                    .bright_magenta()
             );
         }
 
@@ -45,13 +45,13 @@ impl New {
         };
 
         let key = SigningKey::from_random();
-        key.save_as_keystore(&self.file, &password)?;
+        self.kdf.save_keystore(&key, &self.file, &password)?;
 
-        println!(
+        output!(
             "Created new encrypted keystore file: {}",
             std::fs::canonicalize(self.file)?.display()
         );
-        println!(
+        output!(
             "Public key: {}",
             format!("{:#064x}", key.verifying_key().scalar()).bright_yellow()
         );
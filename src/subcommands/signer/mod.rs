@@ -7,6 +7,15 @@ use keystore::Keystore;
 mod gen_keypair;
 use gen_keypair::GenKeypair;
 
+mod sign;
+use sign::Sign;
+
+mod sign_typed_data;
+use sign_typed_data::SignTypedData;
+
+mod verify;
+use verify::Verify;
+
 #[derive(Debug, Parser)]
 pub struct Signer {
     #[clap(subcommand)]
@@ -19,13 +28,22 @@ enum Subcommands {
     Keystore(Keystore),
     #[clap(about = "Randomly generate a new key pair")]
     GenKeypair(GenKeypair),
+    #[clap(about = "Sign a message hash with the signer")]
+    Sign(Sign),
+    #[clap(about = "Sign an off-chain typed data document (SNIP-12) with the signer")]
+    SignTypedData(SignTypedData),
+    #[clap(about = "Verify a signature against a public key offline")]
+    Verify(Verify),
 }
 
 impl Signer {
-    pub fn run(self) -> Result<()> {
+    pub async fn run(self) -> Result<()> {
         match self.command {
             Subcommands::Keystore(cmd) => cmd.run(),
             Subcommands::GenKeypair(cmd) => cmd.run(),
+            Subcommands::Sign(cmd) => cmd.run().await,
+            Subcommands::SignTypedData(cmd) => cmd.run().await,
+            Subcommands::Verify(cmd) => cmd.run(),
         }
     }
 }
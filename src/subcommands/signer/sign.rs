@@ -0,0 +1,61 @@
+use anyhow::Result;
+use clap::Parser;
+use starknet::{
+    core::{crypto::Signature, types::FieldElement},
+    signers::Signer,
+};
+
+use crate::{output::output, signer::SignerArgs};
+
+#[derive(Debug, Parser)]
+pub struct Sign {
+    #[clap(flatten)]
+    signer: SignerArgs,
+    #[clap(help = "Message hash to sign")]
+    hash: String,
+}
+
+impl Sign {
+    pub async fn run(self) -> Result<()> {
+        let signature = self.signature().await?;
+
+        // Printed in the same order `signer verify` expects them
+        output!("{:#064x}", signature.r);
+        output!("{:#064x}", signature.s);
+
+        Ok(())
+    }
+
+    async fn signature(self) -> Result<Signature> {
+        let hash = FieldElement::from_hex_be(&self.hash)?;
+
+        let signer = self.signer.into_signer()?;
+        Ok(signer.sign_hash(&hash).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::macros::felt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sign() {
+        let signature = Sign::try_parse_from(["sign", "--private-key", "0x1234", "0x5678"])
+            .unwrap()
+            .signature()
+            .await
+            .unwrap();
+
+        // Signatures are deterministic (RFC 6979)
+        assert_eq!(
+            signature.r,
+            felt!("0x056741900e1e7745aef76c8cbf1e9459adb88c957146bad9e0beb214ed8f6379")
+        );
+        assert_eq!(
+            signature.s,
+            felt!("0x01c8adb22ac56c2998205f04d4f6c32dd0386be7143f9604de719c78ba3e8a8b")
+        );
+    }
+}
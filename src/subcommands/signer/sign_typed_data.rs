@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use colored::Colorize;
+use starknet::{core::types::FieldElement, signers::Signer};
+
+use crate::{
+    output::output, path::ExpandedPathbufParser, signer::SignerArgs, typed_data::TypedData,
+    verbosity::progress,
+};
+
+#[derive(Debug, Parser)]
+pub struct SignTypedData {
+    #[clap(flatten)]
+    signer: SignerArgs,
+    #[clap(
+        long,
+        help = "Address of the account the signature is for, which is part of the message hash"
+    )]
+    account_address: String,
+    #[clap(
+        value_parser = ExpandedPathbufParser,
+        help = "Path to the typed data JSON document"
+    )]
+    file: PathBuf,
+}
+
+impl SignTypedData {
+    pub async fn run(self) -> Result<()> {
+        let account_address = FieldElement::from_hex_be(&self.account_address)?;
+
+        let typed_data: TypedData = serde_json::from_reader(std::fs::File::open(&self.file)?)
+            .map_err(|err| anyhow::anyhow!("invalid typed data: {}", err))?;
+        let hash = typed_data.message_hash(account_address)?;
+
+        progress!(
+            "Message hash: {}",
+            format!("{:#064x}", hash).bright_yellow()
+        );
+
+        let signer = self.signer.into_signer()?;
+        let signature = signer.sign_hash(&hash).await?;
+
+        // Printed in the same order `signer verify` expects them
+        output!("{:#064x}", signature.r);
+        output!("{:#064x}", signature.s);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use anyhow::Result;
+use clap::Parser;
+use starknet::{
+    core::{crypto::Signature, types::FieldElement},
+    signers::VerifyingKey,
+};
+
+use crate::output::output;
+
+#[derive(Debug, Parser)]
+pub struct Verify {
+    #[clap(help = "Message hash that was signed")]
+    hash: String,
+    #[clap(help = "The r value of the signature")]
+    r: String,
+    #[clap(help = "The s value of the signature")]
+    s: String,
+    #[clap(help = "Public key of the signer")]
+    public_key: String,
+}
+
+impl Verify {
+    pub fn run(self) -> Result<()> {
+        self.verify()?;
+
+        output!("Signature is valid");
+
+        Ok(())
+    }
+
+    fn verify(&self) -> Result<()> {
+        let hash = FieldElement::from_hex_be(&self.hash)?;
+        let signature = Signature {
+            r: FieldElement::from_hex_be(&self.r)?,
+            s: FieldElement::from_hex_be(&self.s)?,
+        };
+        let public_key = VerifyingKey::from_scalar(FieldElement::from_hex_be(&self.public_key)?);
+
+        if !public_key.verify(&hash, &signature)? {
+            anyhow::bail!("invalid signature");
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::signers::SigningKey;
+
+    use super::*;
+
+    fn verify(hash: &str, r: &str, s: &str, public_key: &str) -> Result<()> {
+        Verify::try_parse_from(["verify", hash, r, s, public_key])?.verify()
+    }
+
+    #[test]
+    fn test_verify() {
+        let key = SigningKey::from_secret_scalar(FieldElement::from_hex_be("0x1234").unwrap());
+        let public_key = format!("{:#x}", key.verifying_key().scalar());
+        let r = "0x056741900e1e7745aef76c8cbf1e9459adb88c957146bad9e0beb214ed8f6379";
+        let s = "0x01c8adb22ac56c2998205f04d4f6c32dd0386be7143f9604de719c78ba3e8a8b";
+
+        verify("0x5678", r, s, &public_key).unwrap();
+
+        // Another hash, swapped values, or another public key
+        assert!(verify("0x5679", r, s, &public_key).is_err());
+        assert!(verify("0x5678", s, r, &public_key).is_err());
+        assert!(verify("0x5678", r, s, "0x1").is_err());
+    }
+}
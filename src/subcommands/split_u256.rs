@@ -0,0 +1,40 @@
+use anyhow::Result;
+use clap::Parser;
+use colored_json::{ColorMode, Output};
+use serde_json::json;
+
+use crate::{
+    decode::{parse_u256, split_u256},
+    output::output,
+};
+
+#[derive(Debug, Parser)]
+pub struct SplitU256 {
+    #[clap(long, help = "Print the low and high halves as JSON")]
+    json: bool,
+    #[clap(help = "u256 value in decimal or hexadecimal representation")]
+    value: String,
+}
+
+impl SplitU256 {
+    pub fn run(self) -> Result<()> {
+        let (low, high) = split_u256(&parse_u256(&self.value)?)?;
+
+        if self.json {
+            let result_json = colored_json::to_colored_json(
+                &json!({
+                    "low": format!("{:#x}", low),
+                    "high": format!("{:#x}", high),
+                }),
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{result_json}");
+        } else {
+            // Same order as in calldata
+            output!("{:#x}", low);
+            output!("{:#x}", high);
+        }
+
+        Ok(())
+    }
+}
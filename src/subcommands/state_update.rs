@@ -3,7 +3,7 @@ use clap::Parser;
 use colored_json::{ColorMode, Output};
 use starknet::providers::Provider;
 
-use crate::{utils::parse_block_id, verbosity::VerbosityArgs, ProviderArgs};
+use crate::{output::output, utils::parse_block_id, verbosity::VerbosityArgs, ProviderArgs};
 
 #[derive(Debug, Parser)]
 pub struct StateUpdate {
@@ -19,10 +19,12 @@ pub struct StateUpdate {
 }
 
 impl StateUpdate {
+    pub const RPC_METHODS: &'static [&'static str] = &["starknet_getStateUpdate"];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
 
-        let provider = self.provider.into_provider();
+        let provider = self.provider.into_provider()?;
 
         let block_id = parse_block_id(&self.block_id)?;
 
@@ -30,7 +32,7 @@ impl StateUpdate {
 
         let update_json =
             colored_json::to_colored_json(&update_json, ColorMode::Auto(Output::StdOut))?;
-        println!("{update_json}");
+        output!("{update_json}");
 
         Ok(())
     }
@@ -1,39 +1,129 @@
+use std::path::PathBuf;
+
 use anyhow::Result;
 use clap::Parser;
+use futures_util::{StreamExt, TryStreamExt};
+use serde_json::json;
 use starknet::{
-    core::types::{BlockId, BlockTag, FieldElement},
-    providers::Provider,
+    core::types::FieldElement, core::utils::get_storage_var_address, providers::Provider,
+};
+
+use crate::{
+    output::output, path::ExpandedPathbufParser, utils::parse_block_id, verbosity::VerbosityArgs,
+    ProviderArgs,
 };
 
-use crate::{verbosity::VerbosityArgs, ProviderArgs};
+/// Maximum number of storage reads in flight at the same time.
+const MAX_CONCURRENT_READS: usize = 8;
 
 #[derive(Debug, Parser)]
 pub struct Storage {
     #[clap(flatten)]
     provider: ProviderArgs,
+    #[clap(
+        long,
+        default_value = "pending",
+        help = "Block number, hash, or tag (latest/pending) to read all keys at"
+    )]
+    block_id: String,
+    #[clap(
+        long,
+        value_parser = ExpandedPathbufParser,
+        help = "Path to a file with one storage key per line"
+    )]
+    keys_file: Option<PathBuf>,
     #[clap(help = "Contract address")]
     address: String,
-    #[clap(help = "Storage key")]
-    key: String,
+    #[clap(
+        help = "Storage keys, either as raw felts or in the form of var:name[:key]... to compute \
+        the address of a storage variable"
+    )]
+    keys: Vec<String>,
     #[clap(flatten)]
     verbosity: VerbosityArgs,
 }
 
 impl Storage {
+    pub const RPC_METHODS: &'static [&'static str] = &["starknet_getStorageAt"];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
 
-        let provider = self.provider.into_provider();
+        let provider = self.provider.into_provider()?;
         let address = FieldElement::from_hex_be(&self.address)?;
-        let key = FieldElement::from_hex_be(&self.key)?;
+        let block_id = parse_block_id(&self.block_id)?;
 
-        // TODO: allow custom block
-        let value = provider
-            .get_storage_at(address, key, BlockId::Tag(BlockTag::Pending))
-            .await?;
+        let mut raw_keys = self.keys.clone();
+        if let Some(keys_file) = &self.keys_file {
+            raw_keys.extend(
+                std::fs::read_to_string(keys_file)?
+                    .lines()
+                    .map(|line| line.trim())
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(|line| line.to_owned()),
+            );
+        }
 
-        println!("{:#064x}", value);
+        let keys = raw_keys
+            .iter()
+            .map(|key| Self::parse_key(key))
+            .collect::<Result<Vec<_>>>()?;
+        if keys.is_empty() {
+            anyhow::bail!("no storage key supplied");
+        }
+
+        let batch_params = keys
+            .iter()
+            .map(|key| json!([format!("{:#x}", address), format!("{:#x}", key), block_id]))
+            .collect::<Vec<_>>();
+        let values = match provider
+            .batch_request::<_, FieldElement>("starknet_getStorageAt", &batch_params)
+            .await?
+        {
+            Some(values) => values,
+            None => {
+                futures_util::stream::iter(keys.iter())
+                    .map(|key| provider.get_storage_at(address, key, block_id))
+                    .buffered(MAX_CONCURRENT_READS)
+                    .try_collect::<Vec<_>>()
+                    .await?
+            }
+        };
+
+        // A single key only prints the value, as it always did
+        if self.keys_file.is_none() && keys.len() == 1 {
+            output!("{:#064x}", values[0]);
+        } else {
+            for (key, value) in keys.iter().zip(values.iter()) {
+                output!("{:#064x} {:#064x}", key, value);
+            }
+        }
 
         Ok(())
     }
+
+    fn parse_key(raw: &str) -> Result<FieldElement> {
+        match raw.strip_prefix("var:") {
+            Some(var) => {
+                let mut parts = var.split(':');
+                // `split` always yields at least one item
+                let name = parts.next().unwrap();
+                let keys = parts
+                    .map(|key| key.parse::<FieldElement>())
+                    .collect::<std::result::Result<Vec<_>, _>>()?;
+
+                storage_var_address(name, &keys)
+            }
+            None => Ok(FieldElement::from_hex_be(raw)?),
+        }
+    }
+}
+
+/// Computes the address of a storage variable, or of a mapping entry when `keys` is not empty.
+pub fn storage_var_address(name: &str, keys: &[FieldElement]) -> Result<FieldElement> {
+    if name.is_empty() {
+        anyhow::bail!("storage variable name must not be empty");
+    }
+
+    Ok(get_storage_var_address(name, keys)?)
 }
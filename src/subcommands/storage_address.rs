@@ -0,0 +1,102 @@
+use anyhow::Result;
+use clap::Parser;
+use colored_json::{ColorMode, Output};
+use serde_json::json;
+use starknet::core::types::FieldElement;
+
+use crate::{
+    address_book::AddressBookResolver, chain_id::NoChainIdSource, decode::FeltDecoder,
+    output::output,
+};
+
+use super::storage::storage_var_address;
+
+#[derive(Debug, Parser)]
+pub struct StorageAddress {
+    #[clap(long, help = "Print the address along with its inputs as JSON")]
+    json: bool,
+    #[clap(help = "Name of the storage variable")]
+    var_name: String,
+    #[clap(help = "Mapping keys, each resolved like any other argument")]
+    keys: Vec<String>,
+}
+
+impl StorageAddress {
+    pub async fn run(self) -> Result<()> {
+        let (keys, address) = self.resolve().await?;
+
+        if self.json {
+            let address_json = colored_json::to_colored_json(
+                &json!({
+                    "name": self.var_name.trim(),
+                    "keys": keys
+                        .iter()
+                        .map(|key| format!("{:#064x}", key))
+                        .collect::<Vec<_>>(),
+                    "address": format!("{:#064x}", address),
+                }),
+                ColorMode::Auto(Output::StdOut),
+            )?;
+            output!("{address_json}");
+        } else {
+            output!("{:#064x}", address);
+        }
+
+        Ok(())
+    }
+
+    /// Decodes the keys and computes the address from them.
+    async fn resolve(&self) -> Result<(Vec<FieldElement>, FieldElement)> {
+        // Nothing is read from the network, so address book names can't be used as keys
+        let felt_decoder = FeltDecoder::new(AddressBookResolver::new(NoChainIdSource));
+        let mut keys = vec![];
+        for key in self.keys.iter() {
+            keys.append(&mut felt_decoder.decode(key).await?);
+        }
+
+        let address = storage_var_address(self.var_name.trim(), &keys)?;
+        Ok((keys, address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use starknet::macros::felt;
+
+    async fn address_of(args: &[&str]) -> Result<FieldElement> {
+        let cmd = StorageAddress::try_parse_from(
+            std::iter::once("storage-address").chain(args.iter().copied()),
+        )?;
+        Ok(cmd.resolve().await?.1)
+    }
+
+    #[tokio::test]
+    async fn test_storage_address_simple_var() {
+        // Generated with `cairo-lang`
+        assert_eq!(
+            address_of(&["balance"]).await.unwrap(),
+            felt!("0x0206f38f7e4f15e87567361213c28f235cccdaa1d7fd34c9db1dfe9489c6a091")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_storage_address_mapping() {
+        // Generated with `cairo-lang`
+        let expected = felt!("0x07de334d65aa93d9185729b424025918b18892418c85b802775d1f0d2be30a1d");
+
+        // Keys are decoded like any other argument
+        assert_eq!(address_of(&["balanceOf", "1234"]).await.unwrap(), expected);
+        assert_eq!(address_of(&["balanceOf", "0x4d2"]).await.unwrap(), expected);
+        assert_eq!(
+            address_of(&[" balanceOf ", "1234"]).await.unwrap(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_storage_address_empty_name() {
+        assert!(address_of(&[" "]).await.is_err());
+    }
+}
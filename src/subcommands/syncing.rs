@@ -3,7 +3,7 @@ use clap::Parser;
 use colored_json::{ColorMode, Output};
 use starknet::{core::types::SyncStatusType, providers::Provider};
 
-use crate::{verbosity::VerbosityArgs, ProviderArgs};
+use crate::{output::output, verbosity::VerbosityArgs, ProviderArgs};
 
 #[derive(Debug, Parser)]
 pub struct Syncing {
@@ -14,10 +14,12 @@ pub struct Syncing {
 }
 
 impl Syncing {
+    pub const RPC_METHODS: &'static [&'static str] = &["starknet_syncing"];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
 
-        let provider = self.provider.into_provider();
+        let provider = self.provider.into_provider()?;
 
         let sync_status = provider.syncing().await?;
 
@@ -26,10 +28,10 @@ impl Syncing {
                 let status_json = serde_json::to_value(status)?;
                 let status_json =
                     colored_json::to_colored_json(&status_json, ColorMode::Auto(Output::StdOut))?;
-                println!("{status_json}");
+                output!("{status_json}");
             }
             SyncStatusType::NotSyncing => {
-                println!("Not syncing");
+                output!("Not syncing");
             }
         }
 
@@ -2,6 +2,8 @@ use anyhow::Result;
 use clap::Parser;
 use starknet::core::utils::cairo_short_string_to_felt;
 
+use crate::output::output;
+
 #[derive(Debug, Parser)]
 pub struct ToCairoString {
     #[clap(long, help = "Display the encoded value in decimal representation")]
@@ -14,9 +16,9 @@ impl ToCairoString {
     pub fn run(self) -> Result<()> {
         let felt_value = cairo_short_string_to_felt(&self.text)?;
         if self.dec {
-            println!("{felt_value}");
+            output!("{felt_value}");
         } else {
-            println!("{felt_value:#x}");
+            output!("{felt_value:#x}");
         }
 
         Ok(())
@@ -3,7 +3,7 @@ use clap::Parser;
 use colored_json::{ColorMode, Output};
 use starknet::{core::types::FieldElement, providers::Provider};
 
-use crate::{verbosity::VerbosityArgs, ProviderArgs};
+use crate::{output::output, verbosity::VerbosityArgs, ProviderArgs};
 
 #[derive(Debug, Parser)]
 pub struct Transaction {
@@ -16,10 +16,12 @@ pub struct Transaction {
 }
 
 impl Transaction {
+    pub const RPC_METHODS: &'static [&'static str] = &["starknet_getTransactionByHash"];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
 
-        let provider = self.provider.into_provider();
+        let provider = self.provider.into_provider()?;
         let transaction_hash = FieldElement::from_hex_be(&self.hash)?;
 
         let transaction = provider.get_transaction_by_hash(transaction_hash).await?;
@@ -27,7 +29,7 @@ impl Transaction {
         let transaction_json = serde_json::to_value(transaction)?;
         let transaction_json =
             colored_json::to_colored_json(&transaction_json, ColorMode::Auto(Output::StdOut))?;
-        println!("{transaction_json}");
+        output!("{transaction_json}");
 
         Ok(())
     }
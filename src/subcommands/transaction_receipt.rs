@@ -1,14 +1,29 @@
+use std::collections::{hash_map::Entry, HashMap};
+
 use anyhow::Result;
 use clap::Parser;
 use colored_json::{ColorMode, Output};
+use serde_json::{Map, Value};
 use starknet::{core::types::FieldElement, providers::Provider};
 
-use crate::{verbosity::VerbosityArgs, ProviderArgs};
+use crate::{
+    abi::{AbiArgs, AbiSource, ContractAbi},
+    output::output,
+    verbosity::{warning, VerbosityArgs},
+    ProviderArgs,
+};
 
 #[derive(Debug, Parser)]
 pub struct TransactionReceipt {
     #[clap(flatten)]
     provider: ProviderArgs,
+    #[clap(
+        long,
+        help = "Decode events against the ABI of the emitting contracts where possible"
+    )]
+    decode_events: bool,
+    #[clap(flatten)]
+    abi: AbiArgs,
     #[clap(help = "Transaction hash")]
     hash: String,
     #[clap(flatten)]
@@ -16,19 +31,101 @@ pub struct TransactionReceipt {
 }
 
 impl TransactionReceipt {
+    pub const RPC_METHODS: &'static [&'static str] =
+        &["starknet_getTransactionReceipt", "starknet_getClassAt"];
+
     pub async fn run(self) -> Result<()> {
         self.verbosity.setup_logging();
 
-        let provider = self.provider.into_provider();
+        let provider = self.provider.into_provider()?;
+        let abi_source = self.abi.into_source()?;
         let transaction_hash = FieldElement::from_hex_be(&self.hash)?;
 
         let receipt = provider.get_transaction_receipt(transaction_hash).await?;
 
-        let receipt_json = serde_json::to_value(receipt)?;
+        let mut receipt_json = serde_json::to_value(receipt)?;
+        // Supplying an ABI only makes sense for decoding
+        if self.decode_events || abi_source.is_local() {
+            Self::decode_events(&provider, &abi_source, &mut receipt_json).await?;
+        }
+
         let receipt_json =
             colored_json::to_colored_json(&receipt_json, ColorMode::Auto(Output::StdOut))?;
-        println!("{receipt_json}");
+        output!("{receipt_json}");
+
+        Ok(())
+    }
+
+    /// Adds a `decoded` field to each event that can be decoded. Other events are left untouched.
+    async fn decode_events<P>(
+        provider: P,
+        abi_source: &AbiSource,
+        receipt_json: &mut Value,
+    ) -> Result<()>
+    where
+        P: Provider + Sync,
+        P::Error: 'static,
+    {
+        let events = match receipt_json.get_mut("events").and_then(Value::as_array_mut) {
+            Some(events) => events,
+            None => return Ok(()),
+        };
+
+        let mut abis: HashMap<FieldElement, Option<ContractAbi>> = HashMap::new();
+
+        for event in events.iter_mut() {
+            let from_address = parse_felt_field(event, "from_address")?;
+            let keys = parse_felt_array_field(event, "keys")?;
+            let data = parse_felt_array_field(event, "data")?;
+
+            if let Entry::Vacant(entry) = abis.entry(from_address) {
+                let abi = match abi_source.load(&provider, from_address).await {
+                    Ok(abi) => abi,
+                    Err(err) => {
+                        warning!(
+                            "WARNING: unable to fetch ABI for {:#064x}: {}",
+                            from_address,
+                            err
+                        );
+                        None
+                    }
+                };
+                entry.insert(abi);
+            }
+
+            let decoded = abis[&from_address]
+                .as_ref()
+                .and_then(|abi| abi.decode_event(&keys, &data));
+
+            if let (Some(decoded), Some(event)) = (decoded, event.as_object_mut()) {
+                let mut decoded_json = Map::new();
+                decoded_json.insert("name".into(), Value::String(decoded.name));
+                decoded_json.insert("fields".into(), Value::Object(decoded.fields));
+
+                event.insert("decoded".into(), Value::Object(decoded_json));
+            }
+        }
 
         Ok(())
     }
 }
+
+fn parse_felt_field(value: &Value, field: &str) -> Result<FieldElement> {
+    match value.get(field).and_then(Value::as_str) {
+        Some(felt) => Ok(FieldElement::from_hex_be(felt)?),
+        None => anyhow::bail!("missing event field: {}", field),
+    }
+}
+
+fn parse_felt_array_field(value: &Value, field: &str) -> Result<Vec<FieldElement>> {
+    match value.get(field).and_then(Value::as_array) {
+        Some(array) => array
+            .iter()
+            .map(|item| match item.as_str() {
+                Some(felt) => Ok(FieldElement::from_hex_be(felt)?),
+                None => anyhow::bail!("invalid event field: {}", field),
+            })
+            .collect(),
+        None => anyhow::bail!("missing event field: {}", field),
+    }
+}
@@ -0,0 +1,329 @@
+use anyhow::Result;
+use bigdecimal::BigDecimal;
+use clap::{Parser, ValueEnum};
+use colored::Colorize;
+use starknet::core::{
+    crypto::compute_hash_on_elements,
+    types::FieldElement,
+    utils::{cairo_short_string_to_felt, get_contract_address},
+};
+
+use crate::{
+    address_book::AddressBookResolver,
+    chain_id::NoChainIdSource,
+    config::parse_chain_id,
+    decode::FeltDecoder,
+    output::output,
+    utils::{bigdecimal_to_felt, expand_stdin_args, parse_felt_value},
+    verbosity::progress,
+};
+
+#[derive(Debug, Parser)]
+pub struct TxHash {
+    #[clap(long = "type", value_enum, help = "Type of the transaction")]
+    tx_type: TransactionType,
+    #[clap(
+        long,
+        help = "Transaction version. Defaults to the version Starkli sends for the type"
+    )]
+    tx_version: Option<u64>,
+    #[clap(
+        long,
+        help = "Chain ID, either as a short string like SN_MAIN or as a 0x-prefixed hex value"
+    )]
+    chain_id: String,
+    #[clap(long, help = "Address of the account sending the transaction")]
+    sender: Option<String>,
+    #[clap(long, help = "Nonce of the transaction")]
+    nonce: String,
+    #[clap(long, help = "Maximum transaction fee in Ether (18 decimals)")]
+    max_fee: Option<BigDecimal>,
+    #[clap(
+        long,
+        conflicts_with = "max_fee",
+        help = "Maximum transaction fee in Wei"
+    )]
+    max_fee_raw: Option<FieldElement>,
+    #[clap(
+        long,
+        help = "Hash of the class being declared, or of the account class being deployed"
+    )]
+    class_hash: Option<String>,
+    #[clap(
+        long,
+        help = "CASM hash of the class being declared, for version 2 declarations"
+    )]
+    compiled_class_hash: Option<String>,
+    #[clap(long, help = "Salt of the account deployment")]
+    salt: Option<String>,
+    #[clap(
+        help = "Raw __execute__ calldata for invoke transactions, or constructor calldata for \
+        account deployments. Pass \"-\" to read it from stdin"
+    )]
+    calldata: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum TransactionType {
+    Invoke,
+    Declare,
+    DeployAccount,
+}
+
+impl TxHash {
+    pub async fn run(self) -> Result<()> {
+        output!("{:#064x}", self.transaction_hash().await?);
+
+        Ok(())
+    }
+
+    async fn transaction_hash(&self) -> Result<FieldElement> {
+        let tx_type = self.tx_type.name();
+        let tx_version = self.tx_version.unwrap_or(match self.tx_type {
+            TransactionType::Invoke | TransactionType::DeployAccount => 1,
+            TransactionType::Declare => 2,
+        });
+        match (self.tx_type, tx_version) {
+            (TransactionType::Invoke | TransactionType::DeployAccount, 1)
+            | (TransactionType::Declare, 1 | 2) => {}
+            (_, 3) => anyhow::bail!("version 3 transactions are not supported"),
+            _ => anyhow::bail!("unsupported {} transaction version {}", tx_type, tx_version),
+        }
+
+        let chain_id = parse_chain_id(&self.chain_id)
+            .ok_or_else(|| anyhow::anyhow!("invalid chain id: {}", self.chain_id))?;
+        let nonce = parse_felt_value(&self.nonce)?;
+        let max_fee = match (&self.max_fee, self.max_fee_raw) {
+            (Some(max_fee), _) => bigdecimal_to_felt(max_fee, 18)?,
+            (None, Some(max_fee_raw)) => max_fee_raw,
+            (None, None) => anyhow::bail!("either --max-fee or --max-fee-raw is required"),
+        };
+
+        let felt_decoder = FeltDecoder::new(AddressBookResolver::new(NoChainIdSource));
+        let mut calldata = vec![];
+        for element in expand_stdin_args(&self.calldata, false)?.iter() {
+            calldata.append(&mut felt_decoder.decode(element).await?);
+        }
+
+        // Rejecting parameters that don't go into the hash, as passing one usually means the
+        // wrong transaction type was picked
+        let allowed: &[&str] = match (self.tx_type, tx_version) {
+            (TransactionType::Invoke, _) => &["--sender", "calldata"],
+            (TransactionType::Declare, 1) => &["--sender", "--class-hash"],
+            (TransactionType::Declare, _) => &["--sender", "--class-hash", "--compiled-class-hash"],
+            (TransactionType::DeployAccount, _) => &["--class-hash", "--salt", "calldata"],
+        };
+        for (name, is_set) in [
+            ("--sender", self.sender.is_some()),
+            ("--class-hash", self.class_hash.is_some()),
+            ("--compiled-class-hash", self.compiled_class_hash.is_some()),
+            ("--salt", self.salt.is_some()),
+            ("calldata", !calldata.is_empty()),
+        ] {
+            if is_set && !allowed.contains(&name) {
+                anyhow::bail!(
+                    "{} does not apply to version {} {} transactions",
+                    name,
+                    tx_version,
+                    tx_type
+                );
+            }
+        }
+
+        let required = |value: &Option<String>, name: &str| match value {
+            Some(value) => parse_felt_value(value),
+            None => anyhow::bail!(
+                "{} is required for version {} {} transactions",
+                name,
+                tx_version,
+                tx_type
+            ),
+        };
+
+        // Same fields as the ones hashed by the account before signing
+        let (prefix, address, hashed_data, extra_field) = match self.tx_type {
+            TransactionType::Invoke => (
+                "invoke",
+                required(&self.sender, "--sender")?,
+                compute_hash_on_elements(&calldata),
+                None,
+            ),
+            TransactionType::Declare => (
+                "declare",
+                required(&self.sender, "--sender")?,
+                compute_hash_on_elements(&[required(&self.class_hash, "--class-hash")?]),
+                if tx_version == 2 {
+                    Some(required(
+                        &self.compiled_class_hash,
+                        "--compiled-class-hash",
+                    )?)
+                } else {
+                    None
+                },
+            ),
+            TransactionType::DeployAccount => {
+                let class_hash = required(&self.class_hash, "--class-hash")?;
+                let salt = required(&self.salt, "--salt")?;
+
+                let address = get_contract_address(salt, class_hash, &calldata, FieldElement::ZERO);
+                progress!(
+                    "Account address: {}",
+                    format!("{:#064x}", address).bright_yellow()
+                );
+
+                let mut data_to_hash = vec![class_hash, salt];
+                data_to_hash.extend_from_slice(&calldata);
+
+                (
+                    "deploy_account",
+                    address,
+                    compute_hash_on_elements(&data_to_hash),
+                    None,
+                )
+            }
+        };
+
+        let mut fields = vec![
+            cairo_short_string_to_felt(prefix)?,
+            tx_version.into(),
+            address,
+            FieldElement::ZERO, // entry_point_selector
+            hashed_data,
+            max_fee,
+            chain_id,
+            nonce,
+        ];
+        fields.extend(extra_field);
+
+        Ok(compute_hash_on_elements(&fields))
+    }
+}
+
+impl TransactionType {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Invoke => "invoke",
+            Self::Declare => "declare",
+            Self::DeployAccount => "deploy-account",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::macros::felt;
+
+    use super::*;
+
+    async fn transaction_hash(args: &[&str]) -> Result<FieldElement> {
+        TxHash::try_parse_from([&["tx-hash"], args].concat())?
+            .transaction_hash()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_declare_v2_hash() {
+        // Declaration 0x4c0955d5... in block 788322 of goerli-1
+        assert_eq!(
+            transaction_hash(&[
+                "--type",
+                "declare",
+                "--chain-id",
+                "SN_GOERLI",
+                "--sender",
+                "0x2da37a17affbd2df4ede7120dae305ec36dfe94ec96a8c3f49bbf59f4e9a9fa",
+                "--nonce",
+                "0x262",
+                "--max-fee-raw",
+                "0x1136c3d4eeb5",
+                "--class-hash",
+                "0x571f4a4a5caae490577d67870c431fa560d72038e4c7a270d91aee1112d55d0",
+                "--compiled-class-hash",
+                "0x27302e393fb5bfa69fc207ff22f7a5c9812908952b257b012fa50b6fa8c7be0",
+            ])
+            .await
+            .unwrap(),
+            felt!("0x4c0955d59cd105eb916d0cf24eb12c01be435fe83d95cd184e32a035c85c1d3")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invoke_v1_hash() {
+        // Invocation 0x4a2b544a... in the same block
+        assert_eq!(
+            transaction_hash(&[
+                "--type",
+                "invoke",
+                "--chain-id",
+                "SN_GOERLI",
+                "--sender",
+                "0x4dad3c0e26f75b1bf64ec3fb54b25f641315057c061e1b12804bcca49ae81f7",
+                "--nonce",
+                "0x10",
+                "--max-fee-raw",
+                "0x17caaa9c5bd52",
+                "0x1",
+                "0x5dc687af554664861727c909269dbc339e876bc70352efd64b8d057ddfb285",
+                "0x2f0b3c5710379609eb5495f1ecd348cb28167711b73609fe565a72734550354",
+                "0x0",
+                "0x0",
+                "0x0",
+            ])
+            .await
+            .unwrap(),
+            felt!("0x4a2b544a966f8c43a8cc045448a4e76cd39ee6d48be6c8d7327695a40a976a1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deploy_account_v1_hash() {
+        // Account deployment 0x2a545fc3... in the same block
+        assert_eq!(
+            transaction_hash(&[
+                "--type",
+                "deploy-account",
+                "--chain-id",
+                "SN_GOERLI",
+                "--nonce",
+                "0x0",
+                "--max-fee-raw",
+                "0x12c221cc6a000",
+                "--class-hash",
+                "0x3131fa018d520a037686ce3efddeab8f28895662f019ca3ca18a626650f7d1e",
+                "--salt",
+                "0x2646cb3fd4049ffd0515957c4645f17a8d374bfa47b796693d141f3af01c2f8",
+                "0x5aa23d5bb71ddaa783da7ea79d405315bafa7cf0387a74f4593578c3e9e6570",
+                "0x2dd76e7ad84dbed81c314ffe5e7a7cacfb8f4836f01af4e913f275f89a3de1a",
+                "0x1",
+                "0x2646cb3fd4049ffd0515957c4645f17a8d374bfa47b796693d141f3af01c2f8",
+            ])
+            .await
+            .unwrap(),
+            felt!("0x2a545fc36574e4bd2092fac7323a4e49d8b183946e278956b10b20fc5493af1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inapplicable_fields() {
+        let err = transaction_hash(&[
+            "--type",
+            "invoke",
+            "--chain-id",
+            "SN_GOERLI",
+            "--sender",
+            "0x1",
+            "--nonce",
+            "0",
+            "--max-fee-raw",
+            "0x1",
+            "--class-hash",
+            "0x2",
+        ])
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "--class-hash does not apply to version 1 invoke transactions"
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use clap::Parser;
+
+use crate::{output::output, utils::parse_felt_value};
+
+use super::DecimalsArgs;
+
+#[derive(Debug, Parser)]
+pub struct FromWei {
+    #[clap(flatten)]
+    decimals: DecimalsArgs,
+    #[clap(help = "Raw token amount, in decimal or hexadecimal representation")]
+    amount: String,
+}
+
+impl FromWei {
+    pub fn run(self) -> Result<()> {
+        output!("{}", self.render()?);
+
+        Ok(())
+    }
+
+    fn render(&self) -> Result<String> {
+        let raw = parse_felt_value(&self.amount)?;
+
+        // Same representation as the one used for displaying fees
+        Ok(raw.to_big_decimal(self.decimals.decimals()).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(args: &[&str]) -> Result<String> {
+        FromWei::try_parse_from([&["from-wei"], args].concat())?.render()
+    }
+
+    #[test]
+    fn test_from_wei() {
+        assert_eq!(
+            render(&["1500000000000000000"]).unwrap(),
+            "1.500000000000000000"
+        );
+        assert_eq!(render(&["0x10", "--decimals", "1"]).unwrap(), "1.6");
+        assert_eq!(render(&["1500000", "--decimals", "6"]).unwrap(), "1.500000");
+        assert_eq!(render(&["7", "--decimals", "0"]).unwrap(), "7");
+
+        assert!(render(&["not a number"]).is_err());
+    }
+}
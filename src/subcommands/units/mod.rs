@@ -0,0 +1,103 @@
+use std::{fmt::Display, str::FromStr};
+
+use anyhow::Result;
+use clap::{builder::PossibleValue, Parser, Subcommand, ValueEnum};
+
+mod to_wei;
+use to_wei::ToWei;
+
+mod from_wei;
+use from_wei::FromWei;
+
+#[derive(Debug, Parser)]
+pub struct Units {
+    #[clap(subcommand)]
+    command: Subcommands,
+}
+
+#[derive(Debug, Subcommand)]
+enum Subcommands {
+    #[clap(about = "Convert a decimal token amount into its raw integer representation")]
+    ToWei(ToWei),
+    #[clap(about = "Convert a raw integer token amount into its decimal representation")]
+    FromWei(FromWei),
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct DecimalsArgs {
+    #[clap(long, help = "Token to use the decimals of")]
+    token: Option<Token>,
+    #[clap(
+        long,
+        conflicts_with = "token",
+        help = "Number of decimals of the token. Defaults to 18"
+    )]
+    decimals: Option<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    Eth,
+    Strk,
+}
+
+impl Units {
+    pub fn run(self) -> Result<()> {
+        match self.command {
+            Subcommands::ToWei(cmd) => cmd.run(),
+            Subcommands::FromWei(cmd) => cmd.run(),
+        }
+    }
+}
+
+impl DecimalsArgs {
+    pub fn decimals(&self) -> u8 {
+        match (self.token, self.decimals) {
+            (Some(token), _) => token.decimals(),
+            (None, Some(decimals)) => decimals,
+            (None, None) => 18,
+        }
+    }
+}
+
+impl Token {
+    pub fn decimals(&self) -> u8 {
+        match self {
+            Self::Eth | Self::Strk => 18,
+        }
+    }
+}
+
+impl ValueEnum for Token {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Eth, Self::Strk]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        match self {
+            Self::Eth => Some(PossibleValue::new("eth")),
+            Self::Strk => Some(PossibleValue::new("strk")),
+        }
+    }
+}
+
+impl FromStr for Token {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "eth" => Ok(Self::Eth),
+            "strk" => Ok(Self::Strk),
+            _ => Err(anyhow::anyhow!("unknown token: {}", s)),
+        }
+    }
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Eth => write!(f, "eth"),
+            Self::Strk => write!(f, "strk"),
+        }
+    }
+}
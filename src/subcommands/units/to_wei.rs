@@ -0,0 +1,65 @@
+use anyhow::Result;
+use bigdecimal::BigDecimal;
+use clap::Parser;
+
+use crate::{output::output, utils::bigdecimal_to_felt};
+
+use super::DecimalsArgs;
+
+#[derive(Debug, Parser)]
+pub struct ToWei {
+    #[clap(flatten)]
+    decimals: DecimalsArgs,
+    #[clap(long, help = "Display the raw amount in hexadecimal representation")]
+    hex: bool,
+    #[clap(help = "Token amount in decimal representation (e.g. 0.5)")]
+    amount: BigDecimal,
+}
+
+impl ToWei {
+    pub fn run(self) -> Result<()> {
+        output!("{}", self.render()?);
+
+        Ok(())
+    }
+
+    fn render(&self) -> Result<String> {
+        // Amounts with more fractional digits than the token supports are rejected instead of
+        // being silently rounded.
+        let raw = bigdecimal_to_felt(&self.amount, self.decimals.decimals())?;
+
+        Ok(if self.hex {
+            format!("{raw:#x}")
+        } else {
+            format!("{raw}")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(args: &[&str]) -> Result<String> {
+        ToWei::try_parse_from([&["to-wei"], args].concat())?.render()
+    }
+
+    #[test]
+    fn test_to_wei() {
+        assert_eq!(render(&["0.5"]).unwrap(), "500000000000000000");
+        assert_eq!(
+            render(&["1", "--token", "strk"]).unwrap(),
+            "1000000000000000000"
+        );
+        assert_eq!(render(&["1.5", "--decimals", "6"]).unwrap(), "1500000");
+        assert_eq!(
+            render(&["1.5", "--decimals", "6", "--hex"]).unwrap(),
+            "0x16e360"
+        );
+        assert_eq!(render(&["0", "--hex"]).unwrap(), "0x0");
+
+        assert!(render(&["0.0000001", "--decimals", "6"]).is_err());
+        assert!(render(&["1", "--token", "eth", "--decimals", "6"]).is_err());
+        assert!(render(&["1", "--token", "btc"]).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use std::{
+    future::Future,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Accumulated time and count for each phase when `--timing` is used, indexed by [`Phase`].
+static TIMINGS: Mutex<Option<[(Duration, u64); PHASE_COUNT]>> = Mutex::new(None);
+
+const PHASE_COUNT: usize = 6;
+
+/// Parts of a command that commonly account for its running time. Phases can overlap, e.g. a
+/// chain id fetched while decoding an address book name counts towards both.
+#[derive(Clone, Copy)]
+pub enum Phase {
+    ProviderSetup,
+    ChainId,
+    Decode,
+    Estimate,
+    Send,
+    Watch,
+}
+
+impl Phase {
+    const ALL: [Phase; PHASE_COUNT] = [
+        Self::ProviderSetup,
+        Self::ChainId,
+        Self::Decode,
+        Self::Estimate,
+        Self::Send,
+        Self::Watch,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::ProviderSetup => "Provider setup",
+            Self::ChainId => "Chain id fetch",
+            Self::Decode => "Argument decoding",
+            Self::Estimate => "Fee estimation",
+            Self::Send => "Sending",
+            Self::Watch => "Confirmation wait",
+        }
+    }
+}
+
+/// Starts recording phase timings for [`print_report`].
+pub fn enable() {
+    *TIMINGS.lock().unwrap() = Some(Default::default());
+}
+
+/// Runs `future`, adding the time it takes to `phase` when timings are recorded.
+pub async fn measure<F>(phase: Phase, future: F) -> F::Output
+where
+    F: Future,
+{
+    if TIMINGS.lock().unwrap().is_none() {
+        return future.await;
+    }
+
+    let started_at = Instant::now();
+    let output = future.await;
+    record(phase, started_at.elapsed());
+
+    output
+}
+
+/// Same as [`measure`] for synchronous work.
+pub fn measure_sync<T, F>(phase: Phase, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    let started_at = Instant::now();
+    let output = f();
+    record(phase, started_at.elapsed());
+
+    output
+}
+
+fn record(phase: Phase, elapsed: Duration) {
+    if let Some(timings) = TIMINGS.lock().unwrap().as_mut() {
+        let (total, count) = &mut timings[phase as usize];
+        *total += elapsed;
+        *count += 1;
+    }
+}
+
+/// Prints the time spent in each phase that was entered to stderr, along with the total running
+/// time of the command. Does nothing unless timings are recorded.
+pub fn print_report(total: Duration) {
+    let timings = match TIMINGS.lock().unwrap().take() {
+        Some(timings) => timings,
+        None => return,
+    };
+
+    eprintln!("Timing:");
+    for phase in Phase::ALL.iter() {
+        let (elapsed, count) = timings[*phase as usize];
+        if count > 0 {
+            eprintln!(
+                "  {:<20} {:>9.3}s ({} {})",
+                format!("{}:", phase.label()),
+                elapsed.as_secs_f64(),
+                count,
+                if count == 1 { "time" } else { "times" }
+            );
+        }
+    }
+    eprintln!("  {:<20} {:>9.3}s", "Total:", total.as_secs_f64());
+}
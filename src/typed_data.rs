@@ -0,0 +1,270 @@
+use std::collections::{BTreeSet, HashMap};
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::Value;
+use starknet::core::{
+    crypto::compute_hash_on_elements,
+    types::FieldElement,
+    utils::{cairo_short_string_to_felt, get_selector_from_name, starknet_keccak},
+};
+
+const DOMAIN_TYPE_NAME: &str = "StarkNetDomain";
+const MESSAGE_PREFIX: &str = "StarkNet Message";
+
+/// An off-chain typed data document as defined in SNIP-12 (revision 0), which is the format
+/// currently understood by wallets and `starknet.js`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TypedData {
+    types: HashMap<String, Vec<TypeMember>>,
+    primary_type: String,
+    domain: Value,
+    message: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TypeMember {
+    name: String,
+    r#type: String,
+}
+
+impl TypedData {
+    /// Checks that all referenced types are defined and that the domain is present, so that
+    /// hashing only ever fails on the values themselves.
+    pub fn validate(&self) -> Result<()> {
+        if !self.types.contains_key(DOMAIN_TYPE_NAME) {
+            anyhow::bail!("typed data is missing the {} type", DOMAIN_TYPE_NAME);
+        }
+        if !self.types.contains_key(&self.primary_type) {
+            anyhow::bail!("primary type \"{}\" is not defined", self.primary_type);
+        }
+        if self.primary_type == DOMAIN_TYPE_NAME {
+            anyhow::bail!("primary type cannot be {}", DOMAIN_TYPE_NAME);
+        }
+
+        for (type_name, members) in self.types.iter() {
+            for member in members.iter() {
+                let base_type = member.r#type.trim_end_matches('*');
+                if !Self::is_basic_type(base_type) && !self.types.contains_key(base_type) {
+                    anyhow::bail!(
+                        "type \"{}\" used by {}.{} is not defined",
+                        member.r#type,
+                        type_name,
+                        member.name
+                    );
+                }
+            }
+        }
+
+        let domain = self
+            .domain
+            .as_object()
+            .ok_or_else(|| anyhow::anyhow!("typed data domain must be an object"))?;
+        let domain_members = &self.types[DOMAIN_TYPE_NAME];
+        for field in domain.keys() {
+            if !domain_members.iter().any(|member| &member.name == field) {
+                anyhow::bail!(
+                    "domain field \"{}\" is not part of {}",
+                    field,
+                    DOMAIN_TYPE_NAME
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Computes the message hash to be signed by the account at `account_address`.
+    pub fn message_hash(&self, account_address: FieldElement) -> Result<FieldElement> {
+        self.validate()?;
+
+        Ok(compute_hash_on_elements(&[
+            cairo_short_string_to_felt(MESSAGE_PREFIX)?,
+            self.struct_hash(DOMAIN_TYPE_NAME, &self.domain)?,
+            account_address,
+            self.struct_hash(&self.primary_type, &self.message)?,
+        ]))
+    }
+
+    fn struct_hash(&self, type_name: &str, value: &Value) -> Result<FieldElement> {
+        let members = self
+            .types
+            .get(type_name)
+            .ok_or_else(|| anyhow::anyhow!("type \"{}\" is not defined", type_name))?;
+        let object = value
+            .as_object()
+            .ok_or_else(|| anyhow::anyhow!("value of type {} must be an object", type_name))?;
+
+        let mut elements = vec![self.type_hash(type_name)?];
+        for member in members.iter() {
+            let member_value = object.get(&member.name).ok_or_else(|| {
+                anyhow::anyhow!("missing field \"{}\" of type {}", member.name, type_name)
+            })?;
+            elements.push(self.encode_value(&member.r#type, member_value)?);
+        }
+
+        Ok(compute_hash_on_elements(&elements))
+    }
+
+    fn type_hash(&self, type_name: &str) -> Result<FieldElement> {
+        Ok(starknet_keccak(self.encode_type(type_name)?.as_bytes()))
+    }
+
+    /// Encodes a struct type followed by all the struct types it references, sorted by name.
+    fn encode_type(&self, type_name: &str) -> Result<String> {
+        let mut dependencies = BTreeSet::new();
+        self.collect_dependencies(type_name, &mut dependencies);
+        dependencies.remove(type_name);
+
+        std::iter::once(type_name)
+            .chain(dependencies.iter().map(|name| name.as_str()))
+            .map(|name| {
+                let members = self
+                    .types
+                    .get(name)
+                    .ok_or_else(|| anyhow::anyhow!("type \"{}\" is not defined", name))?;
+                Ok(format!(
+                    "{}({})",
+                    name,
+                    members
+                        .iter()
+                        .map(|member| format!("{}:{}", member.name, member.r#type))
+                        .collect::<Vec<_>>()
+                        .join(",")
+                ))
+            })
+            .collect()
+    }
+
+    fn collect_dependencies(&self, type_name: &str, dependencies: &mut BTreeSet<String>) {
+        if dependencies.contains(type_name) {
+            return;
+        }
+        if let Some(members) = self.types.get(type_name) {
+            dependencies.insert(type_name.to_owned());
+            for member in members.iter() {
+                self.collect_dependencies(member.r#type.trim_end_matches('*'), dependencies);
+            }
+        }
+    }
+
+    fn encode_value(&self, r#type: &str, value: &Value) -> Result<FieldElement> {
+        if let Some(element_type) = r#type.strip_suffix('*') {
+            let elements = value
+                .as_array()
+                .ok_or_else(|| anyhow::anyhow!("value of type {} must be an array", r#type))?
+                .iter()
+                .map(|element| self.encode_value(element_type, element))
+                .collect::<Result<Vec<_>>>()?;
+            return Ok(compute_hash_on_elements(&elements));
+        }
+
+        if self.types.contains_key(r#type) {
+            return self.struct_hash(r#type, value);
+        }
+
+        match (r#type, value) {
+            ("bool", Value::Bool(value)) => Ok(if *value {
+                FieldElement::ONE
+            } else {
+                FieldElement::ZERO
+            }),
+            ("selector", Value::String(value)) if !value.starts_with("0x") => {
+                Ok(get_selector_from_name(value)?)
+            }
+            ("felt" | "string" | "selector" | "bool", value) => Self::encode_felt(value),
+            _ => anyhow::bail!("unsupported type \"{}\"", r#type),
+        }
+    }
+
+    /// Felts can be numbers, hex or decimal strings, or short strings.
+    fn encode_felt(value: &Value) -> Result<FieldElement> {
+        match value {
+            Value::Number(number) => Ok(FieldElement::from_dec_str(&number.to_string())?),
+            Value::String(value) if value.starts_with("0x") => {
+                Ok(FieldElement::from_hex_be(value)?)
+            }
+            Value::String(value)
+                if !value.is_empty() && value.chars().all(|char| char.is_ascii_digit()) =>
+            {
+                Ok(FieldElement::from_dec_str(value)?)
+            }
+            Value::String(value) => Ok(cairo_short_string_to_felt(value)?),
+            _ => anyhow::bail!("invalid felt value: {}", value),
+        }
+    }
+
+    fn is_basic_type(r#type: &str) -> bool {
+        matches!(r#type, "felt" | "string" | "selector" | "bool")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starknet::macros::felt;
+
+    use super::*;
+
+    /// The `Mail` example from starknet.js.
+    const MAIL: &str = r#"{
+        "types": {
+            "StarkNetDomain": [
+                { "name": "name", "type": "felt" },
+                { "name": "version", "type": "felt" },
+                { "name": "chainId", "type": "felt" }
+            ],
+            "Person": [
+                { "name": "name", "type": "felt" },
+                { "name": "wallet", "type": "felt" }
+            ],
+            "Mail": [
+                { "name": "from", "type": "Person" },
+                { "name": "to", "type": "Person" },
+                { "name": "contents", "type": "felt" }
+            ]
+        },
+        "primaryType": "Mail",
+        "domain": {
+            "name": "StarkNet Mail",
+            "version": "1",
+            "chainId": 1
+        },
+        "message": {
+            "from": {
+                "name": "Cow",
+                "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"
+            },
+            "to": {
+                "name": "Bob",
+                "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"
+            },
+            "contents": "Hello, Bob!"
+        }
+    }"#;
+
+    #[test]
+    fn test_message_hash() {
+        let typed_data: TypedData = serde_json::from_str(MAIL).unwrap();
+        typed_data.validate().unwrap();
+
+        assert_eq!(
+            typed_data
+                .message_hash(felt!("0xcd2a3d9f938e13cd947ec05abc7fe734df8dd826"))
+                .unwrap(),
+            felt!("0x06fcff244f63e38b9d88b9e3378d44757710d1b244282b435cb472053c8d78d0")
+        );
+    }
+
+    #[test]
+    fn test_encode_type() {
+        let typed_data: TypedData = serde_json::from_str(MAIL).unwrap();
+
+        // Dependencies come after the primary type
+        assert_eq!(
+            typed_data.encode_type("Mail").unwrap(),
+            "Mail(from:Person,to:Person,contents:felt)Person(name:felt,wallet:felt)"
+        );
+    }
+}
@@ -1,49 +1,740 @@
-use std::time::Duration;
+use std::{
+    future::Future,
+    io::BufRead,
+    path::Path,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use anyhow::Result;
 use bigdecimal::{BigDecimal, Zero};
+use clap::{Parser, ValueEnum};
 use colored::Colorize;
 use num_integer::Integer;
 use regex::Regex;
+use serde::Serialize;
 use starknet::{
-    core::types::{BlockId, BlockTag, FieldElement, StarknetError},
+    accounts::{AccountError, ConnectedAccount, PreparedExecution},
+    core::types::{
+        BlockId, BlockTag, FieldElement, FunctionCall, MaybePendingTransactionReceipt,
+        StarknetError, TransactionReceipt, TransactionStatus,
+    },
+    macros::selector,
     providers::{MaybeUnknownErrorCode, Provider, ProviderError, StarknetErrorWithMessage},
 };
 
-pub async fn watch_tx<P>(provider: P, transaction_hash: FieldElement) -> Result<()>
+use crate::{
+    address_book::AddressBookResolver,
+    chain_id::ChainIdSource,
+    fee::{resolve_fee_token, FeeFormatter},
+    output::output,
+    provider::ExtendedProviderError,
+    spinner::Spinner,
+    timing::{self, Phase},
+    verbosity::{progress, warning},
+};
+
+/// How field elements are printed by commands supporting `--felt-format`.
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum FeltFormat {
+    /// Zero-padded 64-digit hex.
+    #[default]
+    Hex,
+    /// Decimal.
+    Dec,
+}
+
+impl FeltFormat {
+    pub fn format(&self, felt: FieldElement) -> String {
+        match self {
+            Self::Hex => format!("{:#064x}", felt),
+            Self::Dec => felt.to_string(),
+        }
+    }
+}
+
+/// Conditions a watched transaction has to meet all of before watching returns.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WatchConditions {
+    pub min_confirmations: u64,
+    pub finality: Option<Finality>,
+    pub timeout: Option<Duration>,
+}
+
+/// Transaction status to wait for with `--finality`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Finality {
+    AcceptedOnL2,
+    AcceptedOnL1,
+}
+
+pub async fn watch_tx<P>(
+    provider: P,
+    transaction_hash: FieldElement,
+    conditions: WatchConditions,
+) -> Result<()>
+where
+    P: Provider,
+    P::Error: 'static,
+{
+    timing::measure(
+        Phase::Watch,
+        wait_for_tx(provider, transaction_hash, conditions),
+    )
+    .await
+}
+
+async fn wait_for_tx<P>(
+    provider: P,
+    transaction_hash: FieldElement,
+    conditions: WatchConditions,
+) -> Result<()>
 where
     P: Provider,
     P::Error: 'static,
 {
+    let spinner = Spinner::new();
+    let started_at = Instant::now();
+
+    let mut progress = TxProgress::default();
+    let mut last_pending = String::new();
     loop {
         // TODO: check with sequencer gateway if it's not confirmed after an extended period of
         // time, as full nodes don't have access to failed transactions and would report them
         // as `NotReceived`.
-        match provider.get_transaction_receipt(transaction_hash).await {
-            Ok(_) => {
+        let status = match spinner
+            .run(
+                "Waiting for transaction to confirm...",
+                provider.get_transaction_receipt(transaction_hash),
+            )
+            .await
+        {
+            Ok(receipt) => {
                 // With JSON-RPC, once we get a receipt, the transaction must have been confirmed.
                 // Rejected transactions simply aren't available. This needs to be changed once we
                 // implement the sequencer fallback.
 
-                eprintln!(
-                    "Transaction {} confirmed",
-                    format!("{:#064x}", transaction_hash).bright_yellow()
-                );
-                return Ok(());
+                let status = receipt_status(&receipt);
+
+                // Each transition is reported once along with the time since broadcast, so that
+                // a long wait visibly makes progress
+                match progress.update(status, receipt_block_number(&receipt)) {
+                    Some(TxTransition::Included(included_in)) => progress!(
+                        "Transaction {} included in block {} after {}s",
+                        format!("{:#064x}", transaction_hash).bright_yellow(),
+                        included_in,
+                        started_at.elapsed().as_secs()
+                    ),
+                    Some(TxTransition::SeenPending) => progress!(
+                        "Transaction {} seen in the pending block after {}s",
+                        format!("{:#064x}", transaction_hash).bright_yellow(),
+                        started_at.elapsed().as_secs()
+                    ),
+                    Some(TxTransition::StatusChanged(status)) => progress!(
+                        "Transaction {} is now {} after {}s",
+                        format!("{:#064x}", transaction_hash).bright_yellow(),
+                        status_name(status),
+                        started_at.elapsed().as_secs()
+                    ),
+                    None => {}
+                }
+
+                Some(status)
             }
             Err(ProviderError::StarknetError(StarknetErrorWithMessage {
                 code: MaybeUnknownErrorCode::Known(StarknetError::TransactionHashNotFound),
                 ..
-            })) => {
-                eprintln!("Transaction not confirmed yet...");
+            })) => None,
+            Err(err) => return Err(err.into()),
+        };
+        if status == Some(TransactionStatus::Rejected) {
+            anyhow::bail!("transaction {:#064x} was rejected", transaction_hash);
+        }
+
+        // All conditions are evaluated on each poll, so that progress shows everything that's
+        // still pending
+        let mut confirmations = None;
+        let pending = match status {
+            None => vec!["inclusion".to_owned()],
+            Some(status) => {
+                // Confirmations can only be counted once the transaction is in a numbered block
+                if conditions.min_confirmations > 0 {
+                    confirmations = Some(match progress.block_number {
+                        Some(block_number) => {
+                            provider.block_number().await?.saturating_sub(block_number)
+                        }
+                        None => 0,
+                    });
+                }
+                conditions.pending(status, confirmations.unwrap_or_default())
+            }
+        };
+
+        if pending.is_empty() {
+            match confirmations {
+                Some(confirmations) => progress!(
+                    "Transaction {} confirmed with {} confirmations",
+                    format!("{:#064x}", transaction_hash).bright_yellow(),
+                    confirmations
+                ),
+                None => progress!(
+                    "Transaction {} confirmed",
+                    format!("{:#064x}", transaction_hash).bright_yellow()
+                ),
+            }
+            return Ok(());
+        }
+
+        let pending = pending.join(", ");
+        let poll_interval = match conditions.next_poll_interval(started_at.elapsed()) {
+            Some(poll_interval) => poll_interval,
+            None => anyhow::bail!(
+                "timed out after {} seconds watching transaction {:#064x}, still waiting for {}. \
+                The transaction might still confirm later",
+                conditions.timeout.unwrap_or_default().as_secs(),
+                transaction_hash,
+                pending
+            ),
+        };
+
+        // The spinner already shows what's pending. Otherwise, only report changes to avoid
+        // flooding the output
+        if !spinner.is_enabled() && pending != last_pending {
+            progress!("Waiting for {}...", pending);
+        }
+        spinner
+            .run(
+                &format!("Waiting for {}...", pending),
+                tokio::time::sleep(poll_interval),
+            )
+            .await;
+        last_pending = pending;
+    }
+}
+
+impl WatchConditions {
+    const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+    /// Conditions not met yet by an included transaction with this status and number of
+    /// confirmations, as shown in progress messages.
+    fn pending(&self, status: TransactionStatus, confirmations: u64) -> Vec<String> {
+        let mut pending = vec![];
+
+        if let Some(finality) = self.finality {
+            if !finality.is_reached(status) {
+                pending.push(format!(
+                    "{} (currently {})",
+                    finality.name(),
+                    status_name(status)
+                ));
+            }
+        }
+        if confirmations < self.min_confirmations {
+            pending.push(format!(
+                "{}/{} confirmations",
+                confirmations, self.min_confirmations
+            ));
+        }
+
+        pending
+    }
+
+    /// How long to wait before polling again, or `None` once the timeout has elapsed. The last
+    /// poll happens right when the timeout is reached.
+    fn next_poll_interval(&self, elapsed: Duration) -> Option<Duration> {
+        match self.timeout {
+            Some(timeout) => {
+                let remaining = timeout.saturating_sub(elapsed);
+                if remaining.is_zero() {
+                    None
+                } else {
+                    Some(Self::POLL_INTERVAL.min(remaining))
+                }
+            }
+            None => Some(Self::POLL_INTERVAL),
+        }
+    }
+}
+
+impl Finality {
+    fn is_reached(&self, status: TransactionStatus) -> bool {
+        match self {
+            Self::AcceptedOnL2 => matches!(
+                status,
+                TransactionStatus::AcceptedOnL2 | TransactionStatus::AcceptedOnL1
+            ),
+            Self::AcceptedOnL1 => status == TransactionStatus::AcceptedOnL1,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::AcceptedOnL2 => "ACCEPTED_ON_L2",
+            Self::AcceptedOnL1 => "ACCEPTED_ON_L1",
+        }
+    }
+}
+
+/// What's known about a watched transaction from the receipts seen so far.
+#[derive(Debug, Default)]
+struct TxProgress {
+    block_number: Option<u64>,
+    last_status: Option<TransactionStatus>,
+}
+
+/// A change in a watched transaction worth reporting.
+#[derive(Debug, PartialEq, Eq)]
+enum TxTransition {
+    Included(u64),
+    SeenPending,
+    StatusChanged(TransactionStatus),
+}
+
+impl TxProgress {
+    /// Records the status and block of the latest receipt, returning the transition it shows if
+    /// any. Inclusion in a block takes precedence over a status change seen in the same receipt.
+    fn update(
+        &mut self,
+        status: TransactionStatus,
+        block_number: Option<u64>,
+    ) -> Option<TxTransition> {
+        let last_status = self.last_status.replace(status);
+
+        match (self.block_number, block_number) {
+            (None, Some(included_in)) => {
+                self.block_number = Some(included_in);
+                Some(TxTransition::Included(included_in))
+            }
+            _ if status == TransactionStatus::Pending && last_status.is_none() => {
+                Some(TxTransition::SeenPending)
+            }
+            _ if last_status.is_some() && last_status != Some(status) => {
+                Some(TxTransition::StatusChanged(status))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn status_name(status: TransactionStatus) -> &'static str {
+    match status {
+        TransactionStatus::Pending => "PENDING",
+        TransactionStatus::AcceptedOnL2 => "ACCEPTED_ON_L2",
+        TransactionStatus::AcceptedOnL1 => "ACCEPTED_ON_L1",
+        TransactionStatus::Rejected => "REJECTED",
+    }
+}
+
+/// Pending receipts have no status, as the block they're in isn't accepted yet.
+fn receipt_status(receipt: &MaybePendingTransactionReceipt) -> TransactionStatus {
+    match receipt {
+        MaybePendingTransactionReceipt::Receipt(receipt) => match receipt {
+            TransactionReceipt::Invoke(receipt) => receipt.status,
+            TransactionReceipt::L1Handler(receipt) => receipt.status,
+            TransactionReceipt::Declare(receipt) => receipt.status,
+            TransactionReceipt::Deploy(receipt) => receipt.status,
+            TransactionReceipt::DeployAccount(receipt) => receipt.status,
+        },
+        MaybePendingTransactionReceipt::PendingReceipt(_) => TransactionStatus::Pending,
+    }
+}
+
+fn receipt_block_number(receipt: &MaybePendingTransactionReceipt) -> Option<u64> {
+    match receipt {
+        MaybePendingTransactionReceipt::Receipt(receipt) => Some(match receipt {
+            TransactionReceipt::Invoke(receipt) => receipt.block_number,
+            TransactionReceipt::L1Handler(receipt) => receipt.block_number,
+            TransactionReceipt::Declare(receipt) => receipt.block_number,
+            TransactionReceipt::Deploy(receipt) => receipt.block_number,
+            TransactionReceipt::DeployAccount(receipt) => receipt.block_number,
+        }),
+        MaybePendingTransactionReceipt::PendingReceipt(_) => None,
+    }
+}
+
+/// Writes a signed transaction as JSON to `destination`, or to stderr if it's `-`. The transaction
+/// is written exactly as it's about to be broadcast.
+pub fn dump_transaction<T>(transaction: &T, destination: &Path) -> Result<()>
+where
+    T: Serialize,
+{
+    let transaction_json = serde_json::to_string_pretty(transaction)?;
+
+    if destination == Path::new("-") {
+        eprintln!("{}", transaction_json);
+    } else {
+        std::fs::write(destination, format!("{}\n", transaction_json))?;
+        progress!(
+            "Transaction written to {}",
+            format!("{}", destination.display()).bright_yellow()
+        );
+    }
+
+    Ok(())
+}
+
+/// Prints every address a transaction touches for `--show-parties`, along with its address book
+/// name when there's one.
+pub async fn print_parties<S>(
+    resolver: &AddressBookResolver<S>,
+    parties: &[(&str, FieldElement)],
+) -> Result<()>
+where
+    S: ChainIdSource,
+{
+    let label_width = parties
+        .iter()
+        .map(|(label, _)| label.len() + 1)
+        .max()
+        .unwrap_or_default();
+
+    eprintln!("Parties involved:");
+    for (label, address) in parties.iter() {
+        let name = resolver.resolve_address(*address).await?;
+        eprintln!(
+            "  {:width$} {}{}",
+            format!("{}:", label),
+            format!("{:#064x}", address).bright_yellow(),
+            name.map(|name| format!(" ({})", name)).unwrap_or_default(),
+            width = label_width
+        );
+    }
+
+    Ok(())
+}
+
+/// Collects the results of pre-flight checks for `--check-only`. When disabled, checks behave as
+/// usual and the first failure aborts the command.
+pub struct PreflightReport {
+    enabled: bool,
+    results: Vec<(&'static str, CheckOutcome)>,
+}
+
+enum CheckOutcome {
+    Passed,
+    Failed(String),
+    Skipped(String),
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct NonceRetryArgs {
+    #[clap(
+        long,
+        help = "When the transaction is rejected for an invalid nonce, e.g. because another \
+        process sent a transaction from the same account, fetch the nonce again and resubmit"
+    )]
+    retry_on_nonce_error: bool,
+    #[clap(
+        long,
+        default_value = "3",
+        requires = "retry_on_nonce_error",
+        help = "Maximum number of resubmissions with --retry-on-nonce-error"
+    )]
+    max_nonce_retries: u32,
+}
+
+impl NonceRetryArgs {
+    /// Number of times a transaction may be resubmitted with a refetched nonce.
+    pub fn max_retries(&self) -> u32 {
+        if self.retry_on_nonce_error {
+            self.max_nonce_retries
+        } else {
+            0
+        }
+    }
+}
+
+/// Sends a prepared invoke transaction and returns its hash, along with the nonce it used.
+///
+/// When the submission times out, the transaction might have reached the node anyway. Instead of
+/// failing, its locally computed hash is looked up first, and the exact same signed transaction is
+/// only sent again if it's not found. Since a retry reuses the nonce, it can never result in the
+/// calls being executed twice.
+///
+/// A transaction rejected for its nonce is resubmitted up to `max_nonce_retries` times, with
+/// `reprepare` signing it again for the nonce fetched from the account. The same lookup happens
+/// first here, as the rejection might be caused by the transaction itself having made it.
+pub async fn send_execution<'a, A, P, F>(
+    provider: P,
+    account: &A,
+    mut execution: PreparedExecution<'a, A>,
+    mut nonce: FieldElement,
+    max_nonce_retries: u32,
+    mut reprepare: F,
+) -> Result<(FieldElement, FieldElement)>
+where
+    A: ConnectedAccount + Sync,
+    A::SignError: 'static,
+    A::Provider: Provider<Error = ExtendedProviderError>,
+    P: Provider,
+    P::Error: 'static,
+    F: FnMut(FieldElement) -> Result<PreparedExecution<'a, A>>,
+{
+    let mut nonce_retries = 0;
+
+    loop {
+        let transaction_hash = execution.transaction_hash();
+
+        let failure = match execution.send().await {
+            Ok(result) => return Ok((result.transaction_hash, nonce)),
+            Err(AccountError::Provider(ProviderError::Other(ExtendedProviderError::Timeout {
+                method,
+                timeout,
+            }))) => {
+                warning!(
+                    "WARNING: {} timed out after {}s. Checking whether transaction {:#064x} \
+                    reached the node before sending it again...",
+                    method,
+                    timeout,
+                    transaction_hash
+                );
+                SendFailure::Timeout
+            }
+            Err(AccountError::Provider(ProviderError::StarknetError(err)))
+                if nonce_retries < max_nonce_retries && is_nonce_error(&err) =>
+            {
+                SendFailure::InvalidNonce(err)
             }
             Err(err) => return Err(err.into()),
+        };
+
+        let recovery = recover_from_failure(&provider, &failure, transaction_hash, nonce, async {
+            Ok(account.get_nonce().await?)
+        })
+        .await?;
+        match (recovery, failure) {
+            (SendRecovery::AlreadyReceived, SendFailure::Timeout) => {
+                progress!("Transaction found. Not sending it again.");
+                return Ok((transaction_hash, nonce));
+            }
+            (SendRecovery::AlreadyReceived, SendFailure::InvalidNonce(_)) => {
+                progress!(
+                    "Transaction {:#064x} was rejected for its nonce but has already been \
+                    received. Not sending it again.",
+                    transaction_hash
+                );
+                return Ok((transaction_hash, nonce));
+            }
+            (SendRecovery::Resend, _) => {
+                progress!("Transaction not found. Sending it again...");
+                return Ok((execution.send().await?.transaction_hash, nonce));
+            }
+            (SendRecovery::Resign(current_nonce), SendFailure::InvalidNonce(err)) => {
+                nonce_retries += 1;
+                warning!(
+                    "WARNING: transaction rejected for nonce {} ({}). Resubmitting with nonce {} \
+                    (retry {} of {})...",
+                    nonce,
+                    err.message,
+                    current_nonce,
+                    nonce_retries,
+                    max_nonce_retries
+                );
+
+                execution = reprepare(current_nonce)?;
+                nonce = current_nonce;
+            }
+            (SendRecovery::GiveUp, SendFailure::InvalidNonce(err)) => {
+                return Err(ProviderError::<ExtendedProviderError>::StarknetError(err).into());
+            }
+            (SendRecovery::Resign(_) | SendRecovery::GiveUp, SendFailure::Timeout) => {
+                unreachable!("timed out transactions are either found or sent again")
+            }
         }
+    }
+}
+
+/// Submission failures that don't rule out the transaction having reached the node.
+enum SendFailure {
+    Timeout,
+    /// Rejected for its nonce, which might have been used by the transaction itself.
+    InvalidNonce(StarknetErrorWithMessage),
+}
+
+/// What [`send_execution`] does after a [`SendFailure`].
+#[derive(Debug, PartialEq, Eq)]
+enum SendRecovery {
+    /// The transaction reached the node, so it must not be sent again.
+    AlreadyReceived,
+    /// The exact same signed transaction can be sent again.
+    Resend,
+    /// The transaction has to be signed again for this nonce before resubmitting it.
+    Resign(FieldElement),
+    /// Resubmitting would fail the same way.
+    GiveUp,
+}
+
+/// Decides how to recover from `failure` for `transaction_hash` sent with `nonce`. The transaction
+/// is always looked up first, as sending anything again is only safe if the node doesn't know
+/// about it. `current_nonce` yields the account nonce, and is only awaited for nonce errors.
+async fn recover_from_failure<P, N>(
+    provider: &P,
+    failure: &SendFailure,
+    transaction_hash: FieldElement,
+    nonce: FieldElement,
+    current_nonce: N,
+) -> Result<SendRecovery>
+where
+    P: Provider,
+    P::Error: 'static,
+    N: Future<Output = Result<FieldElement>>,
+{
+    if transaction_exists(provider, transaction_hash).await? {
+        return Ok(SendRecovery::AlreadyReceived);
+    }
 
-        tokio::time::sleep(Duration::from_secs(5)).await;
+    match failure {
+        SendFailure::Timeout => Ok(SendRecovery::Resend),
+        SendFailure::InvalidNonce(_) => {
+            let current_nonce = current_nonce.await?;
+            if current_nonce == nonce {
+                Ok(SendRecovery::GiveUp)
+            } else {
+                Ok(SendRecovery::Resign(current_nonce))
+            }
+        }
     }
 }
 
+async fn transaction_exists<P>(provider: &P, transaction_hash: FieldElement) -> Result<bool>
+where
+    P: Provider,
+    P::Error: 'static,
+{
+    match provider.get_transaction_by_hash(transaction_hash).await {
+        Ok(_) => Ok(true),
+        Err(ProviderError::StarknetError(StarknetErrorWithMessage {
+            code: MaybeUnknownErrorCode::Known(StarknetError::TransactionHashNotFound),
+            ..
+        })) => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// JSON-RPC v0.3.0 has no dedicated error for invalid nonces, so nodes report them with the
+/// gateway message, or with the code newer spec versions use for them. Other errors merely
+/// mentioning nonces, e.g. from contracts, don't count.
+fn is_nonce_error(err: &StarknetErrorWithMessage) -> bool {
+    const INVALID_TRANSACTION_NONCE: i64 = 52;
+    const GATEWAY_MESSAGE_PREFIX: &str = "Invalid transaction nonce";
+
+    matches!(
+        err.code,
+        MaybeUnknownErrorCode::Unknown(INVALID_TRANSACTION_NONCE)
+    ) || err.message.starts_with(GATEWAY_MESSAGE_PREFIX)
+}
+
+/// Makes sure the account can afford `max_fee` in the fee token, so that the most common cause of
+/// failed transactions is caught before broadcasting.
+pub async fn check_fee_balance<P>(
+    provider: P,
+    chain_id: FieldElement,
+    account_address: FieldElement,
+    max_fee: FieldElement,
+    fee_formatter: &FeeFormatter,
+) -> Result<()>
+where
+    P: Provider,
+    P::Error: 'static,
+{
+    let (low, high) = fetch_fee_balance(provider, chain_id, account_address).await?;
+
+    if high == FieldElement::ZERO && low < max_fee {
+        anyhow::bail!(
+            "insufficient balance: have {}, need {}. \
+            Use --skip-balance-check to bypass this check.",
+            fee_formatter.format(low),
+            fee_formatter.format(max_fee)
+        );
+    }
+
+    Ok(())
+}
+
+/// Reads the fee token balance of `account_address` on `chain_id` as the low and high halves of a
+/// `u256`.
+pub async fn fetch_fee_balance<P>(
+    provider: P,
+    chain_id: FieldElement,
+    account_address: FieldElement,
+) -> Result<(FieldElement, FieldElement)>
+where
+    P: Provider,
+    P::Error: 'static,
+{
+    let fee_token = resolve_fee_token(chain_id)?;
+    let balance = provider
+        .call(
+            FunctionCall {
+                contract_address: fee_token,
+                entry_point_selector: selector!("balanceOf"),
+                calldata: vec![account_address],
+            },
+            BlockId::Tag(BlockTag::Pending),
+        )
+        .await?;
+
+    match balance.as_slice() {
+        [low, high] => Ok((*low, *high)),
+        _ => anyhow::bail!("unexpected balanceOf result length: {}", balance.len()),
+    }
+}
+
+/// Getters returning the owner public key in common account implementations: OpenZeppelin and
+/// Braavos (`get_public_key`/`getPublicKey`) and Argent X (`get_owner`/`getSigner`).
+pub const PUBLIC_KEY_GETTERS: [FieldElement; 4] = [
+    selector!("get_public_key"),
+    selector!("getPublicKey"),
+    selector!("get_owner"),
+    selector!("getSigner"),
+];
+
+/// Makes sure the account contract is controlled by `public_key`, as a transaction signed by the
+/// wrong key would only fail on validation. The check is skipped with a warning if the account
+/// class doesn't expose any of the known getters.
+pub async fn check_signer_public_key<P>(
+    provider: P,
+    account_address: FieldElement,
+    public_key: FieldElement,
+) -> Result<()>
+where
+    P: Provider,
+    P::Error: 'static,
+{
+    for getter in PUBLIC_KEY_GETTERS.into_iter() {
+        let result = provider
+            .call(
+                FunctionCall {
+                    contract_address: account_address,
+                    entry_point_selector: getter,
+                    calldata: vec![],
+                },
+                BlockId::Tag(BlockTag::Pending),
+            )
+            .await;
+
+        // An error most likely means the getter doesn't exist on this account class
+        if let Some(account_public_key) = result.ok().and_then(|result| result.first().copied()) {
+            if account_public_key != public_key {
+                anyhow::bail!(
+                    "signer public key {:#064x} does not match the public key {:#064x} of the \
+                    account. Use --skip-signer-check to bypass this check.",
+                    public_key,
+                    account_public_key
+                );
+            }
+
+            return Ok(());
+        }
+    }
+
+    warning!(
+        "WARNING: unable to read the public key of the account contract. Skipping the signer \
+        check."
+    );
+
+    Ok(())
+}
+
 pub fn parse_block_id(id: &str) -> Result<BlockId> {
     let regex_block_number = Regex::new("^[0-9]{1,}$").unwrap();
 
@@ -58,6 +749,182 @@ pub fn parse_block_id(id: &str) -> Result<BlockId> {
     }
 }
 
+/// Reads arguments from stdin, one per line. Empty lines and lines starting with `#` are skipped.
+pub fn read_stdin_args() -> Result<Vec<String>> {
+    read_args(std::io::stdin().lock())
+}
+
+fn read_args<R: BufRead>(reader: R) -> Result<Vec<String>> {
+    Ok(reader
+        .lines()
+        .collect::<std::result::Result<Vec<_>, _>>()?
+        .into_iter()
+        .map(|line| line.trim().to_owned())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect())
+}
+
+impl PreflightReport {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            results: vec![],
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Records the result of a check. Failures are only propagated when the report is disabled,
+    /// otherwise `None` is returned so that dependent checks can be skipped.
+    pub fn check<T>(&mut self, name: &'static str, result: Result<T>) -> Result<Option<T>> {
+        match result {
+            Ok(value) => {
+                if self.enabled {
+                    self.results.push((name, CheckOutcome::Passed));
+                }
+                Ok(Some(value))
+            }
+            Err(err) if self.enabled => {
+                self.results
+                    .push((name, CheckOutcome::Failed(format!("{:#}", err))));
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn skip(&mut self, name: &'static str, reason: &str) {
+        if self.enabled {
+            self.results
+                .push((name, CheckOutcome::Skipped(reason.to_owned())));
+        }
+    }
+
+    /// Prints the report to stdout, failing if any check failed.
+    pub fn finish(self) -> Result<()> {
+        for (name, outcome) in self.results.iter() {
+            match outcome {
+                CheckOutcome::Passed => output!("{} {}", "[PASS]".bright_green(), name),
+                CheckOutcome::Failed(reason) => {
+                    output!("{} {}: {}", "[FAIL]".bright_red(), name, reason)
+                }
+                CheckOutcome::Skipped(reason) => {
+                    output!("{} {}: {}", "[SKIP]".bright_black(), name, reason)
+                }
+            }
+        }
+
+        let failed_count = self
+            .results
+            .iter()
+            .filter(|(_, outcome)| matches!(outcome, CheckOutcome::Failed(_)))
+            .count();
+        if failed_count > 0 {
+            anyhow::bail!(
+                "{} of {} pre-flight checks failed",
+                failed_count,
+                self.results.len()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Replaces a `-` argument with arguments read from stdin, or appends them when `args_stdin` is
+/// set.
+pub fn expand_stdin_args(args: &[String], args_stdin: bool) -> Result<Vec<String>> {
+    expand_args(args, args_stdin, read_stdin_args)
+}
+
+/// Same as `expand_stdin_args`, with the arguments read by `read_args`, which is only called when
+/// any are needed.
+fn expand_args<F>(args: &[String], args_stdin: bool, read_args: F) -> Result<Vec<String>>
+where
+    F: FnOnce() -> Result<Vec<String>>,
+{
+    let placeholder_count = args.iter().filter(|arg| arg.as_str() == "-").count();
+    if placeholder_count + usize::from(args_stdin) > 1 {
+        anyhow::bail!("arguments can only be read from stdin once");
+    }
+    if placeholder_count == 0 && !args_stdin {
+        return Ok(args.to_vec());
+    }
+
+    let stdin_args = read_args()?;
+
+    let mut expanded = vec![];
+    for arg in args.iter() {
+        if arg == "-" {
+            expanded.extend(stdin_args.iter().cloned());
+        } else {
+            expanded.push(arg.to_owned());
+        }
+    }
+    if args_stdin {
+        expanded.extend(stdin_args);
+    }
+
+    Ok(expanded)
+}
+
+/// Makes sure an `--export` prefix gives valid shell variable names. Done before anything is sent.
+pub fn validate_export_prefix(prefix: &str) -> Result<()> {
+    let regex_prefix = Regex::new("^[A-Za-z_][A-Za-z0-9_]*$").unwrap();
+    if !regex_prefix.is_match(prefix) {
+        anyhow::bail!(
+            "invalid --export prefix \"{}\": only letters, digits, and underscores are allowed, \
+            and it must not start with a digit",
+            prefix
+        );
+    }
+
+    Ok(())
+}
+
+/// Prints `export PREFIX_NAME=0x...` lines to be `eval`-ed by shell scripts. Values are always
+/// plain hex without quotes or colors.
+pub fn print_shell_exports(prefix: &str, values: &[(&str, FieldElement)]) {
+    for line in shell_export_lines(prefix, values) {
+        output!("{}", line);
+    }
+}
+
+fn shell_export_lines(prefix: &str, values: &[(&str, FieldElement)]) -> Vec<String> {
+    values
+        .iter()
+        .map(|(name, value)| format!("export {}_{}={:#064x}", prefix, name, value))
+        .collect()
+}
+
+/// Joins arguments into a single line that a POSIX shell splits back into the same arguments.
+pub fn shell_command(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            let is_plain = !arg.is_empty()
+                && arg.chars().all(|c| {
+                    c.is_ascii_alphanumeric()
+                        || matches!(c, '-' | '_' | '.' | '/' | ':' | '=' | ',')
+                });
+            if is_plain {
+                arg.to_owned()
+            } else {
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
 pub fn parse_felt_value(felt: &str) -> Result<FieldElement> {
     let regex_dec_number = Regex::new("^[0-9]{1,}$").unwrap();
 
@@ -68,6 +935,18 @@ pub fn parse_felt_value(felt: &str) -> Result<FieldElement> {
     }
 }
 
+/// Parses a deployment salt, in hex (0x-prefixed) or decimal.
+pub fn parse_salt(salt: &str) -> Result<FieldElement> {
+    parse_felt_value(salt).map_err(|err| {
+        anyhow::anyhow!(
+            "invalid salt \"{}\": {}. Salts must be felts, at most {:#x}",
+            salt,
+            err,
+            FieldElement::MAX
+        )
+    })
+}
+
 #[allow(clippy::comparison_chain)]
 pub fn bigdecimal_to_felt<D>(dec: &BigDecimal, decimals: D) -> Result<FieldElement>
 where
@@ -99,3 +978,579 @@ where
 
     Ok(FieldElement::from_byte_slice_be(&biguint.to_bytes_be())?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+    use starknet::{
+        accounts::{Account, Call, SingleOwnerAccount},
+        macros::felt,
+        signers::{LocalWallet, SigningKey},
+    };
+
+    use crate::{
+        mock_rpc::MockRpc,
+        provider::{ExtendedProvider, RpcTimeouts},
+    };
+
+    type TestAccount = SingleOwnerAccount<ExtendedProvider, LocalWallet>;
+
+    const SENDER: FieldElement = felt!("0x1234");
+    const NONCE: FieldElement = felt!("0x5");
+
+    /// Response to `starknet_getTransactionByHash` for a transaction the node knows about.
+    fn invoke_transaction(transaction_hash: FieldElement) -> serde_json::Value {
+        json!({
+            "type": "INVOKE",
+            "version": "0x1",
+            "transaction_hash": format!("{:#x}", transaction_hash),
+            "max_fee": "0x1000",
+            "signature": [],
+            "nonce": format!("{:#x}", NONCE),
+            "sender_address": format!("{:#x}", SENDER),
+            "calldata": []
+        })
+    }
+
+    /// Account on `rpc` whose requests time out after a second.
+    fn test_account(rpc: &MockRpc) -> TestAccount {
+        SingleOwnerAccount::new(
+            rpc.provider().with_timeouts(RpcTimeouts {
+                default: Some(Duration::from_secs(1)),
+                ..Default::default()
+            }),
+            LocalWallet::from_signing_key(SigningKey::from_secret_scalar(felt!("0x1"))),
+            SENDER,
+            felt!("0x534e5f474f45524c49"),
+        )
+    }
+
+    fn prepare(
+        account: &TestAccount,
+        nonce: FieldElement,
+    ) -> Result<PreparedExecution<TestAccount>> {
+        Ok(account
+            .execute(vec![Call {
+                to: felt!("0x1"),
+                selector: selector!("transfer"),
+                calldata: vec![],
+            }])
+            .nonce(nonce)
+            .max_fee(felt!("0x1000"))
+            .prepared()?)
+    }
+
+    /// Stands in for fetching the account nonce where it must not be needed.
+    async fn unused_nonce() -> Result<FieldElement> {
+        Err(anyhow::anyhow!("nonce fetched"))
+    }
+
+    #[tokio::test]
+    async fn test_recover_from_timeout() {
+        let transaction_hash = felt!("0xabc");
+
+        // Found: the transaction made it, so it must not be sent again
+        let rpc = MockRpc::start();
+        rpc.respond(
+            "starknet_getTransactionByHash",
+            invoke_transaction(transaction_hash),
+        );
+        assert_eq!(
+            recover_from_failure(
+                &rpc.provider(),
+                &SendFailure::Timeout,
+                transaction_hash,
+                NONCE,
+                unused_nonce()
+            )
+            .await
+            .unwrap(),
+            SendRecovery::AlreadyReceived
+        );
+
+        // Not found: sending the same transaction again can't execute it twice
+        let rpc = MockRpc::start();
+        rpc.respond_error(
+            "starknet_getTransactionByHash",
+            25,
+            "Transaction hash not found",
+        );
+        assert_eq!(
+            recover_from_failure(
+                &rpc.provider(),
+                &SendFailure::Timeout,
+                transaction_hash,
+                NONCE,
+                unused_nonce()
+            )
+            .await
+            .unwrap(),
+            SendRecovery::Resend
+        );
+
+        // Unknown: nothing is sent again when the lookup itself fails
+        let rpc = MockRpc::start();
+        rpc.respond_error("starknet_getTransactionByHash", -32603, "Internal error");
+        assert!(recover_from_failure(
+            &rpc.provider(),
+            &SendFailure::Timeout,
+            transaction_hash,
+            NONCE,
+            unused_nonce()
+        )
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_send_execution_timeout_found() {
+        let rpc = MockRpc::start();
+        let account = test_account(&rpc);
+        let execution = prepare(&account, NONCE).unwrap();
+        let transaction_hash = execution.transaction_hash();
+        rpc.respond_after(
+            "starknet_addInvokeTransaction",
+            Duration::from_secs(3),
+            json!({ "transaction_hash": format!("{:#x}", transaction_hash) }),
+        )
+        .respond(
+            "starknet_getTransactionByHash",
+            invoke_transaction(transaction_hash),
+        );
+
+        let sent = send_execution(account.provider(), &account, execution, NONCE, 0, |nonce| {
+            prepare(&account, nonce)
+        })
+        .await;
+        assert_eq!(sent.unwrap(), (transaction_hash, NONCE));
+        assert_eq!(rpc.requests("starknet_addInvokeTransaction").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_execution_timeout_not_found() {
+        let rpc = MockRpc::start();
+        let account = test_account(&rpc);
+        let execution = prepare(&account, NONCE).unwrap();
+        let transaction_hash = execution.transaction_hash();
+        rpc.respond_after(
+            "starknet_addInvokeTransaction",
+            Duration::from_secs(3),
+            json!({ "transaction_hash": format!("{:#x}", transaction_hash) }),
+        )
+        .respond(
+            "starknet_addInvokeTransaction",
+            json!({ "transaction_hash": format!("{:#x}", transaction_hash) }),
+        )
+        .respond_error(
+            "starknet_getTransactionByHash",
+            25,
+            "Transaction hash not found",
+        );
+
+        let sent = send_execution(account.provider(), &account, execution, NONCE, 0, |nonce| {
+            prepare(&account, nonce)
+        })
+        .await;
+        assert_eq!(sent.unwrap(), (transaction_hash, NONCE));
+
+        // The exact same signed transaction is sent again
+        let submissions = rpc.requests("starknet_addInvokeTransaction");
+        assert_eq!(submissions.len(), 2);
+        assert_eq!(submissions[0], submissions[1]);
+    }
+
+    fn nonce_error() -> SendFailure {
+        SendFailure::InvalidNonce(StarknetErrorWithMessage {
+            code: MaybeUnknownErrorCode::Unknown(52),
+            message: "Invalid transaction nonce".into(),
+        })
+    }
+
+    #[test]
+    fn test_is_nonce_error() {
+        for (code, message) in [
+            (
+                MaybeUnknownErrorCode::Unknown(52),
+                "Invalid transaction nonce",
+            ),
+            (
+                MaybeUnknownErrorCode::Known(StarknetError::FailedToReceiveTransaction),
+                "Invalid transaction nonce of contract at address \
+                0x0000000000000000000000000000000000000000000000000000000000001234. Account \
+                nonce: 0x6; got: 0x5.",
+            ),
+            (MaybeUnknownErrorCode::Unknown(52), "Nonce too old"),
+        ] {
+            let err = StarknetErrorWithMessage {
+                code,
+                message: message.into(),
+            };
+            assert!(is_nonce_error(&err), "{:?}", err);
+        }
+
+        for (code, message) in [
+            (
+                MaybeUnknownErrorCode::Known(StarknetError::ContractError),
+                "Error in the called contract: invalid nonce for this game",
+            ),
+            (
+                MaybeUnknownErrorCode::Known(StarknetError::FailedToReceiveTransaction),
+                "Nonce handling failed",
+            ),
+            (
+                MaybeUnknownErrorCode::Unknown(53),
+                "Max fee is smaller than the minimal fee",
+            ),
+        ] {
+            let err = StarknetErrorWithMessage {
+                code,
+                message: message.into(),
+            };
+            assert!(!is_nonce_error(&err), "{:?}", err);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recover_from_nonce_error() {
+        // Already received: the rejection was caused by the transaction itself
+        let rpc = MockRpc::start();
+        rpc.respond(
+            "starknet_getTransactionByHash",
+            invoke_transaction(felt!("0xabc")),
+        );
+        assert_eq!(
+            recover_from_failure(
+                &rpc.provider(),
+                &nonce_error(),
+                felt!("0xabc"),
+                NONCE,
+                unused_nonce()
+            )
+            .await
+            .unwrap(),
+            SendRecovery::AlreadyReceived
+        );
+
+        let rpc = MockRpc::start();
+        rpc.respond_error(
+            "starknet_getTransactionByHash",
+            25,
+            "Transaction hash not found",
+        );
+
+        // Another transaction used the nonce
+        assert_eq!(
+            recover_from_failure(
+                &rpc.provider(),
+                &nonce_error(),
+                felt!("0xabc"),
+                NONCE,
+                async { Ok(felt!("0x6")) }
+            )
+            .await
+            .unwrap(),
+            SendRecovery::Resign(felt!("0x6"))
+        );
+
+        // The nonce hasn't moved, so resubmitting would be rejected again
+        assert_eq!(
+            recover_from_failure(
+                &rpc.provider(),
+                &nonce_error(),
+                felt!("0xabc"),
+                NONCE,
+                async { Ok(NONCE) }
+            )
+            .await
+            .unwrap(),
+            SendRecovery::GiveUp
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_execution_nonce_retry() {
+        let rpc = MockRpc::start();
+        let account = test_account(&rpc);
+        rpc.respond_error(
+            "starknet_addInvokeTransaction",
+            52,
+            "Invalid transaction nonce",
+        )
+        .respond(
+            "starknet_addInvokeTransaction",
+            json!({ "transaction_hash": "0xabc" }),
+        )
+        .respond_error(
+            "starknet_getTransactionByHash",
+            25,
+            "Transaction hash not found",
+        )
+        .respond("starknet_getNonce", json!("0x6"));
+
+        let execution = prepare(&account, NONCE).unwrap();
+        let sent = send_execution(account.provider(), &account, execution, NONCE, 1, |nonce| {
+            prepare(&account, nonce)
+        })
+        .await;
+        assert_eq!(sent.unwrap(), (felt!("0xabc"), felt!("0x6")));
+
+        let submissions = rpc.requests("starknet_addInvokeTransaction");
+        assert_eq!(submissions.len(), 2);
+        assert_eq!(submissions[0][0]["nonce"], "0x5");
+        assert_eq!(submissions[1][0]["nonce"], "0x6");
+    }
+
+    #[tokio::test]
+    async fn test_send_execution_nonce_unchanged() {
+        let rpc = MockRpc::start();
+        let account = test_account(&rpc);
+        rpc.respond_error(
+            "starknet_addInvokeTransaction",
+            52,
+            "Invalid transaction nonce",
+        )
+        .respond_error(
+            "starknet_getTransactionByHash",
+            25,
+            "Transaction hash not found",
+        )
+        .respond("starknet_getNonce", json!("0x5"));
+
+        let execution = prepare(&account, NONCE).unwrap();
+        let sent = send_execution(account.provider(), &account, execution, NONCE, 3, |nonce| {
+            prepare(&account, nonce)
+        })
+        .await;
+        assert!(sent
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid transaction nonce"));
+        assert_eq!(rpc.requests("starknet_addInvokeTransaction").len(), 1);
+    }
+
+    #[test]
+    fn test_watch_conditions_pending() {
+        // Inclusion alone is enough by default
+        let conditions = WatchConditions::default();
+        assert!(conditions.pending(TransactionStatus::Pending, 0).is_empty());
+
+        let conditions = WatchConditions {
+            min_confirmations: 2,
+            finality: Some(Finality::AcceptedOnL2),
+            timeout: None,
+        };
+        assert_eq!(
+            conditions.pending(TransactionStatus::Pending, 0),
+            ["ACCEPTED_ON_L2 (currently PENDING)", "0/2 confirmations"]
+        );
+        assert_eq!(
+            conditions.pending(TransactionStatus::AcceptedOnL2, 1),
+            ["1/2 confirmations"]
+        );
+        assert!(conditions
+            .pending(TransactionStatus::AcceptedOnL2, 2)
+            .is_empty());
+        // L1 acceptance implies L2 acceptance
+        assert!(conditions
+            .pending(TransactionStatus::AcceptedOnL1, 3)
+            .is_empty());
+
+        let conditions = WatchConditions {
+            finality: Some(Finality::AcceptedOnL1),
+            ..Default::default()
+        };
+        assert_eq!(
+            conditions.pending(TransactionStatus::AcceptedOnL2, 0),
+            ["ACCEPTED_ON_L1 (currently ACCEPTED_ON_L2)"]
+        );
+        assert!(conditions
+            .pending(TransactionStatus::AcceptedOnL1, 0)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_tx_progress_pending() {
+        let mut progress = TxProgress::default();
+
+        // Seen in the pending block first, then included and accepted in later polls
+        assert_eq!(
+            progress.update(TransactionStatus::Pending, None),
+            Some(TxTransition::SeenPending)
+        );
+        assert_eq!(progress.update(TransactionStatus::Pending, None), None);
+        assert_eq!(
+            progress.update(TransactionStatus::AcceptedOnL2, Some(10)),
+            Some(TxTransition::Included(10))
+        );
+        assert_eq!(
+            progress.update(TransactionStatus::AcceptedOnL2, Some(10)),
+            None
+        );
+        assert_eq!(
+            progress.update(TransactionStatus::AcceptedOnL1, Some(10)),
+            Some(TxTransition::StatusChanged(TransactionStatus::AcceptedOnL1))
+        );
+        assert_eq!(progress.block_number, Some(10));
+    }
+
+    #[test]
+    fn test_tx_progress_included() {
+        // Already in a block on the first receipt, so it's never reported as pending
+        let mut progress = TxProgress::default();
+        assert_eq!(
+            progress.update(TransactionStatus::AcceptedOnL2, Some(10)),
+            Some(TxTransition::Included(10))
+        );
+        assert_eq!(
+            progress.update(TransactionStatus::AcceptedOnL2, Some(10)),
+            None
+        );
+
+        // A status change without a block number is still reported
+        let mut progress = TxProgress::default();
+        assert_eq!(progress.update(TransactionStatus::AcceptedOnL2, None), None);
+        assert_eq!(
+            progress.update(TransactionStatus::AcceptedOnL1, None),
+            Some(TxTransition::StatusChanged(TransactionStatus::AcceptedOnL1))
+        );
+        assert_eq!(progress.block_number, None);
+    }
+
+    #[test]
+    fn test_watch_conditions_next_poll_interval() {
+        let conditions = WatchConditions::default();
+        assert_eq!(
+            conditions.next_poll_interval(Duration::from_secs(3600)),
+            Some(Duration::from_secs(5))
+        );
+
+        // A single timeout covers all conditions, and the last poll is shortened to end on it
+        let conditions = WatchConditions {
+            timeout: Some(Duration::from_secs(12)),
+            ..Default::default()
+        };
+        assert_eq!(
+            conditions.next_poll_interval(Duration::ZERO),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            conditions.next_poll_interval(Duration::from_secs(10)),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(conditions.next_poll_interval(Duration::from_secs(12)), None);
+        assert_eq!(conditions.next_poll_interval(Duration::from_secs(20)), None);
+    }
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_read_args() {
+        let input = "0x1\n\n  # a comment\n  0x2  \n#0x3\nstr:hello world\n";
+        assert_eq!(
+            read_args(input.as_bytes()).unwrap(),
+            ["0x1", "0x2", "str:hello world"]
+        );
+        assert!(read_args("".as_bytes()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_expand_args() {
+        let stdin_args = || Ok(strings(&["0x2", "0x3"]));
+
+        // The placeholder is replaced in place, while `--args-stdin` appends
+        assert_eq!(
+            expand_args(&strings(&["0x1", "-", "0x4"]), false, stdin_args).unwrap(),
+            ["0x1", "0x2", "0x3", "0x4"]
+        );
+        assert_eq!(
+            expand_args(&strings(&["0x1"]), true, stdin_args).unwrap(),
+            ["0x1", "0x2", "0x3"]
+        );
+        assert_eq!(
+            expand_args(&strings(&["-"]), false, || Ok(vec![])).unwrap(),
+            Vec::<String>::new()
+        );
+
+        // Stdin isn't read when nothing asks for it
+        assert_eq!(
+            expand_args(&strings(&["0x1", "0x2"]), false, || {
+                Err(anyhow::anyhow!("stdin read"))
+            })
+            .unwrap(),
+            ["0x1", "0x2"]
+        );
+
+        // Stdin can only be consumed once
+        for (args, args_stdin) in [(&["-", "-"][..], false), (&["0x1", "-"][..], true)] {
+            assert_eq!(
+                expand_args(&strings(args), args_stdin, stdin_args)
+                    .unwrap_err()
+                    .to_string(),
+                "arguments can only be read from stdin once"
+            );
+        }
+    }
+
+    #[test]
+    fn test_shell_export_lines() {
+        assert_eq!(
+            shell_export_lines(
+                "TOKEN",
+                &[("ADDRESS", felt!("0x1234")), ("TX_HASH", felt!("0xabc"))]
+            ),
+            [
+                "export TOKEN_ADDRESS=0x0000000000000000000000000000000000000000000000000000000000001234",
+                "export TOKEN_TX_HASH=0x0000000000000000000000000000000000000000000000000000000000000abc",
+            ]
+        );
+        assert!(shell_export_lines("TOKEN", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_validate_export_prefix() {
+        for prefix in ["TOKEN", "_token", "Token_2"] {
+            assert!(validate_export_prefix(prefix).is_ok(), "{prefix}");
+        }
+        for prefix in [
+            "",
+            "2TOKEN",
+            "MY-TOKEN",
+            "TOKEN PREFIX",
+            "TOKEN=1",
+            "$TOKEN",
+        ] {
+            assert!(validate_export_prefix(prefix)
+                .unwrap_err()
+                .to_string()
+                .starts_with("invalid --export prefix"));
+        }
+    }
+
+    #[test]
+    fn test_parse_salt() {
+        // Both radixes give the same salt
+        assert_eq!(
+            parse_salt("0x1e240").unwrap(),
+            FieldElement::from(123456u64)
+        );
+        assert_eq!(parse_salt("123456").unwrap(), FieldElement::from(123456u64));
+        assert_eq!(parse_salt("0").unwrap(), FieldElement::ZERO);
+
+        // The field prime is out of range in either form
+        for salt in [
+            "0x800000000000011000000000000000000000000000000000000000000000001",
+            "3618502788666131213697322783095070105623107215331596699973092056135872020481",
+        ] {
+            assert!(parse_salt(salt).is_err(), "{salt}");
+        }
+
+        assert!(parse_salt("0xzz")
+            .unwrap_err()
+            .to_string()
+            .starts_with("invalid salt \"0xzz\""));
+    }
+}
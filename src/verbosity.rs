@@ -1,21 +1,315 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
 use clap::Parser;
-use env_logger::Builder;
-use log::LevelFilter;
+use colored::Colorize;
+use env_logger::{Builder, Logger};
+use log::{LevelFilter, Log, Metadata, Record};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+static SILENT: AtomicBool = AtomicBool::new(false);
+
+/// Warnings collected for `--json-envelope` instead of being printed.
+static WARNINGS: Mutex<Option<Vec<String>>> = Mutex::new(None);
+
+static DEBUG_RPC: AtomicBool = AtomicBool::new(false);
+
+/// JSON-RPC exchanges picked out of the provider traffic. Only tracked with `--debug-rpc`.
+static RPC_TRAFFIC: Mutex<RpcTraffic> = Mutex::new(RpcTraffic::new());
+
+/// Target of the traffic logged by `starknet-providers`.
+const PROVIDERS_TARGET: &str = "starknet_providers";
 
 #[derive(Debug, Clone, Parser)]
 pub struct VerbosityArgs {
     #[clap(long, help = "Log raw request/response traffic of providers")]
     log_traffic: bool,
+    #[clap(
+        long,
+        conflicts_with = "log_traffic",
+        help = "When the command fails on a provider error, print the raw JSON-RPC request and \
+        response of the failed call"
+    )]
+    debug_rpc: bool,
+    #[clap(
+        long,
+        short,
+        conflicts_with = "log_traffic",
+        help = "Suppress informational output to stderr. Warnings and errors are still printed"
+    )]
+    quiet: bool,
+}
+
+#[derive(Clone)]
+struct RpcExchange {
+    request: Option<String>,
+    response: Option<String>,
+}
+
+struct RpcTraffic {
+    /// Latest JSON-RPC request sent, along with its response once received.
+    last: RpcExchange,
+    /// Exchange of the latest provider request that failed.
+    failed: RpcExchange,
+}
+
+/// Keeps the provider traffic for `--debug-rpc` instead of logging it. Any other record goes to
+/// the default logger, so it's printed as it would be without the option.
+struct RpcTrafficLogger {
+    inner: Logger,
+}
+
+/// Prints informational progress messages to stderr, unless `--quiet` is used. Warnings should go
+/// through `warning!` instead.
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        if !$crate::verbosity::is_quiet() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+pub(crate) use progress;
+
+/// Prints a highlighted warning to stderr. Warnings are shown even with `--quiet`, and are only
+/// suppressed when a command guarantees clean output with `silence`.
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        $crate::verbosity::print_warning(&format!($($arg)*))
+    };
 }
+pub(crate) use warning;
 
 impl VerbosityArgs {
     pub fn setup_logging(&self) {
+        QUIET.store(self.quiet, Ordering::Relaxed);
+
         let mut builder = Builder::new();
 
         if self.log_traffic {
-            builder.filter_module("starknet_providers", LevelFilter::Trace);
+            builder.filter_module(PROVIDERS_TARGET, LevelFilter::Trace);
+        } else if self.debug_rpc {
+            DEBUG_RPC.store(true, Ordering::Relaxed);
+
+            // Traffic is kept instead of logged, so that only the failing call is ever printed
+            let logger = RpcTrafficLogger {
+                inner: builder.build(),
+            };
+            log::set_max_level(LevelFilter::Trace);
+            if log::set_boxed_logger(Box::new(logger)).is_err() {
+                panic!("logger already initialized");
+            }
+            return;
         }
 
         builder.init();
     }
 }
+
+impl Log for RpcTrafficLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target().starts_with(PROVIDERS_TARGET) || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if record.target().starts_with(PROVIDERS_TARGET) {
+            track_rpc_traffic(&record.args().to_string());
+        }
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Suppresses all informational output and warnings. Only the primary output and fatal errors are
+/// printed afterwards.
+pub fn silence() {
+    QUIET.store(true, Ordering::Relaxed);
+    SILENT.store(true, Ordering::Relaxed);
+}
+
+/// Starts collecting warnings instead of printing them to stderr.
+pub fn collect_warnings() {
+    *WARNINGS.lock().unwrap() = Some(vec![]);
+}
+
+/// Stops collecting and returns the collected warnings.
+pub fn take_warnings() -> Vec<String> {
+    WARNINGS.lock().unwrap().take().unwrap_or_default()
+}
+
+pub fn print_warning(warning: &str) {
+    if SILENT.load(Ordering::Relaxed) {
+        return;
+    }
+
+    match WARNINGS.lock().unwrap().as_mut() {
+        Some(warnings) => warnings.push(
+            warning
+                .strip_prefix("WARNING: ")
+                .unwrap_or(warning)
+                .to_owned(),
+        ),
+        None => eprintln!("{}", warning.bright_magenta()),
+    }
+}
+
+impl RpcExchange {
+    const fn empty() -> Self {
+        Self {
+            request: None,
+            response: None,
+        }
+    }
+}
+
+impl RpcTraffic {
+    const fn new() -> Self {
+        Self {
+            last: RpcExchange::empty(),
+            failed: RpcExchange::empty(),
+        }
+    }
+
+    /// Picks the request and response bodies out of the traffic logged by `starknet-providers`.
+    fn track(&mut self, message: &str) {
+        if let Some(request) = message.strip_prefix("Sending request via JSON-RPC: ") {
+            self.last.request = Some(request.to_owned());
+            self.last.response = None;
+        } else if let Some(response) = message.strip_prefix("Response from JSON-RPC: ") {
+            self.last.response = Some(response.to_owned());
+        }
+    }
+
+    /// Keeps the latest exchange as the failed one on errors, and forgets it once a later request
+    /// succeeds, as errors the caller recovered from aren't worth printing. With requests running
+    /// concurrently, the latest exchange might belong to another request.
+    fn record_result(&mut self, is_ok: bool) {
+        self.failed = if is_ok {
+            RpcExchange::empty()
+        } else {
+            self.last.clone()
+        };
+    }
+
+    /// Report of the failed exchange, if any.
+    fn failed_report(&self) -> Option<String> {
+        let request = self.failed.request.as_ref()?;
+        let response = match &self.failed.response {
+            Some(response) => pretty_json(response),
+            None => "(no response received)".to_owned(),
+        };
+
+        Some(format!(
+            "{}\n{}\n{}\n{}",
+            "Failed JSON-RPC request:".bright_magenta(),
+            pretty_json(request),
+            "Response:".bright_magenta(),
+            response
+        ))
+    }
+}
+
+fn track_rpc_traffic(message: &str) {
+    RPC_TRAFFIC.lock().unwrap().track(message);
+}
+
+pub fn record_rpc_result(is_ok: bool) {
+    if !DEBUG_RPC.load(Ordering::Relaxed) {
+        return;
+    }
+
+    RPC_TRAFFIC.lock().unwrap().record_result(is_ok);
+}
+
+/// Prints the exchange of the latest failed provider request to stderr, if any.
+pub fn print_failed_rpc_request() {
+    if !DEBUG_RPC.load(Ordering::Relaxed) {
+        return;
+    }
+
+    if let Some(report) = RPC_TRAFFIC.lock().unwrap().failed_report() {
+        eprintln!("{report}");
+    }
+}
+
+fn pretty_json(raw: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(raw)
+        .and_then(|value| serde_json::to_string_pretty(&value))
+        .unwrap_or_else(|_| raw.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use log::Level;
+
+    const REQUEST: &str =
+        "Sending request via JSON-RPC: {\"id\":1,\"jsonrpc\":\"2.0\",\"method\":\"starknet_call\"}";
+    const RESPONSE: &str = "Response from JSON-RPC: {\"id\":1,\"error\":{\"code\":40}}";
+
+    #[test]
+    fn test_failed_rpc_request() {
+        let mut traffic = RpcTraffic::new();
+
+        traffic.track(REQUEST);
+        traffic.track(RESPONSE);
+        traffic.record_result(false);
+
+        let report = traffic.failed_report().unwrap();
+        assert!(report.contains("Failed JSON-RPC request:"), "{report}");
+        assert!(
+            report.contains("\n  \"method\": \"starknet_call\"\n"),
+            "{report}"
+        );
+        assert!(report.contains("Response:"), "{report}");
+        assert!(
+            report.contains("\n  \"error\": {\n    \"code\": 40\n"),
+            "{report}"
+        );
+
+        // Later requests don't replace the failed exchange until one of them completes
+        traffic.track("Sending request via JSON-RPC: {\"id\":2}");
+        assert!(traffic.failed_report().unwrap().contains("starknet_call"));
+        traffic.record_result(false);
+        assert!(traffic
+            .failed_report()
+            .unwrap()
+            .ends_with("\n(no response received)"));
+    }
+
+    #[test]
+    fn test_failed_rpc_request_recovered() {
+        let mut traffic = RpcTraffic::new();
+        assert!(traffic.failed_report().is_none());
+
+        traffic.track(REQUEST);
+        traffic.track(RESPONSE);
+        traffic.record_result(false);
+        traffic.track("Sending request via JSON-RPC: {\"id\":2}");
+        traffic.track("Response from JSON-RPC: {\"id\":2,\"result\":\"0x1\"}");
+        traffic.record_result(true);
+
+        assert!(traffic.failed_report().is_none());
+    }
+
+    #[test]
+    fn test_rpc_traffic_logger_keeps_other_records() {
+        let logger = RpcTrafficLogger {
+            inner: Builder::new().build(),
+        };
+        let metadata = |target, level| Metadata::builder().target(target).level(level).build();
+
+        assert!(logger.enabled(&metadata("starknet_providers::jsonrpc", Level::Trace)));
+        assert!(logger.enabled(&metadata("starkli", Level::Error)));
+        assert!(!logger.enabled(&metadata("starkli", Level::Debug)));
+    }
+}
@@ -0,0 +1,170 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Deserialize;
+use starknet::{core::types::FieldElement, signers::SigningKey};
+
+use crate::{
+    account::{AccountConfig, AccountVariant},
+    keystore,
+};
+
+/// Account config and keystore in a single document, used with `--wallet`. This is the same
+/// format as account bundles, so that a bundle exported with `--include-keystore` can be used as
+/// is.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WalletFile {
+    version: u64,
+    account: AccountConfig,
+    /// Either the encrypted keystore itself, or a path to the keystore file. Relative paths are
+    /// resolved against the directory of the wallet file.
+    keystore: Option<serde_json::Value>,
+}
+
+pub struct Wallet {
+    pub account: AccountConfig,
+    keystore: WalletKeystore,
+}
+
+enum WalletKeystore {
+    File(PathBuf),
+    Inline(serde_json::Value),
+}
+
+impl Wallet {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            anyhow::bail!("wallet file not found");
+        }
+
+        let wallet: WalletFile = serde_json::from_reader(std::fs::File::open(path)?)
+            .map_err(|err| anyhow::anyhow!("invalid wallet file {}: {}", path.display(), err))?;
+        if wallet.version != 1 {
+            anyhow::bail!("unsupported wallet file version: {}", wallet.version);
+        }
+
+        let keystore = match wallet.keystore {
+            Some(serde_json::Value::String(keystore_path)) => {
+                let keystore_path = PathBuf::from(shellexpand::tilde(&keystore_path).as_ref());
+                WalletKeystore::File(match path.parent() {
+                    Some(parent) if keystore_path.is_relative() => parent.join(keystore_path),
+                    _ => keystore_path,
+                })
+            }
+            Some(keystore @ serde_json::Value::Object(_)) => WalletKeystore::Inline(keystore),
+            Some(_) => anyhow::bail!(
+                "invalid wallet file {}: \"keystore\" must be either a path or the keystore JSON",
+                path.display()
+            ),
+            None => anyhow::bail!(
+                "wallet file {} has no keystore. Use --account with a separate signer option \
+                instead",
+                path.display()
+            ),
+        };
+
+        Ok(Self {
+            account: wallet.account,
+            keystore,
+        })
+    }
+
+    /// Decrypts the keystore and makes sure the key controls the account, as a mismatch would
+    /// only surface as a failed transaction validation.
+    pub fn signing_key(&self, password: &str) -> Result<SigningKey> {
+        let key = match &self.keystore {
+            WalletKeystore::File(keystore_path) => {
+                if !keystore_path.exists() {
+                    anyhow::bail!(
+                        "keystore file {} referenced by the wallet not found",
+                        keystore_path.display()
+                    );
+                }
+                SigningKey::from_keystore(keystore_path, password)?
+            }
+            WalletKeystore::Inline(keystore) => keystore::decrypt_keystore(keystore, password)?,
+        };
+
+        let public_key = key.verifying_key().scalar();
+        let expected_public_key = self.public_key();
+        if public_key != expected_public_key {
+            anyhow::bail!(
+                "the wallet keystore doesn't match its account. Expected public key: {:#064x}; \
+                actual: {:#064x}",
+                expected_public_key,
+                public_key
+            );
+        }
+
+        Ok(key)
+    }
+
+    fn public_key(&self) -> FieldElement {
+        match &self.account.variant {
+            AccountVariant::OpenZeppelin(oz) => oz.public_key,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_wallet(public_key: FieldElement, keystore: &serde_json::Value) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("starkli-test-wallet-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            serde_json::to_vec(&serde_json::json!({
+                "version": 1,
+                "account": {
+                    "version": 1,
+                    "variant": {
+                        "type": "open_zeppelin",
+                        "version": 1,
+                        "public_key": format!("{:#064x}", public_key),
+                    },
+                    "deployment": {
+                        "status": "deployed",
+                        "class_hash": "0x2",
+                        "address": "0x3",
+                    },
+                },
+                "keystore": keystore,
+            }))
+            .unwrap(),
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_inline_keystore() {
+        let key = SigningKey::from_secret_scalar(FieldElement::from_hex_be("0x1234").unwrap());
+        let keystore_path = std::env::temp_dir().join(format!(
+            "starkli-test-keystore-{}.json",
+            uuid::Uuid::new_v4()
+        ));
+        key.save_as_keystore(&keystore_path, "password").unwrap();
+        let keystore: serde_json::Value =
+            serde_json::from_reader(std::fs::File::open(&keystore_path).unwrap()).unwrap();
+        std::fs::remove_file(&keystore_path).unwrap();
+
+        let wallet_path = write_wallet(key.verifying_key().scalar(), &keystore);
+        let wallet = Wallet::load(&wallet_path).unwrap();
+        std::fs::remove_file(&wallet_path).unwrap();
+        assert_eq!(
+            wallet.signing_key("password").unwrap().secret_scalar(),
+            key.secret_scalar()
+        );
+
+        let wallet_path = write_wallet(FieldElement::ONE, &keystore);
+        let wallet = Wallet::load(&wallet_path).unwrap();
+        std::fs::remove_file(&wallet_path).unwrap();
+        let err = wallet.signing_key("password").err().unwrap();
+        assert!(err
+            .to_string()
+            .starts_with("the wallet keystore doesn't match its account"));
+    }
+}